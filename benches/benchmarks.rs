@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
-use space_filler::{hilbert, morton, CurveIdx};
+use space_filler::{hilbert, morton, Coordinate, CurveIdx};
 
 pub fn morton_benchmark(c: &mut Criterion) {
     c.bench_function("morton min", |b| {
@@ -8,25 +8,74 @@ pub fn morton_benchmark(c: &mut Criterion) {
     c.bench_function("morton max", |b| {
         b.iter(|| morton::decode_2d(black_box(CurveIdx::MAX)))
     });
+    c.bench_function("morton encode min", |b| {
+        b.iter(|| morton::encode_2d(black_box([Coordinate::MIN; 2])))
+    });
+    c.bench_function("morton encode max", |b| {
+        b.iter(|| morton::encode_2d(black_box([Coordinate::MAX; 2])))
+    });
+    c.bench_function("morton lut max", |b| {
+        b.iter(|| morton::decode_2d_lut(black_box(CurveIdx::MAX)))
+    });
+    c.bench_function("morton fast max", |b| {
+        b.iter(|| morton::decode_2d_fast(black_box(CurveIdx::MAX)))
+    });
+    c.bench_function("morton fast encode max", |b| {
+        b.iter(|| morton::encode_2d_fast(black_box([Coordinate::MAX; 2])))
+    });
 
+    // Iteration benchmarks go through the whole curve, so they always use
+    // 16-bit curve indices, whatever the default CurveIdx type is.
     let mut group = c.benchmark_group("morton iter");
-    group.throughput(Throughput::Elements(
-        CurveIdx::MAX as u64 - CurveIdx::MIN as u64 + 1,
-    ));
+    group.throughput(Throughput::Elements(u16::MAX as u64 - u16::MIN as u64 + 1));
     group.bench_function("naive", |b| {
         b.iter(|| {
-            for i in CurveIdx::MIN..=CurveIdx::MAX {
-                black_box(morton::decode_2d(i));
+            for i in u16::MIN..=u16::MAX {
+                black_box(morton::decode_2d_u16(i));
             }
         })
     });
     group.bench_function("optimized", |b| {
         b.iter(|| {
-            for coords in morton::iter_2d() {
+            for coords in morton::iter_2d_u16() {
                 black_box(coords);
             }
         })
     });
+    let codes = (u16::MIN..=u16::MAX).collect::<Vec<_>>();
+    let mut coords = vec![[0; 2]; codes.len()];
+    group.bench_function("simd", |b| {
+        b.iter(|| {
+            morton::decode_2d_into_u16(black_box(&codes), &mut coords);
+            black_box(&mut coords);
+        })
+    });
+    group.finish();
+
+    // Sorting benchmarks use a large set of pseudo-random 64-bit curve points
+    #[cfg(feature = "alloc")]
+    {
+        const NUM_POINTS: u64 = 1 << 20;
+        let points = (0..NUM_POINTS)
+            .map(|i| morton::decode_2d_u64(i.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            .collect::<Vec<_>>();
+        let mut group = c.benchmark_group("morton sort");
+        group.throughput(Throughput::Elements(NUM_POINTS));
+        group.bench_function("by_key", |b| {
+            b.iter_batched_ref(
+                || points.clone(),
+                |points| morton::sort_by_key_u64(points, |&point| point),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function("radix", |b| {
+            b.iter_batched_ref(
+                || points.clone(),
+                |points| morton::sort_radix_u64(points),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
 }
 
 pub fn hilbert_benchmark(c: &mut Criterion) {
@@ -36,19 +85,74 @@ pub fn hilbert_benchmark(c: &mut Criterion) {
     c.bench_function("hilbert max", |b| {
         b.iter(|| hilbert::decode_2d(black_box(CurveIdx::MAX)))
     });
+    c.bench_function("hilbert encode min", |b| {
+        b.iter(|| hilbert::encode_2d(black_box([Coordinate::MIN; 2])))
+    });
+    c.bench_function("hilbert encode max", |b| {
+        b.iter(|| hilbert::encode_2d(black_box([Coordinate::MAX; 2])))
+    });
+    c.bench_function("hilbert lut max", |b| {
+        b.iter(|| hilbert::decode_2d_lut(black_box(CurveIdx::MAX)))
+    });
+    c.bench_function("hilbert fast max", |b| {
+        b.iter(|| hilbert::decode_2d_fast(black_box(CurveIdx::MAX)))
+    });
+    c.bench_function("hilbert fast encode max", |b| {
+        b.iter(|| hilbert::encode_2d_fast(black_box([Coordinate::MAX; 2])))
+    });
 
+    // Iteration benchmarks go through the whole curve, so they always use
+    // 16-bit curve indices, whatever the default CurveIdx type is.
     let mut group = c.benchmark_group("hilbert iter");
-    group.throughput(Throughput::Elements(
-        CurveIdx::MAX as u64 - CurveIdx::MIN as u64 + 1,
-    ));
+    group.throughput(Throughput::Elements(u16::MAX as u64 - u16::MIN as u64 + 1));
     group.bench_function("naive", |b| {
         b.iter(|| {
-            for i in CurveIdx::MIN..=CurveIdx::MAX {
-                black_box(hilbert::decode_2d(i));
+            for i in u16::MIN..=u16::MAX {
+                black_box(hilbert::decode_2d_u16(i));
+            }
+        })
+    });
+    group.bench_function("optimized", |b| {
+        b.iter(|| {
+            for coords in hilbert::iter_2d_u16() {
+                black_box(coords);
             }
         })
     });
-    // TODO: Add optimized Hilbert curve iterator
+    let codes = (u16::MIN..=u16::MAX).collect::<Vec<_>>();
+    let mut coords = vec![[0; 2]; codes.len()];
+    group.bench_function("simd", |b| {
+        b.iter(|| {
+            hilbert::decode_2d_into_u16(black_box(&codes), &mut coords);
+            black_box(&mut coords);
+        })
+    });
+    group.finish();
+
+    // Sorting benchmarks use a large set of pseudo-random 64-bit curve points
+    #[cfg(feature = "alloc")]
+    {
+        const NUM_POINTS: u64 = 1 << 20;
+        let points = (0..NUM_POINTS)
+            .map(|i| hilbert::decode_2d_u64(i.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            .collect::<Vec<_>>();
+        let mut group = c.benchmark_group("hilbert sort");
+        group.throughput(Throughput::Elements(NUM_POINTS));
+        group.bench_function("by_key", |b| {
+            b.iter_batched_ref(
+                || points.clone(),
+                |points| hilbert::sort_by_key_u64(points, |&point| point),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function("radix", |b| {
+            b.iter_batched_ref(
+                || points.clone(),
+                |points| hilbert::sort_radix_u64(points),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
 }
 
 criterion_group!(benches, morton_benchmark, hilbert_benchmark);
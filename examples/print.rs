@@ -2,100 +2,158 @@
 //!
 //! I originally wrote this as a manual algorithm validation tool, and kept it
 //! around because I think it just looks cool :)
+//!
+//! It has since grown a few command-line options, which make it easy to compare
+//! curves without recompiling anything. Run it with `--help` for details.
 
-use space_filler::{hilbert, Coordinates2D, CurveIdx};
+use space_filler::{
+    hilbert, morton,
+    render::{self, SvgOptions},
+    Coordinate, Coordinates2D, CurveKind, Transform2D,
+};
+use std::{env, process};
 
-// Display a Hilbert curve of specified order
-fn print_hilbert(order: u8) {
-    // Print header
-    println!("--- At order {order} ---\n");
+/// Command-line usage, displayed by `--help` and on errors
+const USAGE: &str = "\
+Usage: print [OPTIONS]
 
-    // Compute a Hilbert curve's coordinates
-    let coord_range = 2usize.pow(order as u32);
-    let num_points = coord_range * coord_range;
-    let coordinates = (0..num_points)
-        .map(|idx| {
-            // Here, we simulate a low-order curve from a higher-order one by
-            // swapping coordinates when order is odd.
-            let [mut x, mut y] = hilbert::decode_2d(idx as CurveIdx);
-            if order % 2 == 1 {
-                core::mem::swap(&mut x, &mut y);
-            }
-            [x, y]
-        })
-        .collect::<Vec<_>>();
+Options:
+    --curve <CURVE>      Curve to display: hilbert (default), morton, moore,
+                         sierpinski, hindex, snake or spiral. Curves other
+                         than hilbert and morton are clipped to the square
+                         of the requested order, so they may jump around.
+    --order <N>          Only display the curve of order N
+    --min-order <N>      Lowest order of the curve to display (default: 1)
+    --max-order <N>      Highest order of the curve to display (default: 8)
+    --swap               Swap the x and y coordinates of the curve
+    --mirror-x           Mirror the curve along the x axis
+    --mirror-y           Mirror the curve along the y axis
+    --format <FORMAT>    Output format: unicode (default) or svg
+    --color              Color SVG output by position along the curve
+    --help               Display this help message";
 
-    // Set up a 2D character-based display
-    let mut display = (0..(num_points + coord_range))
-        .map(|idx| {
-            if idx % (coord_range + 1) == coord_range {
-                '\n'
-            } else {
-                // This character is a placeholder that should not persist in
-                // the final program output.
-                '@'
-            }
-        })
-        .collect::<Vec<_>>();
-    let to_index = |coords: Coordinates2D| {
-        assert!(
-            coords.iter().all(|&coord| (coord as usize) < coord_range),
-            "Coordinates out of range: {coords:?} for range {coord_range}"
-        );
-        (coords[1] as usize) * (coord_range + 1) + (coords[0] as usize)
-    };
-    let to_dir = |src: Coordinates2D, dst: Coordinates2D| {
-        [
-            dst[0] as isize - src[0] as isize,
-            dst[1] as isize - src[1] as isize,
-        ]
-    };
-
-    // Draw the start of the curve
-    let start = coordinates[0];
-    let next = coordinates[1];
-    display[to_index(start)] = match to_dir(start, next) {
-        [0, -1] => '┴',
-        [1, 0] => '├',
-        [0, 1] => '┬',
-        [-1, 0] => '┤',
-        _ => unreachable!("Hilbert curve moves by single-coordinate steps"),
-    };
-
-    // Draw the end of the curve
-    let end = coordinates[num_points - 1];
-    let prev = coordinates[num_points - 2];
-    display[to_index(end)] = match to_dir(prev, end) {
-        [0, -1] => '^',
-        [1, 0] => '>',
-        [0, 1] => 'v',
-        [-1, 0] => '<',
-        _ => unreachable!("Hilbert curve moves by single-coordinate steps"),
-    };
+/// Output format
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    Unicode,
+    Svg,
+}
 
-    // Draw the middle of the curve
-    for window in coordinates.windows(3) {
-        let path = match (to_dir(window[0], window[1]), to_dir(window[1], window[2])) {
-            ([-1, 0], [0, -1]) | ([0, 1], [1, 0]) => '└',
-            ([-1, 0], [0, 1]) | ([0, -1], [1, 0]) => '┌',
-            ([1, 0], [1, 0]) | ([-1, 0], [-1, 0]) => '─',
-            ([1, 0], [0, -1]) | ([0, 1], [-1, 0]) => '┘',
-            ([0, 1], [0, 1]) | ([0, -1], [0, -1]) => '│',
-            ([1, 0], [0, 1]) | ([0, -1], [-1, 0]) => '┐',
-            _ => unreachable!("Hilbert curve moves by single-coordinate steps and doesn't go back"),
+/// Command-line options
+#[derive(Debug)]
+struct Options {
+    curve: CurveKind,
+    min_order: u32,
+    max_order: u32,
+    transform: Transform2D,
+    format: Format,
+    color: bool,
+}
+//
+impl Options {
+    /// Parse command-line arguments
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            curve: CurveKind::Hilbert,
+            min_order: 1,
+            max_order: 8,
+            transform: Transform2D::IDENTITY,
+            format: Format::Unicode,
+            color: false,
         };
-        display[to_index(window[1])] = path;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+            let order = |value: String| {
+                value
+                    .parse::<u32>()
+                    .map_err(|e| format!("Invalid order {value:?}: {e}"))
+            };
+            match &arg[..] {
+                "--curve" => options.curve = value()?.parse().map_err(|e| format!("{e}"))?,
+                "--order" => {
+                    options.min_order = order(value()?)?;
+                    options.max_order = options.min_order;
+                }
+                "--min-order" => options.min_order = order(value()?)?,
+                "--max-order" => options.max_order = order(value()?)?,
+                "--swap" => options.transform.swap = true,
+                "--mirror-x" => options.transform.mirror_x = true,
+                "--mirror-y" => options.transform.mirror_y = true,
+                "--format" => {
+                    options.format = match &value()?[..] {
+                        "unicode" => Format::Unicode,
+                        "svg" => Format::Svg,
+                        other => return Err(format!("Unknown output format {other:?}")),
+                    }
+                }
+                "--color" => options.color = true,
+                "--help" => {
+                    println!("{USAGE}");
+                    process::exit(0);
+                }
+                other => return Err(format!("Unknown option {other:?}")),
+            }
+        }
+        if options.max_order > Coordinate::BITS {
+            return Err(format!(
+                "Orders above {} are not supported by this build",
+                Coordinate::BITS
+            ));
+        }
+        Ok(options)
     }
+}
 
-    // Display the curve
-    let display_string = display.into_iter().collect::<String>();
-    println!("{display_string}");
+/// Points of the curve of a certain order, with the requested orientation
+fn curve_points(options: &Options, order: u32) -> Vec<Coordinates2D> {
+    let transform = options.transform;
+    match options.curve {
+        CurveKind::Hilbert => hilbert::iter_2d_order_oriented(order, transform.into()).collect(),
+        CurveKind::Morton => {
+            let num_points = 1usize.checked_shl(2 * order).unwrap_or(usize::MAX);
+            morton::iter_2d()
+                .take(num_points)
+                .map(|coords| transform.apply(coords, order))
+                .collect()
+        }
+        kind => {
+            let points = match Coordinate::try_from(1u128 << order) {
+                Ok(side) => kind.iter_clipped_2d(side, side).collect::<Vec<_>>(),
+                Err(_) => kind.iter_2d().collect(),
+            };
+            points
+                .into_iter()
+                .map(|coords| transform.apply(coords, order))
+                .collect()
+        }
+    }
 }
 
-// Display the Hilbert curve at a few orders
+// Display a curve at each of the requested orders
 fn main() {
-    println!();
-    for order in 1..=8 {
-        print_hilbert(order);
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{e}\n\n{USAGE}");
+        process::exit(1);
+    });
+    let svg_options = SvgOptions {
+        color_by_index: options.color,
+        ..SvgOptions::default()
+    };
+    if options.format == Format::Unicode {
+        println!();
+    }
+    for order in options.min_order..=options.max_order {
+        let points = curve_points(&options, order);
+        match options.format {
+            Format::Unicode => {
+                // Print header
+                println!("--- At order {order} ---\n");
+
+                // Display the curve
+                let display_string = render::unicode(points);
+                println!("{display_string}");
+            }
+            Format::Svg => print!("{}", render::svg(points, &svg_options)),
+        }
     }
 }
@@ -0,0 +1,505 @@
+//! Tools for evaluating the quality of space-filling curves
+
+use crate::{bits, Coordinate, Coordinates2D, CurveIdx, NumBits, SpaceFillingCurve2D};
+
+/// Estimate the average number of clusters needed to cover a rectangular query
+///
+/// When points are stored in the order of a space-filling curve, fetching all
+/// points from an axis-aligned rectangular region of space amounts to scanning
+/// a certain number of contiguous curve index ranges, which we call clusters.
+/// The fewer clusters a query needs, the better, since every new cluster
+/// usually translates into a disk seek or cache miss. As a result, the average
+/// number of clusters per query is one of the best predictors of database scan
+/// performance.
+///
+/// This function considers the sub-curve made of the first 4^`order` points of
+/// a curve, which covers the square [0; 2^`order`[², and computes the average
+/// cluster count over all possible positions of a query within this square.
+/// The query size is drawn from a distribution specified as a list of
+/// ([width, height], weight) pairs, where weights need not be normalized.
+///
+/// The curve is specified through its index-to-coordinates mapping `decode`.
+/// If the sub-curve has no more than `max_samples` points, the result is
+/// computed exactly, otherwise it is estimated by sampling `max_samples` curve
+/// steps in a deterministic pseudo-random fashion.
+///
+/// If `query_sizes` is empty or all weights are zero, there are no queries to
+/// average over, and the result is 0.
+///
+pub fn average_cluster_count(
+    decode: impl Fn(CurveIdx) -> Coordinates2D,
+    order: NumBits,
+    query_sizes: &[([usize; 2], f64)],
+    max_samples: usize,
+) -> f64 {
+    assert!(
+        order <= bits::num_bits::<Coordinate>(),
+        "Curve order {order} is too high for this Coordinate type"
+    );
+    let side = 1u128 << order;
+    // At the highest order of 128-bit curves, the point count doesn't fit in
+    // a u128, but it is then larger than any sample count anyway
+    let num_points = side.checked_mul(side);
+    for &([width, height], weight) in query_sizes {
+        assert!(
+            (1..=side).contains(&(width as u128)) && (1..=side).contains(&(height as u128)),
+            "Query size {width}x{height} doesn't fit in a {side}x{side} domain"
+        );
+        assert!(weight >= 0.0, "Query size weights must be nonnegative");
+    }
+
+    // Every cluster starts with a point which belongs to the query, but whose
+    // predecessor on the curve (if any) doesn't. So we can count clusters by
+    // summing, for every curve step, the number of query positions which this
+    // step enters. This is cheap because the number of query positions that
+    // contain a set of points only depends on the points' bounding box.
+    let count_entered_queries = |prev: Option<Coordinates2D>, curr: Coordinates2D| {
+        query_sizes
+            .iter()
+            .map(|&([width, height], weight)| {
+                let size = [width as u128, height as u128];
+                let entered = num_containing_queries(side, size, curr, curr)
+                    - prev.map_or(0, |prev| {
+                        let min = [prev[0].min(curr[0]), prev[1].min(curr[1])];
+                        let max = [prev[0].max(curr[0]), prev[1].max(curr[1])];
+                        num_containing_queries(side, size, min, max)
+                    });
+                let num_queries = (side - size[0] + 1) as f64 * (side - size[1] + 1) as f64;
+                weight * entered as f64 / num_queries
+            })
+            .sum::<f64>()
+    };
+
+    // Sum the contribution of all curve steps, or estimate that sum
+    let total_clusters = match num_points {
+        Some(num_points) if num_points <= max_samples as u128 => {
+            let mut prev = None;
+            let mut total = 0.0;
+            for idx in 0..num_points {
+                let curr = decode(idx as CurveIdx);
+                total += count_entered_queries(prev, curr);
+                prev = Some(curr);
+            }
+            total
+        }
+        _ => {
+            let mut rng_state = 0x9E37_79B9_7F4A_7C15_u64;
+            let mut total = 0.0;
+            for _ in 0..max_samples {
+                // Larger curves need more random bits to sample all indices
+                let mut random = xorshift64(&mut rng_state) as u128;
+                if num_points.map_or(true, |n| n > 1 << 64) {
+                    random = (random << 64) | xorshift64(&mut rng_state) as u128;
+                }
+                let idx = num_points.map_or(random, |n| random % n) as CurveIdx;
+                let prev = idx.checked_sub(1).map(&decode);
+                total += count_entered_queries(prev, decode(idx));
+            }
+            total * (side as f64 * side as f64) / max_samples as f64
+        }
+    };
+    let total_weight = query_sizes
+        .iter()
+        .map(|(_size, weight)| weight)
+        .sum::<f64>();
+    if total_weight == 0.0 {
+        0.0
+    } else {
+        total_clusters / total_weight
+    }
+}
+
+/// Summary of a locality metric over a set of point pairs or curve segments
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LocalityStats {
+    /// Average value of the metric, or 0 if there is nothing to measure
+    pub average: f64,
+
+    /// Maximal value of the metric, or 0 if there is nothing to measure
+    pub max: f64,
+}
+//
+impl LocalityStats {
+    /// Summarize a sequence of metric values
+    fn from_values(values: impl Iterator<Item = f64>) -> Self {
+        let mut count = 0u128;
+        let mut total = 0.0;
+        let mut max = 0.0f64;
+        for value in values {
+            count += 1;
+            total += value;
+            max = max.max(value);
+        }
+        Self {
+            average: if count == 0 {
+                0.0
+            } else {
+                total / count as f64
+            },
+            max,
+        }
+    }
+}
+
+/// Measure how far apart spatial neighbors are on a curve
+///
+/// This considers the sub-curve made of the first 4^`order` points of a curve,
+/// which must cover the square [0; 2^`order`[² as with the Morton and Hilbert
+/// curves, and computes the distance between the curve indices of every pair of
+/// horizontally or vertically adjacent points of this square.
+///
+/// Large index distances between spatial neighbors mean that stencil
+/// computations, which access neighboring points together, will access distant
+/// memory locations when data is stored in curve order.
+///
+/// # Panics
+///
+/// If the sub-curve goes outside of the square, as happens with the snake and
+/// spiral orders below the highest order.
+///
+pub fn spatial_neighbor_index_distance<Curve: SpaceFillingCurve2D>(
+    order: NumBits,
+) -> LocalityStats {
+    let side = 1u128 << order;
+    let distances = square_prefix::<Curve>(order).flat_map(move |([x, y], idx)| {
+        let idx = idx as CurveIdx;
+        let right = (x as u128 + 1 < side).then(|| [x + 1, y]);
+        let up = (y as u128 + 1 < side).then(|| [x, y + 1]);
+        right.into_iter().chain(up).map(move |neighbor| {
+            let neighbor_idx = Curve::encode(neighbor);
+            (neighbor_idx.max(idx) - neighbor_idx.min(idx)) as f64
+        })
+    });
+    LocalityStats::from_values(distances)
+}
+
+/// Measure how far apart consecutive points of a curve are in space
+///
+/// This considers the same sub-curve as `spatial_neighbor_index_distance()`,
+/// and computes the Manhattan distance between the points of every pair of
+/// consecutive curve indices. Curves that never jump, like the Hilbert curve,
+/// have a distance of 1 everywhere.
+///
+/// # Panics
+///
+/// If the sub-curve goes outside of the square, see
+/// `spatial_neighbor_index_distance()`.
+///
+pub fn index_neighbor_spatial_distance<Curve: SpaceFillingCurve2D>(
+    order: NumBits,
+) -> LocalityStats {
+    let mut prev = None;
+    let distances = square_prefix::<Curve>(order).filter_map(move |(curr, _idx)| {
+        let distance = prev.map(|prev: Coordinates2D| {
+            let axis_distance =
+                |axis: usize| (prev[axis].max(curr[axis]) - prev[axis].min(curr[axis])) as f64;
+            axis_distance(0) + axis_distance(1)
+        });
+        prev = Some(curr);
+        distance
+    });
+    LocalityStats::from_values(distances)
+}
+
+/// Measure how spread out in space the segments of a curve are
+///
+/// This considers the same sub-curve as `spatial_neighbor_index_distance()`,
+/// and computes the dilation of every segment of `segment_len` consecutive
+/// points, i.e. the ratio of the area of the segment's bounding box to its
+/// number of points. A dilation of 1 means that the segment is a rectangle,
+/// while larger dilations mean that fetching the bounding box of a segment
+/// touches many points that are outside of it, as happens when a cache
+/// block is a curve segment.
+///
+/// The cost of this computation is proportional to 4^`order` x `segment_len`.
+///
+/// # Panics
+///
+/// If `segment_len` is zero or larger than the number of points of the
+/// sub-curve, or if the sub-curve goes outside of the square, see
+/// `spatial_neighbor_index_distance()`.
+///
+pub fn segment_dilation<Curve: SpaceFillingCurve2D>(
+    order: NumBits,
+    segment_len: usize,
+) -> LocalityStats {
+    let num_points = check_order(order);
+    // Segments are decoded separately, so check the whole sub-curve first
+    square_prefix::<Curve>(order).for_each(drop);
+    assert!(
+        (1..=num_points).contains(&(segment_len as u128)),
+        "Segment length {segment_len} doesn't fit in a curve of order {order}"
+    );
+    let dilations = (0..=num_points - segment_len as u128).map(|start| {
+        let mut points = Curve::iter_from(start as CurveIdx).take(segment_len);
+        let first = points.next().expect("Segments are not empty");
+        let [min, max] = points.fold([first, first], |[min, max], [x, y]| {
+            [
+                [min[0].min(x), min[1].min(y)],
+                [max[0].max(x), max[1].max(y)],
+            ]
+        });
+        let area = (max[0] as u128 - min[0] as u128 + 1) * (max[1] as u128 - min[1] as u128 + 1);
+        area as f64 / segment_len as f64
+    });
+    LocalityStats::from_values(dilations)
+}
+
+/// Check that a curve order is supported, and return the number of points of
+/// the corresponding sub-curve
+fn check_order(order: NumBits) -> u128 {
+    assert!(
+        order <= bits::num_bits::<Coordinate>(),
+        "Curve order {order} is too high for this Coordinate type"
+    );
+    1u128 << (2 * order)
+}
+
+/// Iterate over the first 4^`order` points of a curve and their indices,
+/// checking that they stay within the square [0; 2^`order`[²
+///
+/// The locality metrics assume that this sub-curve covers the square, which
+/// is true of quadtree-based curves like Morton and Hilbert, but not of the
+/// snake and spiral orders, which only cover a square at the highest order.
+///
+fn square_prefix<Curve: SpaceFillingCurve2D>(
+    order: NumBits,
+) -> impl Iterator<Item = (Coordinates2D, u128)> {
+    let num_points = check_order(order);
+    let side = 1u128 << order;
+    Curve::iter()
+        .zip(0..num_points)
+        .inspect(move |&([x, y], idx)| {
+            assert!(
+                (x as u128) < side && (y as u128) < side,
+                "Point {idx} of the curve is outside of the order {order} square"
+            );
+        })
+}
+
+/// Count how many queries of a certain size contain a certain bounding box
+///
+/// Queries must lie within the square [0; `side`[², and the bounding box is
+/// specified by its minimal and maximal coordinates along each axis.
+///
+fn num_containing_queries(
+    side: u128,
+    [width, height]: [u128; 2],
+    min: Coordinates2D,
+    max: Coordinates2D,
+) -> u128 {
+    let num_positions = |size: u128, min: Coordinate, max: Coordinate| {
+        let first_start = (max as u128 + 1).saturating_sub(size);
+        let last_start = (min as u128).min(side - size);
+        (last_start + 1).saturating_sub(first_start)
+    };
+    num_positions(width, min[0], max[0]) * num_positions(height, min[1], max[1])
+}
+
+/// Minimal pseudo-random number generator used for sampling
+///
+/// Statistical quality is not a concern here, we only need to avoid aliasing
+/// with the regular structure of space-filling curves, and to produce
+/// reproducible results.
+///
+fn xorshift64(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bits::test_utils::MAX_TESTED_ORDER, hilbert, morton};
+
+    // Count clusters the dumb way, by enumerating query positions and scanning
+    // the whole curve for each of them
+    fn brute_force_cluster_count(
+        decode: impl Fn(CurveIdx) -> Coordinates2D,
+        order: NumBits,
+        query_sizes: &[([usize; 2], f64)],
+    ) -> f64 {
+        let side = 1usize << order;
+        let points = (0..side * side)
+            .map(|idx| decode(idx as CurveIdx))
+            .collect::<Vec<_>>();
+        let mut total = 0.0;
+        for &([width, height], weight) in query_sizes {
+            let mut num_clusters = 0;
+            let mut num_queries = 0;
+            for x_start in 0..=(side - width) {
+                for y_start in 0..=(side - height) {
+                    let contains = |[x, y]: Coordinates2D| {
+                        (x_start..x_start + width).contains(&(x as usize))
+                            && (y_start..y_start + height).contains(&(y as usize))
+                    };
+                    let mut inside = false;
+                    for &point in &points {
+                        let new_inside = contains(point);
+                        num_clusters += (new_inside && !inside) as usize;
+                        inside = new_inside;
+                    }
+                    num_queries += 1;
+                }
+            }
+            total += weight * num_clusters as f64 / num_queries as f64;
+        }
+        total
+            / query_sizes
+                .iter()
+                .map(|(_size, weight)| weight)
+                .sum::<f64>()
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance * expected.abs(),
+            "Expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn exact() {
+        let query_sizes = [([1, 1], 1.0), ([2, 3], 2.0), ([3, 2], 0.5), ([5, 4], 1.0)];
+        for order in 3..=4 {
+            for decode in [morton::decode_2d, hilbert::decode_2d] {
+                assert_close(
+                    average_cluster_count(decode, order, &query_sizes, usize::MAX),
+                    brute_force_cluster_count(decode, order, &query_sizes),
+                    1e-12,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trivial_queries() {
+        let order = MAX_TESTED_ORDER;
+        let side = 1 << order;
+        for decode in [morton::decode_2d, hilbert::decode_2d] {
+            for size in [[1, 1], [side, side]] {
+                assert_close(
+                    average_cluster_count(decode, order, &[(size, 1.0)], usize::MAX),
+                    1.0,
+                    1e-12,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zero_weight() {
+        assert_eq!(
+            average_cluster_count(morton::decode_2d, 3, &[], usize::MAX),
+            0.0
+        );
+        assert_eq!(
+            average_cluster_count(hilbert::decode_2d, 3, &[([2, 2], 0.0)], 1 << 4),
+            0.0
+        );
+    }
+
+    #[test]
+    fn sampled() {
+        let order = MAX_TESTED_ORDER;
+        let query_sizes = [([4, 4], 1.0), ([16, 3], 1.0)];
+        for decode in [morton::decode_2d, hilbert::decode_2d] {
+            assert_close(
+                average_cluster_count(decode, order, &query_sizes, 1 << 14),
+                average_cluster_count(decode, order, &query_sizes, usize::MAX),
+                0.05,
+            );
+        }
+    }
+
+    #[test]
+    fn locality_stats() {
+        use crate::{Hilbert2D, Morton2D};
+        let stats = |average, max| LocalityStats { average, max };
+
+        // The Morton curve goes through [0, 0], [1, 0], [0, 1] and [1, 1]
+        assert_eq!(
+            spatial_neighbor_index_distance::<Morton2D>(1),
+            stats(1.5, 2.0)
+        );
+        assert_eq!(
+            index_neighbor_spatial_distance::<Morton2D>(1),
+            stats(4.0 / 3.0, 2.0)
+        );
+        assert_eq!(segment_dilation::<Morton2D>(1, 2), stats(4.0 / 3.0, 2.0));
+
+        // The Hilbert curve goes through [0, 0], [1, 0], [1, 1] and [0, 1]
+        assert_eq!(
+            spatial_neighbor_index_distance::<Hilbert2D>(1),
+            stats(1.5, 3.0)
+        );
+        assert_eq!(segment_dilation::<Hilbert2D>(1, 2), stats(1.0, 1.0));
+
+        // The Hilbert curve never jumps, and whole curves fill their square
+        for order in 0..=4 {
+            let expected = if order == 0 { 0.0 } else { 1.0 };
+            assert_eq!(
+                index_neighbor_spatial_distance::<Hilbert2D>(order),
+                stats(expected, expected)
+            );
+            for segment_len in [1, 1 << (2 * order)] {
+                assert_eq!(
+                    segment_dilation::<Morton2D>(order, segment_len),
+                    stats(1.0, 1.0)
+                );
+            }
+        }
+        assert_eq!(
+            spatial_neighbor_index_distance::<Morton2D>(0),
+            stats(0.0, 0.0)
+        );
+
+        // Hilbert has better locality than Morton at higher orders
+        let order = 4;
+        assert!(
+            index_neighbor_spatial_distance::<Hilbert2D>(order).max
+                < index_neighbor_spatial_distance::<Morton2D>(order).max
+        );
+        assert!(
+            segment_dilation::<Hilbert2D>(order, 16).average
+                < segment_dilation::<Morton2D>(order, 16).average
+        );
+    }
+
+    // The snake order only covers a square at the highest order, where it
+    // never jumps and has rows of consecutive indices. Going through the whole
+    // curve is only tractable with 16-bit curve indices.
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn snake_locality() {
+        use crate::Snake2D;
+        let order = bits::num_bits::<Coordinate>();
+        let side = (1u128 << order) as f64;
+        assert_eq!(
+            index_neighbor_spatial_distance::<Snake2D>(order),
+            LocalityStats {
+                average: 1.0,
+                max: 1.0
+            }
+        );
+        assert_eq!(
+            spatial_neighbor_index_distance::<Snake2D>(order).max,
+            2.0 * side - 1.0
+        );
+        assert_eq!(segment_dilation::<Snake2D>(order, 1).max, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn snake_locality_below_max_order() {
+        spatial_neighbor_index_distance::<crate::Snake2D>(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn spiral_locality_below_max_order() {
+        segment_dilation::<crate::Spiral2D>(2, 4);
+    }
+}
@@ -0,0 +1,33 @@
+//! CPU feature detection
+//!
+//! Without the `std` feature, runtime CPU feature detection is not available,
+//! so these functions can only report the target features that were enabled
+//! at compile time (e.g. via `-C target-cpu=native`).
+
+/// Truth that the CPU supports the BMI2 instruction set extension
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(crate) fn has_bmi2() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("bmi2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "bmi2")
+    }
+}
+
+/// Truth that the CPU supports the AVX2 instruction set extension
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub(crate) fn has_avx2() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
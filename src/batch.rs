@@ -0,0 +1,183 @@
+//! Batch processing utilities
+//!
+//! The curve algorithms only use bitwise operations and constant shifts, so
+//! applying them to consecutive inputs is easily auto-vectorized by compilers.
+//! However, the width of the SIMD vectors that can be used is limited by the
+//! target's baseline instruction set (e.g. 128-bit SSE2 on x86_64). So on
+//! x86_64, we check at runtime if AVX2 is available, and if so use a version
+//! of the batch processing loop that is compiled with 256-bit vectors enabled.
+
+/// Apply a function to a batch of inputs, writing the results to a batch of
+/// outputs, in a way that allows the compiler to use SIMD instructions
+///
+/// # Panics
+///
+/// If `inputs` and `outputs` do not have the same length.
+///
+#[inline]
+pub(crate) fn map<In: Copy, Out>(inputs: &[In], outputs: &mut [Out], f: impl Fn(In) -> Out) {
+    check_lengths(inputs.len(), outputs.len());
+    dispatch(
+        #[inline(always)]
+        || {
+            for (&input, output) in inputs.iter().zip(outputs) {
+                *output = f(input);
+            }
+        },
+    )
+}
+
+/// Like `map()`, but the function produces a pair of outputs, which are
+/// written to two separate output batches (structure-of-arrays layout)
+///
+/// # Panics
+///
+/// If the input and output batches do not have the same length.
+///
+#[inline]
+pub(crate) fn map_to_soa<In: Copy, Out>(
+    inputs: &[In],
+    [outputs1, outputs2]: [&mut [Out]; 2],
+    f: impl Fn(In) -> [Out; 2],
+) {
+    check_lengths(inputs.len(), outputs1.len());
+    check_lengths(inputs.len(), outputs2.len());
+    dispatch(
+        #[inline(always)]
+        || {
+            for ((&input, output1), output2) in inputs.iter().zip(outputs1).zip(outputs2) {
+                let [out1, out2] = f(input);
+                *output1 = out1;
+                *output2 = out2;
+            }
+        },
+    )
+}
+
+/// Like `map()`, but the function takes a pair of inputs, which are read from
+/// two separate input batches (structure-of-arrays layout)
+///
+/// # Panics
+///
+/// If the input and output batches do not have the same length.
+///
+#[inline]
+pub(crate) fn map_from_soa<In: Copy, Out>(
+    [inputs1, inputs2]: [&[In]; 2],
+    outputs: &mut [Out],
+    f: impl Fn([In; 2]) -> Out,
+) {
+    check_lengths(inputs1.len(), outputs.len());
+    check_lengths(inputs2.len(), outputs.len());
+    dispatch(
+        #[inline(always)]
+        || {
+            for ((&input1, &input2), output) in inputs1.iter().zip(inputs2).zip(outputs) {
+                *output = f([input1, input2]);
+            }
+        },
+    )
+}
+
+/// Number of inputs that `map_bulk()` hands over to each parallel task
+///
+/// This is large enough to amortize the overhead of rayon task spawning.
+///
+#[cfg(feature = "rayon")]
+const BULK_TASK_LEN: usize = 1 << 16;
+
+/// Like `map()`, but for large batches, which are split into chunks that are
+/// processed in parallel when the `rayon` feature is enabled
+///
+/// # Panics
+///
+/// If `inputs` and `outputs` do not have the same length.
+///
+#[inline]
+pub(crate) fn map_bulk<In, Out>(
+    inputs: &[In],
+    outputs: &mut [Out],
+    f: impl Fn(In) -> Out + Send + Sync,
+) where
+    In: Copy + Sync,
+    Out: Send,
+{
+    check_lengths(inputs.len(), outputs.len());
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        inputs
+            .par_chunks(BULK_TASK_LEN)
+            .zip(outputs.par_chunks_mut(BULK_TASK_LEN))
+            .for_each(|(inputs, outputs)| map(inputs, outputs, &f));
+    }
+    #[cfg(not(feature = "rayon"))]
+    map(inputs, outputs, f)
+}
+
+/// Number of inputs that `map_stream()` buffers before processing them
+///
+/// This is large enough to amortize the overhead of SIMD dispatch, and small
+/// enough for the buffers to stay in the L1 cache.
+///
+pub(crate) const STREAM_CHUNK_LEN: usize = 1024;
+
+/// Like `map()`, but the inputs come from an iterator, and are processed in
+/// chunks of `STREAM_CHUNK_LEN` elements whose outputs are passed to `sink`
+///
+/// All chunks but the last one are full, and the last one is not empty.
+///
+#[inline]
+pub(crate) fn map_stream<In: Copy + Default, Out: Copy + Default>(
+    inputs: impl IntoIterator<Item = In>,
+    mut sink: impl FnMut(&[Out]),
+    f: impl Fn(In) -> Out,
+) {
+    let mut inputs = inputs.into_iter();
+    let mut input_buf = [In::default(); STREAM_CHUNK_LEN];
+    let mut output_buf = [Out::default(); STREAM_CHUNK_LEN];
+    loop {
+        let mut len = 0;
+        for (slot, input) in input_buf.iter_mut().zip(&mut inputs) {
+            *slot = input;
+            len += 1;
+        }
+        if len == 0 {
+            return;
+        }
+        map(&input_buf[..len], &mut output_buf[..len], &f);
+        sink(&output_buf[..len]);
+        if len < STREAM_CHUNK_LEN {
+            return;
+        }
+    }
+}
+
+/// Check that the input and output batches have the same length
+#[inline(always)]
+fn check_lengths(input_len: usize, output_len: usize) {
+    assert_eq!(
+        input_len, output_len,
+        "Input and output batches should have the same length"
+    );
+}
+
+/// Run a batch processing loop with the widest available SIMD instruction set
+#[inline(always)]
+fn dispatch(kernel: impl FnOnce()) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if crate::arch::has_avx2() {
+            // Safe because we just checked that the CPU supports AVX2
+            return unsafe { dispatch_avx2(kernel) };
+        }
+    }
+    kernel()
+}
+
+/// Run a batch processing loop, compiled with AVX2 support
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn dispatch_avx2(kernel: impl FnOnce()) {
+    kernel()
+}
@@ -1,6 +1,52 @@
 //! Binary arithmetic utilities used for space-filling curve computations
+//!
+//! The execution time of these functions only depends on their bit length
+//! parameters, never on the bits of their integer inputs, so that the curve
+//! computations which are built upon them can run in constant time.
+//!
+//! These primitives are exposed for the benefit of people who implement their
+//! own curve variants, and are available for all unsigned integer types from
+//! `u8` to `u128`. The functions without a suffix operate on the `CurveIdx`
+//! type (masks and ranges) or on the `Coordinate` type (scans and swaps), while
+//! e.g. `low_order_mask_u64()` and `bitwise_scan_u64()` operate on `u64`.
 
-use crate::{Coordinate, CurveIdx, NumBits};
+use crate::NumBits;
+
+/// Bitwise operation that a scan applies
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ScanOp {
+    /// Bitwise AND, whose scans tell if all bits so far are set
+    And,
+
+    /// Bitwise OR, whose scans tell if any bit so far is set
+    Or,
+
+    /// Bitwise XOR, whose scans compute the parity of the bits so far
+    Xor,
+}
+
+/// Order in which a scan goes through the bits of an integer
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ScanDirection {
+    /// From the high-order bits to the low-order bits
+    LeftToRight,
+
+    /// From the low-order bits to the high-order bits
+    RightToLeft,
+}
+
+/// Whether each output bit of a scan accounts for the matching input bit
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ScanKind {
+    /// Each output bit accounts for the input bits up to and including the
+    /// matching input bit
+    Inclusive,
+
+    /// Each output bit only accounts for the input bits before the matching
+    /// input bit, and the first output bit is the neutral element of the scan
+    /// operation
+    Exclusive,
+}
 
 /// Count the number of bits of an integer
 #[inline(always)]
@@ -9,99 +55,436 @@ pub const fn num_bits<T>() -> NumBits {
     (core::mem::size_of::<T>() * 8) as _
 }
 
-/// Generate a mask that selects a certain number of low-order bits: 0000...0011
-#[inline(always)]
-pub const fn low_order_mask(length: NumBits) -> CurveIdx {
-    assert!(length <= num_bits::<CurveIdx>());
-    if length < num_bits::<CurveIdx>() {
-        (1 << length) - 1
-    } else {
-        CurveIdx::MAX
-    }
+/// Generate binary arithmetic utilities for a certain unsigned integer type
+///
+/// Ideally, these utilities would be generic over the integer types that they
+/// operate on, but that is not possible in const fn yet. So instead, we
+/// generate one module per unsigned integer type, containing utilities that
+/// operate on that type.
+///
+macro_rules! uint_bits_impl {
+    ($uint:ty) => {
+        pub use $crate::bits::num_bits;
+        use $crate::NumBits;
+
+        type Uint = $uint;
+
+        /// Generate a mask that selects a certain number of low-order bits: 0000...0011
+        ///
+        /// # Panics
+        ///
+        /// If `length` is larger than the number of bits of the integer type. See
+        /// `checked_low_order_mask()` for a non-panicking alternative.
+        ///
+        #[inline(always)]
+        pub const fn low_order_mask(length: NumBits) -> Uint {
+            match checked_low_order_mask(length) {
+                Some(mask) => mask,
+                None => panic!("Mask is longer than the integer type"),
+            }
+        }
+
+        /// Like `low_order_mask()`, but returns `None` if `length` is larger than
+        /// the number of bits of the integer type
+        #[inline(always)]
+        pub const fn checked_low_order_mask(length: NumBits) -> Option<Uint> {
+            if length < num_bits::<Uint>() {
+                Some((1 << length) - 1)
+            } else if length == num_bits::<Uint>() {
+                Some(Uint::MAX)
+            } else {
+                None
+            }
+        }
+
+        /// Generate a mask with an alternating "striped" bit pattern: 00110011...0011
+        ///
+        /// # Panics
+        ///
+        /// If `stripe_length` is zero, or is not smaller than the number of bits of
+        /// the integer type. See `checked_striped_mask()` for a non-panicking
+        /// alternative.
+        ///
+        #[inline(always)]
+        pub const fn striped_mask(stripe_length: NumBits) -> Uint {
+            match checked_striped_mask(stripe_length) {
+                Some(stripes) => stripes,
+                None => panic!("Stripe length must be nonzero and smaller than the integer type"),
+            }
+        }
+
+        /// Like `striped_mask()`, but returns `None` if `stripe_length` is zero, or
+        /// is not smaller than the number of bits of the integer type
+        #[inline(always)]
+        pub const fn checked_striped_mask(stripe_length: NumBits) -> Option<Uint> {
+            if stripe_length == 0 || stripe_length >= num_bits::<Uint>() {
+                return None;
+            }
+            let mut stripes = low_order_mask(stripe_length);
+            let mut curr_length = 2 * stripe_length;
+            while curr_length < num_bits::<Uint>() {
+                // Iteration 0: 00...00000000000000011
+                // Iteration 1: 00...00000000000110011
+                // Iteration 2: 00...00011001100110011
+                stripes |= stripes << curr_length;
+                curr_length *= 2;
+            }
+            Some(stripes)
+        }
+
+        /// Compute a scan of an integer's bits
+        ///
+        /// Given an integer with bits [ x1 x2 x3 ... ] and a bitwise operation
+        /// `op`, a left-to-right inclusive scan produces an integer with bits
+        /// [ x1  x1 op x2  x1 op x2 op x3 ... ], and the matching exclusive scan
+        /// produces an integer with bits [ e  x1  x1 op x2 ... ], where e is
+        /// the neutral element of `op` (1 for AND, 0 for OR and XOR).
+        /// Right-to-left scans go through the bits in the opposite order.
+        ///
+        #[inline(always)]
+        pub const fn bitwise_scan(
+            mut bits: Uint,
+            op: $crate::bits::ScanOp,
+            direction: $crate::bits::ScanDirection,
+            kind: $crate::bits::ScanKind,
+        ) -> Uint {
+            use $crate::bits::{ScanKind, ScanOp};
+
+            // An exclusive scan is an inclusive scan of the input bits shifted by
+            // one position, where the vacated bit is the neutral element.
+            if let ScanKind::Exclusive = kind {
+                bits = shift_for_scan(bits, 1, op, direction);
+            }
+
+            // This is a bitwise implementation of the Hillis/Steele parallel
+            // inclusive scan algorithm. For a left-to-right XOR scan, it goes as
+            // follows:
+            //
+            // Iteration 0: [ x1     x2        x3           x4           x5 ... ]
+            // Iteration 1: [ x1  x1^x2     x2^x3        x3^x4        x4^x5 ... ]
+            // Iteration 2: [ x1  x1^x2  x1^x2^x3  x1^x2^x3^x4  x2^x3^x4^x5 ... ]
+            let mut stride = 1;
+            while stride < num_bits::<Uint>() {
+                let shifted = shift_for_scan(bits, stride, op, direction);
+                bits = match op {
+                    ScanOp::And => bits & shifted,
+                    ScanOp::Or => bits | shifted,
+                    ScanOp::Xor => bits ^ shifted,
+                };
+                stride *= 2;
+            }
+            bits
+        }
+
+        /// Shift an integer's bits by `amount` positions in the direction of a
+        /// scan, filling the vacated bits with the neutral element of the scan
+        /// operation
+        #[inline(always)]
+        const fn shift_for_scan(
+            bits: Uint,
+            amount: NumBits,
+            op: $crate::bits::ScanOp,
+            direction: $crate::bits::ScanDirection,
+        ) -> Uint {
+            use $crate::bits::{ScanDirection, ScanOp};
+            let (shifted, vacated) = match direction {
+                ScanDirection::LeftToRight => (bits >> amount, !(Uint::MAX >> amount)),
+                ScanDirection::RightToLeft => (bits << amount, !(Uint::MAX << amount)),
+            };
+            match op {
+                ScanOp::And => shifted | vacated,
+                ScanOp::Or | ScanOp::Xor => shifted,
+            }
+        }
+
+        /// Compute the left-to-right inclusive XOR scan of an integer's bits
+        ///
+        /// Given an integer with bits [ x1 x2 x3 ... ], this produces another integer
+        /// with bits [ x1  x1^x2  x1^x2^x3 ... ]. See `bitwise_scan()` for other
+        /// scans.
+        ///
+        #[inline(always)]
+        pub const fn bitwise_xor_ltr_inclusive_scan(bits: Uint) -> Uint {
+            bitwise_scan(
+                bits,
+                $crate::bits::ScanOp::Xor,
+                $crate::bits::ScanDirection::LeftToRight,
+                $crate::bits::ScanKind::Inclusive,
+            )
+        }
+
+        /// Compute the left-to-right exclusive XOR scan of an integer's bits
+        ///
+        /// Given an integer with bits [ x1 x2 x3 x4 ... ], this produces another
+        /// integer with bits [ 0  x1  x1^x2  x1^x2^x3 ... ]. See `bitwise_scan()`
+        /// for other scans.
+        ///
+        #[inline(always)]
+        pub const fn bitwise_xor_ltr_exclusive_scan(bits: Uint) -> Uint {
+            bitwise_scan(
+                bits,
+                $crate::bits::ScanOp::Xor,
+                $crate::bits::ScanDirection::LeftToRight,
+                $crate::bits::ScanKind::Exclusive,
+            )
+        }
+
+        /// Conditionally swap two integers' bits according to a mask
+        ///
+        /// Given an integer A with bits [ a1 a2 ... aN ], an integer B with bits
+        /// [ b1 b2 ... bN ], and a mask with bits [ m1 m2 ... mN ], this function
+        /// produces two integers:
+        ///
+        /// - One whose bits are equal to ai where the corresponding mask bit mi is
+        ///   false and to bi where mi is true.
+        /// - One whose bits are conversely equal to bi where the corresponding mask
+        ///   bit mi is false and to ai where mi is true.
+        ///
+        #[inline(always)]
+        pub const fn bitwise_swaps(swap_mask: Uint, src1: Uint, src2: Uint) -> [Uint; 2] {
+            let same_mask = !swap_mask;
+            let res1 = (src1 & same_mask) | (src2 & swap_mask);
+            let res2 = (src2 & same_mask) | (src1 & swap_mask);
+            [res1, res2]
+        }
+
+        /// Convert any range of integers into the equivalent inclusive range
+        ///
+        /// Empty ranges, including those that cannot be expressed as a start and an
+        /// end integer (e.g. `..0`), are mapped to an empty inclusive range.
+        ///
+        #[inline]
+        pub fn inclusive_range(
+            range: impl core::ops::RangeBounds<Uint>,
+        ) -> core::ops::RangeInclusive<Uint> {
+            use core::ops::Bound;
+            let start = match range.start_bound() {
+                Bound::Included(&start) => Some(start),
+                Bound::Excluded(&start) => start.checked_add(1),
+                Bound::Unbounded => Some(Uint::MIN),
+            };
+            let end = match range.end_bound() {
+                Bound::Included(&end) => Some(end),
+                Bound::Excluded(&end) => end.checked_sub(1),
+                Bound::Unbounded => Some(Uint::MAX),
+            };
+            match (start, end) {
+                (Some(start), Some(end)) => start..=end,
+                #[allow(clippy::reversed_empty_ranges)]
+                _ => 1..=0,
+            }
+        }
+    };
 }
 
-/// Generate a mask with an alternating "striped" bit pattern: 00110011...0011
-#[inline(always)]
-pub const fn striped_mask(stripe_length: NumBits) -> CurveIdx {
-    assert!(stripe_length != 0 && stripe_length < num_bits::<CurveIdx>());
-    let mut stripes = low_order_mask(stripe_length);
-    let mut curr_length = 2 * stripe_length;
-    while curr_length < num_bits::<CurveIdx>() {
-        // Iteration 0: 00...00000000000000011
-        // Iteration 1: 00...00000000000110011
-        // Iteration 2: 00...00011001100110011
-        stripes |= stripes << curr_length;
-        curr_length *= 2;
-    }
-    stripes
+/// Binary arithmetic utilities for 8-bit integers
+pub(crate) mod uint8 {
+    uint_bits_impl!(u8);
 }
 
-/// Compute the left-to-right inclusive XOR scan of an integer's bits
-///
-/// Given an integer with bits [ x1 x2 x3 ... ], this produces another integer
-/// with bits [ x1  x1^x2  x1^x2^x3 ... ].
-///
-// FIXME: Extract common inclusive/exclusive scan routines once const fn
-//        supports that (requires at least function pointers, ideally traits)
-//
-#[inline(always)]
-pub const fn bitwise_xor_ltr_inclusive_scan(mut bits: Coordinate) -> Coordinate {
-    // This is a bitwise implementation of the Hillis/Steele parallel inclusive
-    // scan algorithm. It can be trivially generalized to right-to-left scans or
-    // other bitwise operations if there is demand.
-    let mut stride = 1;
-    while stride < num_bits::<Coordinate>() {
-        // Iteration 0: [ x1     x2        x3           x4           x5 ... ]
-        // Iteration 1: [ x1  x1^x2     x2^x3        x3^x4        x4^x5 ... ]
-        // Iteration 2: [ x1  x1^x2  x1^x2^x3  x1^x2^x3^x4  x2^x3^x4^x5 ... ]
-        bits ^= bits >> stride;
-        stride *= 2;
-    }
-    bits
-}
-
-/// Compute the left-to-right exclusive XOR scan of an integer's bits
-///
-/// Given an integer with bits [ x1 x2 x3 x4 ... ], this produces another
-/// integer with bits [ 0  x1  x1^x2  x1^x2^x3 ... ].
-///
-// FIXME: Extract common inclusive/exclusive scan routines once const fn
-//        supports that (requires at least function pointers, ideally traits)
-//
-#[inline(always)]
-pub const fn bitwise_xor_ltr_exclusive_scan(bits: Coordinate) -> Coordinate {
-    bitwise_xor_ltr_inclusive_scan(bits >> 1)
+/// Binary arithmetic utilities for 16-bit integers
+pub(crate) mod uint16 {
+    uint_bits_impl!(u16);
 }
 
-/// Conditionally swap two integers' bits according to a mask
-///
-/// Given an integer A with bits [ a1 a2 ... aN ], an integer B with bits
-/// [ b1 b2 ... bN ], and a mask with bits [ m1 m2 ... mN ], this function
-/// produces two integers:
+/// Binary arithmetic utilities for 32-bit integers
+pub(crate) mod uint32 {
+    uint_bits_impl!(u32);
+}
+
+/// Binary arithmetic utilities for 64-bit integers
+pub(crate) mod uint64 {
+    uint_bits_impl!(u64);
+}
+
+/// Binary arithmetic utilities for 128-bit integers
+pub(crate) mod uint128 {
+    uint_bits_impl!(u128);
+}
+
+/// Generate binary arithmetic utilities for a certain curve index type
 ///
-/// - One whose bits are equal to ai where the corresponding mask bit mi is
-///   false and to bi where mi is true.
-/// - One whose bits are conversely equal to bi where the corresponding mask
-///   bit mi is false and to ai where mi is true.
+/// This gathers the utilities for the curve index type and the matching
+/// coordinate type, and adds some utilities that are specific to curve
+/// indices.
 ///
-#[inline(always)]
-pub const fn bitwise_swaps(
-    swap_mask: Coordinate,
-    src1: Coordinate,
-    src2: Coordinate,
-) -> [Coordinate; 2] {
-    let same_mask = !swap_mask;
-    let res1 = (src1 & same_mask) | (src2 & swap_mask);
-    let res2 = (src2 & same_mask) | (src1 & swap_mask);
-    [res1, res2]
+macro_rules! bits_impl {
+    ($curve_idx:ty, $curve_idx_bits:ident, $coordinate_bits:ident) => {
+        // Not every curve uses every utility at every width, but the default
+        // width must provide all of them for the public interface
+        #[allow(unused_imports)]
+        pub use super::$coordinate_bits::{
+            bitwise_scan, bitwise_swaps, bitwise_xor_ltr_exclusive_scan,
+            bitwise_xor_ltr_inclusive_scan,
+        };
+        #[allow(unused_imports)]
+        pub use super::$curve_idx_bits::{checked_low_order_mask, checked_striped_mask};
+        pub use super::$curve_idx_bits::{inclusive_range, low_order_mask, num_bits, striped_mask};
+
+        type CurveIdx = $curve_idx;
+
+        /// Extract the bits of `src` selected by `mask` into the low-order bits of
+        /// the result, using the BMI2 PEXT instruction
+        ///
+        /// PEXT operates on at most 64 bits, so wider integers are processed in
+        /// 64-bit chunks.
+        ///
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "bmi2")]
+        #[inline]
+        pub(crate) unsafe fn pext(src: CurveIdx, mask: CurveIdx) -> CurveIdx {
+            use core::arch::x86_64::_pext_u64;
+            let mut result = 0;
+            let mut num_extracted = 0;
+            let mut chunk_start = 0;
+            while chunk_start < num_bits::<CurveIdx>() {
+                let mask_chunk = (mask >> chunk_start) as u64;
+                let chunk = _pext_u64((src >> chunk_start) as u64, mask_chunk) as CurveIdx;
+                result |= chunk << num_extracted;
+                num_extracted += mask_chunk.count_ones();
+                chunk_start += 64;
+            }
+            result
+        }
+
+        /// Deposit the low-order bits of `src` into the bits of the result that are
+        /// selected by `mask`, using the BMI2 PDEP instruction
+        ///
+        /// This is the inverse of `pext()`, and it handles wide integers similarly.
+        ///
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "bmi2")]
+        #[inline]
+        pub(crate) unsafe fn pdep(src: CurveIdx, mask: CurveIdx) -> CurveIdx {
+            use core::arch::x86_64::_pdep_u64;
+            let mut result = 0;
+            let mut num_deposited = 0;
+            let mut chunk_start = 0;
+            while chunk_start < num_bits::<CurveIdx>() {
+                let mask_chunk = (mask >> chunk_start) as u64;
+                let chunk = _pdep_u64((src >> num_deposited) as u64, mask_chunk) as CurveIdx;
+                result |= chunk << chunk_start;
+                num_deposited += mask_chunk.count_ones();
+                chunk_start += 64;
+            }
+            result
+        }
+    };
+}
+
+/// Binary arithmetic utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    bits_impl!(u16, uint16, uint8);
+}
+
+/// Binary arithmetic utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    bits_impl!(u32, uint32, uint16);
+}
+
+/// Binary arithmetic utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    bits_impl!(u64, uint64, uint32);
+}
+
+/// Binary arithmetic utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    bits_impl!(u128, uint128, uint64);
 }
 
+pub_use_default_width!(*);
+pub use uint128::{
+    bitwise_scan as bitwise_scan_u128, bitwise_swaps as bitwise_swaps_u128,
+    bitwise_xor_ltr_exclusive_scan as bitwise_xor_ltr_exclusive_scan_u128,
+    bitwise_xor_ltr_inclusive_scan as bitwise_xor_ltr_inclusive_scan_u128,
+    checked_low_order_mask as checked_low_order_mask_u128,
+    checked_striped_mask as checked_striped_mask_u128, inclusive_range as inclusive_range_u128,
+    low_order_mask as low_order_mask_u128, striped_mask as striped_mask_u128,
+};
+pub use uint16::{
+    bitwise_scan as bitwise_scan_u16, bitwise_swaps as bitwise_swaps_u16,
+    bitwise_xor_ltr_exclusive_scan as bitwise_xor_ltr_exclusive_scan_u16,
+    bitwise_xor_ltr_inclusive_scan as bitwise_xor_ltr_inclusive_scan_u16,
+    checked_low_order_mask as checked_low_order_mask_u16,
+    checked_striped_mask as checked_striped_mask_u16, inclusive_range as inclusive_range_u16,
+    low_order_mask as low_order_mask_u16, striped_mask as striped_mask_u16,
+};
+pub use uint32::{
+    bitwise_scan as bitwise_scan_u32, bitwise_swaps as bitwise_swaps_u32,
+    bitwise_xor_ltr_exclusive_scan as bitwise_xor_ltr_exclusive_scan_u32,
+    bitwise_xor_ltr_inclusive_scan as bitwise_xor_ltr_inclusive_scan_u32,
+    checked_low_order_mask as checked_low_order_mask_u32,
+    checked_striped_mask as checked_striped_mask_u32, inclusive_range as inclusive_range_u32,
+    low_order_mask as low_order_mask_u32, striped_mask as striped_mask_u32,
+};
+pub use uint64::{
+    bitwise_scan as bitwise_scan_u64, bitwise_swaps as bitwise_swaps_u64,
+    bitwise_xor_ltr_exclusive_scan as bitwise_xor_ltr_exclusive_scan_u64,
+    bitwise_xor_ltr_inclusive_scan as bitwise_xor_ltr_inclusive_scan_u64,
+    checked_low_order_mask as checked_low_order_mask_u64,
+    checked_striped_mask as checked_striped_mask_u64, inclusive_range as inclusive_range_u64,
+    low_order_mask as low_order_mask_u64, striped_mask as striped_mask_u64,
+};
+pub use uint8::{
+    bitwise_scan as bitwise_scan_u8, bitwise_swaps as bitwise_swaps_u8,
+    bitwise_xor_ltr_exclusive_scan as bitwise_xor_ltr_exclusive_scan_u8,
+    bitwise_xor_ltr_inclusive_scan as bitwise_xor_ltr_inclusive_scan_u8,
+    inclusive_range as inclusive_range_u8, low_order_mask as low_order_mask_u8,
+    striped_mask as striped_mask_u8,
+};
+
 #[cfg(test)]
 pub(crate) mod test_utils {
-    use crate::NumBits;
+    use crate::{Coordinate, CurveIdx, NumBits};
     use core::ops::ShrAssign;
     use num_traits::{PrimInt, Unsigned};
 
+    /// Curve indices for tests that would ideally check every curve index
+    ///
+    /// With the default 16-bit curve indices, this is every curve index. Wider
+    /// curve index types have too many of them, so 2^16 evenly spaced indices
+    /// are used instead, starting with `CurveIdx::MIN` and ending with
+    /// `CurveIdx::MAX`.
+    ///
+    pub fn sampled_indices() -> impl DoubleEndedIterator<Item = CurveIdx> + Clone {
+        let last_sample = u16::MAX as CurveIdx;
+        let stride = CurveIdx::MAX / last_sample;
+        (0..=last_sample).map(move |sample| sample * stride)
+    }
+
+    /// Coordinates for tests that would ideally check every coordinate
+    ///
+    /// Like `sampled_indices()`, this is every coordinate up to 16-bit
+    /// coordinates, and 2^16 evenly spaced coordinates for wider ones.
+    ///
+    pub fn sampled_coordinates() -> impl DoubleEndedIterator<Item = Coordinate> + Clone {
+        let last_sample = (u16::MAX as u128).min(Coordinate::MAX as u128) as Coordinate;
+        let stride = Coordinate::MAX / last_sample;
+        (0..=last_sample).map(move |sample| sample * stride)
+    }
+
+    /// Highest curve order for tests that go through every point of the curve
+    /// at each order, for the same reason as `sampled_indices()`
+    pub const MAX_TESTED_ORDER: NumBits = u8::BITS;
+
+    /// Curve families for tests that go through the points of a rectangle in
+    /// curve order
+    ///
+    /// Only the Morton and Hilbert curves can do so without going through the
+    /// whole curve, which is only tractable with 16-bit curve indices.
+    ///
+    #[cfg(all(
+        feature = "alloc",
+        not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128"))
+    ))]
+    pub const CLIPPED_KINDS: &[crate::CurveKind] = &crate::CurveKind::ALL;
+    #[cfg(all(
+        feature = "alloc",
+        any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")
+    ))]
+    pub const CLIPPED_KINDS: &[crate::CurveKind] =
+        &[crate::CurveKind::Morton, crate::CurveKind::Hilbert];
+
     pub fn push_bit<I: PrimInt + Unsigned>(target: &mut I, bit: bool) {
         let bit = if bit { I::one() } else { I::zero() };
         *target = (*target << 1) | bit
@@ -116,12 +499,77 @@ pub(crate) mod test_utils {
         *target >>= 1;
         res
     }
+
+    /// Check that a function's execution time does not depend on its input
+    ///
+    /// This is a simplified version of the "dudect" methodology: we measure
+    /// the execution time of batches of calls with a fixed input and with
+    /// random inputs, interleaved in random order, and use Welch's t-test to
+    /// check that both timing distributions have compatible means.
+    ///
+    /// Timing measurements are only meaningful in release mode on a quiet
+    /// machine, so tests based on this function should be `#[ignore]`d.
+    ///
+    pub fn assert_constant_time<I: Copy, O>(
+        fixed_input: I,
+        mut random_input: impl FnMut() -> I,
+        f: impl Fn(I) -> O,
+    ) {
+        use criterion::black_box;
+        use quickcheck::{Arbitrary, Gen};
+        use std::time::Instant;
+
+        const NUM_MEASUREMENTS: usize = 10_000;
+        const BATCH_SIZE: usize = 100;
+        let mut gen = Gen::new(usize::MAX);
+        let mut batch = [fixed_input; BATCH_SIZE];
+        let mut samples: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+        for _ in 0..NUM_MEASUREMENTS {
+            let class = usize::from(bool::arbitrary(&mut gen));
+            for input in &mut batch {
+                // Generate random inputs in both cases to avoid any bias
+                // caused by different cache/branch predictor footprints
+                let random = random_input();
+                *input = [fixed_input, random][class];
+            }
+            let start = Instant::now();
+            for &input in &batch {
+                black_box(f(black_box(input)));
+            }
+            samples[class].push(start.elapsed().as_nanos() as f64);
+        }
+
+        // Discard the slowest measurements, which are likely to be affected
+        // by OS interrupts and other sources of noise unrelated to the input
+        for class_samples in &mut samples {
+            class_samples.sort_by(f64::total_cmp);
+            class_samples.truncate(class_samples.len() * 9 / 10);
+        }
+        let stats = samples.map(|class_samples| {
+            let len = class_samples.len() as f64;
+            let mean = class_samples.iter().sum::<f64>() / len;
+            let variance = class_samples
+                .iter()
+                .map(|sample| (sample - mean).powi(2))
+                .sum::<f64>()
+                / (len - 1.0);
+            (mean, variance / len)
+        });
+        let t = (stats[0].0 - stats[1].0) / (stats[0].1 + stats[1].1).sqrt();
+        assert!(
+            t.abs() < 10.0,
+            "Timing depends on input (Welch's t = {t}, mean timings = {} vs {} ns)",
+            stats[0].0,
+            stats[1].0
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{test_utils::*, *};
-    use core::ops::BitXor;
+    use crate::{Coordinate, CurveIdx};
+    use core::ops::{BitAnd, BitOr, BitXor};
 
     #[test]
     fn num_bits() {
@@ -139,6 +587,20 @@ mod tests {
             assert_eq!(super::low_order_mask(i), expected);
             push_bit(&mut expected, true);
         }
+        assert_eq!(
+            super::checked_low_order_mask(super::num_bits::<CurveIdx>()),
+            Some(CurveIdx::MAX)
+        );
+        assert_eq!(
+            super::checked_low_order_mask(super::num_bits::<CurveIdx>() + 1),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn low_order_mask_too_long() {
+        super::low_order_mask(super::num_bits::<CurveIdx>() + 1);
     }
 
     #[test]
@@ -156,7 +618,16 @@ mod tests {
                 mask,
                 "Unexpected striped mask for length {length}"
             );
+            assert_eq!(super::checked_striped_mask(length), Some(mask));
         }
+        assert_eq!(super::checked_striped_mask(0), None);
+        assert_eq!(super::checked_striped_mask(num_bits), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn striped_mask_empty() {
+        super::striped_mask(0);
     }
 
     fn ltr_inclusive_scan(
@@ -176,7 +647,7 @@ mod tests {
 
     #[test]
     fn bitwise_xor_ltr_inclusive_scan() {
-        for input in 0..=Coordinate::MAX {
+        for input in sampled_coordinates() {
             assert_eq!(
                 super::bitwise_xor_ltr_inclusive_scan(input),
                 ltr_inclusive_scan(input, bool::bitxor, false),
@@ -187,7 +658,7 @@ mod tests {
 
     #[test]
     fn bitwise_xor_ltr_exclusive_scan() {
-        for input in 0..=Coordinate::MAX {
+        for input in sampled_coordinates() {
             assert_eq!(
                 super::bitwise_xor_ltr_exclusive_scan(input),
                 super::bitwise_xor_ltr_inclusive_scan(input) >> 1,
@@ -196,6 +667,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bitwise_scan() {
+        use ScanDirection::*;
+        for &(op, bool_op, neutral) in &[
+            (ScanOp::And, bool::bitand as fn(bool, bool) -> bool, true),
+            (ScanOp::Or, bool::bitor, false),
+            (ScanOp::Xor, bool::bitxor, false),
+        ] {
+            for direction in [LeftToRight, RightToLeft] {
+                for input in sampled_coordinates() {
+                    // Right-to-left scans are left-to-right scans of the
+                    // reversed bits
+                    let reverse = |bits: Coordinate| match direction {
+                        LeftToRight => bits,
+                        RightToLeft => bits.reverse_bits(),
+                    };
+                    let inclusive = reverse(ltr_inclusive_scan(reverse(input), bool_op, neutral));
+                    let shifted_in = if neutral { !(Coordinate::MAX >> 1) } else { 0 };
+                    let exclusive = match direction {
+                        LeftToRight => (inclusive >> 1) | shifted_in,
+                        RightToLeft => (inclusive << 1) | shifted_in.reverse_bits(),
+                    };
+                    assert_eq!(
+                        super::bitwise_scan(input, op, direction, ScanKind::Inclusive),
+                        inclusive,
+                        "Unexpected inclusive {op:?} {direction:?} scan result for input {input:08b}"
+                    );
+                    assert_eq!(
+                        super::bitwise_scan(input, op, direction, ScanKind::Exclusive),
+                        exclusive,
+                        "Unexpected exclusive {op:?} {direction:?} scan result for input {input:08b}"
+                    );
+                }
+            }
+        }
+    }
+
+    // Utilities are also available for integer types that are neither a curve
+    // index type nor a coordinate type
+    #[test]
+    fn other_widths() {
+        assert_eq!(super::low_order_mask_u8(3), 0b111);
+        assert_eq!(super::low_order_mask_u8(8), u8::MAX);
+        assert_eq!(super::striped_mask_u8(2), 0b0011_0011);
+        assert_eq!(super::striped_mask_u128(64), u128::from(u64::MAX));
+        assert_eq!(super::inclusive_range_u8(..), 0..=u8::MAX);
+        assert_eq!(
+            super::bitwise_xor_ltr_inclusive_scan_u128(1 << 127),
+            u128::MAX
+        );
+        assert_eq!(
+            super::bitwise_scan_u128(
+                1,
+                ScanOp::Or,
+                ScanDirection::RightToLeft,
+                ScanKind::Exclusive
+            ),
+            u128::MAX - 1
+        );
+        assert_eq!(
+            super::bitwise_swaps_u128(u128::MAX << 64, 0, u128::MAX),
+            [u128::MAX << 64, u128::MAX >> 64]
+        );
+    }
+
     mod bitwise_swaps {
         use super::*;
         use quickcheck::quickcheck;
@@ -250,4 +786,97 @@ mod tests {
             );
         }
     }
+
+    // Wider curve index types are tested using simpler reference formulas, as
+    // exhaustive testing is not an option.
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+            mod $width {
+                use crate::bits::$width;
+                use quickcheck::quickcheck;
+
+                const IDX_BITS: u32 = <$curve_idx>::BITS;
+                const COORD_BITS: u32 = <$coordinate>::BITS;
+
+                #[test]
+                fn low_order_mask() {
+                    for length in 0..=IDX_BITS {
+                        assert_eq!(
+                            $width::low_order_mask(length),
+                            <$curve_idx>::MAX
+                                .checked_shr(IDX_BITS - length)
+                                .unwrap_or(0)
+                        );
+                    }
+                }
+
+                #[test]
+                fn striped_mask() {
+                    for length in 1..IDX_BITS {
+                        let mask = $width::striped_mask(length);
+                        for bit in 0..IDX_BITS {
+                            assert_eq!((mask >> bit) & 1 == 1, (bit / length) % 2 == 0);
+                        }
+                    }
+                }
+
+                quickcheck! {
+                    fn bitwise_scan(input: $coordinate) -> bool {
+                        use crate::bits::{ScanDirection::*, ScanKind::*, ScanOp::*};
+                        [And, Or, Xor].into_iter().all(|op| {
+                            [LeftToRight, RightToLeft].into_iter().all(|direction| {
+                                [Inclusive, Exclusive].into_iter().all(|kind| {
+                                    let output = $width::bitwise_scan(input, op, direction, kind);
+                                    (0..COORD_BITS).all(|bit| {
+                                        // Select the input bits that the scan has seen
+                                        // when it produces this output bit
+                                        let num_seen = match (direction, kind) {
+                                            (LeftToRight, Inclusive) => COORD_BITS - bit,
+                                            (LeftToRight, Exclusive) => COORD_BITS - bit - 1,
+                                            (RightToLeft, Inclusive) => bit + 1,
+                                            (RightToLeft, Exclusive) => bit,
+                                        };
+                                        let seen = match direction {
+                                            LeftToRight => input.checked_shr(COORD_BITS - num_seen),
+                                            RightToLeft => input.checked_shl(COORD_BITS - num_seen),
+                                        }
+                                        .unwrap_or(0);
+                                        let expected = match op {
+                                            And => seen.count_ones() == num_seen,
+                                            Or => seen != 0,
+                                            Xor => seen.count_ones() % 2 == 1,
+                                        };
+                                        ((output >> bit) & 1 == 1) == expected
+                                    })
+                                })
+                            })
+                        })
+                    }
+
+                    fn bitwise_xor_ltr_scans(input: $coordinate) -> bool {
+                        let inclusive = $width::bitwise_xor_ltr_inclusive_scan(input);
+                        let exclusive = $width::bitwise_xor_ltr_exclusive_scan(input);
+                        (0..COORD_BITS).all(|bit| {
+                            let expected = (input >> bit).count_ones() % 2 == 1;
+                            let input_bit = (input >> bit) & 1 == 1;
+                            ((inclusive >> bit) & 1 == 1) == expected
+                                && ((exclusive >> bit) & 1 == 1) == (expected ^ input_bit)
+                        })
+                    }
+
+                    fn bitwise_swaps(
+                        mask: $coordinate,
+                        src1: $coordinate,
+                        src2: $coordinate
+                    ) -> bool {
+                        $width::bitwise_swaps(mask, src1, src2)
+                            == [(src1 & !mask) | (src2 & mask), (src2 & !mask) | (src1 & mask)]
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32, u16);
+    wide_tests!(idx64, u64, u32);
+    wide_tests!(idx128, u128, u64);
 }
@@ -0,0 +1,142 @@
+//! Iteration over space-filling curves in aligned blocks
+//!
+//! On the Morton and Hilbert curves, every run of 4^k points that starts at a
+//! multiple of 4^k covers an aligned square of side 2^k. Walking the curve one
+//! such block at a time is a common way to implement cache blocking, and this
+//! module provides iterators that do so without any index arithmetic on the
+//! caller's side. When the `rayon` feature is enabled, blocks can also be
+//! handed to worker threads, each of which walks its blocks in curve order.
+
+/// Generate block iteration utilities for a curve module
+///
+/// The module must provide `decode_2d()`, `iter_range_2d()` and `Iter2D`, along
+/// with the usual `CurveIdx`, `Coordinate`, `Coordinates2D`, `NumBits` and
+/// `bits` names.
+///
+macro_rules! blocks_impl {
+    ($curve_name:literal) => {
+        #[doc = concat!("Iterate over the 2D ", $curve_name, " curve in aligned blocks of 4^block_bits points")]
+        ///
+        /// Each block is a contiguous range of curve indices that covers a square of
+        /// side 2^block_bits, and blocks are yielded in curve order.
+        ///
+        /// # Panics
+        ///
+        /// If `block_bits` is larger than the number of bits of `Coordinate`.
+        ///
+        pub fn iter_blocks_2d(block_bits: NumBits) -> Blocks2D {
+            assert!(
+                block_bits <= bits::num_bits::<Coordinate>(),
+                "Blocks of {block_bits} bits do not fit in the curve's domain"
+            );
+            Blocks2D {
+                blocks: 0..=CurveIdx::MAX.checked_shr(2 * block_bits).unwrap_or(0),
+                block_bits,
+            }
+        }
+
+        #[doc = concat!("Iterate over the aligned blocks of the 2D ", $curve_name, " curve in parallel")]
+        ///
+        /// This yields the same blocks as `iter_blocks_2d()`, but distributes them
+        /// across rayon worker threads. The points of each block can then be
+        /// iterated over sequentially with `Block2D::points()`, in curve order.
+        ///
+        /// # Panics
+        ///
+        /// If `block_bits` is larger than the number of bits of `Coordinate`, or if
+        /// the curve has more blocks than `usize` can count.
+        ///
+        #[cfg(feature = "rayon")]
+        pub fn par_blocks_2d(block_bits: NumBits) -> $crate::par::ParBlocks2D<CurveIdx, Block2D> {
+            $crate::par::ParBlocks2D::new(
+                iter_blocks_2d(block_bits).blocks,
+                block_bits,
+                Block2D::from_number,
+            )
+        }
+
+        #[doc = concat!("Aligned block of the 2D ", $curve_name, " curve, yielded by `iter_blocks_2d()`")]
+        /// and `par_blocks_2d()`
+        #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+        pub struct Block2D {
+            /// Curve indices of the points of the block
+            pub indices: core::ops::RangeInclusive<CurveIdx>,
+
+            /// Lower corner of the square that the block covers
+            pub min: Coordinates2D,
+
+            /// Upper corner of the square that the block covers, inclusive
+            pub max: Coordinates2D,
+        }
+        //
+        impl Block2D {
+            /// Describe the block with a certain number, among blocks of
+            /// 4^block_bits points
+            #[inline]
+            fn from_number(block: CurveIdx, block_bits: NumBits) -> Self {
+                debug_assert!(block_bits <= bits::num_bits::<Coordinate>());
+                debug_assert!(block <= CurveIdx::MAX.checked_shr(2 * block_bits).unwrap_or(0));
+                let start = block.checked_shl(2 * block_bits).unwrap_or(0);
+                let end = start | bits::low_order_mask(2 * block_bits);
+                let coord_mask = bits::low_order_mask(block_bits) as Coordinate;
+                let [x, y] = decode_2d(start);
+                let min = [x & !coord_mask, y & !coord_mask];
+                Self {
+                    indices: start..=end,
+                    min,
+                    max: [min[0] | coord_mask, min[1] | coord_mask],
+                }
+            }
+
+            /// Iterate over the points of the block, in curve order
+            #[inline]
+            pub fn points(&self) -> Iter2D {
+                iter_range_2d(self.indices.clone())
+            }
+        }
+
+        /// Iterator over the aligned blocks of a curve, created by
+        /// `iter_blocks_2d()`
+        #[derive(Clone, Debug)]
+        pub struct Blocks2D {
+            /// Numbers of the remaining blocks
+            blocks: core::ops::RangeInclusive<CurveIdx>,
+
+            /// Each block has 4^block_bits points
+            block_bits: NumBits,
+        }
+        //
+        impl Iterator for Blocks2D {
+            type Item = Block2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Block2D> {
+                self.blocks.next().map(|block| Block2D::from_number(block, self.block_bits))
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Block2D> {
+                self.blocks.nth(n).map(|block| Block2D::from_number(block, self.block_bits))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.blocks.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Blocks2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Block2D> {
+                self.blocks.next_back().map(|block| Block2D::from_number(block, self.block_bits))
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Block2D> {
+                self.blocks.nth_back(n).map(|block| Block2D::from_number(block, self.block_bits))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Blocks2D {}
+    };
+}
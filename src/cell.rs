@@ -0,0 +1,355 @@
+//! Hierarchical cells of the curves' domain
+//!
+//! The Morton and Hilbert curves recursively split the domain into 4 aligned
+//! squares, and go through each of them in turn. Therefore, at any recursion
+//! level L, the curve indices whose 2 x L high-order bits are equal designate
+//! the points of one aligned square, which is a cell of the quadtree that
+//! recursively splits the domain. This module identifies such cells by their
+//! level and curve index prefix, in the spirit of S2 cell IDs, and provides
+//! the usual quadtree navigation operations on these identifiers.
+//!
+//! A `CellId` does not depend on the curve: it only becomes a square of the
+//! domain when the curve is specified, and the same identifier designates
+//! different squares on the Morton and Hilbert curves.
+
+use crate::{bits, hilbert, morton, Coordinate, Coordinates2D, CurveIdx, CurveKind, NumBits};
+use core::{cmp::Ordering, ops::RangeInclusive};
+
+/// Cell of the curves' domain, designated by its level and curve index prefix
+///
+/// Cells are ordered by the first curve index that they contain, and parent
+/// cells come before their first child. This is the order in which a
+/// depth-first traversal of the quadtree along the curve goes through them.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CellId {
+    /// Curve index prefix, which has 2 x `level` meaningful bits
+    prefix: CurveIdx,
+
+    /// Recursion level, from 0 for the full domain to the number of bits of
+    /// `Coordinate` for single points
+    level: NumBits,
+}
+//
+impl CellId {
+    /// Cell that covers the full domain
+    pub const ROOT: Self = Self {
+        prefix: 0,
+        level: 0,
+    };
+
+    /// Deepest level, where cells are single points
+    pub const MAX_LEVEL: NumBits = Coordinate::BITS;
+
+    /// Cell with a certain curve index prefix at a certain level
+    ///
+    /// # Panics
+    ///
+    /// If `level` is larger than `MAX_LEVEL`, or if `prefix` is not smaller
+    /// than 4^`level`.
+    ///
+    #[inline]
+    pub const fn new(prefix: CurveIdx, level: NumBits) -> Self {
+        assert!(level <= Self::MAX_LEVEL, "Level is too deep for this curve");
+        assert!(
+            prefix <= bits::low_order_mask(2 * level),
+            "Curve index prefix is out of range for this level"
+        );
+        Self { prefix, level }
+    }
+
+    /// Cell that contains the point of index `code` at a certain level
+    ///
+    /// # Panics
+    ///
+    /// If `level` is larger than `MAX_LEVEL`.
+    ///
+    #[inline]
+    pub const fn from_index(code: CurveIdx, level: NumBits) -> Self {
+        assert!(level <= Self::MAX_LEVEL, "Level is too deep for this curve");
+        Self {
+            prefix: shr(code, 2 * (Self::MAX_LEVEL - level)),
+            level,
+        }
+    }
+
+    /// Cell that contains a point of a certain curve at a certain level
+    ///
+    /// # Panics
+    ///
+    /// If `level` is larger than `MAX_LEVEL`.
+    ///
+    #[inline]
+    pub const fn from_coords(kind: CurveKind, coords: Coordinates2D, level: NumBits) -> Self {
+        Self::from_index(kind.encode_2d(coords), level)
+    }
+
+    /// Curve index prefix of this cell
+    #[inline]
+    pub const fn prefix(self) -> CurveIdx {
+        self.prefix
+    }
+
+    /// Level of this cell
+    #[inline]
+    pub const fn level(self) -> NumBits {
+        self.level
+    }
+
+    /// Truth that this cell is a single point, and thus has no children
+    #[inline]
+    pub const fn is_leaf(self) -> bool {
+        self.level == Self::MAX_LEVEL
+    }
+
+    /// Cell at the previous level that contains this cell, if any
+    #[inline]
+    pub const fn parent(self) -> Option<Self> {
+        if self.level == 0 {
+            None
+        } else {
+            Some(Self {
+                prefix: self.prefix >> 2,
+                level: self.level - 1,
+            })
+        }
+    }
+
+    /// Cell at a lower level that contains this cell
+    ///
+    /// # Panics
+    ///
+    /// If `level` is larger than this cell's level.
+    ///
+    #[inline]
+    pub const fn ancestor(self, level: NumBits) -> Self {
+        assert!(level <= self.level, "Ancestors must have a lower level");
+        Self {
+            prefix: shr(self.prefix, 2 * (self.level - level)),
+            level,
+        }
+    }
+
+    /// Cells at the next level that this cell contains, in curve order, if
+    /// any
+    #[inline]
+    pub const fn children(self) -> Option<[Self; 4]> {
+        if self.is_leaf() {
+            return None;
+        }
+        let first = self.prefix << 2;
+        let level = self.level + 1;
+        Some([
+            Self {
+                prefix: first,
+                level,
+            },
+            Self {
+                prefix: first | 1,
+                level,
+            },
+            Self {
+                prefix: first | 2,
+                level,
+            },
+            Self {
+                prefix: first | 3,
+                level,
+            },
+        ])
+    }
+
+    /// Truth that this cell contains another cell, which includes the case
+    /// where both cells are the same
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        other.level >= self.level && other.ancestor(self.level).prefix == self.prefix
+    }
+
+    /// Deepest cell that contains both this cell and another cell
+    #[inline]
+    pub const fn common_ancestor(self, other: Self) -> Self {
+        let common_level = common_level(
+            *self.range_of_descendants().start(),
+            *other.range_of_descendants().start(),
+        );
+        let mut level = if self.level < other.level {
+            self.level
+        } else {
+            other.level
+        };
+        if common_level < level {
+            level = common_level;
+        }
+        self.ancestor(level)
+    }
+
+    /// Curve indices of the points that this cell contains
+    #[inline]
+    pub const fn range_of_descendants(self) -> RangeInclusive<CurveIdx> {
+        let low_bits = 2 * (Self::MAX_LEVEL - self.level);
+        let start = shl(self.prefix, low_bits);
+        start..=start | bits::low_order_mask(low_bits)
+    }
+
+    /// Aligned square that this cell covers on a certain curve, as the
+    /// coordinates of its lower corner and its side length
+    ///
+    /// See `morton::cell_2d()` and `hilbert::cell_2d()` for more information.
+    ///
+    /// # Panics
+    ///
+    /// If `kind` is neither the Morton curve nor the Hilbert curve, as other
+    /// curves do not map curve index prefixes to aligned squares.
+    ///
+    #[inline]
+    pub const fn square_2d(self, kind: CurveKind) -> (Coordinates2D, CurveIdx) {
+        let start = *self.range_of_descendants().start();
+        match kind {
+            CurveKind::Morton => morton::cell_2d(start, self.level),
+            CurveKind::Hilbert => hilbert::cell_2d(start, self.level),
+            _ => panic!("Only the Morton and Hilbert curves have square cells"),
+        }
+    }
+}
+//
+impl Ord for CellId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.range_of_descendants()
+            .start()
+            .cmp(other.range_of_descendants().start())
+            .then(self.level.cmp(&other.level))
+    }
+}
+//
+impl PartialOrd for CellId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Deepest level at which two curve indices belong to the same cell
+///
+/// This is the number of leading pairs of bits that the indices have in
+/// common, which does not depend on the curve. When bulk-loading a tree from
+/// curve-sorted data, it tells how deep two consecutive points go down the same
+/// branch of the tree.
+///
+#[inline]
+pub const fn common_level(a: CurveIdx, b: CurveIdx) -> NumBits {
+    (a ^ b).leading_zeros() / 2
+}
+
+/// Right shift that handles shifting out all bits of a curve index
+#[inline(always)]
+const fn shr(code: CurveIdx, shift: NumBits) -> CurveIdx {
+    if shift < CurveIdx::BITS {
+        code >> shift
+    } else {
+        0
+    }
+}
+
+/// Left shift that handles shifting out all bits of a curve index
+#[inline(always)]
+const fn shl(code: CurveIdx, shift: NumBits) -> CurveIdx {
+    if shift < CurveIdx::BITS {
+        code << shift
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::*;
+
+    #[test]
+    fn hierarchy() {
+        assert_eq!(CellId::ROOT.parent(), None);
+        assert_eq!(CellId::ROOT.range_of_descendants(), 0..=CurveIdx::MAX);
+        for level in 0..=CellId::MAX_LEVEL {
+            for code in sampled_indices().step_by(7) {
+                let cell = CellId::from_index(code, level);
+                assert_eq!(CellId::new(cell.prefix(), level), cell);
+                assert_eq!(cell.level(), level);
+                assert!(cell.range_of_descendants().contains(&code));
+                assert!(cell.contains(cell));
+                assert!(cell.contains(CellId::from_index(code, CellId::MAX_LEVEL)));
+                assert_eq!(cell.ancestor(0), CellId::ROOT);
+                assert!(CellId::ROOT.contains(cell));
+
+                if let Some(parent) = cell.parent() {
+                    assert_eq!(parent, CellId::from_index(code, level - 1));
+                    assert!(parent.contains(cell) && !cell.contains(parent));
+                    assert!(parent < cell);
+                    assert!(parent.children().unwrap().contains(&cell));
+                }
+
+                match cell.children() {
+                    Some(children) => {
+                        let range = cell.range_of_descendants();
+                        let quarter = (range.end() - range.start()) / 4 + 1;
+                        for (idx, child) in children.into_iter().enumerate() {
+                            assert_eq!(child.parent(), Some(cell));
+                            assert_eq!(
+                                *child.range_of_descendants().start(),
+                                range.start() + idx as CurveIdx * quarter
+                            );
+                        }
+                        assert!(children.windows(2).all(|pair| pair[0] < pair[1]));
+                        assert!(!children[0].contains(children[1]));
+                        assert!(cell < children[0]);
+                    }
+                    None => {
+                        assert!(cell.is_leaf());
+                        assert_eq!(cell.range_of_descendants(), code..=code);
+                    }
+                }
+
+                for kind in [CurveKind::Morton, CurveKind::Hilbert] {
+                    let coords = kind.decode_2d(code);
+                    assert_eq!(CellId::from_coords(kind, coords, level), cell);
+                    let cell_2d = match kind {
+                        CurveKind::Morton => morton::cell_2d(code, level),
+                        CurveKind::Hilbert => hilbert::cell_2d(code, level),
+                        _ => unreachable!(),
+                    };
+                    assert_eq!(cell.square_2d(kind), cell_2d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn square_2d_not_quadtree() {
+        CellId::from_index(0, 1).square_2d(CurveKind::Snake);
+    }
+
+    #[test]
+    fn common_level() {
+        for a in sampled_indices().step_by(37) {
+            for b in sampled_indices().step_by(41) {
+                let level = super::common_level(a, b);
+                assert_eq!(level, super::common_level(b, a));
+                assert_eq!(CellId::from_index(a, level), CellId::from_index(b, level));
+                if level < CellId::MAX_LEVEL {
+                    assert_ne!(
+                        CellId::from_index(a, level + 1),
+                        CellId::from_index(b, level + 1)
+                    );
+                }
+                let [cell_a, cell_b] = [
+                    CellId::from_index(a, CellId::MAX_LEVEL.min(level + 2)),
+                    CellId::from_index(b, CellId::MAX_LEVEL.min(level + 1)),
+                ];
+                assert_eq!(cell_a.common_ancestor(cell_b), CellId::from_index(a, level));
+            }
+            assert_eq!(super::common_level(a, a), CellId::MAX_LEVEL);
+            let cell = CellId::from_index(a, 3);
+            assert_eq!(cell.common_ancestor(CellId::from_index(a, 5)), cell);
+        }
+    }
+}
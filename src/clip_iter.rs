@@ -0,0 +1,214 @@
+//! Iteration over the part of a space-filling curve that lies within bounds
+//!
+//! The Morton and Hilbert curves cover square domains whose side is a power of
+//! two, but many applications need to go through smaller rectangular domains.
+//! Filtering out the points of the curve that are out of bounds is wasteful, as
+//! up to 75% of the points may need to be decoded and discarded, so this module
+//! provides iterators that skip entire out-of-bounds ranges of curve indices.
+//!
+//! The same idea extends to domains of arbitrary shape, such as masks, circles
+//! or half-planes, when the caller can tell whether an aligned square of the
+//! curve is entirely inside, entirely outside, or partially inside the domain.
+
+/// Decision taken by the filter of a masked curve iterator about an aligned
+/// square of the curve's domain
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MaskAction {
+    /// Every point of the square is yielded
+    Keep,
+
+    /// No point of the square is yielded
+    Skip,
+
+    /// The square is split into 4 smaller squares, which are filtered in turn.
+    /// Single points cannot be split, so they are kept instead.
+    Descend,
+}
+
+/// Generate clipped iteration utilities for a curve module whose curve maps
+/// every aligned block of 4^N curve indices to an aligned square of side 2^N
+///
+/// The module must provide `decode_2d()`, `iter_range_2d()` and an `Iter2D` type
+/// with an `indices` field, along with the usual `CurveIdx`, `Coordinate`,
+/// `Coordinates2D`, `NumBits` and `bits` names.
+///
+macro_rules! clip_iter_impl {
+    ($curve_name:literal) => {
+        #[doc = concat!("Iterate over the points of the 2D ", $curve_name, " curve that")]
+        /// are within a `width` x `height` rectangle whose lower corner is `[0, 0]`
+        ///
+        /// This is equivalent to filtering the output of `iter_2d()`, but instead
+        /// of discarding out-of-bounds points one by one, the iterator skips over
+        /// the largest out-of-bounds blocks of curve indices that it can find.
+        ///
+        pub fn iter_clipped_2d(width: Coordinate, height: Coordinate) -> ClippedIter2D {
+            ClippedIter2D {
+                iter: iter_2d(),
+                bounds: [width, height],
+            }
+        }
+
+        #[doc = concat!("Iterator over the in-bounds points of the 2D ", $curve_name, " curve,")]
+        /// created by `iter_clipped_2d()`
+        #[derive(Clone, Debug)]
+        pub struct ClippedIter2D {
+            /// Iterator over the remaining points of the curve, in bounds or not
+            iter: Iter2D,
+
+            /// Width and height of the rectangle of interest
+            bounds: Coordinates2D,
+        }
+        //
+        impl ClippedIter2D {
+            /// Truth that some coordinates are in bounds
+            #[inline(always)]
+            fn in_bounds(&self, [x, y]: Coordinates2D) -> bool {
+                x < self.bounds[0] && y < self.bounds[1]
+            }
+        }
+        //
+        impl Iterator for ClippedIter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                loop {
+                    let idx = *self.iter.indices.start();
+                    let coords = self.iter.next()?;
+                    if self.in_bounds(coords) {
+                        return Some(coords);
+                    }
+
+                    // This point is out of bounds, and so is every point of the
+                    // aligned square whose lower corner it is. Look for the largest
+                    // such square, which starts at this point of the curve.
+                    let mut depth = 0;
+                    while depth < Coordinate::BITS - 1 {
+                        let next_depth = depth + 1;
+                        let aligned = idx & bits::low_order_mask(2 * next_depth) == 0;
+                        let corner_mask = !(bits::low_order_mask(next_depth) as Coordinate);
+                        let corner = [coords[0] & corner_mask, coords[1] & corner_mask];
+                        if !aligned || self.in_bounds(corner) {
+                            break;
+                        }
+                        depth = next_depth;
+                    }
+
+                    // Skip the points of that square, if there are others
+                    if depth > 0 {
+                        let end = *self.iter.indices.end();
+                        let next_idx = idx + bits::low_order_mask(2 * depth);
+                        if next_idx >= end {
+                            self.iter = iter_range_2d(..0);
+                            return None;
+                        }
+                        self.iter = iter_range_2d(next_idx + 1..=end);
+                    }
+                }
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (0, self.iter.size_hint().1)
+            }
+        }
+        //
+        impl core::iter::FusedIterator for ClippedIter2D {}
+
+        #[doc = concat!("Iterate over the points of the 2D ", $curve_name, " curve that")]
+        /// are accepted by a filter on aligned squares
+        ///
+        /// The curve is walked from the top of its recursive structure: `filter`
+        /// is first called on the square that covers the whole domain, then on
+        /// the 4 squares that it is made of if it returns `MaskAction::Descend`,
+        /// and so on. Each square is described by its lower corner and its side
+        /// length, and squares that are kept or skipped are not split further,
+        /// so arbitrarily shaped domains can be iterated over without filtering
+        /// every point of the curve one by one.
+        ///
+        /// Squares are filtered lazily, in curve order, as the iterator is
+        /// consumed.
+        ///
+        pub fn iter_masked_2d<F>(filter: F) -> MaskedIter2D<F>
+        where
+            F: FnMut(Coordinates2D, CurveIdx) -> $crate::MaskAction,
+        {
+            MaskedIter2D {
+                kept: iter_range_2d(..0),
+                next_square: Some((0, Coordinate::BITS)),
+                filter,
+            }
+        }
+
+        #[doc = concat!("Iterator over the points of the 2D ", $curve_name, " curve that")]
+        /// are accepted by a filter, created by `iter_masked_2d()`
+        #[derive(Clone)]
+        pub struct MaskedIter2D<F> {
+            /// Remaining points of the last square that was kept
+            kept: Iter2D,
+
+            /// First curve index and number of side bits of the next square to
+            /// be filtered, if any
+            next_square: Option<(CurveIdx, NumBits)>,
+
+            /// Filter on aligned squares
+            filter: F,
+        }
+        //
+        impl<F> Iterator for MaskedIter2D<F>
+        where
+            F: FnMut(Coordinates2D, CurveIdx) -> $crate::MaskAction,
+        {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                loop {
+                    if let Some(coords) = self.kept.next() {
+                        return Some(coords);
+                    }
+                    let (start, side_bits) = self.next_square?;
+
+                    // Ask the filter what should be done with the next square
+                    let corner_mask = !(bits::low_order_mask(side_bits) as Coordinate);
+                    let [x, y] = decode_2d(start);
+                    let corner = [x & corner_mask, y & corner_mask];
+                    let action = (self.filter)(corner, 1 << side_bits);
+                    let end = start | bits::low_order_mask(2 * side_bits);
+                    match action {
+                        $crate::MaskAction::Descend if side_bits > 0 => {
+                            self.next_square = Some((start, side_bits - 1));
+                            continue;
+                        }
+                        $crate::MaskAction::Keep | $crate::MaskAction::Descend => {
+                            self.kept = iter_range_2d(start..=end);
+                        }
+                        $crate::MaskAction::Skip => {}
+                    }
+
+                    // Every parent of the square was descended into, so the next
+                    // square is the largest aligned one that starts after it. Its
+                    // parent is also a parent of the current square.
+                    self.next_square = end
+                        .checked_add(1)
+                        .map(|next| (next, next.trailing_zeros() / 2));
+                }
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let kept = self.kept.size_hint();
+                if self.next_square.is_none() {
+                    kept
+                } else {
+                    (kept.0, None)
+                }
+            }
+        }
+        //
+        impl<F> core::iter::FusedIterator for MaskedIter2D<F> where
+            F: FnMut(Coordinates2D, CurveIdx) -> $crate::MaskAction
+        {
+        }
+    };
+}
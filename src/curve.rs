@@ -0,0 +1,738 @@
+//! Abstractions over space-filling curve families
+
+use crate::{
+    bits, hilbert, hindex, moore, morton, sierpinski, snake, spiral, Coordinate, Coordinates2D,
+    CurveIdx, NumBits,
+};
+use core::{fmt, str::FromStr};
+
+/// 2D space-filling curve
+///
+/// This trait is implemented by marker types representing the curve families
+/// provided by this crate, so that code can be generic over the curve family.
+/// Its methods forward to the free functions of the corresponding module, which
+/// should be preferred when the curve family is known as they are `const fn`.
+///
+pub trait SpaceFillingCurve2D {
+    /// Iterator over the curve's points, in curve order
+    type Iter: DoubleEndedIterator<Item = Coordinates2D>;
+
+    /// Compute the coordinates of the i-th point of the curve
+    fn decode(code: CurveIdx) -> Coordinates2D;
+
+    /// Compute the index of a point on the curve
+    fn encode(coords: Coordinates2D) -> CurveIdx;
+
+    /// Iterate over the curve
+    ///
+    /// This is equivalent to running `decode()` on the sequence of all possible
+    /// curve indices (CurveIdx::MIN..=CurveIdx::MAX), but more efficient.
+    ///
+    fn iter() -> Self::Iter {
+        Self::iter_from(CurveIdx::MIN)
+    }
+
+    /// Iterate over the curve, starting from a certain index
+    ///
+    /// This is equivalent to running `decode()` on the sequence of curve
+    /// indices (start..=CurveIdx::MAX), but more efficient.
+    ///
+    fn iter_from(start: CurveIdx) -> Self::Iter;
+}
+
+/// 2D Morton curve, see the `morton` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Morton2D;
+//
+impl SpaceFillingCurve2D for Morton2D {
+    type Iter = morton::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        morton::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        morton::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        morton::iter_from_2d(start)
+    }
+}
+
+/// 2D Hilbert curve, see the `hilbert` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Hilbert2D;
+//
+impl SpaceFillingCurve2D for Hilbert2D {
+    type Iter = hilbert::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        hilbert::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        hilbert::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        hilbert::iter_from_2d(start)
+    }
+}
+
+/// 2D Moore curve, see the `moore` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Moore2D;
+//
+impl SpaceFillingCurve2D for Moore2D {
+    type Iter = moore::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        moore::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        moore::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        moore::iter_from_2d(start)
+    }
+}
+
+/// 2D Sierpiński curve, see the `sierpinski` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Sierpinski2D;
+//
+impl SpaceFillingCurve2D for Sierpinski2D {
+    type Iter = sierpinski::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        sierpinski::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        sierpinski::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        sierpinski::iter_from_2d(start)
+    }
+}
+
+/// 2D H-index curve, see the `hindex` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct HIndex2D;
+//
+impl SpaceFillingCurve2D for HIndex2D {
+    type Iter = hindex::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        hindex::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        hindex::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        hindex::iter_from_2d(start)
+    }
+}
+
+/// 2D snake order, see the `snake` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Snake2D;
+//
+impl SpaceFillingCurve2D for Snake2D {
+    type Iter = snake::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        snake::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        snake::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        snake::iter_from_2d(start)
+    }
+}
+
+/// 2D spiral order, see the `spiral` module
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Spiral2D;
+//
+impl SpaceFillingCurve2D for Spiral2D {
+    type Iter = spiral::Iter2D;
+
+    #[inline]
+    fn decode(code: CurveIdx) -> Coordinates2D {
+        spiral::decode_2d(code)
+    }
+
+    #[inline]
+    fn encode(coords: Coordinates2D) -> CurveIdx {
+        spiral::encode_2d(coords)
+    }
+
+    #[inline]
+    fn iter_from(start: CurveIdx) -> Self::Iter {
+        spiral::iter_from_2d(start)
+    }
+}
+
+/// 2D Morton curve of a fixed order, which covers a 2^ORDER x 2^ORDER domain
+///
+/// The points of this curve are the first 4^ORDER points of `Morton2D`. Since
+/// its order is known at compile time, curve indices and coordinates can be
+/// truncated to the bits that are meaningful at this order instead of being
+/// checked at runtime. Using an ORDER that is larger than the number of bits of
+/// `Coordinate` is a compile-time error.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BoundedMorton2D<const ORDER: NumBits>;
+//
+impl<const ORDER: NumBits> BoundedMorton2D<ORDER> {
+    /// Number of unused high-order bits in the coordinates of this curve
+    const SHIFT: NumBits = Coordinate::BITS - check_order(ORDER);
+
+    /// Index of the last point of the curve
+    pub const MAX_IDX: CurveIdx = bits::low_order_mask(2 * check_order(ORDER));
+
+    /// Largest coordinate of a point of the curve
+    pub const MAX_COORD: Coordinate = bits::low_order_mask(check_order(ORDER)) as Coordinate;
+
+    /// Compute the coordinates of the i-th point of the curve
+    ///
+    /// Bits of `code` above the first 2 x ORDER bits are ignored.
+    ///
+    #[inline]
+    pub const fn decode(code: CurveIdx) -> Coordinates2D {
+        if ORDER == 0 {
+            return [0; 2];
+        }
+        let [x, y] = morton::decode_2d(code << (2 * Self::SHIFT));
+        [x >> Self::SHIFT, y >> Self::SHIFT]
+    }
+
+    /// Compute the index of a point on the curve
+    ///
+    /// Bits of the coordinates above the first ORDER bits are ignored.
+    ///
+    #[inline]
+    pub const fn encode(coords: Coordinates2D) -> CurveIdx {
+        if ORDER == 0 {
+            return 0;
+        }
+        morton::encode_2d([coords[0] << Self::SHIFT, coords[1] << Self::SHIFT]) >> (2 * Self::SHIFT)
+    }
+
+    /// Iterate over the curve
+    #[inline]
+    pub fn iter() -> morton::Iter2D {
+        morton::iter_range_2d(..=Self::MAX_IDX)
+    }
+}
+
+/// 2D Hilbert curve of a fixed order, which covers a 2^ORDER x 2^ORDER domain
+///
+/// This is the curve of `hilbert::decode_2d_order()`, with an order that is
+/// known at compile time. As with `BoundedMorton2D`, curve indices and
+/// coordinates are truncated to the bits that are meaningful at this order
+/// instead of being checked at runtime, and using an ORDER that is larger than
+/// the number of bits of `Coordinate` is a compile-time error.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BoundedHilbert2D<const ORDER: NumBits>;
+//
+impl<const ORDER: NumBits> BoundedHilbert2D<ORDER> {
+    /// Number of unused high-order bits in the coordinates of this curve
+    const SHIFT: NumBits = Coordinate::BITS - check_order(ORDER);
+
+    /// Index of the last point of the curve
+    pub const MAX_IDX: CurveIdx = bits::low_order_mask(2 * check_order(ORDER));
+
+    /// Largest coordinate of a point of the curve
+    pub const MAX_COORD: Coordinate = bits::low_order_mask(check_order(ORDER)) as Coordinate;
+
+    /// Compute the coordinates of the i-th point of the curve
+    ///
+    /// Bits of `code` above the first 2 x ORDER bits are ignored.
+    ///
+    #[inline]
+    pub const fn decode(code: CurveIdx) -> Coordinates2D {
+        if ORDER == 0 {
+            return [0; 2];
+        }
+        let [x, y] = hilbert::decode_2d(code << (2 * Self::SHIFT));
+        [x >> Self::SHIFT, y >> Self::SHIFT]
+    }
+
+    /// Compute the index of a point on the curve
+    ///
+    /// Bits of the coordinates above the first ORDER bits are ignored.
+    ///
+    #[inline]
+    pub const fn encode(coords: Coordinates2D) -> CurveIdx {
+        if ORDER == 0 {
+            return 0;
+        }
+        hilbert::encode_2d([coords[0] << Self::SHIFT, coords[1] << Self::SHIFT])
+            >> (2 * Self::SHIFT)
+    }
+
+    /// Iterate over the curve
+    #[inline]
+    pub fn iter() -> hilbert::OrderIter2D {
+        hilbert::iter_2d_order(Coordinate::BITS - Self::SHIFT)
+    }
+}
+
+/// Check the ORDER parameter of `BoundedMorton2D` and `BoundedHilbert2D`
+const fn check_order(order: NumBits) -> NumBits {
+    assert!(
+        order <= Coordinate::BITS,
+        "Curve order is too high for the CurveIdx type"
+    );
+    order
+}
+
+/// 2D space-filling curve family, selected at runtime
+///
+/// This is the dynamic counterpart of the `SpaceFillingCurve2D` trait, for use
+/// when the curve family comes from e.g. a configuration file or a command line
+/// flag. It can be parsed from and displayed as a lowercase string.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CurveKind {
+    /// Morton curve, see the `morton` module
+    Morton,
+
+    /// Hilbert curve, see the `hilbert` module
+    Hilbert,
+
+    /// Moore curve, see the `moore` module
+    Moore,
+
+    /// Sierpiński curve, see the `sierpinski` module
+    Sierpinski,
+
+    /// H-index curve, see the `hindex` module
+    HIndex,
+
+    /// Snake order, see the `snake` module
+    Snake,
+
+    /// Spiral order, see the `spiral` module
+    Spiral,
+}
+//
+impl CurveKind {
+    /// Every supported curve family
+    pub const ALL: [CurveKind; 7] = [
+        CurveKind::Morton,
+        CurveKind::Hilbert,
+        CurveKind::Moore,
+        CurveKind::Sierpinski,
+        CurveKind::HIndex,
+        CurveKind::Snake,
+        CurveKind::Spiral,
+    ];
+
+    /// Compute the coordinates of the i-th point of the curve
+    #[inline]
+    pub const fn decode_2d(self, code: CurveIdx) -> Coordinates2D {
+        match self {
+            CurveKind::Morton => morton::decode_2d(code),
+            CurveKind::Hilbert => hilbert::decode_2d(code),
+            CurveKind::Moore => moore::decode_2d(code),
+            CurveKind::Sierpinski => sierpinski::decode_2d(code),
+            CurveKind::HIndex => hindex::decode_2d(code),
+            CurveKind::Snake => snake::decode_2d(code),
+            CurveKind::Spiral => spiral::decode_2d(code),
+        }
+    }
+
+    /// Compute the index of a point on the curve
+    #[inline]
+    pub const fn encode_2d(self, coords: Coordinates2D) -> CurveIdx {
+        match self {
+            CurveKind::Morton => morton::encode_2d(coords),
+            CurveKind::Hilbert => hilbert::encode_2d(coords),
+            CurveKind::Moore => moore::encode_2d(coords),
+            CurveKind::Sierpinski => sierpinski::encode_2d(coords),
+            CurveKind::HIndex => hindex::encode_2d(coords),
+            CurveKind::Snake => snake::encode_2d(coords),
+            CurveKind::Spiral => spiral::encode_2d(coords),
+        }
+    }
+
+    /// Iterate over the curve
+    #[inline]
+    pub fn iter_2d(self) -> CurveIter2D {
+        self.iter_from_2d(CurveIdx::MIN)
+    }
+
+    /// Iterate over the curve, starting from a certain index
+    #[inline]
+    pub fn iter_from_2d(self, start: CurveIdx) -> CurveIter2D {
+        match self {
+            CurveKind::Morton => CurveIter2D::Morton(morton::iter_from_2d(start)),
+            CurveKind::Hilbert => CurveIter2D::Hilbert(hilbert::iter_from_2d(start)),
+            CurveKind::Moore => CurveIter2D::Moore(moore::iter_from_2d(start)),
+            CurveKind::Sierpinski => CurveIter2D::Sierpinski(sierpinski::iter_from_2d(start)),
+            CurveKind::HIndex => CurveIter2D::HIndex(hindex::iter_from_2d(start)),
+            CurveKind::Snake => CurveIter2D::Snake(snake::iter_from_2d(start)),
+            CurveKind::Spiral => CurveIter2D::Spiral(spiral::iter_from_2d(start)),
+        }
+    }
+
+    /// Iterate over the points of the curve that are within a `width` x `height`
+    /// rectangle whose lower corner is `[0, 0]`
+    ///
+    /// See `morton::iter_clipped_2d()` for more information. Only the Morton and
+    /// Hilbert curves can skip out-of-bounds blocks of curve indices, for other
+    /// curves the out-of-bounds points are decoded and filtered out one by one.
+    ///
+    #[inline]
+    pub fn iter_clipped_2d(self, width: Coordinate, height: Coordinate) -> ClippedCurveIter2D {
+        match self {
+            CurveKind::Morton => ClippedCurveIter2D::Morton(morton::iter_clipped_2d(width, height)),
+            CurveKind::Hilbert => {
+                ClippedCurveIter2D::Hilbert(hilbert::iter_clipped_2d(width, height))
+            }
+            _ => ClippedCurveIter2D::Filtered(self.iter_2d(), [width, height]),
+        }
+    }
+
+    /// Lowercase name of the curve family
+    pub const fn name(self) -> &'static str {
+        match self {
+            CurveKind::Morton => "morton",
+            CurveKind::Hilbert => "hilbert",
+            CurveKind::Moore => "moore",
+            CurveKind::Sierpinski => "sierpinski",
+            CurveKind::HIndex => "hindex",
+            CurveKind::Snake => "snake",
+            CurveKind::Spiral => "spiral",
+        }
+    }
+}
+//
+impl fmt::Display for CurveKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+//
+impl FromStr for CurveKind {
+    type Err = ParseCurveKindError;
+
+    /// Parse a curve family name, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CurveKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name().eq_ignore_ascii_case(s))
+            .ok_or(ParseCurveKindError)
+    }
+}
+
+/// Error returned when parsing an unknown curve family name
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseCurveKindError;
+//
+impl fmt::Display for ParseCurveKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown space-filling curve, expected one of ")?;
+        for (idx, kind) in CurveKind::ALL.iter().enumerate() {
+            let separator = if idx == 0 { "" } else { ", " };
+            write!(f, "{separator}{kind}")?;
+        }
+        Ok(())
+    }
+}
+//
+#[cfg(feature = "std")]
+impl std::error::Error for ParseCurveKindError {}
+
+/// Iterator over a 2D space-filling curve selected at runtime, created by
+/// `CurveKind::iter_2d()` and `CurveKind::iter_from_2d()`
+#[derive(Clone, Debug)]
+pub enum CurveIter2D {
+    /// Iterator over the Morton curve
+    Morton(morton::Iter2D),
+
+    /// Iterator over the Hilbert curve
+    Hilbert(hilbert::Iter2D),
+
+    /// Iterator over the Moore curve
+    Moore(moore::Iter2D),
+
+    /// Iterator over the Sierpiński curve
+    Sierpinski(sierpinski::Iter2D),
+
+    /// Iterator over the H-index curve
+    HIndex(hindex::Iter2D),
+
+    /// Iterator over the snake order
+    Snake(snake::Iter2D),
+
+    /// Iterator over the spiral order
+    Spiral(spiral::Iter2D),
+}
+
+/// Forward a method call to the inner iterator of a `CurveIter2D`
+macro_rules! dispatch {
+    ($self:ident, $iter:ident => $call:expr) => {
+        match $self {
+            CurveIter2D::Morton($iter) => $call,
+            CurveIter2D::Hilbert($iter) => $call,
+            CurveIter2D::Moore($iter) => $call,
+            CurveIter2D::Sierpinski($iter) => $call,
+            CurveIter2D::HIndex($iter) => $call,
+            CurveIter2D::Snake($iter) => $call,
+            CurveIter2D::Spiral($iter) => $call,
+        }
+    };
+}
+//
+impl Iterator for CurveIter2D {
+    type Item = Coordinates2D;
+
+    #[inline]
+    fn next(&mut self) -> Option<Coordinates2D> {
+        dispatch!(self, iter => iter.next())
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Coordinates2D> {
+        dispatch!(self, iter => iter.nth(n))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        dispatch!(self, iter => iter.size_hint())
+    }
+}
+//
+impl DoubleEndedIterator for CurveIter2D {
+    #[inline]
+    fn next_back(&mut self) -> Option<Coordinates2D> {
+        dispatch!(self, iter => iter.next_back())
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Coordinates2D> {
+        dispatch!(self, iter => iter.nth_back(n))
+    }
+}
+//
+impl core::iter::FusedIterator for CurveIter2D {}
+
+/// Iterator over the in-bounds points of a 2D space-filling curve selected at
+/// runtime, created by `CurveKind::iter_clipped_2d()`
+#[derive(Clone, Debug)]
+pub enum ClippedCurveIter2D {
+    /// Iterator over the in-bounds points of the Morton curve
+    Morton(morton::ClippedIter2D),
+
+    /// Iterator over the in-bounds points of the Hilbert curve
+    Hilbert(hilbert::ClippedIter2D),
+
+    /// Iterator over the points of another curve, which filters out the points
+    /// that are not within the rectangle whose width and height are given
+    Filtered(CurveIter2D, Coordinates2D),
+}
+//
+impl Iterator for ClippedCurveIter2D {
+    type Item = Coordinates2D;
+
+    #[inline]
+    fn next(&mut self) -> Option<Coordinates2D> {
+        match self {
+            ClippedCurveIter2D::Morton(iter) => iter.next(),
+            ClippedCurveIter2D::Hilbert(iter) => iter.next(),
+            ClippedCurveIter2D::Filtered(iter, [width, height]) => {
+                iter.find(|&[x, y]| x < *width && y < *height)
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            ClippedCurveIter2D::Morton(iter) => iter.size_hint(),
+            ClippedCurveIter2D::Hilbert(iter) => iter.size_hint(),
+            ClippedCurveIter2D::Filtered(iter, _bounds) => (0, iter.size_hint().1),
+        }
+    }
+}
+//
+impl core::iter::FusedIterator for ClippedCurveIter2D {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::*;
+
+    // Check that a curve's trait methods are consistent with each other
+    fn test<Curve: SpaceFillingCurve2D>() {
+        for idx in sampled_indices() {
+            let coords = Curve::decode(idx);
+            assert_eq!(Curve::encode(coords), idx);
+            assert_eq!(Curve::iter_from(idx).next(), Some(coords));
+        }
+        assert!(Curve::iter().take(1000).eq((0..1000).map(Curve::decode)));
+        assert_eq!(
+            Curve::iter_from(CurveIdx::MAX).collect::<Vec<_>>(),
+            vec![Curve::decode(CurveIdx::MAX)]
+        );
+        assert_eq!(
+            Curve::iter().next_back(),
+            Some(Curve::decode(CurveIdx::MAX))
+        );
+    }
+
+    #[test]
+    fn morton() {
+        test::<Morton2D>();
+    }
+
+    #[test]
+    fn hilbert() {
+        test::<Hilbert2D>();
+    }
+
+    #[test]
+    fn moore() {
+        test::<Moore2D>();
+    }
+
+    #[test]
+    fn sierpinski() {
+        test::<Sierpinski2D>();
+    }
+
+    #[test]
+    fn hindex() {
+        test::<HIndex2D>();
+    }
+
+    #[test]
+    fn snake() {
+        test::<Snake2D>();
+    }
+
+    #[test]
+    fn spiral() {
+        test::<Spiral2D>();
+    }
+
+    #[test]
+    fn bounded() {
+        fn test<const ORDER: NumBits>() {
+            type Morton<const ORDER: NumBits> = BoundedMorton2D<ORDER>;
+            type Hilbert<const ORDER: NumBits> = BoundedHilbert2D<ORDER>;
+            for num_bits in [
+                Morton::<ORDER>::MAX_IDX.count_ones(),
+                Morton::<ORDER>::MAX_IDX.trailing_ones(),
+                2 * Morton::<ORDER>::MAX_COORD.count_ones(),
+                2 * Morton::<ORDER>::MAX_COORD.trailing_ones(),
+            ] {
+                assert_eq!(num_bits, 2 * ORDER);
+            }
+            assert_eq!(Hilbert::<ORDER>::MAX_IDX, Morton::<ORDER>::MAX_IDX);
+            assert_eq!(Hilbert::<ORDER>::MAX_COORD, Morton::<ORDER>::MAX_COORD);
+            assert!(Morton::<ORDER>::iter().eq(morton::iter_range_2d(..=Morton::<ORDER>::MAX_IDX)));
+            assert!(Hilbert::<ORDER>::iter().eq(hilbert::iter_2d_order(ORDER)));
+            for code in 0..=Morton::<ORDER>::MAX_IDX {
+                let coords = morton::decode_2d(code);
+                assert_eq!(Morton::<ORDER>::decode(code), coords);
+                assert_eq!(Morton::<ORDER>::encode(coords), code);
+                let coords = hilbert::decode_2d_order(code, ORDER);
+                assert_eq!(Hilbert::<ORDER>::decode(code), coords);
+                assert_eq!(Hilbert::<ORDER>::encode(coords), code);
+
+                // Out-of-range bits are ignored
+                let high = !Morton::<ORDER>::MAX_IDX;
+                let high_coord = !Morton::<ORDER>::MAX_COORD;
+                assert_eq!(Hilbert::<ORDER>::decode(code | high), coords);
+                assert_eq!(
+                    Hilbert::<ORDER>::encode([coords[0] | high_coord, coords[1] | high_coord]),
+                    code
+                );
+            }
+        }
+        test::<0>();
+        test::<1>();
+        test::<3>();
+        test::<MAX_TESTED_ORDER>();
+    }
+
+    #[test]
+    fn curve_kind() {
+        for (kind, name) in [
+            (CurveKind::Morton, "morton"),
+            (CurveKind::Hilbert, "hilbert"),
+            (CurveKind::Moore, "moore"),
+            (CurveKind::Sierpinski, "sierpinski"),
+            (CurveKind::HIndex, "hindex"),
+            (CurveKind::Snake, "snake"),
+            (CurveKind::Spiral, "spiral"),
+        ] {
+            assert_eq!(kind.to_string(), name);
+            assert_eq!(name.parse(), Ok(kind));
+            assert_eq!(name.to_uppercase().parse(), Ok(kind));
+            for code in sampled_indices() {
+                let coords = kind.decode_2d(code);
+                assert_eq!(kind.encode_2d(coords), code);
+                assert_eq!(kind.iter_from_2d(code).next(), Some(coords));
+            }
+            assert!(kind
+                .iter_2d()
+                .take(1000)
+                .eq((0..1000).map(|code| kind.decode_2d(code))));
+            // Filtering the whole curve is only tractable with 16-bit curve indices
+            #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+            assert!(kind
+                .iter_clipped_2d(3, 5)
+                .eq(kind.iter_2d().filter(|&[x, y]| x < 3 && y < 5)));
+        }
+        assert_eq!(CurveKind::Morton.decode_2d(42), morton::decode_2d(42));
+        assert_eq!(CurveKind::Hilbert.decode_2d(42), hilbert::decode_2d(42));
+        assert_eq!(
+            CurveKind::Spiral.encode_2d([3, 4]),
+            spiral::encode_2d([3, 4])
+        );
+        assert_eq!(
+            CurveKind::Moore.iter_from_2d(7).nth_back(2),
+            moore::iter_from_2d(7).nth_back(2)
+        );
+        assert_eq!(
+            CurveKind::Hilbert.iter_2d().next_back(),
+            Some(hilbert::decode_2d(CurveIdx::MAX))
+        );
+        assert!("peano".parse::<CurveKind>().is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Genericity over the curve index type
+
+use crate::{hilbert, morton};
+use core::{fmt::Debug, hash::Hash};
+
+/// Unsigned integer type that can be used as a space-filling curve index
+///
+/// This trait provides a generic interface to the curve decoding and encoding
+/// functions, which are otherwise instantiated for each supported curve index
+/// type with a suffix in their name (e.g. `morton::decode_2d_u64()`). Since
+/// trait methods cannot be `const fn` yet, code that needs compile-time
+/// evaluation should keep using the suffixed functions.
+///
+/// This trait is sealed, it is only implemented for the integer types that the
+/// curve algorithms have been instantiated for.
+///
+pub trait CurveUint: Copy + Debug + Eq + Hash + Ord + sealed::Sealed {
+    /// Coordinate type, which has half as many bits as the curve index type
+    type Coordinate: Copy + Debug + Eq + Hash + Ord;
+
+    /// Compute the coordinates of the point at this index on the Morton curve
+    ///
+    /// See `morton::decode_2d()` for details.
+    ///
+    fn morton_decode_2d(self) -> [Self::Coordinate; 2];
+
+    /// Compute the index of a point on the Morton curve
+    ///
+    /// See `morton::encode_2d()` for details.
+    ///
+    fn morton_encode_2d(coords: [Self::Coordinate; 2]) -> Self;
+
+    /// Compute the coordinates of the point at this index on the Hilbert curve
+    ///
+    /// See `hilbert::decode_2d()` for details.
+    ///
+    fn hilbert_decode_2d(self) -> [Self::Coordinate; 2];
+
+    /// Compute the index of a point on the Hilbert curve
+    ///
+    /// See `hilbert::encode_2d()` for details.
+    ///
+    fn hilbert_encode_2d(coords: [Self::Coordinate; 2]) -> Self;
+
+    /// Compute the coordinates of the point at this index on the N-dimensional
+    /// Hilbert curve
+    ///
+    /// See `hilbert::decode_nd()` for details.
+    ///
+    fn hilbert_decode_nd<const N: usize>(self) -> [Self::Coordinate; N];
+
+    /// Compute the index of a point on the N-dimensional Hilbert curve
+    ///
+    /// See `hilbert::encode_nd()` for details.
+    ///
+    fn hilbert_encode_nd<const N: usize>(coords: [Self::Coordinate; N]) -> Self;
+}
+
+/// Implement CurveUint for a curve index type by forwarding to the matching
+/// instantiation of the curve algorithms
+macro_rules! impl_curve_uint {
+    ($curve_idx:ty, $coordinate:ty, $width:ident) => {
+        impl sealed::Sealed for $curve_idx {}
+        //
+        impl CurveUint for $curve_idx {
+            type Coordinate = $coordinate;
+
+            #[inline]
+            fn morton_decode_2d(self) -> [$coordinate; 2] {
+                morton::$width::decode_2d(self)
+            }
+
+            #[inline]
+            fn morton_encode_2d(coords: [$coordinate; 2]) -> Self {
+                morton::$width::encode_2d(coords)
+            }
+
+            #[inline]
+            fn hilbert_decode_2d(self) -> [$coordinate; 2] {
+                hilbert::$width::decode_2d(self)
+            }
+
+            #[inline]
+            fn hilbert_encode_2d(coords: [$coordinate; 2]) -> Self {
+                hilbert::$width::encode_2d(coords)
+            }
+
+            #[inline]
+            fn hilbert_decode_nd<const N: usize>(self) -> [$coordinate; N] {
+                hilbert::$width::decode_nd(self)
+            }
+
+            #[inline]
+            fn hilbert_encode_nd<const N: usize>(coords: [$coordinate; N]) -> Self {
+                hilbert::$width::encode_nd(coords)
+            }
+        }
+    };
+}
+//
+impl_curve_uint!(u16, u8, idx16);
+impl_curve_uint!(u32, u16, idx32);
+impl_curve_uint!(u64, u32, idx64);
+impl_curve_uint!(u128, u64, idx128);
+
+/// Private trait used to prevent implementations of CurveUint outside of
+/// this crate, so that it can gain new methods without breaking changes
+mod sealed {
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    // Check that trait methods agree with the suffixed functions
+    fn test<I: CurveUint>(
+        code: I,
+        morton_decode_2d: impl Fn(I) -> [I::Coordinate; 2],
+        hilbert_decode_2d: impl Fn(I) -> [I::Coordinate; 2],
+        hilbert_encode_nd: impl Fn([I::Coordinate; 2]) -> I,
+    ) -> bool {
+        let morton = code.morton_decode_2d();
+        let hilbert = code.hilbert_decode_2d();
+        let hilbert_nd = I::hilbert_encode_nd(morton);
+        morton == morton_decode_2d(code)
+            && I::morton_encode_2d(morton) == code
+            && hilbert == hilbert_decode_2d(code)
+            && I::hilbert_encode_2d(hilbert) == code
+            && hilbert_nd == hilbert_encode_nd(morton)
+            && hilbert_nd.hilbert_decode_nd() == morton
+    }
+
+    #[test]
+    fn u16() {
+        for code in 0..=u16::MAX {
+            assert!(test(
+                code,
+                morton::decode_2d_u16,
+                hilbert::decode_2d_u16,
+                hilbert::encode_nd_u16
+            ));
+        }
+    }
+
+    quickcheck! {
+        fn u32(code: u32) -> bool {
+            test(code, morton::decode_2d_u32, hilbert::decode_2d_u32, hilbert::encode_nd_u32)
+        }
+
+        fn u64(code: u64) -> bool {
+            test(code, morton::decode_2d_u64, hilbert::decode_2d_u64, hilbert::encode_nd_u64)
+        }
+
+        fn u128(code: u128) -> bool {
+            test(code, morton::decode_2d_u128, hilbert::decode_2d_u128, hilbert::encode_nd_u128)
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Iteration over space-filling curves without a specialized iterator
+//!
+//! The Morton and Hilbert curves have iterators that compute each point from the
+//! previous one, which is faster than decoding it from scratch. For other curves,
+//! this module provides iterators that simply decode each curve index, with the
+//! same interface so that one can easily switch from one curve to another.
+
+/// Generate iteration utilities for a curve module, based on its `decode_2d()`
+/// function and the `CurveIdx`, `Coordinates2D` and `bits` names in scope
+macro_rules! decode_iter_impl {
+    ($curve_name:literal) => {
+        #[doc = concat!("Iterate over the 2D ", $curve_name, " curve")]
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of all possible
+        /// curve indices (CurveIdx::MIN..=CurveIdx::MAX).
+        ///
+        pub fn iter_2d() -> Iter2D {
+            iter_range_2d(..)
+        }
+
+        #[doc = concat!("Iterate over the 2D ", $curve_name, " curve, starting from a certain index")]
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of curve
+        /// indices (start..=CurveIdx::MAX).
+        ///
+        pub fn iter_from_2d(start: CurveIdx) -> Iter2D {
+            iter_range_2d(start..)
+        }
+
+        #[doc = concat!("Iterate over a range of indices of the 2D ", $curve_name, " curve")]
+        ///
+        /// This is equivalent to running `decode_2d()` on the range of curve
+        /// indices.
+        ///
+        pub fn iter_range_2d(indices: impl core::ops::RangeBounds<CurveIdx>) -> Iter2D {
+            Iter2D {
+                indices: bits::inclusive_range(indices),
+            }
+        }
+
+        #[doc = concat!("Iterator over the 2D ", $curve_name, " curve, created by `iter_2d()`,")]
+        /// `iter_from_2d()` and `iter_range_2d()`
+        ///
+        /// This iterator decodes each curve index from scratch, which means that
+        /// skipping points with `nth()` or `step_by()` is as fast as iterating.
+        ///
+        #[derive(Clone, Debug)]
+        pub struct Iter2D {
+            /// Indices of the remaining points
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        }
+        //
+        impl Iterator for Iter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                self.indices.next().map(decode_2d)
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Coordinates2D> {
+                self.indices.nth(n).map(decode_2d)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.indices.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Iter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Coordinates2D> {
+                self.indices.next_back().map(decode_2d)
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Coordinates2D> {
+                self.indices.nth_back(n).map(decode_2d)
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Iter2D {}
+    };
+}
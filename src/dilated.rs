@@ -0,0 +1,276 @@
+//! Dilated integers
+//!
+//! A dilated integer is an integer whose bits have been spread apart, so that
+//! there is a zero between each of them. Morton codes are made of two dilated
+//! coordinates, the x coordinate at even bit positions and the y coordinate at
+//! odd positions, and keeping coordinates in dilated form is a classic way to
+//! move around a Z-ordered grid without decoding and re-encoding Morton codes,
+//! since dilated integers can be added and subtracted directly.
+//!
+//! The `Dilated` type keeps track of which integers are dilated, so that they
+//! cannot be mixed up with normal integers. `Dilated<u8>` is stored as a `u16`,
+//! `Dilated<u16>` as a `u32`, and so on, and the Morton code of a point is
+//! `x.to_bits() | (y.to_bits() << 1)`. Dilated integers are built with
+//! `From`, or with `const fn`s such as `Dilated::<u8>::new()`. See `morton::add_x()` and friends for
+//! the equivalent operations on whole Morton codes.
+
+use crate::morton;
+use core::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+/// Unsigned integer type that can be dilated
+///
+/// This trait is sealed, it is only implemented for the coordinate types that
+/// the crate supports.
+///
+pub trait Dilatable: Copy + Debug + Eq + Hash + Ord + sealed::Sealed {
+    /// Integer type with twice as many bits, which stores the dilated integer
+    type Bits: Copy + Debug + Default + Eq + Hash + Ord;
+}
+
+/// Dilated integer, whose bits are at the even bit positions of the underlying
+/// storage
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Dilated<T: Dilatable>(T::Bits);
+
+/// Implement `Dilated` for a certain integer type, dilated by the Morton
+/// encoding functions of the matching curve index type
+macro_rules! dilated_impl {
+    ($uint:ty, $bits:ty, $encode_2d:path, $decode_2d:path) => {
+        impl Dilatable for $uint {
+            type Bits = $bits;
+        }
+        //
+        impl sealed::Sealed for $uint {}
+        //
+        impl Dilated<$uint> {
+            /// Mask of the bits that a dilated integer can use
+            const MASK: $bits = $encode_2d([<$uint>::MAX, 0]);
+
+            /// Dilated zero
+            pub const ZERO: Self = Self(0);
+
+            /// Largest dilated integer
+            pub const MAX: Self = Self(Self::MASK);
+
+            /// Dilate an integer
+            #[inline]
+            pub const fn new(value: $uint) -> Self {
+                Self($encode_2d([value, 0]))
+            }
+
+            /// Integer that was dilated
+            #[inline]
+            pub const fn get(self) -> $uint {
+                $decode_2d(self.0)[0]
+            }
+
+            /// Dilated integer with certain underlying bits, ignoring the bits that
+            /// are at odd positions
+            #[inline]
+            pub const fn from_bits(bits: $bits) -> Self {
+                Self(bits & Self::MASK)
+            }
+
+            /// Underlying bits of this dilated integer
+            #[inline]
+            pub const fn to_bits(self) -> $bits {
+                self.0
+            }
+
+            /// Checked addition, returning `None` on overflow
+            #[inline]
+            pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+                match self.overflowing_add(rhs) {
+                    (sum, false) => Some(sum),
+                    (_, true) => None,
+                }
+            }
+
+            /// Wrapping addition, which wraps around at the boundary of the
+            /// integer type
+            #[inline]
+            pub const fn wrapping_add(self, rhs: Self) -> Self {
+                self.overflowing_add(rhs).0
+            }
+
+            /// Checked subtraction, returning `None` on overflow
+            #[inline]
+            pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                match self.overflowing_sub(rhs) {
+                    (difference, false) => Some(difference),
+                    (_, true) => None,
+                }
+            }
+
+            /// Wrapping subtraction, which wraps around at the boundary of the
+            /// integer type
+            #[inline]
+            pub const fn wrapping_sub(self, rhs: Self) -> Self {
+                self.overflowing_sub(rhs).0
+            }
+
+            /// Addition, along with the truth that it overflowed
+            #[inline(always)]
+            const fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+                // Carries propagate through the odd bits once they are set, and
+                // out of the underlying integer if the addition overflows
+                let (sum, overflow) = (self.0 | !Self::MASK).overflowing_add(rhs.0);
+                (Self(sum & Self::MASK), overflow)
+            }
+
+            /// Subtraction, along with the truth that it overflowed
+            #[inline(always)]
+            const fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+                // Borrows propagate through the odd bits, which are cleared
+                let (difference, overflow) = self.0.overflowing_sub(rhs.0);
+                (Self(difference & Self::MASK), overflow)
+            }
+        }
+        //
+        impl From<$uint> for Dilated<$uint> {
+            #[inline]
+            fn from(value: $uint) -> Self {
+                Self::new(value)
+            }
+        }
+        //
+        impl From<Dilated<$uint>> for $uint {
+            #[inline]
+            fn from(dilated: Dilated<$uint>) -> Self {
+                dilated.get()
+            }
+        }
+        //
+        impl Add for Dilated<$uint> {
+            type Output = Self;
+
+            /// # Panics
+            ///
+            /// On overflow, if debug assertions are enabled.
+            ///
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                let (sum, overflow) = self.overflowing_add(rhs);
+                debug_assert!(!overflow, "attempt to add with overflow");
+                sum
+            }
+        }
+        //
+        impl AddAssign for Dilated<$uint> {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+        //
+        impl Sub for Dilated<$uint> {
+            type Output = Self;
+
+            /// # Panics
+            ///
+            /// On overflow, if debug assertions are enabled.
+            ///
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                let (difference, overflow) = self.overflowing_sub(rhs);
+                debug_assert!(!overflow, "attempt to subtract with overflow");
+                difference
+            }
+        }
+        //
+        impl SubAssign for Dilated<$uint> {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+//
+dilated_impl!(u8, u16, morton::encode_2d_u16, morton::decode_2d_u16);
+dilated_impl!(u16, u32, morton::encode_2d_u32, morton::decode_2d_u32);
+dilated_impl!(u32, u64, morton::encode_2d_u64, morton::decode_2d_u64);
+dilated_impl!(u64, u128, morton::encode_2d_u128, morton::decode_2d_u128);
+
+/// Private trait used to prevent implementations of Dilatable outside of
+/// this crate, so that it can gain new methods without breaking changes
+mod sealed {
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    #[test]
+    fn u8() {
+        assert_eq!(Dilated::<u8>::MAX.to_bits(), 0x5555);
+        for a in 0..=u8::MAX {
+            let dilated = Dilated::<u8>::new(a);
+            assert_eq!(dilated.get(), a);
+            assert_eq!(u8::from(Dilated::from(a)), a);
+            assert_eq!(
+                Dilated::<u8>::from_bits(dilated.to_bits() | 0xaaaa),
+                dilated
+            );
+            assert_eq!(
+                morton::encode_2d_u16([a, 0]) | (dilated.to_bits() << 1),
+                morton::encode_2d_u16([a, a])
+            );
+            for b in 0..=u8::MAX {
+                let other = Dilated::<u8>::new(b);
+                assert_eq!(dilated.cmp(&other), a.cmp(&b));
+                assert_eq!(
+                    dilated.checked_add(other).map(|sum| sum.get()),
+                    a.checked_add(b)
+                );
+                assert_eq!(
+                    dilated.checked_sub(other).map(|sum| sum.get()),
+                    a.checked_sub(b)
+                );
+                assert_eq!(dilated.wrapping_add(other).get(), a.wrapping_add(b));
+                assert_eq!(dilated.wrapping_sub(other).get(), a.wrapping_sub(b));
+            }
+        }
+        let mut x = Dilated::<u8>::new(3);
+        x += Dilated::<u8>::new(4);
+        assert_eq!(x, Dilated::<u8>::new(7));
+        x -= Dilated::<u8>::new(2);
+        assert_eq!(
+            x - Dilated::<u8>::new(1) + Dilated::<u8>::new(10),
+            Dilated::<u8>::new(14)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn add_overflow() {
+        let _ = Dilated::<u8>::MAX + Dilated::<u8>::new(1);
+    }
+
+    macro_rules! wide_tests {
+        ($($uint:ident),*) => {
+            $(
+                quickcheck! {
+                    fn $uint(a: $uint, b: $uint) -> bool {
+                        let [x, y] = [Dilated::<$uint>::new(a), Dilated::<$uint>::new(b)];
+                        x.get() == a
+                            && x.cmp(&y) == a.cmp(&b)
+                            && x.checked_add(y).map(|sum| sum.get()) == a.checked_add(b)
+                            && x.checked_sub(y).map(|sum| sum.get()) == a.checked_sub(b)
+                            && x.wrapping_add(y).get() == a.wrapping_add(b)
+                            && x.wrapping_sub(y).get() == a.wrapping_sub(b)
+                    }
+                }
+            )*
+        };
+    }
+    wide_tests!(u16, u32, u64);
+}
@@ -0,0 +1,35 @@
+//! Crate-wide error type
+//!
+//! Each parser of this crate has its own error type, as the set of things that
+//! can go wrong when parsing a string depends a lot on the format. But the
+//! checked curve index and coordinate conversions all fail for the same few
+//! reasons, which are listed by the `Error` enum of this module.
+
+use core::fmt;
+
+/// Error returned by checked curve index and coordinate conversions
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A curve index lies outside of the domain of the curve
+    IndexOutOfDomain,
+
+    /// A coordinate lies outside of the domain of the curve
+    CoordinateOutOfDomain,
+
+    /// The requested curve order does not fit in the `Coordinate` type
+    UnsupportedOrder,
+}
+//
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Error::IndexOutOfDomain => "curve index is outside of the curve's domain",
+            Error::CoordinateOutOfDomain => "coordinate is outside of the curve's domain",
+            Error::UnsupportedOrder => "curve order is not supported by the Coordinate type",
+        })
+    }
+}
+//
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
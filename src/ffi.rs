@@ -0,0 +1,162 @@
+//! C foreign function interface
+//!
+//! This module exposes the 2D Morton and Hilbert curves to C and C++ code via
+//! `extern "C"` functions, which are prefixed with `sf_` to avoid name clashes.
+//! Whatever `CurveIdx` type is selected via cargo features, these functions use
+//! 64-bit curve indices and 32-bit coordinates, so that the C interface does not
+//! depend on the crate's configuration.
+//!
+//! The signatures are kept simple enough for cbindgen to generate a C header
+//! from them. To link this crate into a C or C++ program, build it as a static
+//! or dynamic library with the `ffi` feature enabled, e.g. with
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use crate::{hilbert, morton};
+use core::slice;
+
+/// Point of the curves' domain, as seen from C
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(C)]
+pub struct SfPoint2D {
+    /// Horizontal coordinate
+    pub x: u32,
+
+    /// Vertical coordinate
+    pub y: u32,
+}
+//
+impl From<[u32; 2]> for SfPoint2D {
+    #[inline]
+    fn from([x, y]: [u32; 2]) -> Self {
+        Self { x, y }
+    }
+}
+//
+impl From<SfPoint2D> for [u32; 2] {
+    #[inline]
+    fn from(point: SfPoint2D) -> Self {
+        [point.x, point.y]
+    }
+}
+
+/// Generate the C interface of a curve module
+macro_rules! ffi_impl {
+    (
+        $curve:ident, $curve_name:literal,
+        $decode_2d:ident, $encode_2d:ident,
+        $decode_2d_batch:ident, $encode_2d_batch:ident
+    ) => {
+        #[doc = concat!("Compute the coordinates of the point at index `code` on the 2D ", $curve_name)]
+        /// curve
+        #[no_mangle]
+        pub extern "C" fn $decode_2d(code: u64) -> SfPoint2D {
+            $curve::decode_2d_u64(code).into()
+        }
+
+        #[doc = concat!("Compute the index of a point on the 2D ", $curve_name, " curve")]
+        #[no_mangle]
+        pub extern "C" fn $encode_2d(point: SfPoint2D) -> u64 {
+            $curve::encode_2d_u64(point.into())
+        }
+
+        #[doc = concat!("Compute the coordinates of a batch of points of the 2D ", $curve_name)]
+        /// curve, using SIMD instructions
+        ///
+        /// # Safety
+        ///
+        /// `codes` must point to `len` readable curve indices and `points` must
+        /// point to `len` writable points, and these buffers must not overlap.
+        /// They may be null if `len` is zero.
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $decode_2d_batch(
+            codes: *const u64,
+            points: *mut SfPoint2D,
+            len: usize,
+        ) {
+            if len == 0 {
+                return;
+            }
+            // SfPoint2D is a repr(C) pair of u32, so it has the layout of [u32; 2]
+            let codes = slice::from_raw_parts(codes, len);
+            let points = slice::from_raw_parts_mut(points.cast::<[u32; 2]>(), len);
+            $curve::decode_2d_into_u64(codes, points);
+        }
+
+        #[doc = concat!("Compute the indices of a batch of points on the 2D ", $curve_name)]
+        /// curve, using SIMD instructions
+        ///
+        /// # Safety
+        ///
+        /// `points` must point to `len` readable points and `codes` must point
+        /// to `len` writable curve indices, and these buffers must not overlap.
+        /// They may be null if `len` is zero.
+        ///
+        #[no_mangle]
+        pub unsafe extern "C" fn $encode_2d_batch(
+            points: *const SfPoint2D,
+            codes: *mut u64,
+            len: usize,
+        ) {
+            if len == 0 {
+                return;
+            }
+            let points = slice::from_raw_parts(points.cast::<[u32; 2]>(), len);
+            let codes = slice::from_raw_parts_mut(codes, len);
+            $curve::encode_2d_into_u64(points, codes);
+        }
+    };
+}
+//
+ffi_impl!(
+    morton,
+    "Morton",
+    sf_morton_decode_2d,
+    sf_morton_encode_2d,
+    sf_morton_decode_2d_batch,
+    sf_morton_encode_2d_batch
+);
+ffi_impl!(
+    hilbert,
+    "Hilbert",
+    sf_hilbert_decode_2d,
+    sf_hilbert_encode_2d,
+    sf_hilbert_decode_2d_batch,
+    sf_hilbert_encode_2d_batch
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr;
+
+    #[test]
+    fn ffi() {
+        let codes = [0, 1, 2, 3, 42, u64::MAX];
+        let mut points = [SfPoint2D::default(); 6];
+        let mut roundtrip = [0; 6];
+        unsafe {
+            sf_morton_decode_2d_batch(codes.as_ptr(), points.as_mut_ptr(), codes.len());
+            sf_morton_encode_2d_batch(points.as_ptr(), roundtrip.as_mut_ptr(), points.len());
+            sf_morton_decode_2d_batch(ptr::null(), ptr::null_mut(), 0);
+        }
+        for (&code, &point) in codes.iter().zip(&points) {
+            assert_eq!(point, morton::decode_2d_u64(code).into());
+            assert_eq!(sf_morton_decode_2d(code), point);
+            assert_eq!(sf_morton_encode_2d(point), code);
+        }
+        assert_eq!(roundtrip, codes);
+
+        unsafe {
+            sf_hilbert_decode_2d_batch(codes.as_ptr(), points.as_mut_ptr(), codes.len());
+            sf_hilbert_encode_2d_batch(points.as_ptr(), roundtrip.as_mut_ptr(), points.len());
+            sf_hilbert_encode_2d_batch(ptr::null(), ptr::null_mut(), 0);
+        }
+        for (&code, &point) in codes.iter().zip(&points) {
+            assert_eq!(point, hilbert::decode_2d_u64(code).into());
+            assert_eq!(sf_hilbert_decode_2d(code), point);
+            assert_eq!(sf_hilbert_encode_2d(point), code);
+        }
+        assert_eq!(roundtrip, codes);
+    }
+}
@@ -0,0 +1,193 @@
+//! Hilbert curve indexing of geographic coordinates
+//!
+//! Ordering GPS points along a space-filling curve is a common way to shard
+//! geospatial data, as points that are close on Earth then tend to end up in the
+//! same shard. This module maps latitudes and longitudes to Hilbert curve
+//! indices at a chosen precision, and back to the corresponding grid cells.
+//!
+//! Longitudes go from -180 to 180 degrees and are mapped to the x coordinate,
+//! while latitudes go from -90 to 90 degrees and are mapped to the y coordinate.
+//! At a precision of N bits, each axis is split into 2^N intervals of equal size,
+//! and the resulting curve indices go from 0 to 4^N - 1.
+
+use crate::{hilbert, Coordinate, Coordinates2D, CurveIdx, NumBits};
+
+/// Axis-aligned bounding box in latitude/longitude space, in degrees
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LatLonBox {
+    /// Southern bound
+    pub min_lat: f64,
+
+    /// Western bound
+    pub min_lon: f64,
+
+    /// Northern bound
+    pub max_lat: f64,
+
+    /// Eastern bound
+    pub max_lon: f64,
+}
+//
+impl LatLonBox {
+    /// Truth that a point is inside of the box, counting the southern and
+    /// western bounds in but not the northern and eastern ones, which belong to
+    /// the neighboring boxes
+    pub fn contains(&self, lat: f64, lon: f64) -> bool {
+        (self.min_lat..self.max_lat).contains(&lat) && (self.min_lon..self.max_lon).contains(&lon)
+    }
+}
+
+/// Quantize a latitude and a longitude, in degrees, into grid coordinates with
+/// `bits` bits of precision
+///
+/// Out-of-range latitudes and longitudes are clamped, so the north pole and the
+/// antimeridian belong to the last row and column of the grid.
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`.
+///
+pub fn quantize_lat_lon(lat: f64, lon: f64, bits: NumBits) -> Coordinates2D {
+    check_bits(bits);
+    [quantize(lon, 180.0, bits), quantize(lat, 90.0, bits)]
+}
+
+/// Bounding box of the grid cell with certain coordinates, at a precision of
+/// `bits` bits
+///
+/// This is the inverse of `quantize_lat_lon()`, in the sense that the box
+/// contains every point that quantizes to these coordinates.
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`, or if the
+/// coordinates do not fit in `bits` bits.
+///
+pub fn cell_bounds([x, y]: Coordinates2D, bits: NumBits) -> LatLonBox {
+    check_bits(bits);
+    assert!(
+        (x | y) as u128 >> bits == 0,
+        "Coordinates are out of range for this precision"
+    );
+    LatLonBox {
+        min_lat: dequantize(y, 90.0, bits),
+        min_lon: dequantize(x, 180.0, bits),
+        max_lat: dequantize_end(y, 90.0, bits),
+        max_lon: dequantize_end(x, 180.0, bits),
+    }
+}
+
+/// Compute the Hilbert curve index of a point given by its latitude and
+/// longitude, in degrees, at a precision of `bits` bits per axis
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`.
+///
+pub fn hilbert_key(lat: f64, lon: f64, bits: NumBits) -> CurveIdx {
+    hilbert::encode_2d(quantize_lat_lon(lat, lon, bits))
+}
+
+/// Bounding box of the grid cell with a certain Hilbert curve index, at a
+/// precision of `bits` bits per axis
+///
+/// This is the inverse of `hilbert_key()`.
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`, or if `key`
+/// does not fit in 2 x `bits` bits.
+///
+pub fn hilbert_cell(key: CurveIdx, bits: NumBits) -> LatLonBox {
+    cell_bounds(hilbert::decode_2d(key), bits)
+}
+
+/// Check that a precision is supported
+fn check_bits(bits: NumBits) {
+    assert!(
+        bits <= Coordinate::BITS,
+        "Precision of {bits} bits is too high for this Coordinate type"
+    );
+}
+
+/// Number of grid intervals along an axis at a certain precision
+fn num_intervals(bits: NumBits) -> f64 {
+    (1u128 << bits) as f64
+}
+
+/// Map a value from [-range; range] to [0; 2^bits[
+fn quantize(value: f64, range: f64, bits: NumBits) -> Coordinate {
+    // Float-to-integer casts saturate, so only the upper bound needs clamping
+    let max = ((1u128 << bits) - 1) as Coordinate;
+    let scaled = (value + range) / (2.0 * range) * num_intervals(bits);
+    (scaled as Coordinate).min(max)
+}
+
+/// Lower bound of the range of values that `quantize()` maps to an integer
+fn dequantize(value: Coordinate, range: f64, bits: NumBits) -> f64 {
+    value as f64 / num_intervals(bits) * (2.0 * range) - range
+}
+
+/// Upper bound of the range of values that `quantize()` maps to an integer
+///
+/// The increment is performed in integer arithmetic because at high precision,
+/// `value as f64 + 1.0` can round back to `value as f64`.
+///
+fn dequantize_end(value: Coordinate, range: f64, bits: NumBits) -> f64 {
+    (value as u128 + 1) as f64 / num_intervals(bits) * (2.0 * range) - range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hilbert_key() {
+        for bits in 0..=Coordinate::BITS {
+            for &(lat, lon) in &[
+                (0.0, 0.0),
+                (48.8566, 2.3522),
+                (-33.8688, 151.2093),
+                (-90.0, -180.0),
+                (90.0, 180.0),
+                (89.999, -179.999),
+            ] {
+                let key = super::hilbert_key(lat, lon, bits);
+                assert_eq!((key as u128).checked_shr(2 * bits).unwrap_or(0), 0);
+                let cell = hilbert_cell(key, bits);
+                // Beyond f64 precision, quantization and cell bounds are
+                // rounded to the nearest representable angle
+                let check_axis = |value: f64, min: f64, max: f64, range: f64| {
+                    let tolerance = 4.0 * range * f64::EPSILON;
+                    assert!(
+                        (min - tolerance..=max + tolerance).contains(&value),
+                        "{cell:?} does not contain ({lat}, {lon}) at {bits} bits"
+                    );
+                    let error = (max - min) - 2.0 * range / num_intervals(bits);
+                    assert!(
+                        error.abs() <= tolerance,
+                        "Unexpected cell width {} at {bits} bits",
+                        max - min
+                    );
+                };
+                check_axis(lat, cell.min_lat, cell.max_lat, 90.0);
+                check_axis(lon, cell.min_lon, cell.max_lon, 180.0);
+            }
+        }
+
+        // At 1 bit of precision, the curve goes through the 4 quadrants of the
+        // globe, starting from the south-west one
+        let mut quadrants = (0..4)
+            .map(|key| {
+                let cell = hilbert_cell(key, 1);
+                [cell.min_lat, cell.min_lon]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(quadrants[0], [-90.0, -180.0]);
+        quadrants.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            quadrants,
+            [[-90.0, -180.0], [-90.0, 0.0], [0.0, -180.0], [0.0, 0.0]]
+        );
+    }
+}
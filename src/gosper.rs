@@ -0,0 +1,379 @@
+//! Utilities related to the Gosper space-filling curve (aka flowsnake)
+//!
+//! Unlike the other curves of this crate, which fill square grids, the Gosper
+//! curve fills a hexagonal grid. Hexagons are identified by their axial
+//! coordinates `[q, r]`, which are signed integers: the six neighbors of the
+//! hexagon `[q, r]` are `[q ± 1, r]`, `[q, r ± 1]`, `[q + 1, r - 1]` and
+//! `[q - 1, r + 1]`.
+//!
+//! The curve starts at the hexagon `[0, 0]` and can be extended indefinitely,
+//! each group of 7^N points being a copy of the first one at a different
+//! location, rotation and orientation. Therefore, it fills the hexagonal grid in
+//! all directions, but only in a roughly circular region around its start.
+//! As a result, not every hexagon has a curve index, and `encode_2d()` returns
+//! `None` for hexagons that are too far away from the origin.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+//! Axial coordinates are signed integers of the same width as curve indices.
+//! Like the Sierpiński curve, this curve goes through recursion depths one by
+//! one, so decoding and encoding are slower and do not run in constant time.
+
+/// Integer type used to identify the direction of a curve segment, as a number
+/// of 60° counterclockwise turns from the q axis
+type Direction = u8;
+
+/// State of the recursion, that is the kind of curve segment (0 for A, 1 for B
+/// in the L-system notation) and its direction, encoded as `kind * 6 + dir`
+type State = u8;
+
+/// Number of possible recursion states
+const NUM_STATES: usize = 12;
+
+/// Sub-segments of each kind of curve segment, as (kind, direction offset)
+///
+/// This is the L-system A → A-B--B+A++AA+B-, B → +A-BB--B-A++A+B, where + and
+/// - are 60° counterclockwise and clockwise turns.
+///
+const SUB_SEGMENTS: [[(u8, Direction); 7]; 2] = [
+    [(0, 0), (1, 5), (1, 3), (0, 4), (0, 0), (0, 0), (1, 1)],
+    [(0, 1), (1, 0), (1, 0), (1, 4), (0, 3), (0, 5), (1, 0)],
+];
+
+/// Start of each sub-segment of a curve segment, relative to the start of the
+/// segment, in axial coordinates and in units of the sub-segment length
+///
+/// This is a running sum of the unit vectors in the directions of
+/// `SUB_SEGMENTS`.
+///
+const SUB_SEGMENT_STARTS: [[[i8; 2]; 7]; 2] = [
+    [[0, 0], [1, 0], [2, -1], [1, -1], [1, -2], [2, -2], [3, -2]],
+    [[0, 0], [0, 1], [1, 1], [2, 1], [2, 0], [1, 0], [2, -1]],
+];
+
+/// Sub-segment whose start has a certain residue, for each segment kind
+///
+/// A curve segment spans the axial vector `S = [3, -1]`, and each sub-segment
+/// spans `S` scaled down by a factor of `S`. The Eisenstein integers modulo `S`
+/// form the field `Z/7Z`, where the residue of `[q, r]` is `(q + 3r) mod 7`,
+/// and the sub-segment starts of each kind of segment have distinct residues.
+/// This table tells which sub-segment has which residue.
+///
+const SUB_SEGMENT_OF_RESIDUE: [[u8; 7]; 2] = {
+    let mut result = [[0; 7]; 2];
+    let mut kind = 0;
+    while kind < 2 {
+        let mut sub_segment = 0;
+        while sub_segment < 7 {
+            let [q, r] = SUB_SEGMENT_STARTS[kind][sub_segment];
+            result[kind][(q + 3 * r).rem_euclid(7) as usize] = sub_segment as u8;
+            sub_segment += 1;
+        }
+        kind += 1;
+    }
+    result
+};
+
+/// Inverse of the residue factor 3 associated with a 60° rotation, raised to
+/// the power of a number of rotations, modulo 7
+const INV_ROTATION_RESIDUE: [u8; 6] = [1, 5, 4, 6, 2, 3];
+
+/// Recursion state of a sub-segment of a curve segment
+#[inline(always)]
+const fn sub_segment_state(state: State, sub_segment: u8) -> State {
+    let (kind, turn) = SUB_SEGMENTS[(state / 6) as usize][sub_segment as usize];
+    kind * 6 + (state % 6 + turn) % 6
+}
+
+/// Generate Gosper curve utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! gosper_impl {
+    ($width:ident, $curve_idx:ty, $axial:ty) => {
+        use super::{
+            sub_segment_state, State, INV_ROTATION_RESIDUE, NUM_STATES, SUB_SEGMENT_OF_RESIDUE,
+            SUB_SEGMENT_STARTS,
+        };
+        use $crate::bits::$width as bits;
+
+        type CurveIdx = $curve_idx;
+        type Axial = $axial;
+        type Coordinates2D = [Axial; 2];
+
+        /// Largest power of 7 that fits in a curve index
+        const TOP_POWER: CurveIdx = {
+            let mut power: CurveIdx = 1;
+            while power <= CurveIdx::MAX / 7 {
+                power *= 7;
+            }
+            power
+        };
+
+        /// Compute the axial coordinates of the i-th hexagon of the Gosper curve
+        ///
+        /// The Gosper curve is a succession of segments of two kinds, A and B, that
+        /// are recursively subdivided into 7 smaller segments as described by the
+        /// L-system A → A-B--B+A++AA+B-, B → +A-BB--B-A++A+B. At the bottom of the
+        /// recursion, each segment goes from one hexagon to one of its neighbors.
+        /// So each base 7 digit of the curve index, starting from the most
+        /// significant one, tells which sub-segment to recurse into.
+        ///
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            let mut coords: Coordinates2D = [0, 0];
+            let mut state: State = 0;
+            let mut power = TOP_POWER;
+            while power > 0 {
+                // Sub-segment starts are scaled by the sub-segment length, so we use
+                // Horner's method to scale previous offsets by the segment length
+                // ratio S whenever we go down one level of recursion.
+                let sub_segment = ((code / power) % 7) as u8;
+                let [q, r] = SUB_SEGMENT_STARTS[(state / 6) as usize][sub_segment as usize];
+                let offset = rotate([q as Axial, r as Axial], state % 6);
+                coords = scale_up(coords);
+                coords = [coords[0] + offset[0], coords[1] + offset[1]];
+                state = sub_segment_state(state, sub_segment);
+                power /= 7;
+            }
+            coords
+        }
+
+        /// Compute the index of a hexagon on the Gosper curve, if it has one
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        /// It returns `None` if the hexagon is not among the first
+        /// `CurveIdx::MAX + 1` hexagons of the curve.
+        ///
+        pub const fn encode_2d(coords: Coordinates2D) -> Option<CurveIdx> {
+            // The least significant digit of the curve index can be deduced from
+            // the residue of the coordinates modulo S, if we know the recursion
+            // state of the corresponding segment. Since we don't, we track one
+            // hypothesis per possible state, going up the recursion until we reach
+            // the initial state with no remaining offset. Each hypothesis is made of
+            // the remaining coordinates, scaled down by S, and the curve index
+            // digits found so far. It turns out that only one hypothesis can reach
+            // each state at a given recursion depth, which keeps this tractable.
+            let mut hypotheses: [Option<(Coordinates2D, CurveIdx)>; NUM_STATES] =
+                [Some((coords, 0)); NUM_STATES];
+            let mut power: CurveIdx = 1;
+            loop {
+                let mut parent_hypotheses = [None; NUM_STATES];
+                let mut state = 0;
+                while state < NUM_STATES {
+                    if let Some((coords, code)) = hypotheses[state] {
+                        let residue = (coords[0].wrapping_add(coords[1].wrapping_mul(3)))
+                            .rem_euclid(7) as usize;
+                        let mut parent_state = 0;
+                        while parent_state < NUM_STATES {
+                            let (kind, dir) = (parent_state / 6, parent_state % 6);
+                            let residue = (residue * INV_ROTATION_RESIDUE[dir] as usize) % 7;
+                            let sub_segment = SUB_SEGMENT_OF_RESIDUE[kind][residue];
+                            if parent_hypotheses[parent_state].is_none()
+                                && sub_segment_state(parent_state as State, sub_segment)
+                                    == state as State
+                            {
+                                let [q, r] = SUB_SEGMENT_STARTS[kind][sub_segment as usize];
+                                let offset = rotate([q as Axial, r as Axial], dir as u8);
+                                let coords = scale_down([
+                                    coords[0].wrapping_sub(offset[0]),
+                                    coords[1].wrapping_sub(offset[1]),
+                                ]);
+                                let code =
+                                    code.wrapping_add(power.wrapping_mul(sub_segment as CurveIdx));
+                                parent_hypotheses[parent_state] = Some((coords, code));
+                            }
+                            parent_state += 1;
+                        }
+                    }
+                    state += 1;
+                }
+                hypotheses = parent_hypotheses;
+
+                // Once all curve index digits have been found, check if we reached
+                // the initial state. Since the arithmetic wraps around for hexagons
+                // which are far away from the origin, we also check the result.
+                if power == TOP_POWER {
+                    return match hypotheses[0] {
+                        Some(([0, 0], code)) => {
+                            let decoded = decode_2d(code);
+                            if decoded[0] == coords[0] && decoded[1] == coords[1] {
+                                Some(code)
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    };
+                }
+                power *= 7;
+            }
+        }
+
+        /// Rotate axial coordinates by a multiple of 60° counterclockwise
+        #[inline(always)]
+        const fn rotate(mut coords: Coordinates2D, turns: u8) -> Coordinates2D {
+            let mut turn = 0;
+            while turn < turns {
+                coords = [-coords[1], coords[0] + coords[1]];
+                turn += 1;
+            }
+            coords
+        }
+
+        /// Multiply axial coordinates by the segment length ratio S = [3, -1]
+        #[inline(always)]
+        const fn scale_up([q, r]: Coordinates2D) -> Coordinates2D {
+            [3 * q + r, 2 * r - q]
+        }
+
+        /// Divide axial coordinates by the segment length ratio S = [3, -1], which
+        /// they must be a multiple of
+        ///
+        /// Arithmetic wraps around on overflow, which `encode_2d()` accounts for.
+        ///
+        #[inline(always)]
+        const fn scale_down([q, r]: Coordinates2D) -> Coordinates2D {
+            // Multiply by the conjugate [2, 1] of S, then divide by its norm 7
+            [
+                q.wrapping_mul(2).wrapping_sub(r) / 7,
+                q.wrapping_add(r.wrapping_mul(3)) / 7,
+            ]
+        }
+
+        decode_iter_impl!("Gosper");
+    };
+}
+
+/// Gosper curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    gosper_impl!(idx16, u16, i16);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Gosper curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    gosper_impl!(idx32, u32, i32);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Gosper curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    gosper_impl!(idx64, u64, i64);
+}
+
+/// Gosper curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    gosper_impl!(idx128, u128, i128);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    /// Truth that two hexagons are neighbors
+    fn are_neighbors([q1, r1]: [i128; 2], [q2, r2]: [i128; 2]) -> bool {
+        let [dq, dr] = [q2 - q1, r2 - r1];
+        [[1, 0], [0, 1], [-1, 1], [-1, 0], [0, -1], [1, -1]].contains(&[dq, dr])
+    }
+
+    // Check that the curve visits every hexagon once, moving to a neighbor at
+    // every step, and that its first points match the L-system's first segments
+    #[test]
+    fn decode_2d() {
+        let first_points = (0..7).map(super::decode_2d_u16).collect::<Vec<_>>();
+        assert_eq!(
+            first_points,
+            [[0, 0], [1, 0], [2, -1], [1, -1], [1, -2], [2, -2], [3, -2]]
+        );
+        let mut visited = HashSet::new();
+        let mut prev_coords = None;
+        for code in 0..=u16::MAX {
+            let coords = super::decode_2d_u16(code);
+            assert!(visited.insert(coords), "Point {code} was visited twice");
+            if let Some(prev) = prev_coords {
+                assert!(
+                    are_neighbors(prev, coords.map(i128::from)),
+                    "Curve jumped from {prev:?} to {coords:?} at index {code}"
+                );
+            }
+            prev_coords = Some(coords.map(i128::from));
+        }
+    }
+
+    #[test]
+    fn encode_2d() {
+        for code in 0..=u16::MAX {
+            assert_eq!(super::encode_2d_u16(super::decode_2d_u16(code)), Some(code));
+        }
+        assert_eq!(super::encode_2d_u16([1000, 1000]), None);
+        assert_eq!(super::encode_2d_u16([i16::MIN, i16::MAX]), None);
+    }
+
+    #[test]
+    fn iter_2d() {
+        assert!(super::iter_2d_u16().eq((0..=u16::MAX).map(super::decode_2d_u16)));
+    }
+
+    // Wider curve index types are tested using random inputs and consistency
+    // checks with respect to narrower curve index types
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty, $narrower:ident) => {
+            mod $width {
+                use super::are_neighbors;
+                use crate::gosper::{$narrower, $width};
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let code = code.min(<$curve_idx>::MAX - 1);
+                        are_neighbors(
+                            $width::decode_2d(code).map(i128::from),
+                            $width::decode_2d(code + 1).map(i128::from),
+                        )
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == Some(code)
+                    }
+
+                    fn narrower(code: u16) -> bool {
+                        $width::decode_2d(code.into()).map(i128::from)
+                            == $narrower::decode_2d(code.into()).map(i128::from)
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32, idx16);
+    wide_tests!(idx64, u64, idx32);
+    wide_tests!(idx128, u128, idx64);
+}
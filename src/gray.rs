@@ -0,0 +1,204 @@
+//! Binary reflected Gray codes
+//!
+//! The Gray code of an integer is such that the Gray codes of consecutive
+//! integers only differ by one bit. Going through the Gray codes of 0 to 2^N - 1
+//! is therefore a way to visit every corner of an N-dimensional hypercube while
+//! only moving to a neighboring corner at every step, which is how the Hilbert
+//! curve goes through the sub-squares of each recursion level.
+//!
+//! Gray code conversions and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+
+/// Generate Gray code utilities for a certain integer type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! gray_impl {
+    ($bits:ident, $uint:ty) => {
+        use $crate::{bits::$bits as bits, NumBits};
+
+        type Uint = $uint;
+
+        /// Compute the Gray code of an integer
+        #[inline]
+        pub const fn encode(value: Uint) -> Uint {
+            value ^ (value >> 1)
+        }
+
+        /// Compute the integer that has a certain Gray code
+        ///
+        /// This is the inverse of `encode()`.
+        ///
+        #[inline]
+        pub const fn decode(gray: Uint) -> Uint {
+            // Each bit of the integer is the XOR of the Gray code bits at and
+            // above its position
+            bits::bitwise_xor_ltr_inclusive_scan(gray)
+        }
+
+        /// Iterate over the Gray codes of all integers with `num_bits` bits, in
+        /// order
+        ///
+        /// This is equivalent to running `encode()` on the sequence of integers
+        /// (0..2^num_bits). Each Gray code differs from the previous one by a
+        /// single bit, so this enumerates the corners of a `num_bits`-dimensional
+        /// hypercube such that every corner is a neighbor of the previous one.
+        ///
+        /// # Panics
+        ///
+        /// If `num_bits` is larger than the number of bits of the integer type.
+        ///
+        pub fn iter(num_bits: NumBits) -> Iter {
+            assert!(
+                num_bits <= Uint::BITS,
+                "Gray codes of {num_bits} bits do not fit in this integer type"
+            );
+            Iter {
+                values: 0..=bits::low_order_mask(num_bits),
+            }
+        }
+
+        /// Iterator over a sequence of Gray codes, created by `iter()`
+        #[derive(Clone, Debug)]
+        pub struct Iter {
+            /// Integers whose Gray codes remain to be produced
+            values: core::ops::RangeInclusive<Uint>,
+        }
+        //
+        impl Iterator for Iter {
+            type Item = Uint;
+
+            #[inline]
+            fn next(&mut self) -> Option<Uint> {
+                self.values.next().map(encode)
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Uint> {
+                self.values.nth(n).map(encode)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.values.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Iter {
+            #[inline]
+            fn next_back(&mut self) -> Option<Uint> {
+                self.values.next_back().map(encode)
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Uint> {
+                self.values.nth_back(n).map(encode)
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Iter {}
+    };
+}
+
+/// Gray code utilities for 16-bit integers
+pub(crate) mod idx16 {
+    gray_impl!(uint16, u16);
+
+    // See morton::idx16 for why Iter is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter {}
+}
+
+/// Gray code utilities for 32-bit integers
+pub(crate) mod idx32 {
+    gray_impl!(uint32, u32);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter {}
+}
+
+/// Gray code utilities for 64-bit integers
+pub(crate) mod idx64 {
+    gray_impl!(uint64, u64);
+}
+
+/// Gray code utilities for 128-bit integers
+pub(crate) mod idx128 {
+    gray_impl!(uint128, u128);
+}
+
+pub_use_default_width!(decode, encode, iter, Iter);
+pub use idx128::{
+    decode as decode_u128, encode as encode_u128, iter as iter_u128, Iter as IterU128,
+};
+pub use idx16::{decode as decode_u16, encode as encode_u16, iter as iter_u16, Iter as IterU16};
+pub use idx32::{decode as decode_u32, encode as encode_u32, iter as iter_u32, Iter as IterU32};
+pub use idx64::{decode as decode_u64, encode as encode_u64, iter as iter_u64, Iter as IterU64};
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encode() {
+        use super::idx16::{decode, encode};
+        assert_eq!(
+            (0..8).map(encode).collect::<Vec<_>>(),
+            [0b000, 0b001, 0b011, 0b010, 0b110, 0b111, 0b101, 0b100]
+        );
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        for value in 0..=u16::MAX {
+            let gray = encode(value);
+            assert_eq!(decode(gray), value);
+            assert!(
+                !visited[usize::from(gray)],
+                "Gray code {gray} was produced twice"
+            );
+            visited[usize::from(gray)] = true;
+            if value < u16::MAX {
+                assert_eq!((gray ^ encode(value + 1)).count_ones(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn iter() {
+        use super::idx16::{encode, iter};
+        for num_bits in 0..=u16::BITS {
+            let num_values = 1usize << num_bits;
+            assert_eq!(iter(num_bits).len(), num_values);
+            assert!(iter(num_bits).eq((0..=u16::MAX).take(num_values).map(encode)));
+            assert!(iter(num_bits).all(|gray| usize::from(gray) < num_values));
+            assert!(iter(num_bits).rev().step_by(3).eq((0..=u16::MAX)
+                .take(num_values)
+                .rev()
+                .step_by(3)
+                .map(encode)));
+        }
+    }
+
+    // Wider integer types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $uint:ty) => {
+            mod $width {
+                use crate::gray::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn encode(value: $uint) -> bool {
+                        let gray = $width::encode(value);
+                        let next_gray = $width::encode(value.wrapping_add(1));
+                        $width::decode(gray) == value
+                            && (value == <$uint>::MAX || (gray ^ next_gray).count_ones() == 1)
+                    }
+                }
+
+                #[test]
+                fn iter() {
+                    let last = $width::iter(<$uint>::BITS).next_back();
+                    assert_eq!(last, Some(1 << (<$uint>::BITS - 1)));
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
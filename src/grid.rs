@@ -0,0 +1,484 @@
+//! 2D container with curve-ordered storage
+//!
+//! Storing a 2D array in row-major order gives good memory locality along rows,
+//! but poor locality along columns. Storing it in Hilbert curve order instead
+//! keeps points that are close in space close in memory in every direction,
+//! which benefits access patterns like neighborhood traversals. This module
+//! provides a container that does so, while still letting users address its
+//! elements by their coordinates.
+//!
+//! For sparse data, like voxels or map tiles of which only a few are populated,
+//! it also provides a map keyed by Morton code. Rectangular range queries then
+//! amount to a few lookups of contiguous ranges of keys.
+
+use crate::{hilbert, morton, Coordinate, Coordinates2D, CurveIdx};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    iter::FromIterator,
+    ops::{Index, IndexMut},
+};
+
+/// Square 2D array whose elements are stored in Hilbert curve order
+///
+/// The side of the array must be a power of two, so that the Hilbert curve
+/// covers the array with its first `width * width` points and the element of
+/// coordinates `[x, y]` is stored at index `hilbert::encode_2d([x, y])`.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct HilbertGrid<T> {
+    /// Side of the array
+    width: usize,
+
+    /// Elements of the array, in Hilbert curve order
+    data: Vec<T>,
+}
+//
+impl<T> HilbertGrid<T> {
+    /// Create a `width` x `width` array where every element is `value`
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not a power of two, or is larger than the side of the
+    /// curve's domain.
+    ///
+    pub fn new(width: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        let len = check_width(width);
+        Self {
+            width,
+            data: alloc::vec![value; len],
+        }
+    }
+
+    /// Build an array from elements in row-major order
+    ///
+    /// The element of coordinates `[x, y]` must be at index `y * width + x`.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not a power of two, is larger than the side of the curve's
+    /// domain, or does not match the length of `data`.
+    ///
+    pub fn from_row_major(mut data: Vec<T>, width: usize) -> Self {
+        hilbert::permute_in_place(&mut data, width);
+        Self { width, data }
+    }
+
+    /// Build an array from elements that are already in Hilbert curve order
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not a power of two, is larger than the side of the curve's
+    /// domain, or does not match the length of `data`.
+    ///
+    pub fn from_curve_order(data: Vec<T>, width: usize) -> Self {
+        assert_eq!(
+            data.len(),
+            check_width(width),
+            "Expected {width}x{width} elements"
+        );
+        Self { width, data }
+    }
+
+    /// Extract the elements of the array in row-major order
+    pub fn into_row_major(mut self) -> Vec<T> {
+        hilbert::unpermute_in_place(&mut self.data, self.width);
+        self.data
+    }
+
+    /// Extract the elements of the array in Hilbert curve order
+    #[inline]
+    pub fn into_curve_order(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Side of the array
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Elements of the array, in Hilbert curve order
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Mutable elements of the array, in Hilbert curve order
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Index of the element of coordinates `[x, y]` in Hilbert curve order, if
+    /// it is within the array
+    #[inline]
+    fn curve_idx(&self, x: Coordinate, y: Coordinate) -> Option<usize> {
+        if (x as usize) < self.width && (y as usize) < self.width {
+            Some(hilbert::encode_2d([x, y]) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Access the element of coordinates `[x, y]`, if it is within the array
+    #[inline]
+    pub fn get(&self, x: Coordinate, y: Coordinate) -> Option<&T> {
+        self.curve_idx(x, y).map(|idx| &self.data[idx])
+    }
+
+    /// Mutably access the element of coordinates `[x, y]`, if it is within the
+    /// array
+    #[inline]
+    pub fn get_mut(&mut self, x: Coordinate, y: Coordinate) -> Option<&mut T> {
+        self.curve_idx(x, y).map(move |idx| &mut self.data[idx])
+    }
+
+    /// Iterate over the elements of row `y`, by increasing x coordinate
+    ///
+    /// # Panics
+    ///
+    /// If `y` is outside of the array.
+    ///
+    pub fn row(&self, y: Coordinate) -> impl Iterator<Item = &T> + '_ {
+        assert!((y as usize) < self.width, "Row {y} is outside of the grid");
+        (0..self.width).map(move |x| {
+            let x = x as Coordinate;
+            &self.data[hilbert::encode_2d([x, y]) as usize]
+        })
+    }
+
+    /// Iterate over the elements of column `x`, by increasing y coordinate
+    ///
+    /// # Panics
+    ///
+    /// If `x` is outside of the array.
+    ///
+    pub fn column(&self, x: Coordinate) -> impl Iterator<Item = &T> + '_ {
+        assert!(
+            (x as usize) < self.width,
+            "Column {x} is outside of the grid"
+        );
+        (0..self.width).map(move |y| {
+            let y = y as Coordinate;
+            &self.data[hilbert::encode_2d([x, y]) as usize]
+        })
+    }
+
+    /// Iterate over the direct neighbors of the element of coordinates `[x, y]`
+    /// that are within the array, along with their coordinates
+    ///
+    /// Neighbors are yielded in -x, +x, -y, +y order, skipping those that are
+    /// outside of the array. `[x, y]` itself need not be within the array.
+    ///
+    pub fn neighbors(
+        &self,
+        x: Coordinate,
+        y: Coordinate,
+    ) -> impl Iterator<Item = (Coordinates2D, &T)> + '_ {
+        let candidates = [
+            x.checked_sub(1).map(|x| [x, y]),
+            x.checked_add(1).map(|x| [x, y]),
+            y.checked_sub(1).map(|y| [x, y]),
+            y.checked_add(1).map(|y| [x, y]),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .filter_map(move |[x, y]| self.get(x, y).map(|elem| ([x, y], elem)))
+    }
+
+    /// Iterate over the elements of the array in storage order, along with
+    /// their coordinates
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates2D, &T)> + '_ {
+        hilbert::iter_2d().zip(&self.data)
+    }
+}
+//
+impl<T> Index<Coordinates2D> for HilbertGrid<T> {
+    type Output = T;
+
+    /// # Panics
+    ///
+    /// If the coordinates are outside of the array.
+    ///
+    #[inline]
+    fn index(&self, [x, y]: Coordinates2D) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("Point [{x}, {y}] is outside of the grid"))
+    }
+}
+//
+impl<T> IndexMut<Coordinates2D> for HilbertGrid<T> {
+    /// # Panics
+    ///
+    /// If the coordinates are outside of the array.
+    ///
+    #[inline]
+    fn index_mut(&mut self, [x, y]: Coordinates2D) -> &mut T {
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("Point [{x}, {y}] is outside of the grid"))
+    }
+}
+
+/// Sparse 2D map whose elements are stored in Morton order
+///
+/// Elements are stored in a `BTreeMap` keyed by the Morton code of their
+/// coordinates, so memory usage only depends on the number of elements, and
+/// elements that are close in space tend to be close in the map.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SparseGrid2D<T> {
+    /// Elements of the map, keyed by Morton code
+    data: BTreeMap<CurveIdx, T>,
+}
+//
+impl<T> SparseGrid2D<T> {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self {
+            data: BTreeMap::new(),
+        }
+    }
+
+    /// Number of elements in the map
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Truth that the map has no elements
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Access the element of coordinates `[x, y]`, if there is one
+    #[inline]
+    pub fn get(&self, x: Coordinate, y: Coordinate) -> Option<&T> {
+        self.data.get(&morton::encode_2d([x, y]))
+    }
+
+    /// Mutably access the element of coordinates `[x, y]`, if there is one
+    #[inline]
+    pub fn get_mut(&mut self, x: Coordinate, y: Coordinate) -> Option<&mut T> {
+        self.data.get_mut(&morton::encode_2d([x, y]))
+    }
+
+    /// Insert an element at coordinates `[x, y]`, returning the element that
+    /// was previously there, if any
+    #[inline]
+    pub fn insert(&mut self, x: Coordinate, y: Coordinate, value: T) -> Option<T> {
+        self.data.insert(morton::encode_2d([x, y]), value)
+    }
+
+    /// Remove the element of coordinates `[x, y]`, if there is one
+    #[inline]
+    pub fn remove(&mut self, x: Coordinate, y: Coordinate) -> Option<T> {
+        self.data.remove(&morton::encode_2d([x, y]))
+    }
+
+    /// Iterate over the elements that are within the rectangle that goes from
+    /// `min` to `max` coordinates, inclusive, along with their coordinates
+    ///
+    /// Elements are yielded in Morton order. The rectangle is decomposed into
+    /// ranges of Morton codes using `morton::ranges_for_rect()`, so the cost of
+    /// this query depends on the shape of the rectangle and the number of
+    /// elements inside of it, not on the total number of elements.
+    ///
+    pub fn range(
+        &self,
+        min: Coordinates2D,
+        max: Coordinates2D,
+    ) -> impl Iterator<Item = (Coordinates2D, &T)> + '_ {
+        morton::ranges_for_rect(min, max)
+            .flat_map(move |codes| self.data.range(codes))
+            .map(|(&code, value)| (morton::decode_2d(code), value))
+    }
+
+    /// Iterate over the direct neighbors of the point of coordinates `[x, y]`
+    /// that are in the map, along with their coordinates
+    ///
+    /// Neighbors are yielded in -x, +x, -y, +y order, skipping those that are
+    /// not in the map or outside of the curve's domain.
+    ///
+    pub fn neighbors(
+        &self,
+        x: Coordinate,
+        y: Coordinate,
+    ) -> impl Iterator<Item = (Coordinates2D, &T)> + '_ {
+        let candidates = [
+            x.checked_sub(1).map(|x| [x, y]),
+            x.checked_add(1).map(|x| [x, y]),
+            y.checked_sub(1).map(|y| [x, y]),
+            y.checked_add(1).map(|y| [x, y]),
+        ];
+        candidates
+            .into_iter()
+            .flatten()
+            .filter_map(move |[x, y]| self.get(x, y).map(|elem| ([x, y], elem)))
+    }
+
+    /// Iterate over the elements of the map in Morton order, along with their
+    /// coordinates
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinates2D, &T)> + '_ {
+        self.data
+            .iter()
+            .map(|(&code, value)| (morton::decode_2d(code), value))
+    }
+}
+//
+impl<T> Default for SparseGrid2D<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+//
+impl<T> Extend<(Coordinates2D, T)> for SparseGrid2D<T> {
+    fn extend<I: IntoIterator<Item = (Coordinates2D, T)>>(&mut self, iter: I) {
+        self.data.extend(
+            iter.into_iter()
+                .map(|(coords, value)| (morton::encode_2d(coords), value)),
+        );
+    }
+}
+//
+impl<T> FromIterator<(Coordinates2D, T)> for SparseGrid2D<T> {
+    fn from_iter<I: IntoIterator<Item = (Coordinates2D, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+/// Check that a grid width is supported, and compute the number of elements
+fn check_width(width: usize) -> usize {
+    assert!(
+        width.is_power_of_two() && width.trailing_zeros() <= Coordinate::BITS,
+        "Grid width {width} is not a power of two that fits in the curve's domain"
+    );
+    width
+        .checked_mul(width)
+        .expect("Grid does not fit in the address space")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hilbert_grid() {
+        for order in 0..=Coordinate::BITS.min(6) {
+            let width = 1usize << order;
+            let row_major = (0..width * width).collect::<Vec<_>>();
+            let mut grid = HilbertGrid::from_row_major(row_major.clone(), width);
+            assert_eq!(grid.width(), width);
+            let expected = |x: usize, y: usize| y * width + x;
+            for y in 0..width {
+                assert!(grid
+                    .row(y as Coordinate)
+                    .copied()
+                    .eq((0..width).map(|x| expected(x, y))));
+            }
+            for x in 0..width {
+                assert!(grid
+                    .column(x as Coordinate)
+                    .copied()
+                    .eq((0..width).map(|y| expected(x, y))));
+            }
+            for ([x, y], &elem) in grid.iter() {
+                assert_eq!(elem, expected(x as usize, y as usize));
+                assert_eq!(grid[[x, y]], elem);
+                assert_eq!(grid.get(x, y), Some(&elem));
+                let neighbors = grid.neighbors(x, y).collect::<Vec<_>>();
+                let num_inner_sides = [x, y]
+                    .iter()
+                    .map(|&c| (c > 0) as usize + ((c as usize) < width - 1) as usize)
+                    .sum::<usize>();
+                assert_eq!(neighbors.len(), num_inner_sides);
+                for ([nx, ny], &neighbor) in neighbors {
+                    assert_eq!(
+                        (nx as isize - x as isize).abs() + (ny as isize - y as isize).abs(),
+                        1
+                    );
+                    assert_eq!(neighbor, expected(nx as usize, ny as usize));
+                }
+            }
+            if width <= Coordinate::MAX as usize {
+                assert_eq!(grid.get(width as Coordinate, 0), None);
+                assert_eq!(grid.get(0, width as Coordinate), None);
+            }
+            grid[[0, 0]] = usize::MAX;
+            *grid.get_mut(0, 0).unwrap() = 0;
+            assert_eq!(grid.as_slice()[0], 0);
+            assert_eq!(grid.clone().into_curve_order(), grid.as_slice());
+            assert_eq!(grid.into_row_major(), row_major);
+        }
+        assert_eq!(HilbertGrid::new(2, 'a').as_slice(), &['a'; 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        let _ = HilbertGrid::new(4, 0)[[4, 0]];
+    }
+
+    #[test]
+    fn sparse_grid() {
+        let points = [[0, 0], [1, 0], [3, 2], [2, 3], [5, 5], [6, 1]];
+        let mut grid = points
+            .iter()
+            .enumerate()
+            .map(|(idx, &coords)| (coords, idx))
+            .collect::<SparseGrid2D<_>>();
+        assert_eq!(grid.len(), points.len());
+        for (idx, &[x, y]) in points.iter().enumerate() {
+            assert_eq!(grid.get(x, y), Some(&idx));
+        }
+        assert_eq!(grid.get(1, 1), None);
+        assert!(grid
+            .iter()
+            .map(|(coords, _)| morton::encode_2d(coords))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+
+        for min_x in 0..7 {
+            for min_y in 0..7 {
+                for max_x in min_x..7 {
+                    for max_y in min_y..7 {
+                        let mut expected = points
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, &[x, y])| {
+                                (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+                            })
+                            .map(|(idx, &coords)| (coords, idx))
+                            .collect::<Vec<_>>();
+                        expected.sort_by_key(|&(coords, _)| morton::encode_2d(coords));
+                        let actual = grid
+                            .range([min_x, min_y], [max_x, max_y])
+                            .map(|(coords, &idx)| (coords, idx))
+                            .collect::<Vec<_>>();
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(grid.neighbors(0, 0).collect::<Vec<_>>(), [([1, 0], &1)]);
+        assert_eq!(
+            grid.neighbors(2, 2).collect::<Vec<_>>(),
+            [([3, 2], &2), ([2, 3], &3)]
+        );
+        assert_eq!(grid.insert(1, 1, 42), None);
+        assert_eq!(grid.insert(1, 1, 43), Some(42));
+        *grid.get_mut(1, 1).unwrap() += 1;
+        assert_eq!(grid.neighbors(1, 0).count(), 2);
+        assert_eq!(grid.remove(1, 1), Some(44));
+        assert_eq!(grid.remove(1, 1), None);
+        assert!(SparseGrid2D::<()>::default().is_empty());
+    }
+}
@@ -1,195 +1,4327 @@
 //! Utilities related to the Hilbert space-filling curve
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types. Functions without a suffix use the `CurveIdx` and `Coordinate`
+//! types, while functions with a `_uN` suffix use N-bit curve indices and
+//! N/2-bit coordinates (e.g. `_u64` functions use 64-bit curve indices and
+//! 32-bit coordinates). Supported curve index widths are 16, 32, 64 and 128
+//! bits.
 
-use crate::{bits, morton, Coordinates2D, CurveIdx};
+/// Generate Hilbert curve utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! hilbert_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::{
+            bits::$width as bits,
+            hilbert::{Direction, Orientation, OrientationNd},
+            morton::$width as morton,
+            NumBits,
+        };
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Compute the coordinate of the i-th point of a ]-shaped Hilbert curve
+        ///
+        /// Compared to the Morton curve, the Hilbert curve never jumps across space, it
+        /// always moves from one point of space to one of its direct neighbors. The
+        /// price to pay for this superior spatial locality is that it follows a more
+        /// complex geometrical pattern (that, logically, requires more complex
+        /// computations), based on recursively flipped C-like shapes.
+        ///
+        /// There are technically 4 C-like shapes that one could start from. Here we use
+        /// a vertically flipped C shape (]) instead of the U shape that is more
+        /// commonly seen in literature, because an extension to N dimensions and
+        /// non-square domains has been performed in the "Compact Hilbert Indices"
+        /// paper by Chris Hamilton (ref: CS-2006-07) for this particular variation of
+        /// the Hilbert curve, and a ] is just a coordinate transpose away from a U.
+        ///
+        /// The first 256 iterations of this Hilbert curve look like this:
+        ///
+        /// ┬┌─┐┌─┐┌─┐┌─┐┌─┐
+        /// └┘┌┘└┐└┘┌┘└┐└┘┌┘
+        /// ┌┐└┐┌┘┌┐│┌┐│┌┐└┐
+        /// │└─┘└─┘│└┘└┘│└─┘
+        /// └┐┌──┐┌┘┌┐┌┐│┌─┐
+        /// ┌┘└┐┌┘└┐│└┘│└┘┌┘
+        /// │┌┐││┌┐│└┐┌┘┌┐└┐
+        /// └┘└┘└┘└┘┌┘└─┘└─┘
+        /// ┌┐┌┐┌┐┌┐└┐┌─┐┌─┐
+        /// │└┘││└┘│┌┘└┐└┘┌┘
+        /// └┐┌┘└┐┌┘│┌┐│┌┐└┐
+        /// ┌┘└──┘└┐└┘└┘│└─┘
+        /// │┌─┐┌─┐│┌┐┌┐│┌─┐
+        /// └┘┌┘└┐└┘│└┘│└┘┌┘
+        /// ┌┐└┐┌┘┌┐└┐┌┘┌┐└┐
+        /// v└─┘└─┘└─┘└─┘└─┘
+        ///
+        /// Like `morton::decode_2d()`, this function runs in constant time, which makes
+        /// it suitable for processing secret data.
+        ///
+        #[inline]
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // Here's the mathematical derivation of this algorithm.
+            //
+            // ---
+            //
+            // Remember that we took this shape as our basic pattern:
+            //
+            // ├┐
+            // <┘
+            //
+            // This means that...
+            //
+            // * On iteration 0, (x, y) is (0, 0)
+            // * On iteration 1, (x, y) is (1, 0)
+            // * On iteration 2, (x, y) is (1, 1)
+            // * On iteration 3, (x, y) is (0, 1)
+            //
+            // So if we denote ij the binary digits of the iteration number, we have...
+            //
+            // * x = i XOR j
+            // * y = i
+            //
+            // ...which happens to be the Gray code associated with the 2D Morton code
+            // yx. This is totally not a coincidence, it simplifies extension to higher
+            // dimensions, where both the Gray and Morton code are defined...
+            //
+            // Now, if we were to turn this basic shape into a fractal without extra
+            // precautions, we would still get jumps from some sub-patterns to the next:
+            //
+            // 0┐1┐
+            // <┘┌┘
+            // 3┐2┐
+            // <┘<┘
+            //
+            // To avoid this, we need to transform the sub-patterns, which we can do
+            // without changing the curve's endpoints by swapping the coordinates of
+            // sub-pattern 0...
+            //
+            // 0┌1┐
+            // └┘┌┘
+            // 3┐2┐
+            // <┘<┘
+            //
+            // ...and swapping and inverting the coordinates of sub-pattern 3:
+            //
+            // 0┌1┐
+            // └┘┌┘
+            // ┌┐2┐
+            // v3─┘
+            //
+            // Let's translate those transformations into binary arithmetic:
+            //
+            // - If (i XOR j) is 0, we need to swap the coordinates of our sub-pattern
+            // - If (i AND j) is 1, we need to invert the coordinates of our sub-pattern
+            //   * In binary, this can be done by NOT-ing x and y when (i AND j) is 1...
+            //   * ...which we can do without testing the value of i and j by XORing x
+            //     and y with (i AND j).
+            //
+            // With that, we get the first layer of fractal recursion, but then we must
+            // recursively apply the same recursion rules to our transformed patterns at
+            // the next level of recursion:
+            //
+            // 0┐┌─1┌─┐
+            // ┌┘└┐└┘┌┘
+            // │┌┐│┌┐└┐
+            // └┘└┘│└─┘
+            // ┌┐┌┐2┌─┐
+            // │└┘│└┘┌┘
+            // └┐┌┘┌┐└┐
+            // <┘└3┘└─┘
+            //
+            // It so happens, however, that the transforms applied above are their own
+            // inverse: swapping coordinates twice gives back the original coordinates,
+            // and inverting coordinates twice gives back the original coordinates.
+            //
+            // Therefore, if for every level of recursion, we can compute a bit b that
+            // controls whether a certain transform is applied when going to the next
+            // level of recursion, the truth that we need to apply that transform at a
+            // given recursion depth is given by the XOR of those control bits at all
+            // previous recursion depths.
+            //
+            // ---
+            //
+            // Now, with that in mind, let's make the observation that when written out
+            // in binary, the index of a point on the curve is [ i1 j1 i2 j2 ... iN jN ]
+            // where (ix, jx) controls how the pattern is followed at recursion depth x.
+            //
+            // This looks very much like a 2D Morton code, and we can use a 2D Morton
+            // code decoder to separate that index into two integers with bits
+            // [ j1 j2 ... jN ] and [ i1 i2 ... iN ].
+            //
+            debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
+            decode_2d_from_morton(morton::decode_2d(code))
+        }
+
+        /// Second half of `decode_2d()`, which takes the Morton-decoded curve index
+        /// as input so that it can be shared with `decode_2d_fast()`
+        #[inline(always)]
+        const fn decode_2d_from_morton(morton: Coordinates2D) -> Coordinates2D {
+            let [low_order, high_order] = morton;
+
+            // From that, we can compute the binary combinations of i-s and j-s that we
+            // need at every depth in order to move through the curve's basic ]-shaped
+            // pattern and recurse to the next depth.
+            //
+            let and_bits = low_order & high_order; // Controls coordinate inversion
+            let xor_bits = low_order ^ high_order; // Basic pattern's x coordinate
+            let not_xor_bits = !(xor_bits); // Controls coordinate swapping
+
+            // Then we can compute whether coordinates should be swapped or inverted
+            // at every depth by computing the XOR of the recursive swapping/conversion
+            // bits at every previous depth. This is most efficiently done by using a
+            // bitwise version of the parallel scan algorithm.
+            //
+            let coord_swap_bits = bits::bitwise_xor_ltr_exclusive_scan(not_xor_bits);
+            let coord_not_bits = bits::bitwise_xor_ltr_exclusive_scan(and_bits);
+
+            // Finally, we start from the top-level Gray code coordinates, transform
+            // every bit through coordinate swapping and inversion as appropriate, and
+            // we get integer words whose bits are the coordinate on the Hilbert curve
+            // at increasing recursion depths, which is what we want.
+            //
+            let [coord1, coord2] = bits::bitwise_swaps(coord_swap_bits, xor_bits, high_order);
+            [coord1 ^ coord_not_bits, coord2 ^ coord_not_bits]
+        }
+
+        /// Compute the index of a point on a ]-shaped Hilbert curve
+        ///
+        /// This is the inverse of `decode_2d()`. Like it, it runs in constant time,
+        /// which makes it suitable for processing secret data.
+        ///
+        #[inline]
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            // Interleave the i and j bits of encode_2d_to_morton() into a curve index
+            debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
+            morton::encode_2d(encode_2d_to_morton(coords))
+        }
+
+        /// Compare two points according to the order of their Hilbert curve
+        /// indices
+        ///
+        /// This is equivalent to comparing `encode_2d(a)` with `encode_2d(b)`, but
+        /// walks the recursion depths of the curve from the top, applying the
+        /// coordinate swaps and inversions of `encode_2d()` incrementally, and
+        /// stops at the first depth where the points are in different
+        /// sub-squares. Since the curve indices are never computed, the same
+        /// technique can compare points whose coordinates are too wide for their
+        /// curve indices to fit in any integer type.
+        ///
+        #[inline]
+        pub const fn cmp_2d(a: Coordinates2D, b: Coordinates2D) -> core::cmp::Ordering {
+            // See encode_2d_to_morton() for the meaning of these bits. The points
+            // share the same transform state until they are in different
+            // sub-squares, at which point their order is known.
+            let mut swap_xor_invert: Coordinate = 0;
+            let mut invert: Coordinate = 0;
+            let mut depth = 0;
+            while depth < bits::num_bits::<Coordinate>() {
+                let shift = bits::num_bits::<Coordinate>() - 1 - depth;
+                let mut digits = [0; 2];
+                let mut point = 0;
+                while point < 2 {
+                    let [x, y] = if point == 0 { a } else { b };
+                    let x_bit = (x >> shift) & 1;
+                    let j_bit = ((x ^ y) >> shift) & 1;
+                    let i_bit = x_bit ^ (j_bit & (swap_xor_invert ^ 1)) ^ ((j_bit ^ 1) & invert);
+                    digits[point] = (i_bit << 1) | j_bit;
+                    point += 1;
+                }
+                if digits[0] != digits[1] {
+                    return if digits[0] < digits[1] {
+                        core::cmp::Ordering::Less
+                    } else {
+                        core::cmp::Ordering::Greater
+                    };
+                }
+                let i_bit = digits[0] >> 1;
+                let j_bit = digits[0] & 1;
+                swap_xor_invert ^= (j_bit ^ 1) & (i_bit ^ 1);
+                invert ^= j_bit & i_bit;
+                depth += 1;
+            }
+            core::cmp::Ordering::Equal
+        }
+
+        /// First half of `encode_2d()`, which produces the Morton-decoded curve
+        /// index so that it can be shared with `encode_2d_fast()`
+        #[inline(always)]
+        const fn encode_2d_to_morton(coords: Coordinates2D) -> Coordinates2D {
+            // We want to invert the decoding algorithm, so let's reuse its notations:
+            // at each recursion depth, the curve index has two bits i and j, which are
+            // grouped into the high_order and low_order integers by Morton decoding.
+            //
+            // First, notice that coordinate swapping and coordinate inversion both
+            // preserve the XOR of the x and y coordinates, since XOR is symmetric and
+            // NOT-ing both of its inputs leaves its result unchanged. Therefore, the
+            // XOR of the output coordinates at a given depth is the XOR of the basic
+            // pattern's coordinates, which is (i XOR j) XOR i = j.
+            //
+            // So we can compute low_order without further ado.
+            //
+            let [x, y] = coords;
+            let low_order = x ^ y;
+
+            // Computing high_order is harder, because whether the coordinates at a
+            // given depth were swapped and inverted depends on the i bits at previous
+            // depths, which are what we're trying to compute. Let's enumerate cases.
+            //
+            // - If j is 0, then the basic pattern's coordinates are both i, so the
+            //   swap is irrelevant and x = i XOR invert, i.e. i = x XOR invert. Then
+            //   swapping must be toggled at the next depth if i is 0, and inversion
+            //   must not be toggled.
+            // - If j is 1, then the basic pattern's coordinates are (NOT i, i), so
+            //   x = i XOR (NOT swap) XOR invert, i.e. i = x XOR NOT (swap XOR invert).
+            //   Then swapping and inversion must both be toggled if i is 1.
+            //
+            // This suggests tracking the XOR of the swap and invert bits instead of
+            // the swap bit: it only changes when j is 0, and the invert bit only
+            // changes when j is 1.
+            //
+            // Unfortunately, the resulting recursion cannot be computed using a XOR
+            // scan, since the updates to the swap-xor-invert and invert bits both
+            // depend on each other. So we need to go through the depths sequentially.
+            //
+            let mut high_order: Coordinate = 0;
+            let mut swap_xor_invert: Coordinate = 0;
+            let mut invert: Coordinate = 0;
+            let mut depth = 0;
+            while depth < bits::num_bits::<Coordinate>() {
+                let shift = bits::num_bits::<Coordinate>() - 1 - depth;
+                let x_bit = (x >> shift) & 1;
+                let j_bit = (low_order >> shift) & 1;
+                let not_j_bit = j_bit ^ 1;
+                let i_bit = x_bit ^ (j_bit & (swap_xor_invert ^ 1)) ^ (not_j_bit & invert);
+                swap_xor_invert ^= not_j_bit & (i_bit ^ 1);
+                invert ^= j_bit & i_bit;
+                high_order |= i_bit << shift;
+                depth += 1;
+            }
+
+            // Finally, the i and j bits only need to be interleaved back into a curve
+            // index, which is done by the caller.
+            [low_order, high_order]
+        }
+
+        /// Like `encode_2d()`, but restricted to the first 4^`domain_bits` points
+        /// of the curve, which cover the square of 2^`domain_bits` x
+        /// 2^`domain_bits` points at the origin
+        ///
+        /// Returns `Error::CoordinateOutOfDomain` if `coords` is outside of this
+        /// square, or `Error::UnsupportedOrder` if the square does not fit in the
+        /// curve's domain. This is meant for coordinates that come from untrusted
+        /// input, such as network requests.
+        ///
+        #[inline]
+        pub const fn try_encode_2d(
+            coords: Coordinates2D,
+            domain_bits: NumBits,
+        ) -> Result<CurveIdx, $crate::Error> {
+            if domain_bits > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if (coords[0] | coords[1]) as CurveIdx > bits::low_order_mask(domain_bits) {
+                return Err($crate::Error::CoordinateOutOfDomain);
+            }
+            Ok(encode_2d(coords))
+        }
+
+        /// Like `decode_2d()`, but restricted to the first 4^`domain_bits` points
+        /// of the curve
+        ///
+        /// Returns `Error::IndexOutOfDomain` if `code` is not smaller than
+        /// 4^`domain_bits`, or `Error::UnsupportedOrder` if the square of
+        /// 2^`domain_bits` x 2^`domain_bits` points does not fit in the curve's
+        /// domain. This is the inverse of `try_encode_2d()`.
+        ///
+        #[inline]
+        pub const fn try_decode_2d(
+            code: CurveIdx,
+            domain_bits: NumBits,
+        ) -> Result<Coordinates2D, $crate::Error> {
+            if domain_bits > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if code > bits::low_order_mask(2 * domain_bits) {
+                return Err($crate::Error::IndexOutOfDomain);
+            }
+            Ok(decode_2d(code))
+        }
+
+        /// Compute the aligned square of the domain that the curve goes through at
+        /// the same time as the point of index `code`, at a certain recursion level
+        ///
+        /// At level L, the curve goes through 4^L aligned squares of side
+        /// 2^(N - L), where N is the number of bits of `Coordinate`, and the curve
+        /// indices within each square share the same 2 x L high-order bits. In a
+        /// quadtree that recursively splits the domain into 4 squares, these are
+        /// the cells at depth L.
+        ///
+        /// The square is returned as the coordinates of its lower corner, along
+        /// with its side length. It contains the points of curve indices
+        /// `code & !mask` to `code | mask`, where `mask` is 4^(N - L) - 1.
+        ///
+        /// # Panics
+        ///
+        /// If `level` is larger than the number of bits of `Coordinate`. See
+        /// `try_cell_2d()` for a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn cell_2d(code: CurveIdx, level: NumBits) -> (Coordinates2D, CurveIdx) {
+            match try_cell_2d(code, level) {
+                Ok(cell) => cell,
+                Err(_) => panic!("Level is too deep for this curve"),
+            }
+        }
+
+        /// Like `cell_2d()`, but returns `Error::UnsupportedOrder` if `level` is
+        /// larger than the number of bits of `Coordinate`
+        #[inline]
+        pub const fn try_cell_2d(
+            code: CurveIdx,
+            level: NumBits,
+        ) -> Result<(Coordinates2D, CurveIdx), $crate::Error> {
+            if level > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            let side_bits = Coordinate::BITS - level;
+            let [x, y] = decode_2d(code);
+            let low_mask = bits::low_order_mask(side_bits) as Coordinate;
+            Ok(([x & !low_mask, y & !low_mask], 1 << side_bits))
+        }
+
+        /// Compute the coordinates of the i-th point of a ]-shaped Hilbert curve of
+        /// a certain order, which covers a 2^`order` x 2^`order` domain
+        ///
+        /// The first 4^`order` points of the curve of `decode_2d()` cover the same
+        /// domain, but their x and y coordinates are swapped when the difference
+        /// between `order` and the number of bits of `Coordinate` is odd. This
+        /// function instead produces the same ]-shaped orientation at every order,
+        /// going from [0, 0] to [0, 2^`order` - 1], by decoding the curve index at
+        /// the top recursion depths of `decode_2d()`. Like it, it runs in constant
+        /// time.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`, or if
+        /// `code` is not smaller than 4^`order`. See `try_decode_2d_order()` for
+        /// a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn decode_2d_order(code: CurveIdx, order: NumBits) -> Coordinates2D {
+            match try_decode_2d_order(code, order) {
+                Ok(coords) => coords,
+                Err($crate::Error::UnsupportedOrder) => panic!("Order is too high for this curve"),
+                Err(_) => panic!("Curve index is out of range for this order"),
+            }
+        }
+
+        /// Like `decode_2d_order()`, but returns `Error::UnsupportedOrder` if
+        /// `order` is larger than the number of bits of `Coordinate`, or
+        /// `Error::IndexOutOfDomain` if `code` is not smaller than 4^`order`
+        #[inline]
+        pub const fn try_decode_2d_order(
+            code: CurveIdx,
+            order: NumBits,
+        ) -> Result<Coordinates2D, $crate::Error> {
+            if order > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if code > bits::low_order_mask(2 * order) {
+                return Err($crate::Error::IndexOutOfDomain);
+            }
+            if order == 0 {
+                return Ok([0; 2]);
+            }
+            let shift = Coordinate::BITS - order;
+            let [x, y] = decode_2d(code << (2 * shift));
+            Ok([x >> shift, y >> shift])
+        }
+
+        /// Compute the index of a point on a ]-shaped Hilbert curve of a certain
+        /// order
+        ///
+        /// This is the inverse of `decode_2d_order()`, and it also runs in constant
+        /// time.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`, or if
+        /// `coords` are not smaller than 2^`order`. See `try_encode_2d_order()`
+        /// for a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn encode_2d_order(coords: Coordinates2D, order: NumBits) -> CurveIdx {
+            match try_encode_2d_order(coords, order) {
+                Ok(code) => code,
+                Err($crate::Error::UnsupportedOrder) => panic!("Order is too high for this curve"),
+                Err(_) => panic!("Coordinates are out of range for this order"),
+            }
+        }
+
+        /// Like `encode_2d_order()`, but returns `Error::UnsupportedOrder` if
+        /// `order` is larger than the number of bits of `Coordinate`, or
+        /// `Error::CoordinateOutOfDomain` if `coords` are not smaller than
+        /// 2^`order`
+        #[inline]
+        pub const fn try_encode_2d_order(
+            coords: Coordinates2D,
+            order: NumBits,
+        ) -> Result<CurveIdx, $crate::Error> {
+            if order > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if (coords[0] | coords[1]) as CurveIdx > bits::low_order_mask(order) {
+                return Err($crate::Error::CoordinateOutOfDomain);
+            }
+            if order == 0 {
+                return Ok(0);
+            }
+            let shift = Coordinate::BITS - order;
+            Ok(encode_2d([coords[0] << shift, coords[1] << shift]) >> (2 * shift))
+        }
+
+        /// Compute the coordinates of the i-th point of a Hilbert curve of a
+        /// certain order and orientation
+        ///
+        /// This is `decode_2d_order()` followed by the coordinate transform
+        /// described by `orientation`, with mirroring taking place within the
+        /// 2^`order` x 2^`order` domain of the curve. For example,
+        /// `Orientation { swap: true, ..Orientation::IDENTITY }` gives the
+        /// transposed, U-shaped curve that goes from [0, 0] to [2^`order` - 1, 0].
+        /// Like `decode_2d_order()`, it runs in constant time.
+        ///
+        /// # Panics
+        ///
+        /// Under the same conditions as `decode_2d_order()`. See
+        /// `try_decode_2d_order_oriented()` for a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn decode_2d_order_oriented(
+            code: CurveIdx,
+            order: NumBits,
+            orientation: Orientation,
+        ) -> Coordinates2D {
+            match try_decode_2d_order_oriented(code, order, orientation) {
+                Ok(coords) => coords,
+                Err($crate::Error::UnsupportedOrder) => panic!("Order is too high for this curve"),
+                Err(_) => panic!("Curve index is out of range for this order"),
+            }
+        }
+
+        /// Like `decode_2d_order_oriented()`, but returns the errors of
+        /// `try_decode_2d_order()` instead of panicking
+        #[inline]
+        pub const fn try_decode_2d_order_oriented(
+            code: CurveIdx,
+            order: NumBits,
+            orientation: Orientation,
+        ) -> Result<Coordinates2D, $crate::Error> {
+            match try_decode_2d_order(code, order) {
+                Ok(coords) => Ok(mirror_within(
+                    swap(coords, orientation),
+                    orientation,
+                    bits::low_order_mask(order) as Coordinate,
+                )),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Compute the index of a point on a Hilbert curve of a certain order
+        /// and orientation
+        ///
+        /// This is the inverse of `decode_2d_order_oriented()`, and it also runs
+        /// in constant time.
+        ///
+        /// # Panics
+        ///
+        /// Under the same conditions as `encode_2d_order()`. See
+        /// `try_encode_2d_order_oriented()` for a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn encode_2d_order_oriented(
+            coords: Coordinates2D,
+            order: NumBits,
+            orientation: Orientation,
+        ) -> CurveIdx {
+            match try_encode_2d_order_oriented(coords, order, orientation) {
+                Ok(code) => code,
+                Err($crate::Error::UnsupportedOrder) => panic!("Order is too high for this curve"),
+                Err(_) => panic!("Coordinates are out of range for this order"),
+            }
+        }
+
+        /// Like `encode_2d_order_oriented()`, but returns the errors of
+        /// `try_encode_2d_order()` instead of panicking
+        #[inline]
+        pub const fn try_encode_2d_order_oriented(
+            coords: Coordinates2D,
+            order: NumBits,
+            orientation: Orientation,
+        ) -> Result<CurveIdx, $crate::Error> {
+            if order > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            // Mirroring keeps the bits above the mask, so coordinates that are out
+            // of range stay out of range
+            let mask = bits::low_order_mask(order) as Coordinate;
+            try_encode_2d_order(swap(mirror_within(coords, orientation, mask), orientation), order)
+        }
+
+        /// Compute the coordinates of the i-th point of a Hilbert curve of a
+        /// certain order, transformed by a symmetry of its domain
+        ///
+        /// This is `decode_2d_order()` followed by `transform.apply()`, and
+        /// `Transform2D`s can be composed to stitch curves together. Like
+        /// `decode_2d_order()`, it runs in constant time.
+        ///
+        /// # Panics
+        ///
+        /// Under the same conditions as `decode_2d_order()`.
+        ///
+        #[inline]
+        pub const fn decode_2d_transformed(
+            code: CurveIdx,
+            order: NumBits,
+            transform: $crate::Transform2D,
+        ) -> Coordinates2D {
+            decode_2d_order_oriented(code, order, transform_orientation(transform))
+        }
+
+        /// Compute the index of a point on a Hilbert curve of a certain order,
+        /// transformed by a symmetry of its domain
+        ///
+        /// This is the inverse of `decode_2d_transformed()`.
+        ///
+        /// # Panics
+        ///
+        /// Under the same conditions as `encode_2d_order()`.
+        ///
+        #[inline]
+        pub const fn encode_2d_transformed(
+            coords: Coordinates2D,
+            order: NumBits,
+            transform: $crate::Transform2D,
+        ) -> CurveIdx {
+            encode_2d_order_oriented(coords, order, transform_orientation(transform))
+        }
+
+        /// Orientation that performs the same coordinate transform as a
+        /// `Transform2D`
+        #[inline(always)]
+        const fn transform_orientation(transform: $crate::Transform2D) -> Orientation {
+            Orientation {
+                swap: transform.swap,
+                mirror_x: transform.mirror_x,
+                mirror_y: transform.mirror_y,
+            }
+        }
+
+        /// Compute the coordinates of the i-th point of a Hilbert curve with a
+        /// certain orientation
+        ///
+        /// This is `decode_2d()` followed by the coordinate transform described by
+        /// `orientation`, so it also runs in constant time.
+        ///
+        #[inline]
+        pub const fn decode_2d_oriented(code: CurveIdx, orientation: Orientation) -> Coordinates2D {
+            mirror(swap(decode_2d(code), orientation), orientation)
+        }
+
+        /// Compute the index of a point on a Hilbert curve with a certain
+        /// orientation
+        ///
+        /// This is the inverse of `decode_2d_oriented()`, and it also runs in
+        /// constant time.
+        ///
+        #[inline]
+        pub const fn encode_2d_oriented(coords: Coordinates2D, orientation: Orientation) -> CurveIdx {
+            encode_2d(swap(mirror(coords, orientation), orientation))
+        }
+
+        /// Swap coordinates if an `Orientation` says so, without branching
+        #[inline(always)]
+        const fn swap(coords: Coordinates2D, orientation: Orientation) -> Coordinates2D {
+            let [x, y] = coords;
+            let diff = (x ^ y) & (orientation.swap as Coordinate).wrapping_neg();
+            [x ^ diff, y ^ diff]
+        }
+
+        /// Mirror coordinates if an `Orientation` says so, without branching
+        #[inline(always)]
+        const fn mirror(coords: Coordinates2D, orientation: Orientation) -> Coordinates2D {
+            mirror_within(coords, orientation, Coordinate::MAX)
+        }
+
+        /// Mirror the low-order bits of coordinates that are selected by `mask`
+        /// if an `Orientation` says so, without branching
+        #[inline(always)]
+        const fn mirror_within(
+            coords: Coordinates2D,
+            orientation: Orientation,
+            mask: Coordinate,
+        ) -> Coordinates2D {
+            let [x, y] = coords;
+            [
+                x ^ (mask & (orientation.mirror_x as Coordinate).wrapping_neg()),
+                y ^ (mask & (orientation.mirror_y as Coordinate).wrapping_neg()),
+            ]
+        }
+
+        /// Decode a batch of curve indices using SIMD instructions
+        ///
+        /// This is equivalent to calling `decode_2d()` on each curve index, but the
+        /// decoding loop is compiled so that multiple curve indices are processed
+        /// at once, using the widest SIMD instruction set that the CPU supports.
+        /// Like `decode_2d()`, this runs in constant time.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn decode_2d_simd(codes: &[CurveIdx]) -> alloc::vec::Vec<Coordinates2D> {
+            let mut coords = alloc::vec![[0; 2]; codes.len()];
+            decode_2d_into(codes, &mut coords);
+            coords
+        }
+
+        /// Decode a batch of curve indices into a caller-provided buffer
+        ///
+        /// This is the non-allocating version of `decode_2d_simd()`, for use in
+        /// loops that can reuse the same output buffer.
+        ///
+        /// # Panics
+        ///
+        /// If `codes` and `coords` do not have the same length.
+        ///
+        pub fn decode_2d_into(codes: &[CurveIdx], coords: &mut [Coordinates2D]) {
+            $crate::batch::map(codes, coords, decode_2d);
+        }
+
+        /// Encode a batch of points into a caller-provided buffer
+        ///
+        /// This is equivalent to calling `encode_2d()` on each point, but uses
+        /// SIMD instructions like `decode_2d_simd()`.
+        ///
+        /// # Panics
+        ///
+        /// If `coords` and `codes` do not have the same length.
+        ///
+        pub fn encode_2d_into(coords: &[Coordinates2D], codes: &mut [CurveIdx]) {
+            $crate::batch::map(coords, codes, encode_2d);
+        }
+
+        /// Variant of `decode_2d_into()` that writes the x and y coordinates into
+        /// separate buffers (structure-of-arrays layout)
+        ///
+        /// # Panics
+        ///
+        /// If `codes`, `xs` and `ys` do not have the same length.
+        ///
+        pub fn decode_2d_into_soa(
+            codes: &[CurveIdx],
+            xs: &mut [Coordinate],
+            ys: &mut [Coordinate],
+        ) {
+            $crate::batch::map_to_soa(codes, [xs, ys], decode_2d);
+        }
+
+        /// Variant of `encode_2d_into()` that reads the x and y coordinates from
+        /// separate buffers (structure-of-arrays layout)
+        ///
+        /// # Panics
+        ///
+        /// If `xs`, `ys` and `codes` do not have the same length.
+        ///
+        pub fn encode_2d_into_soa(xs: &[Coordinate], ys: &[Coordinate], codes: &mut [CurveIdx]) {
+            $crate::batch::map_from_soa([xs, ys], codes, encode_2d);
+        }
+
+        /// Encode a large batch of points into a caller-provided buffer
+        ///
+        /// This is equivalent to `encode_2d_into()`, but when the `rayon` feature
+        /// is enabled, the batch is split into chunks that are encoded in
+        /// parallel. This is the fastest way to encode e.g. a point cloud with
+        /// millions of points.
+        ///
+        /// # Panics
+        ///
+        /// If `coords` and `codes` do not have the same length.
+        ///
+        pub fn encode_bulk(coords: &[Coordinates2D], codes: &mut [CurveIdx]) {
+            $crate::batch::map_bulk(coords, codes, encode_2d);
+        }
+
+        /// Encode a stream of points, passing the resulting curve indices to
+        /// `sink` in chunks
+        ///
+        /// Points are gathered into fixed-size chunks, which are encoded using
+        /// SIMD instructions like `encode_2d_into()`, so this is much faster than
+        /// encoding the points of an iterator one by one. Chunks are passed to
+        /// `sink` in order, and all of them but the last one have the same length.
+        ///
+        pub fn encode_stream(
+            coords: impl IntoIterator<Item = Coordinates2D>,
+            sink: impl FnMut(&[CurveIdx]),
+        ) {
+            $crate::batch::map_stream(coords, sink, encode_2d);
+        }
+
+        /// Sort items by the Hilbert curve index of their coordinates
+        ///
+        /// `key` extracts the coordinates of an item, and is called only once per
+        /// item since the resulting curve indices are cached. Like
+        /// `slice::sort_by_cached_key()`, which it is built upon, this sort is
+        /// stable and allocates a temporary buffer.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn sort_by_key<T>(items: &mut [T], key: impl Fn(&T) -> Coordinates2D) {
+            items.sort_by_cached_key(|item| encode_2d(key(item)));
+        }
+
+        /// Sort points in Hilbert curve order, using a radix sort
+        ///
+        /// This computes the Hilbert curve index of every point, sorts these with a radix
+        /// sort, which takes linear time, and decodes them back into points.
+        /// Unlike `sort_by_key()`, it only sorts points, not items that have
+        /// coordinates, but its running time scales better with the number of
+        /// points, so it is usually faster for large datasets. Check the
+        /// `hilbert sort` benchmark on your hardware to find out. It allocates
+        /// temporary buffers for two curve indices per point.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn sort_radix(points: &mut [Coordinates2D]) {
+            let mut codes = alloc::vec![0; points.len()];
+            encode_2d_into(points, &mut codes);
+            $crate::radix::sort(&mut codes);
+            decode_2d_into(&codes, points);
+        }
+
+        /// Reorder a row-major square image into Hilbert curve order, in place
+        ///
+        /// `data` must contain `width` rows of `width` elements, where `width` is a
+        /// power of two, and the element of coordinates `[x, y]` must initially
+        /// be at index `y * width + x`. Afterwards, it is at index
+        /// `encode_2d([x, y])`.
+        ///
+        /// Instead of copying `data` into a second buffer, this function follows
+        /// the cycles of the permutation, which only requires a temporary bitset
+        /// with one bit per element.
+        ///
+        /// # Panics
+        ///
+        /// If `width` is not a power of two, is larger than the side of the
+        /// curve's domain, or does not match the length of `data`.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn permute_in_place<T>(data: &mut [T], width: usize) {
+            check_square(data.len(), width);
+            $crate::order::permute_in_place(data, |row_major| {
+                let coords = [(row_major % width) as Coordinate, (row_major / width) as Coordinate];
+                encode_2d(coords) as usize
+            });
+        }
+
+        /// Reorder a square image from Hilbert curve order back into row-major
+        /// order, in place
+        ///
+        /// This is the inverse of `permute_in_place()`, see its documentation for
+        /// details.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn unpermute_in_place<T>(data: &mut [T], width: usize) {
+            check_square(data.len(), width);
+            $crate::order::permute_in_place(data, |code| {
+                let [x, y] = decode_2d(code as CurveIdx);
+                y as usize * width + x as usize
+            });
+        }
+
+        /// Check that a slice of `len` elements is a square image of side `width`,
+        /// and that the curve covers it with its first `len` points
+        #[cfg(feature = "alloc")]
+        fn check_square(len: usize, width: usize) {
+            assert!(
+                width.is_power_of_two() && width.trailing_zeros() <= Coordinate::BITS,
+                "Image width {width} is not a power of two that fits in the curve's domain"
+            );
+            assert_eq!(
+                Some(len),
+                width.checked_mul(width),
+                "Expected a square image of width {width}"
+            );
+        }
+
+        /// Build a table of the coordinates of the first `LEN` points of the curve
+        ///
+        /// This is meant to be used in const context, in order to bake a table of
+        /// curve points into the binary at compile time, e.g.
+        /// `const TABLE: [Coordinates2D; 256] = build_table();`. `LEN` must not be
+        /// larger than the number of points of the curve.
+        ///
+        pub const fn build_table<const LEN: usize>() -> [Coordinates2D; LEN] {
+            assert!(LEN == 0 || LEN - 1 <= CurveIdx::MAX as usize);
+            let mut table = [[0; 2]; LEN];
+            let mut idx = 0;
+            while idx < LEN {
+                table[idx] = decode_2d(idx as CurveIdx);
+                idx += 1;
+            }
+            table
+        }
+
+        /// Variant of `decode_2d()` that uses a lookup table
+        ///
+        /// This decodes the curve index one byte (i.e. 4 recursion depths) at a time
+        /// using a 2 KiB table, which can be faster than the bitwise algorithm of
+        /// `decode_2d()` on hardware where memory accesses are cheap and shifts are
+        /// expensive, such as some microcontrollers. Since the table is indexed by
+        /// the curve index, this function does not run in constant time.
+        ///
+        #[inline]
+        pub fn decode_2d_lut(code: CurveIdx) -> Coordinates2D {
+            let mut coords: Coordinates2D = [0; 2];
+            let mut state = 0;
+            let mut shift = bits::num_bits::<CurveIdx>();
+            while shift > 0 {
+                shift -= 8;
+                let byte = ((code >> shift) & 0xFF) as usize;
+                let entry = super::DECODE_LUT[(state << 8) | byte];
+                coords[0] = (coords[0] << 4) | (entry & 0xF) as Coordinate;
+                coords[1] = (coords[1] << 4) | ((entry >> 4) & 0xF) as Coordinate;
+                state = (entry >> 8) as usize;
+            }
+            coords
+        }
+
+        /// Variant of `decode_2d()` that uses hardware acceleration if available
+        ///
+        /// See `morton::decode_2d_fast()` for details, the same caveats apply.
+        ///
+        #[inline]
+        pub fn decode_2d_fast(code: CurveIdx) -> Coordinates2D {
+            decode_2d_from_morton(morton::decode_2d_fast(code))
+        }
+
+        /// Variant of `encode_2d()` that uses hardware acceleration if available
+        ///
+        /// See `morton::encode_2d_fast()` for details, the same caveats apply.
+        ///
+        #[inline]
+        pub fn encode_2d_fast(coords: Coordinates2D) -> CurveIdx {
+            morton::encode_2d_fast(encode_2d_to_morton(coords))
+        }
+
+        // TODO: Study if there's a parallel prefix formulation of Hilbert curve
+        //       encoding that could replace the sequential loop of encode_2d()
+
+        /// Iterate over the 2D Hilbert curve
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of all possible
+        /// curve indices (CurveIdx::MIN..=CurveIdx::MAX), but each point is derived
+        /// from the previous one with a few bit operations instead of being decoded
+        /// from scratch. Since `decode_2d()` is already branchless and cheap, the
+        /// gain is modest, around 25% in the `hilbert iter` benchmark. Decoding
+        /// slices of curve indices with `decode_2d_into()` is much faster when the
+        /// points don't need to be produced one at a time.
+        ///
+        pub fn iter_2d() -> Iter2D {
+            iter_from_2d(CurveIdx::MIN)
+        }
+
+        /// Iterate over the 2D Hilbert curve, starting from a certain index
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of curve
+        /// indices (start..=CurveIdx::MAX), but a bit faster, see `iter_2d()`.
+        ///
+        pub fn iter_from_2d(start: CurveIdx) -> Iter2D {
+            iter_range_2d(start..)
+        }
+
+        /// Iterate over a range of indices of the 2D Hilbert curve
+        ///
+        /// This is equivalent to running `decode_2d()` on the range of curve
+        /// indices, but should be a bit more efficient.
+        ///
+        pub fn iter_range_2d(indices: impl core::ops::RangeBounds<CurveIdx>) -> Iter2D {
+            let indices = bits::inclusive_range(indices);
+            if indices.is_empty() {
+                return Iter2D {
+                    coords: [0; 2],
+                    back_coords: [0; 2],
+                    indices,
+                };
+            }
+            Iter2D {
+                coords: decode_2d(*indices.start()),
+                back_coords: decode_2d(*indices.end()),
+                indices,
+            }
+        }
+
+        /// Iterate over the ]-shaped Hilbert curve of a certain order
+        ///
+        /// This is equivalent to running `decode_2d_order()` on the sequence of
+        /// curve indices (0..4^`order`), but more efficient.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`.
+        ///
+        pub fn iter_2d_order(order: NumBits) -> OrderIter2D {
+            iter_2d_order_oriented(order, Orientation::IDENTITY)
+        }
+
+        /// Iterate over the Hilbert curve of a certain order and orientation
+        ///
+        /// This is equivalent to running `decode_2d_order_oriented()` on the
+        /// sequence of curve indices (0..4^`order`), but more efficient.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`.
+        ///
+        pub fn iter_2d_order_oriented(order: NumBits, orientation: Orientation) -> OrderIter2D {
+            assert!(order <= Coordinate::BITS, "Order is too high for this curve");
+            // The full curve must be swapped back to the ]-shaped orientation first
+            let swap = orientation.swap ^ ((Coordinate::BITS - order) % 2 == 1);
+            OrderIter2D {
+                iter: iter_range_2d(..=bits::low_order_mask(2 * order)),
+                orientation: Orientation { swap, ..orientation },
+                mask: bits::low_order_mask(order) as Coordinate,
+            }
+        }
+
+        /// Direction in which the Hilbert curve moves from the point of index
+        /// `code` to the next point, or `None` for the last point of the curve
+        ///
+        /// This is computed from the curve index alone, using the same algorithm as
+        /// `Iter2D`, which is faster than decoding the coordinates of both points.
+        ///
+        #[inline]
+        pub fn direction_at(code: CurveIdx) -> Option<Direction> {
+            (code != CurveIdx::MAX).then(|| step_direction(code))
+        }
+
+        /// Iterate over the moves between consecutive points of the 2D Hilbert
+        /// curve
+        ///
+        /// This is equivalent to running `direction_at()` on the sequence of curve
+        /// indices (CurveIdx::MIN..CurveIdx::MAX), and is meant for applications
+        /// that only care about relative moves, such as turtle graphics.
+        ///
+        pub fn steps_2d() -> Steps2D {
+            steps_range_2d(..)
+        }
+
+        /// Iterate over the moves from the points of a range of indices of the 2D
+        /// Hilbert curve to the next points
+        ///
+        /// This is equivalent to running `direction_at()` on the range of curve
+        /// indices, stopping before the last point of the curve.
+        ///
+        pub fn steps_range_2d(indices: impl core::ops::RangeBounds<CurveIdx>) -> Steps2D {
+            let indices = bits::inclusive_range(indices);
+            let end = (*indices.end()).min(CurveIdx::MAX - 1);
+            Steps2D {
+                indices: *indices.start()..=end,
+            }
+        }
+
+        /// Direction of the move from the point of index `code` to the next one,
+        /// which must exist
+        #[inline(always)]
+        fn step_direction(code: CurveIdx) -> Direction {
+            match Iter2D::step(code) {
+                [1, 0] => Direction::East,
+                [0, 1] => Direction::North,
+                [0, _] => Direction::South,
+                _ => Direction::West,
+            }
+        }
+
+        /// Iterator over the moves between consecutive points of the 2D Hilbert
+        /// curve, created by `steps_2d()` and `steps_range_2d()`
+        #[derive(Clone, Debug)]
+        pub struct Steps2D {
+            /// Indices of the remaining starting points
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        }
+        //
+        impl Iterator for Steps2D {
+            type Item = Direction;
+
+            #[inline]
+            fn next(&mut self) -> Option<Direction> {
+                self.indices.next().map(step_direction)
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Direction> {
+                self.indices.nth(n).map(step_direction)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.indices.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Steps2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Direction> {
+                self.indices.next_back().map(step_direction)
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Direction> {
+                self.indices.nth_back(n).map(step_direction)
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Steps2D {}
+
+        /// Iterate over the 2D Hilbert curve in parallel
+        ///
+        /// This is the parallel version of `iter_2d()`, see the `par` module for
+        /// details. It panics if the curve has more points than `usize` can count.
+        ///
+        #[cfg(feature = "rayon")]
+        pub fn par_iter_2d() -> $crate::par::ParIter2D<CurveIdx, Iter2D> {
+            par_iter_range_2d(CurveIdx::MIN..=CurveIdx::MAX)
+        }
+
+        /// Iterate over a range of indices of the 2D Hilbert curve in parallel
+        ///
+        /// This is equivalent to running `decode_2d()` on the range of curve
+        /// indices in parallel, but more efficient. It panics if the range has more
+        /// points than `usize` can count.
+        ///
+        #[cfg(feature = "rayon")]
+        pub fn par_iter_range_2d(
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        ) -> $crate::par::ParIter2D<CurveIdx, Iter2D> {
+            $crate::par::ParIter2D::new(indices, decode_2d, iter_from_2d)
+        }
+
+        /// Iterator over the 2D Hilbert curve, created by `iter_2d()`,
+        /// `iter_from_2d()` and `iter_range_2d()`
+        ///
+        /// This iterator can also go through the curve backwards, using the same
+        /// incremental algorithm as when going forward. Its length is exactly known
+        /// for 16-bit curve indices, and for 32-bit curve indices on 64-bit
+        /// targets, where `ExactSizeIterator` is implemented.
+        ///
+        #[derive(Clone, Debug)]
+        pub struct Iter2D {
+            /// Coordinates of the next point from the front
+            coords: Coordinates2D,
+
+            /// Coordinates of the next point from the back
+            back_coords: Coordinates2D,
+
+            /// Indices of the remaining points
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        }
+        //
+        impl Iter2D {
+            /// Also yield the curve index of each point, as `(index, coordinates)`
+            #[inline]
+            pub fn indexed(self) -> IndexedIter2D {
+                IndexedIter2D { iter: self }
+            }
+
+            /// Compute the step from the point at curve index `idx` to the next one,
+            /// as a pair of wrapping coordinate offsets
+            #[inline(always)]
+            fn step(idx: CurveIdx) -> Coordinates2D {
+                // When the curve index is incremented, a number of trailing pairs of
+                // bits at the lowest recursion depths go from 11 to 00, and the pair
+                // of bits just above them is incremented. Since the Hilbert curve
+                // always moves to a neighboring point, the resulting move is a unit
+                // step in the direction that the basic ]-shaped pattern would follow
+                // at the depth of the incremented pair of bits, which depends on
+                // whether coordinates are swapped and inverted at that depth.
+                let depth_from_bottom = idx.trailing_ones() / 2;
+                let pair = (idx >> (2 * depth_from_bottom)) & 0b11;
+                let [swap, invert] = transforms_at_depth(idx, depth_from_bottom);
+
+                // The basic ]-shaped pattern goes from ij = 00 to 01 by moving along
+                // +x, from 01 to 10 along +y, and from 10 to 11 along -x. Swapping
+                // coordinates exchanges the x and y axes, and inverting coordinates
+                // flips the direction of motion.
+                let along_y = (pair == 0b01) ^ swap;
+                let backward = (pair == 0b10) ^ invert;
+                let step: Coordinate = if backward { Coordinate::MAX } else { 1 };
+                if along_y {
+                    [0, step]
+                } else {
+                    [step, 0]
+                }
+            }
+        }
+        //
+        impl Iterator for Iter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                let idx = self.indices.next()?;
+
+                // We'll return the current coordinates after preparing the next ones
+                let result = self.coords;
+                if idx == CurveIdx::MAX {
+                    return Some(result);
+                }
+                let [dx, dy] = Self::step(idx);
+                self.coords = [
+                    self.coords[0].wrapping_add(dx),
+                    self.coords[1].wrapping_add(dy),
+                ];
+
+                // And then we return the current coordinates
+                Some(result)
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Coordinates2D> {
+                // Skipping points is faster done by decoding the curve index that
+                // we're jumping to than by stepping once per skipped point.
+                if n > 0 {
+                    let idx = self.indices.nth(n - 1)?;
+                    if idx != CurveIdx::MAX {
+                        self.coords = decode_2d(idx + 1);
+                    }
+                }
+                self.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.indices.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Iter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Coordinates2D> {
+                let idx = self.indices.next_back()?;
+
+                // Going backwards undoes the step from the previous point
+                let result = self.back_coords;
+                if idx == CurveIdx::MIN {
+                    return Some(result);
+                }
+                let [dx, dy] = Self::step(idx - 1);
+                self.back_coords = [
+                    self.back_coords[0].wrapping_sub(dx),
+                    self.back_coords[1].wrapping_sub(dy),
+                ];
+                Some(result)
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Coordinates2D> {
+                if n > 0 {
+                    let idx = self.indices.nth_back(n - 1)?;
+                    if idx != CurveIdx::MIN {
+                        self.back_coords = decode_2d(idx - 1);
+                    }
+                }
+                self.next_back()
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Iter2D {}
+
+        /// Iterator over the 2D Hilbert curve that also yields curve indices,
+        /// created by `Iter2D::indexed()`
+        #[derive(Clone, Debug)]
+        pub struct IndexedIter2D {
+            /// Underlying curve iterator
+            iter: Iter2D,
+        }
+        //
+        impl Iterator for IndexedIter2D {
+            type Item = (CurveIdx, Coordinates2D);
+
+            #[inline]
+            fn next(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.start();
+                self.iter.next().map(|coords| (idx, coords))
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<(CurveIdx, Coordinates2D)> {
+                // If the n-th point exists, n fits in a CurveIdx
+                let idx = *self.iter.indices.start();
+                self.iter.nth(n).map(|coords| (idx + n as CurveIdx, coords))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for IndexedIter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.end();
+                self.iter.next_back().map(|coords| (idx, coords))
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.end();
+                self.iter.nth_back(n).map(|coords| (idx - n as CurveIdx, coords))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for IndexedIter2D {}
+
+        /// Iterator over the Hilbert curve of a certain order, created by
+        /// `iter_2d_order()`
+        #[derive(Clone, Debug)]
+        pub struct OrderIter2D {
+            /// Iterator over the first points of the full curve
+            iter: Iter2D,
+
+            /// Transform from the points of the full curve to the points of the
+            /// curve of this order and orientation
+            orientation: Orientation,
+
+            /// Mask of the coordinate bits that are used at this order
+            mask: Coordinate,
+        }
+        //
+        impl OrderIter2D {
+            /// Give the points of the full curve the orientation of this order
+            #[inline(always)]
+            fn orient(&self, coords: Coordinates2D) -> Coordinates2D {
+                mirror_within(swap(coords, self.orientation), self.orientation, self.mask)
+            }
+        }
+        //
+        impl Iterator for OrderIter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                self.iter.next().map(|coords| self.orient(coords))
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Coordinates2D> {
+                self.iter.nth(n).map(|coords| self.orient(coords))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for OrderIter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Coordinates2D> {
+                self.iter.next_back().map(|coords| self.orient(coords))
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Coordinates2D> {
+                self.iter.nth_back(n).map(|coords| self.orient(coords))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for OrderIter2D {}
+
+        clip_iter_impl!("Hilbert");
+
+        neighbors_impl!("Hilbert");
+
+        blocks_impl!("Hilbert");
+
+        join_impl!("Hilbert");
+
+        /// Decompose an axis-aligned rectangle into ranges of Hilbert curve indices
+        ///
+        /// The rectangle goes from `min` to `max` coordinates, inclusive. The
+        /// resulting iterator yields the minimal set of contiguous ranges of curve
+        /// indices that cover it, in increasing order, which is what range queries
+        /// on a database indexed by Hilbert curve indices need.
+        ///
+        /// This works by recursively splitting aligned blocks of curve indices,
+        /// which correspond to squares of space, until they are either inside or
+        /// outside of the rectangle, and merging the contiguous blocks that are
+        /// inside of it into ranges.
+        ///
+        pub fn ranges_for_rect(min: Coordinates2D, max: Coordinates2D) -> RectRanges {
+            let mut ranges = RectRanges {
+                blocks: [(0, 0); RECT_RANGES_STACK_LEN],
+                num_blocks: 0,
+                pending: None,
+                min,
+                max,
+            };
+            if min[0] <= max[0] && min[1] <= max[1] {
+                ranges.push(0, bits::num_bits::<Coordinate>());
+            }
+            ranges
+        }
+
+        /// Maximal number of pending blocks in `RectRanges`
+        ///
+        /// Every block that is split is replaced by four blocks from the next
+        /// recursion depth, and the first of them is processed immediately.
+        ///
+        const RECT_RANGES_STACK_LEN: usize = 3 * Coordinate::BITS as usize + 1;
+
+        /// Iterator over the ranges of Hilbert curve indices that cover a
+        /// rectangle, created by `ranges_for_rect()`
+        #[derive(Clone, Debug)]
+        pub struct RectRanges {
+            /// Stack of aligned blocks of curve indices that remain to be processed,
+            /// as a first curve index and a recursion depth (from the bottom), the
+            /// last of which is to be processed first
+            blocks: [(CurveIdx, NumBits); RECT_RANGES_STACK_LEN],
+
+            /// Number of valid entries in `blocks`
+            num_blocks: usize,
+
+            /// Range of curve indices that is inside of the rectangle and may still
+            /// be extended by the next block
+            pending: Option<core::ops::RangeInclusive<CurveIdx>>,
+
+            /// Lower corner of the rectangle
+            min: Coordinates2D,
+
+            /// Upper corner of the rectangle
+            max: Coordinates2D,
+        }
+        //
+        impl RectRanges {
+            /// Push a block on the stack of pending blocks
+            fn push(&mut self, start: CurveIdx, depth: NumBits) {
+                self.blocks[self.num_blocks] = (start, depth);
+                self.num_blocks += 1;
+            }
+
+            /// Pop a block from the stack of pending blocks
+            fn pop(&mut self) -> Option<(CurveIdx, NumBits)> {
+                self.num_blocks = self.num_blocks.checked_sub(1)?;
+                Some(self.blocks[self.num_blocks])
+            }
+        }
+        //
+        impl Iterator for RectRanges {
+            type Item = core::ops::RangeInclusive<CurveIdx>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                while let Some((start, depth)) = self.pop() {
+                    // Compute the square of space that this block covers
+                    let size = bits::low_order_mask(depth) as Coordinate;
+                    let [x, y] = decode_2d(start);
+                    let [x, y] = [x & !size, y & !size];
+
+                    // Skip blocks outside of the rectangle, split blocks that are
+                    // partially inside of it, and record blocks that are inside of it
+                    if x > self.max[0]
+                        || y > self.max[1]
+                        || x + size < self.min[0]
+                        || y + size < self.min[1]
+                    {
+                        continue;
+                    }
+                    if x < self.min[0]
+                        || y < self.min[1]
+                        || x + size > self.max[0]
+                        || y + size > self.max[1]
+                    {
+                        let sub_block_len: CurveIdx = 1 << (2 * (depth - 1));
+                        let mut sub_block = 4;
+                        while sub_block > 0 {
+                            sub_block -= 1;
+                            self.push(start + sub_block * sub_block_len, depth - 1);
+                        }
+                        continue;
+                    }
+                    let end = start | bits::low_order_mask(2 * depth);
+                    match &mut self.pending {
+                        Some(pending) if *pending.end() + 1 == start => {
+                            *pending = *pending.start()..=end;
+                        }
+                        Some(pending) => return Some(core::mem::replace(pending, start..=end)),
+                        None => self.pending = Some(start..=end),
+                    }
+                }
+                self.pending.take()
+            }
+        }
+        //
+        impl core::iter::FusedIterator for RectRanges {}
+
+        /// Find the smallest Hilbert curve index that is not smaller than `from`
+        /// and whose point is inside of an axis-aligned rectangle, if any
+        ///
+        /// The rectangle goes from `min` to `max` coordinates, inclusive. This is
+        /// the Hilbert curve counterpart of the BIGMIN computation of the Morton
+        /// curve, which lets skip-scan queries on a database that is sorted by
+        /// curve index jump over the parts of the curve that are outside of the
+        /// rectangle.
+        ///
+        /// This works by going down the aligned blocks of curve indices that
+        /// contain `from`, like `ranges_for_rect()` does. Only the blocks that
+        /// contain `from` may need backtracking, since any block of indices above
+        /// `from` that overlaps with the rectangle contains a suitable index.
+        ///
+        pub fn next_index_in_rect(
+            from: CurveIdx,
+            min: Coordinates2D,
+            max: Coordinates2D,
+        ) -> Option<CurveIdx> {
+            if min[0] > max[0] || min[1] > max[1] {
+                return None;
+            }
+            next_index_in_block(0, bits::num_bits::<Coordinate>(), from, [min, max])
+        }
+
+        /// Recursive implementation of `next_index_in_rect()` for an aligned block
+        /// of curve indices, given by its first index and its recursion depth
+        /// from the bottom
+        fn next_index_in_block(
+            start: CurveIdx,
+            depth: NumBits,
+            from: CurveIdx,
+            [min, max]: [Coordinates2D; 2],
+        ) -> Option<CurveIdx> {
+            // Compute the square of space that this block covers
+            let size = bits::low_order_mask(depth) as Coordinate;
+            let [x, y] = decode_2d(start);
+            let [x, y] = [x & !size, y & !size];
+
+            // Skip blocks outside of the rectangle, and pick the first suitable
+            // index of blocks that are inside of it
+            if x > max[0] || y > max[1] || x + size < min[0] || y + size < min[1] {
+                return None;
+            }
+            if x >= min[0] && y >= min[1] && x + size <= max[0] && y + size <= max[1] {
+                return Some(start.max(from));
+            }
+
+            // Look for the first suitable sub-block of partially covered blocks
+            let sub_block_len: CurveIdx = 1 << (2 * (depth - 1));
+            (0..4)
+                .map(|sub_block| start + sub_block * sub_block_len)
+                .filter(|&sub_start| sub_start + (sub_block_len - 1) >= from)
+                .find_map(|sub_start| next_index_in_block(sub_start, depth - 1, from, [min, max]))
+        }
+
+        /// Iterate over the points of the Hilbert curve that are inside of an
+        /// axis-aligned rectangle, in curve order
+        ///
+        /// The rectangle goes from `min` to `max` coordinates, inclusive. Points are
+        /// yielded as `(index, coordinates)` pairs, like `Iter2D::indexed()` does,
+        /// and points outside of the rectangle are never visited, since the
+        /// iterator goes through the ranges of curve indices from
+        /// `ranges_for_rect()`.
+        ///
+        pub fn iter_rect_query(min: Coordinates2D, max: Coordinates2D) -> RectQueryIter {
+            RectQueryIter {
+                ranges: ranges_for_rect(min, max),
+                points: iter_range_2d(..0).indexed(),
+            }
+        }
+
+        /// Iterator over the points of the Hilbert curve that are inside of a
+        /// rectangle, created by `iter_rect_query()`
+        #[derive(Clone, Debug)]
+        pub struct RectQueryIter {
+            /// Ranges of curve indices that remain to be iterated over
+            ranges: RectRanges,
+
+            /// Points of the current range of curve indices
+            points: IndexedIter2D,
+        }
+        //
+        impl Iterator for RectQueryIter {
+            type Item = (CurveIdx, Coordinates2D);
+
+            #[inline]
+            fn next(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                loop {
+                    if let Some(point) = self.points.next() {
+                        return Some(point);
+                    }
+                    self.points = iter_range_2d(self.ranges.next()?).indexed();
+                }
+            }
+        }
+        //
+        impl core::iter::FusedIterator for RectQueryIter {}
+
+        /// Iterate over a rectangle of arbitrary dimensions in a Hilbert-like order
+        ///
+        /// This is a generalization of the Hilbert curve to domains whose sides are
+        /// not powers of two, based on the "gilbert" algorithm by Jakub Červený. The
+        /// rectangle is recursively split into two or three sub-rectangles, which
+        /// are traversed in an order that connects their endpoints, until they are
+        /// only one cell wide.
+        ///
+        /// Every cell of the rectangle is yielded exactly once, and every point is a
+        /// direct neighbor of the previous one. The traversal starts at `[0, 0]` and
+        /// ends at another corner of the rectangle, which is picked so that this is
+        /// possible: the rectangle is traversed along its even side if only one of
+        /// its sides has an even length, and along its longest side otherwise.
+        ///
+        /// Unlike other Hilbert curve utilities, this iterator has no associated
+        /// curve index encoding and decoding functions.
+        ///
+        pub fn iter_rect(width: Coordinate, height: Coordinate) -> IterRect {
+            let mut iter = IterRect {
+                pending: [RectPart {
+                    start: [0, 0],
+                    major: RectAxis::x(width),
+                    minor: RectAxis::y(height),
+                }; RECT_STACK_LEN],
+                num_pending: 0,
+                run_start: [0, 0],
+                run_axis: RectAxis::x(0),
+                remaining: width as CurveIdx * height as CurveIdx,
+            };
+            if iter.remaining > 0 {
+                let along_x = if width % 2 != height % 2 {
+                    width % 2 == 0
+                } else {
+                    width >= height
+                };
+                if !along_x {
+                    iter.pending[0].major = RectAxis::y(height);
+                    iter.pending[0].minor = RectAxis::x(width);
+                }
+                iter.num_pending = 1;
+            }
+            iter
+        }
+
+        /// Maximal number of pending sub-rectangles in `IterRect`
+        ///
+        /// Every sub-rectangle that is pushed on the stack of pending
+        /// sub-rectangles goes with a halving of one side of the sub-rectangle
+        /// that is traversed next, which can only happen about `Coordinate::BITS`
+        /// times per side.
+        ///
+        const RECT_STACK_LEN: usize = 2 * Coordinate::BITS as usize + 3;
+
+        /// Side of a rectangle traversed by `IterRect`
+        #[derive(Clone, Copy, Debug)]
+        struct RectAxis {
+            /// Number of cells along this side
+            length: Coordinate,
+
+            /// Coordinate that varies along this side (0 for x, 1 for y)
+            dim: usize,
+
+            /// Truth that this side goes towards lower coordinates
+            reverse: bool,
+        }
+        //
+        impl RectAxis {
+            /// Side of a given length that goes along the x axis
+            fn x(length: Coordinate) -> Self {
+                Self {
+                    length,
+                    dim: 0,
+                    reverse: false,
+                }
+            }
+
+            /// Side of a given length that goes along the y axis
+            fn y(length: Coordinate) -> Self {
+                Self {
+                    length,
+                    dim: 1,
+                    reverse: false,
+                }
+            }
+
+            /// Same direction, different length
+            fn with_length(self, length: Coordinate) -> Self {
+                Self { length, ..self }
+            }
+
+            /// Opposite direction, different length
+            fn reversed(self, length: Coordinate) -> Self {
+                Self {
+                    length,
+                    reverse: !self.reverse,
+                    ..self
+                }
+            }
+
+            /// Move some coordinates by a number of cells along this side
+            fn offset(self, mut coords: Coordinates2D, distance: Coordinate) -> Coordinates2D {
+                if self.reverse {
+                    coords[self.dim] -= distance;
+                } else {
+                    coords[self.dim] += distance;
+                }
+                coords
+            }
+        }
+
+        /// Sub-rectangle to be traversed by `IterRect`, from its `start` corner
+        /// along its `major` side, ending at the other end of that side
+        #[derive(Clone, Copy, Debug)]
+        struct RectPart {
+            start: Coordinates2D,
+            major: RectAxis,
+            minor: RectAxis,
+        }
+
+        /// Iterator over a rectangle in a Hilbert-like order, created by
+        /// `iter_rect()`
+        #[derive(Clone, Debug)]
+        pub struct IterRect {
+            /// Stack of sub-rectangles that remain to be traversed, the last of
+            /// which is to be traversed first
+            pending: [RectPart; RECT_STACK_LEN],
+
+            /// Number of valid entries in `pending`
+            num_pending: usize,
+
+            /// Next point of the line of cells that is being traversed
+            run_start: Coordinates2D,
+
+            /// Direction and remaining length of that line of cells
+            run_axis: RectAxis,
+
+            /// Number of points that remain to be yielded
+            remaining: CurveIdx,
+        }
+        //
+        impl IterRect {
+            /// Split pending sub-rectangles until one of them is a line of cells
+            fn next_run(&mut self) -> Option<()> {
+                loop {
+                    self.num_pending = self.num_pending.checked_sub(1)?;
+                    let RectPart { start, major, minor } = self.pending[self.num_pending];
+                    if minor.length == 1 {
+                        self.run_start = start;
+                        self.run_axis = major;
+                        return Some(());
+                    } else if major.length == 1 {
+                        self.run_start = start;
+                        self.run_axis = minor;
+                        return Some(());
+                    }
+
+                    // Prefer even sub-rectangle sides, which avoid diagonal steps
+                    let even_half = |length: Coordinate| {
+                        let half = length / 2;
+                        if half % 2 == 1 && length > 2 {
+                            half + 1
+                        } else {
+                            half
+                        }
+                    };
+                    let mut half_major = major.length / 2;
+                    if 2 * major.length as CurveIdx > 3 * minor.length as CurveIdx {
+                        // Long rectangles are split in two along their major side
+                        half_major = even_half(major.length);
+                        self.push(RectPart {
+                            start: major.offset(start, half_major),
+                            major: major.with_length(major.length - half_major),
+                            minor,
+                        });
+                        self.push(RectPart {
+                            start,
+                            major: major.with_length(half_major),
+                            minor,
+                        });
+                    } else {
+                        // Other rectangles are traversed by going along the minor
+                        // side, then along the major side, then back
+                        let half_minor = even_half(minor.length);
+                        let end = minor.offset(major.offset(start, major.length - 1), half_minor - 1);
+                        self.push(RectPart {
+                            start: end,
+                            major: minor.reversed(half_minor),
+                            minor: major.reversed(major.length - half_major),
+                        });
+                        self.push(RectPart {
+                            start: minor.offset(start, half_minor),
+                            major,
+                            minor: minor.with_length(minor.length - half_minor),
+                        });
+                        self.push(RectPart {
+                            start,
+                            major: minor.with_length(half_minor),
+                            minor: major.with_length(half_major),
+                        });
+                    }
+                }
+            }
+
+            /// Push a sub-rectangle on the stack of pending sub-rectangles
+            fn push(&mut self, part: RectPart) {
+                self.pending[self.num_pending] = part;
+                self.num_pending += 1;
+            }
+        }
+        //
+        impl Iterator for IterRect {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                if self.run_axis.length == 0 {
+                    self.next_run()?;
+                }
+                let result = self.run_start;
+                self.run_axis.length -= 1;
+                if self.run_axis.length > 0 {
+                    self.run_start = self.run_axis.offset(result, 1);
+                }
+                self.remaining -= 1;
+                Some(result)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match usize::try_from(self.remaining) {
+                    Ok(remaining) => (remaining, Some(remaining)),
+                    Err(_) => (usize::MAX, None),
+                }
+            }
+        }
+        //
+        impl core::iter::FusedIterator for IterRect {}
+
+        /// Truth that coordinates are swapped and inverted at a certain recursion
+        /// depth (counted from the bottom) when decoding a certain curve index
+        ///
+        /// This is the information which `decode_2d()` computes for all depths at
+        /// once using bitwise XOR scans. But for a single depth, we can compute the
+        /// parity of the number of control bits that are set at previous depths
+        /// more efficiently, without even needing to Morton-decode the curve index:
+        ///
+        /// - Coordinate swapping is controlled by NOT (i XOR j), and the parity of a
+        ///   number of XORed bits is the parity of the number of set bits in the
+        ///   input, so it's the parity of the number of set bits in the previous
+        ///   depths of the curve index, plus the number of previous depths.
+        /// - Coordinate inversion is controlled by (i AND j), which can be computed by
+        ///   AND-ing the curve index with a shifted version of itself, then masking
+        ///   out every other bit.
+        ///
+        #[inline(always)]
+        fn transforms_at_depth(code: CurveIdx, depth_from_bottom: NumBits) -> [bool; 2] {
+            debug_assert!(depth_from_bottom < bits::num_bits::<Coordinate>());
+            let previous_depths = !bits::low_order_mask(2 * (depth_from_bottom + 1));
+            let num_previous_depths = bits::num_bits::<Coordinate>() - 1 - depth_from_bottom;
+            let num_xor_bits = (code & previous_depths).count_ones();
+            let and_bits = code & (code >> 1) & bits::striped_mask(1);
+            let swap = (num_previous_depths + num_xor_bits) % 2 == 1;
+            let invert = (and_bits & previous_depths).count_ones() % 2 == 1;
+            [swap, invert]
+        }
+
+        /// Compute the coordinates of the i-th point of an N-dimensional Hilbert curve
+        ///
+        /// This function implements the N-dimensional generalization of the Hilbert
+        /// curve from the "Compact Hilbert Indices" paper by Chris Hamilton (ref:
+        /// CS-2006-07). The bits of the curve index are split into groups of N bits,
+        /// one per recursion depth, so the curve spans the N-dimensional hypercube
+        /// whose side is 2 to the power of the number of bits of CurveIdx divided by
+        /// N, capped to the number of bits of Coordinate. Higher-order bits of the
+        /// curve index, if any, must be zero.
+        ///
+        /// Since the curve's orientation is arbitrary, we pick the initial orientation
+        /// such that for N = 2, this function produces the same results as the
+        /// specialized `decode_2d()` algorithm, which should be preferred for 2D use.
+        ///
+        pub const fn decode_nd<const N: usize>(code: CurveIdx) -> [Coordinate; N] {
+            let num_dims = num_dims_nd::<N>();
+            let order = order_nd::<N>();
+            debug_assert!(code & !bits::low_order_mask(num_dims * order) == 0);
+
+            // At each recursion depth, the basic pattern of the Hilbert curve goes
+            // through the 2^N corners of a hypercube in the order of the Gray code of
+            // the corresponding N bits of the curve index. This basic pattern is
+            // transformed at each recursion depth by XOR-ing the coordinates of the
+            // corners with an entry point and rotating their bits according to an
+            // intra-sub-hypercube direction, which both depend on previous depths.
+            let mut coords = [0; N];
+            let mut entry: CurveIdx = 0;
+            let mut direction = num_dims - 1;
+            let mut depth = order;
+            while depth > 0 {
+                depth -= 1;
+                let digit = (code >> (depth * num_dims)) & bits::low_order_mask(num_dims);
+                let gray = digit ^ (digit >> 1);
+                let corner = rotate_left_nd(gray, direction + 1, num_dims) ^ entry;
+                let mut dim = 0;
+                while dim < N {
+                    coords[dim] |= (((corner >> dim) & 1) as Coordinate) << depth;
+                    dim += 1;
+                }
+                entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+                direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+            }
+            coords
+        }
+
+        /// Compute the index of a point on an N-dimensional Hilbert curve
+        ///
+        /// This is the inverse of `decode_nd()`, see its documentation for details.
+        ///
+        pub const fn encode_nd<const N: usize>(coords: [Coordinate; N]) -> CurveIdx {
+            let num_dims = num_dims_nd::<N>();
+            let order = order_nd::<N>();
+            let mut code = 0;
+            let mut entry: CurveIdx = 0;
+            let mut direction = num_dims - 1;
+            let mut depth = order;
+            while depth > 0 {
+                depth -= 1;
+
+                // Gather the coordinate bits at this depth into a hypercube corner
+                let mut corner: CurveIdx = 0;
+                let mut dim = 0;
+                while dim < N {
+                    debug_assert!((coords[dim] as CurveIdx) >> order == 0);
+                    corner |= (((coords[dim] >> depth) & 1) as CurveIdx) << dim;
+                    dim += 1;
+                }
+
+                // Undo the transform of the basic pattern at this depth, and the Gray
+                // coding of the curve index bits, which is done by XOR-scanning bits
+                // from the most significant one to the least significant one.
+                let gray = rotate_right_nd(corner ^ entry, direction + 1, num_dims);
+                let mut digit = gray;
+                let mut stride = 1;
+                while stride < num_dims {
+                    digit ^= digit >> stride;
+                    stride *= 2;
+                }
+                code |= digit << (depth * num_dims);
+
+                // Update the transform for the next depth
+                entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+                direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+            }
+            code
+        }
+
+        /// Compute the coordinates of the i-th point of a 4-dimensional Hilbert curve
+        ///
+        /// This is `decode_nd::<4>()`, meant for e.g. indexing points of spacetime.
+        /// Each coordinate has a quarter of the bits of `CurveIdx`, so 64-bit curve
+        /// indices can hold four 16-bit coordinates.
+        ///
+        #[inline]
+        pub const fn decode_4d(code: CurveIdx) -> [Coordinate; 4] {
+            decode_nd::<4>(code)
+        }
+
+        /// Compute the index of a point on a 4-dimensional Hilbert curve
+        ///
+        /// This is the inverse of `decode_4d()`. The high-order bits of the
+        /// coordinates, beyond the quarter of the bits of `CurveIdx`, must be zero.
+        ///
+        #[inline]
+        pub const fn encode_4d(coords: [Coordinate; 4]) -> CurveIdx {
+            encode_nd(coords)
+        }
+
+        /// Compute the coordinates of the i-th point of an N-dimensional Hilbert curve
+        /// with a certain orientation
+        ///
+        /// This is `decode_nd()` followed by the coordinate transform that moves
+        /// the start and end corners of its curve to the corners selected by
+        /// `orientation`: coordinate axes are rotated, then mirrored within the
+        /// domain of the curve.
+        ///
+        pub const fn decode_nd_oriented<const N: usize>(
+            code: CurveIdx,
+            orientation: OrientationNd<N>,
+        ) -> [Coordinate; N] {
+            let coords = decode_nd::<N>(code);
+            let mask = bits::low_order_mask(order_nd::<N>()) as Coordinate;
+
+            // The curve of decode_nd() goes from the origin to the corner that is
+            // at the end of the last axis, so the last axis goes to end_axis.
+            let rotation = orientation.end_axis + 1;
+            let mut result = [0; N];
+            let mut dim = 0;
+            while dim < N {
+                let new_dim = (dim + rotation) % N;
+                let mirror = (orientation.start[new_dim] as Coordinate).wrapping_neg();
+                result[new_dim] = coords[dim] ^ (mask & mirror);
+                dim += 1;
+            }
+            result
+        }
+
+        /// Compute the index of a point on an N-dimensional Hilbert curve with a
+        /// certain orientation
+        ///
+        /// This is the inverse of `decode_nd_oriented()`.
+        ///
+        pub const fn encode_nd_oriented<const N: usize>(
+            coords: [Coordinate; N],
+            orientation: OrientationNd<N>,
+        ) -> CurveIdx {
+            let mask = bits::low_order_mask(order_nd::<N>()) as Coordinate;
+            let rotation = orientation.end_axis + 1;
+            let mut original = [0; N];
+            let mut dim = 0;
+            while dim < N {
+                let new_dim = (dim + rotation) % N;
+                let mirror = (orientation.start[new_dim] as Coordinate).wrapping_neg();
+                original[dim] = coords[new_dim] ^ (mask & mirror);
+                dim += 1;
+            }
+            encode_nd(original)
+        }
+
+        /// Compute the coordinates of the i-th point of an N-dimensional Hilbert curve
+        /// whose dimensions have different precisions
+        ///
+        /// This function implements the compact Hilbert indices from the "Compact
+        /// Hilbert Indices" paper by Chris Hamilton (ref: CS-2006-07). The curve's
+        /// domain is an N-dimensional box whose side along dimension `i` is
+        /// `2^precisions[i]`. Points are ordered as on the N-dimensional Hilbert
+        /// curve that spans the smallest enclosing hypercube, but curve indices are
+        /// dense: they go from 0 to `2^sum(precisions) - 1`. Higher-order bits of
+        /// the curve index, if any, must be zero.
+        ///
+        /// When all precisions are equal, this is the same curve as `decode_nd()`
+        /// with that number of bits per coordinate.
+        ///
+        pub const fn decode_nd_compact<const N: usize>(
+            code: CurveIdx,
+            precisions: [NumBits; N],
+        ) -> [Coordinate; N] {
+            let num_dims = num_dims_nd::<N>();
+            let (order, mut remaining_bits) = compact_order_and_bits(precisions);
+            debug_assert!(code & !bits::low_order_mask(remaining_bits) == 0);
+
+            // This is decode_nd(), except the curve index only contains the bits of
+            // the Gray-decoded hypercube corner for the dimensions that are not
+            // exhausted yet. The other bits of the transformed corner are known to
+            // come from zero coordinate bits, which lets us recover them.
+            let mut coords = [0; N];
+            let mut entry: CurveIdx = 0;
+            let mut direction = num_dims - 1;
+            let mut depth = order;
+            while depth > 0 {
+                depth -= 1;
+                let free =
+                    rotate_right_nd(free_dims_nd(precisions, depth), direction + 1, num_dims);
+                let fixed_gray = rotate_right_nd(entry, direction + 1, num_dims) & !free;
+                let num_free = free.count_ones();
+                remaining_bits -= num_free;
+                let free_digit = (code >> remaining_bits) & bits::low_order_mask(num_free);
+
+                // Merge the free digit bits with the fixed Gray code bits, going from
+                // the most significant bit to the least significant one since each
+                // digit bit is the XOR of the Gray code bits above it.
+                let mut digit: CurveIdx = 0;
+                let mut gray: CurveIdx = 0;
+                let mut prev_digit_bit = 0;
+                let mut num_free_left = num_free;
+                let mut bit = num_dims;
+                while bit > 0 {
+                    bit -= 1;
+                    let digit_bit = if (free >> bit) & 1 == 1 {
+                        num_free_left -= 1;
+                        (free_digit >> num_free_left) & 1
+                    } else {
+                        ((fixed_gray >> bit) & 1) ^ prev_digit_bit
+                    };
+                    digit |= digit_bit << bit;
+                    gray |= (digit_bit ^ prev_digit_bit) << bit;
+                    prev_digit_bit = digit_bit;
+                }
+
+                let corner = rotate_left_nd(gray, direction + 1, num_dims) ^ entry;
+                let mut dim = 0;
+                while dim < N {
+                    coords[dim] |= (((corner >> dim) & 1) as Coordinate) << depth;
+                    dim += 1;
+                }
+                entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+                direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+            }
+            coords
+        }
+
+        /// Compute the index of a point on an N-dimensional Hilbert curve whose
+        /// dimensions have different precisions
+        ///
+        /// This is the inverse of `decode_nd_compact()`, see its documentation for
+        /// details.
+        ///
+        pub const fn encode_nd_compact<const N: usize>(
+            coords: [Coordinate; N],
+            precisions: [NumBits; N],
+        ) -> CurveIdx {
+            let num_dims = num_dims_nd::<N>();
+            let (order, _num_bits) = compact_order_and_bits(precisions);
+            let mut code = 0;
+            let mut entry: CurveIdx = 0;
+            let mut direction = num_dims - 1;
+            let mut depth = order;
+            while depth > 0 {
+                depth -= 1;
+
+                // Compute the Gray-decoded hypercube corner as in encode_nd()
+                let mut corner: CurveIdx = 0;
+                let mut dim = 0;
+                while dim < N {
+                    debug_assert!((coords[dim] as CurveIdx) >> precisions[dim] == 0);
+                    corner |= (((coords[dim] >> depth) & 1) as CurveIdx) << dim;
+                    dim += 1;
+                }
+                let gray = rotate_right_nd(corner ^ entry, direction + 1, num_dims);
+                let mut digit = gray;
+                let mut stride = 1;
+                while stride < num_dims {
+                    digit ^= digit >> stride;
+                    stride *= 2;
+                }
+
+                // Only keep the digit bits of dimensions that are not exhausted yet
+                let free =
+                    rotate_right_nd(free_dims_nd(precisions, depth), direction + 1, num_dims);
+                let mut bit = num_dims;
+                while bit > 0 {
+                    bit -= 1;
+                    if (free >> bit) & 1 == 1 {
+                        code = (code << 1) | ((digit >> bit) & 1);
+                    }
+                }
+
+                entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+                direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+            }
+            code
+        }
+
+        /// Number of recursion depths and number of curve index bits of a compact
+        /// N-dimensional Hilbert curve with certain per-dimension precisions
+        #[inline(always)]
+        const fn compact_order_and_bits<const N: usize>(
+            precisions: [NumBits; N],
+        ) -> (NumBits, NumBits) {
+            let mut order = 0;
+            let mut num_bits = 0;
+            let mut dim = 0;
+            while dim < N {
+                let precision = precisions[dim];
+                assert!(precision <= bits::num_bits::<Coordinate>());
+                if precision > order {
+                    order = precision;
+                }
+                num_bits += precision;
+                dim += 1;
+            }
+            assert!(num_bits <= bits::num_bits::<CurveIdx>());
+            (order, num_bits)
+        }
+
+        /// Mask of the dimensions whose precision is higher than a recursion depth,
+        /// which are thus not exhausted yet at that depth
+        #[inline(always)]
+        const fn free_dims_nd<const N: usize>(
+            precisions: [NumBits; N],
+            depth: NumBits,
+        ) -> CurveIdx {
+            let mut mask = 0;
+            let mut dim = 0;
+            while dim < N {
+                mask |= ((precisions[dim] > depth) as CurveIdx) << dim;
+                dim += 1;
+            }
+            mask
+        }
+
+        /// Number of dimensions of an N-dimensional Hilbert curve
+        #[inline(always)]
+        const fn num_dims_nd<const N: usize>() -> NumBits {
+            assert!(N != 0 && N <= bits::num_bits::<CurveIdx>() as usize);
+            N as _
+        }
+
+        /// Number of recursion depths (bits per coordinate) of an N-dimensional
+        /// Hilbert curve, given the CurveIdx and Coordinate types
+        #[inline(always)]
+        const fn order_nd<const N: usize>() -> NumBits {
+            let order = bits::num_bits::<CurveIdx>() / num_dims_nd::<N>();
+            if order < bits::num_bits::<Coordinate>() {
+                order
+            } else {
+                bits::num_bits::<Coordinate>()
+            }
+        }
+
+        /// Entry point of the i-th sub-hypercube of the basic N-dimensional pattern,
+        /// noted e(i) in Hamilton's paper
+        #[inline(always)]
+        pub(super) const fn entry_nd(digit: CurveIdx) -> CurveIdx {
+            if digit == 0 {
+                0
+            } else {
+                let even = (digit - 1) & !1;
+                even ^ (even >> 1)
+            }
+        }
+
+        /// Intra-sub-hypercube direction of the i-th sub-hypercube of the basic
+        /// N-dimensional pattern, noted d(i) in Hamilton's paper
+        #[inline(always)]
+        pub(super) const fn direction_nd(digit: CurveIdx, num_dims: NumBits) -> NumBits {
+            if digit == 0 {
+                0
+            } else if digit % 2 == 0 {
+                (digit - 1).trailing_ones() % num_dims
+            } else {
+                digit.trailing_ones() % num_dims
+            }
+        }
+
+        /// Rotate the low-order `num_dims` bits of an integer to the left
+        #[inline(always)]
+        pub(super) const fn rotate_left_nd(
+            bits: CurveIdx,
+            amount: NumBits,
+            num_dims: NumBits,
+        ) -> CurveIdx {
+            let amount = amount % num_dims;
+            if amount == 0 {
+                bits
+            } else {
+                ((bits << amount) | (bits >> (num_dims - amount))) & bits::low_order_mask(num_dims)
+            }
+        }
+
+        /// Rotate the low-order `num_dims` bits of an integer to the right
+        #[inline(always)]
+        pub(super) const fn rotate_right_nd(
+            bits: CurveIdx,
+            amount: NumBits,
+            num_dims: NumBits,
+        ) -> CurveIdx {
+            rotate_left_nd(bits, num_dims - amount % num_dims, num_dims)
+        }
+    };
+}
+
+/// Orientation of a 2D Hilbert curve
+///
+/// `decode_2d()` and `encode_2d()` use a ]-shaped curve, which starts at the
+/// `[0, 0]` corner of the domain and ends at the `[0, Coordinate::MAX]` corner.
+/// Other orientations are obtained by optionally swapping the coordinates of
+/// that curve, then optionally mirroring them along each axis, which covers the
+/// four rotations of the basic pattern and their reflections.
+///
+/// Corners of the domain are designated using a pair of booleans, which tell
+/// for each coordinate whether the corner is at its maximal value.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Orientation {
+    /// Truth that x and y coordinates are swapped before mirroring
+    pub swap: bool,
+
+    /// Truth that the curve is mirrored along the x axis
+    pub mirror_x: bool,
+
+    /// Truth that the curve is mirrored along the y axis
+    pub mirror_y: bool,
+}
+//
+impl Orientation {
+    /// Orientation of the curve of `decode_2d()` and `encode_2d()`
+    pub const IDENTITY: Self = Self {
+        swap: false,
+        mirror_x: false,
+        mirror_y: false,
+    };
+
+    /// Orientation of a curve that starts and ends at certain corners
+    ///
+    /// Returns `None` if the corners are not distinct corners that share a side
+    /// of the domain, since a Hilbert curve cannot start and end there.
+    ///
+    pub const fn from_corners(start: [bool; 2], end: [bool; 2]) -> Option<Self> {
+        let [start_x, start_y] = start;
+        let [end_x, end_y] = end;
+        if (start_x != end_x) == (start_y != end_y) {
+            return None;
+        }
+        Some(Self {
+            swap: start_x != end_x,
+            mirror_x: start_x,
+            mirror_y: start_y,
+        })
+    }
+
+    /// Corner of the domain where the curve starts
+    pub const fn start(self) -> [bool; 2] {
+        [self.mirror_x, self.mirror_y]
+    }
+
+    /// Corner of the domain where the curve ends
+    pub const fn end(self) -> [bool; 2] {
+        [self.mirror_x ^ self.swap, self.mirror_y ^ !self.swap]
+    }
+
+    /// Orientation of a curve of the same order that continues this one in the
+    /// neighboring domain in a certain `direction`, and ends at the `end` corner
+    /// of that domain
+    ///
+    /// Curves of a certain order can be laid out as tiles of a larger domain,
+    /// each with its own start and end corners, as in Hamilton's generalization
+    /// of the Hilbert curve. Two tiles connect seamlessly when the first point
+    /// of the second tile is next to the last point of the first one, so the
+    /// start corner of the following tile is the mirror image of the end corner
+    /// of this one across their shared side.
+    ///
+    /// Returns `None` if this curve does not end on the side of its domain that
+    /// faces `direction`, or if no Hilbert curve goes from that start corner to
+    /// `end`.
+    ///
+    pub const fn following(self, direction: Direction, end: [bool; 2]) -> Option<Self> {
+        let [end_x, end_y] = self.end();
+        let start = match direction {
+            Direction::North if end_y => [end_x, false],
+            Direction::South if !end_y => [end_x, true],
+            Direction::East if end_x => [false, end_y],
+            Direction::West if !end_x => [true, end_y],
+            _ => return None,
+        };
+        Self::from_corners(start, end)
+    }
+}
+
+/// Orientation of an N-dimensional Hilbert curve, for `decode_nd_oriented()`
+/// and `encode_nd_oriented()`
+///
+/// N-dimensional Hilbert curves go from a corner of their hypercube domain to
+/// a neighboring corner, which differs from it along a single axis. There is
+/// an orientation of the curve for each of the 2^N start corners and N end
+/// axes, which is enough to chain curves through the faces of neighboring
+/// domains, such as the chunks of a voxel grid.
+///
+/// Corners of the domain are designated using N booleans, which tell for each
+/// coordinate whether the corner is at its maximal value.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OrientationNd<const N: usize> {
+    /// Corner of the domain where the curve starts
+    start: [bool; N],
+
+    /// Axis along which the end corner differs from the start corner
+    end_axis: usize,
+}
+//
+impl<const N: usize> OrientationNd<N> {
+    /// Orientation of the curve of `decode_nd()` and `encode_nd()`, which
+    /// starts at the origin and ends along the last axis
+    pub const IDENTITY: Self = Self {
+        start: [false; N],
+        end_axis: N - 1,
+    };
+
+    /// Orientation of a curve that starts and ends at certain corners
+    ///
+    /// Returns `None` if the corners do not differ along exactly one axis,
+    /// since a Hilbert curve cannot start and end there.
+    ///
+    pub const fn from_corners(start: [bool; N], end: [bool; N]) -> Option<Self> {
+        let mut end_axis = None;
+        let mut dim = 0;
+        while dim < N {
+            if start[dim] != end[dim] {
+                if end_axis.is_some() {
+                    return None;
+                }
+                end_axis = Some(dim);
+            }
+            dim += 1;
+        }
+        match end_axis {
+            Some(end_axis) => Some(Self { start, end_axis }),
+            None => None,
+        }
+    }
+
+    /// Corner of the domain where the curve starts
+    pub const fn start(self) -> [bool; N] {
+        self.start
+    }
+
+    /// Corner of the domain where the curve ends
+    pub const fn end(self) -> [bool; N] {
+        let mut end = self.start;
+        end[self.end_axis] = !end[self.end_axis];
+        end
+    }
+
+    /// Axis along which the end corner differs from the start corner
+    pub const fn end_axis(self) -> usize {
+        self.end_axis
+    }
+
+    /// Orientation of a curve of the same order that continues this one in the
+    /// neighboring domain along a certain `axis`, towards increasing or
+    /// decreasing coordinates depending on `increasing`, and ends at the `end`
+    /// corner of that domain
+    ///
+    /// This is the N-dimensional counterpart of `Orientation::following()`.
+    /// Returns `None` if this curve does not end on the face of its domain
+    /// that leads to the neighboring domain, or if no Hilbert curve goes from
+    /// the matching start corner to `end`.
+    ///
+    /// # Panics
+    ///
+    /// If `axis` is not smaller than N.
+    ///
+    pub const fn following(self, axis: usize, increasing: bool, end: [bool; N]) -> Option<Self> {
+        assert!(axis < N, "Axis is out of range");
+        let mut start = self.end();
+        if start[axis] != increasing {
+            return None;
+        }
+        start[axis] = !increasing;
+        Self::from_corners(start, end)
+    }
+}
+
+/// Direction of a move between two neighboring points of a 2D curve
+///
+/// The x coordinate increases towards the east, and the y coordinate increases
+/// towards the north.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// Towards increasing y
+    North,
+
+    /// Towards decreasing y
+    South,
+
+    /// Towards increasing x
+    East,
+
+    /// Towards decreasing x
+    West,
+}
+
+/// Lookup table used by `decode_2d_lut()`
+///
+/// Indexed by a transform state (bit 0 tells whether coordinates are swapped,
+/// bit 1 whether they are inverted) and a byte of a curve index, each entry
+/// contains the corresponding 4 bits of the x coordinate (bits 0 to 3), of the
+/// y coordinate (bits 4 to 7) and the transform state for the next byte of the
+/// curve index (bits 8 and 9).
+///
+static DECODE_LUT: [u16; 4 * 256] = build_decode_lut();
+
+/// Generate `DECODE_LUT` at compile time
+const fn build_decode_lut() -> [u16; 4 * 256] {
+    let mut lut = [0; 4 * 256];
+    let mut byte = 0;
+    while byte < 256 {
+        // A byte is a curve index over 4 recursion depths. Decoding it with 16-bit
+        // decode_2d() gives its coordinates in the untransformed state, since the
+        // 4 zeroed high-order recursion depths each toggle coordinate swapping.
+        let [x, y] = idx16::decode_2d(byte as u16);
+
+        // Count how many times this byte toggles coordinate swapping and
+        // inversion, using the same rules as decode_2d()
+        let mut num_swaps = 0;
+        let mut num_inverts = 0;
+        let mut depth = 0;
+        while depth < 4 {
+            let digit = (byte >> (2 * depth)) & 0b11;
+            num_swaps += (digit == 0b00 || digit == 0b11) as usize;
+            num_inverts += (digit == 0b11) as usize;
+            depth += 1;
+        }
+
+        // Apply the incoming transform state, which commutes with the ones
+        // applied within this byte since they are all XORs of control bits
+        let mut state = 0;
+        while state < 4 {
+            let swap = state & 1;
+            let invert = state >> 1;
+            let [x, y] = if swap == 1 { [y, x] } else { [x, y] };
+            let mask = if invert == 1 { 0xF } else { 0 };
+            let next_state = (swap ^ (num_swaps % 2)) | ((invert ^ (num_inverts % 2)) << 1);
+            lut[(state << 8) | byte] =
+                ((x ^ mask) as u16) | (((y ^ mask) as u16) << 4) | ((next_state as u16) << 8);
+            state += 1;
+        }
+        byte += 1;
+    }
+    lut
+}
+
+/// Hilbert curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    hilbert_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Blocks2D {}
+    impl ExactSizeIterator for Iter2D {}
+    impl ExactSizeIterator for IndexedIter2D {}
+    impl ExactSizeIterator for Window2D {}
+    impl ExactSizeIterator for OrderIter2D {}
+    impl ExactSizeIterator for Steps2D {}
+}
+
+/// Hilbert curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    hilbert_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Blocks2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for IndexedIter2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Window2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for OrderIter2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Steps2D {}
+}
+
+/// Hilbert curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    hilbert_impl!(idx64, u64, u32);
+}
+
+/// Hilbert curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    hilbert_impl!(idx128, u128, u64);
+}
+
+/// Hilbert curve utilities for arbitrary-precision curve indices
+#[cfg(feature = "num-bigint")]
+mod big;
+
+#[cfg(feature = "num-bigint")]
+pub use big::{decode_nd_big, encode_nd_big};
+pub use idx128::{
+    build_table as build_table_u128, cell_2d as cell_2d_u128, cmp_2d as cmp_2d_u128,
+    decode_2d as decode_2d_u128, decode_2d_fast as decode_2d_fast_u128,
+    decode_2d_into as decode_2d_into_u128, decode_2d_into_soa as decode_2d_into_soa_u128,
+    decode_2d_lut as decode_2d_lut_u128, decode_2d_order as decode_2d_order_u128,
+    decode_2d_order_oriented as decode_2d_order_oriented_u128,
+    decode_2d_oriented as decode_2d_oriented_u128,
+    decode_2d_transformed as decode_2d_transformed_u128, decode_4d as decode_4d_u128,
+    decode_nd as decode_nd_u128, decode_nd_compact as decode_nd_compact_u128,
+    decode_nd_oriented as decode_nd_oriented_u128, direction_at as direction_at_u128,
+    encode_2d as encode_2d_u128, encode_2d_fast as encode_2d_fast_u128,
+    encode_2d_into as encode_2d_into_u128, encode_2d_into_soa as encode_2d_into_soa_u128,
+    encode_2d_order as encode_2d_order_u128,
+    encode_2d_order_oriented as encode_2d_order_oriented_u128,
+    encode_2d_oriented as encode_2d_oriented_u128,
+    encode_2d_transformed as encode_2d_transformed_u128, encode_4d as encode_4d_u128,
+    encode_bulk as encode_bulk_u128, encode_nd as encode_nd_u128,
+    encode_nd_compact as encode_nd_compact_u128, encode_nd_oriented as encode_nd_oriented_u128,
+    encode_stream as encode_stream_u128, index_distance as index_distance_u128,
+    iter_2d as iter_2d_u128, iter_2d_order as iter_2d_order_u128,
+    iter_2d_order_oriented as iter_2d_order_oriented_u128, iter_blocks_2d as iter_blocks_2d_u128,
+    iter_clipped_2d as iter_clipped_2d_u128, iter_from_2d as iter_from_2d_u128,
+    iter_masked_2d as iter_masked_2d_u128, iter_range_2d as iter_range_2d_u128,
+    iter_rect as iter_rect_u128, iter_rect_query as iter_rect_query_u128,
+    iter_window_2d as iter_window_2d_u128, join_2d as join_2d_u128,
+    neighbors_2d as neighbors_2d_u128, next_index_in_rect as next_index_in_rect_u128,
+    ranges_for_rect as ranges_for_rect_u128, steps_2d as steps_2d_u128,
+    steps_range_2d as steps_range_2d_u128, try_cell_2d as try_cell_2d_u128,
+    try_decode_2d as try_decode_2d_u128, try_decode_2d_order as try_decode_2d_order_u128,
+    try_decode_2d_order_oriented as try_decode_2d_order_oriented_u128,
+    try_encode_2d as try_encode_2d_u128, try_encode_2d_order as try_encode_2d_order_u128,
+    try_encode_2d_order_oriented as try_encode_2d_order_oriented_u128, Block2D as Block2DU128,
+    Blocks2D as Blocks2DU128, ClippedIter2D as ClippedIter2DU128,
+    IndexedIter2D as IndexedIter2DU128, Iter2D as Iter2DU128, IterRect as IterRectU128,
+    Join2D as Join2DU128, MaskedIter2D as MaskedIter2DU128, OrderIter2D as OrderIter2DU128,
+    RectQueryIter as RectQueryIterU128, RectRanges as RectRangesU128, Steps2D as Steps2DU128,
+    Window2D as Window2DU128,
+};
+#[cfg(feature = "alloc")]
+pub use idx128::{
+    decode_2d_simd as decode_2d_simd_u128, permute_in_place as permute_in_place_u128,
+    sort_by_key as sort_by_key_u128, sort_radix as sort_radix_u128,
+    unpermute_in_place as unpermute_in_place_u128,
+};
+#[cfg(feature = "alloc")]
+pub_use_default_width!(
+    decode_2d_simd,
+    permute_in_place,
+    sort_by_key,
+    sort_radix,
+    unpermute_in_place
+);
+#[cfg(feature = "rayon")]
+pub use idx128::{
+    par_blocks_2d as par_blocks_2d_u128, par_iter_2d as par_iter_2d_u128,
+    par_iter_range_2d as par_iter_range_2d_u128,
+};
+#[cfg(feature = "alloc")]
+pub use idx16::{
+    decode_2d_simd as decode_2d_simd_u16, permute_in_place as permute_in_place_u16,
+    sort_by_key as sort_by_key_u16, sort_radix as sort_radix_u16,
+    unpermute_in_place as unpermute_in_place_u16,
+};
+#[cfg(feature = "alloc")]
+pub use idx32::{
+    decode_2d_simd as decode_2d_simd_u32, permute_in_place as permute_in_place_u32,
+    sort_by_key as sort_by_key_u32, sort_radix as sort_radix_u32,
+    unpermute_in_place as unpermute_in_place_u32,
+};
+#[cfg(feature = "alloc")]
+pub use idx64::{
+    decode_2d_simd as decode_2d_simd_u64, permute_in_place as permute_in_place_u64,
+    sort_by_key as sort_by_key_u64, sort_radix as sort_radix_u64,
+    unpermute_in_place as unpermute_in_place_u64,
+};
+#[cfg(feature = "rayon")]
+pub_use_default_width!(par_blocks_2d, par_iter_2d, par_iter_range_2d);
+#[cfg(feature = "rayon")]
+pub use idx16::{
+    par_blocks_2d as par_blocks_2d_u16, par_iter_2d as par_iter_2d_u16,
+    par_iter_range_2d as par_iter_range_2d_u16,
+};
+#[cfg(feature = "rayon")]
+pub use idx32::{
+    par_blocks_2d as par_blocks_2d_u32, par_iter_2d as par_iter_2d_u32,
+    par_iter_range_2d as par_iter_range_2d_u32,
+};
+#[cfg(feature = "rayon")]
+pub use idx64::{
+    par_blocks_2d as par_blocks_2d_u64, par_iter_2d as par_iter_2d_u64,
+    par_iter_range_2d as par_iter_range_2d_u64,
+};
+pub_use_default_width!(
+    Block2D,
+    Blocks2D,
+    iter_blocks_2d,
+    ClippedIter2D,
+    Window2D,
+    Join2D,
+    MaskedIter2D,
+    IndexedIter2D,
+    Iter2D,
+    build_table,
+    cell_2d,
+    cmp_2d,
+    decode_2d,
+    decode_2d_fast,
+    decode_2d_into,
+    decode_2d_into_soa,
+    decode_2d_lut,
+    decode_2d_order,
+    decode_2d_order_oriented,
+    decode_2d_oriented,
+    decode_2d_transformed,
+    encode_2d_transformed,
+    decode_4d,
+    decode_nd,
+    decode_nd_oriented,
+    decode_nd_compact,
+    direction_at,
+    encode_2d,
+    encode_2d_fast,
+    encode_2d_into,
+    encode_2d_into_soa,
+    encode_bulk,
+    encode_stream,
+    encode_2d_order,
+    encode_2d_order_oriented,
+    encode_2d_oriented,
+    encode_4d,
+    encode_nd,
+    encode_nd_oriented,
+    encode_nd_compact,
+    iter_2d,
+    iter_2d_order,
+    iter_2d_order_oriented,
+    iter_clipped_2d,
+    iter_window_2d,
+    index_distance,
+    join_2d,
+    iter_masked_2d,
+    iter_from_2d,
+    iter_range_2d,
+    neighbors_2d,
+    next_index_in_rect,
+    iter_rect,
+    iter_rect_query,
+    ranges_for_rect,
+    steps_2d,
+    steps_range_2d,
+    try_cell_2d,
+    try_decode_2d_order,
+    try_decode_2d_order_oriented,
+    try_encode_2d_order,
+    try_encode_2d_order_oriented,
+    try_decode_2d,
+    try_encode_2d,
+    IterRect,
+    OrderIter2D,
+    RectQueryIter,
+    RectRanges,
+    Steps2D
+);
+pub use idx16::{
+    build_table as build_table_u16, cell_2d as cell_2d_u16, cmp_2d as cmp_2d_u16,
+    decode_2d as decode_2d_u16, decode_2d_fast as decode_2d_fast_u16,
+    decode_2d_into as decode_2d_into_u16, decode_2d_into_soa as decode_2d_into_soa_u16,
+    decode_2d_lut as decode_2d_lut_u16, decode_2d_order as decode_2d_order_u16,
+    decode_2d_order_oriented as decode_2d_order_oriented_u16,
+    decode_2d_oriented as decode_2d_oriented_u16,
+    decode_2d_transformed as decode_2d_transformed_u16, decode_4d as decode_4d_u16,
+    decode_nd as decode_nd_u16, decode_nd_compact as decode_nd_compact_u16,
+    decode_nd_oriented as decode_nd_oriented_u16, direction_at as direction_at_u16,
+    encode_2d as encode_2d_u16, encode_2d_fast as encode_2d_fast_u16,
+    encode_2d_into as encode_2d_into_u16, encode_2d_into_soa as encode_2d_into_soa_u16,
+    encode_2d_order as encode_2d_order_u16,
+    encode_2d_order_oriented as encode_2d_order_oriented_u16,
+    encode_2d_oriented as encode_2d_oriented_u16,
+    encode_2d_transformed as encode_2d_transformed_u16, encode_4d as encode_4d_u16,
+    encode_bulk as encode_bulk_u16, encode_nd as encode_nd_u16,
+    encode_nd_compact as encode_nd_compact_u16, encode_nd_oriented as encode_nd_oriented_u16,
+    encode_stream as encode_stream_u16, index_distance as index_distance_u16,
+    iter_2d as iter_2d_u16, iter_2d_order as iter_2d_order_u16,
+    iter_2d_order_oriented as iter_2d_order_oriented_u16, iter_blocks_2d as iter_blocks_2d_u16,
+    iter_clipped_2d as iter_clipped_2d_u16, iter_from_2d as iter_from_2d_u16,
+    iter_masked_2d as iter_masked_2d_u16, iter_range_2d as iter_range_2d_u16,
+    iter_rect as iter_rect_u16, iter_rect_query as iter_rect_query_u16,
+    iter_window_2d as iter_window_2d_u16, join_2d as join_2d_u16, neighbors_2d as neighbors_2d_u16,
+    next_index_in_rect as next_index_in_rect_u16, ranges_for_rect as ranges_for_rect_u16,
+    steps_2d as steps_2d_u16, steps_range_2d as steps_range_2d_u16, try_cell_2d as try_cell_2d_u16,
+    try_decode_2d as try_decode_2d_u16, try_decode_2d_order as try_decode_2d_order_u16,
+    try_decode_2d_order_oriented as try_decode_2d_order_oriented_u16,
+    try_encode_2d as try_encode_2d_u16, try_encode_2d_order as try_encode_2d_order_u16,
+    try_encode_2d_order_oriented as try_encode_2d_order_oriented_u16, Block2D as Block2DU16,
+    Blocks2D as Blocks2DU16, ClippedIter2D as ClippedIter2DU16, IndexedIter2D as IndexedIter2DU16,
+    Iter2D as Iter2DU16, IterRect as IterRectU16, Join2D as Join2DU16,
+    MaskedIter2D as MaskedIter2DU16, OrderIter2D as OrderIter2DU16,
+    RectQueryIter as RectQueryIterU16, RectRanges as RectRangesU16, Steps2D as Steps2DU16,
+    Window2D as Window2DU16,
+};
+pub use idx32::{
+    build_table as build_table_u32, cell_2d as cell_2d_u32, cmp_2d as cmp_2d_u32,
+    decode_2d as decode_2d_u32, decode_2d_fast as decode_2d_fast_u32,
+    decode_2d_into as decode_2d_into_u32, decode_2d_into_soa as decode_2d_into_soa_u32,
+    decode_2d_lut as decode_2d_lut_u32, decode_2d_order as decode_2d_order_u32,
+    decode_2d_order_oriented as decode_2d_order_oriented_u32,
+    decode_2d_oriented as decode_2d_oriented_u32,
+    decode_2d_transformed as decode_2d_transformed_u32, decode_4d as decode_4d_u32,
+    decode_nd as decode_nd_u32, decode_nd_compact as decode_nd_compact_u32,
+    decode_nd_oriented as decode_nd_oriented_u32, direction_at as direction_at_u32,
+    encode_2d as encode_2d_u32, encode_2d_fast as encode_2d_fast_u32,
+    encode_2d_into as encode_2d_into_u32, encode_2d_into_soa as encode_2d_into_soa_u32,
+    encode_2d_order as encode_2d_order_u32,
+    encode_2d_order_oriented as encode_2d_order_oriented_u32,
+    encode_2d_oriented as encode_2d_oriented_u32,
+    encode_2d_transformed as encode_2d_transformed_u32, encode_4d as encode_4d_u32,
+    encode_bulk as encode_bulk_u32, encode_nd as encode_nd_u32,
+    encode_nd_compact as encode_nd_compact_u32, encode_nd_oriented as encode_nd_oriented_u32,
+    encode_stream as encode_stream_u32, index_distance as index_distance_u32,
+    iter_2d as iter_2d_u32, iter_2d_order as iter_2d_order_u32,
+    iter_2d_order_oriented as iter_2d_order_oriented_u32, iter_blocks_2d as iter_blocks_2d_u32,
+    iter_clipped_2d as iter_clipped_2d_u32, iter_from_2d as iter_from_2d_u32,
+    iter_masked_2d as iter_masked_2d_u32, iter_range_2d as iter_range_2d_u32,
+    iter_rect as iter_rect_u32, iter_rect_query as iter_rect_query_u32,
+    iter_window_2d as iter_window_2d_u32, join_2d as join_2d_u32, neighbors_2d as neighbors_2d_u32,
+    next_index_in_rect as next_index_in_rect_u32, ranges_for_rect as ranges_for_rect_u32,
+    steps_2d as steps_2d_u32, steps_range_2d as steps_range_2d_u32, try_cell_2d as try_cell_2d_u32,
+    try_decode_2d as try_decode_2d_u32, try_decode_2d_order as try_decode_2d_order_u32,
+    try_decode_2d_order_oriented as try_decode_2d_order_oriented_u32,
+    try_encode_2d as try_encode_2d_u32, try_encode_2d_order as try_encode_2d_order_u32,
+    try_encode_2d_order_oriented as try_encode_2d_order_oriented_u32, Block2D as Block2DU32,
+    Blocks2D as Blocks2DU32, ClippedIter2D as ClippedIter2DU32, IndexedIter2D as IndexedIter2DU32,
+    Iter2D as Iter2DU32, IterRect as IterRectU32, Join2D as Join2DU32,
+    MaskedIter2D as MaskedIter2DU32, OrderIter2D as OrderIter2DU32,
+    RectQueryIter as RectQueryIterU32, RectRanges as RectRangesU32, Steps2D as Steps2DU32,
+    Window2D as Window2DU32,
+};
+pub use idx64::{
+    build_table as build_table_u64, cell_2d as cell_2d_u64, cmp_2d as cmp_2d_u64,
+    decode_2d as decode_2d_u64, decode_2d_fast as decode_2d_fast_u64,
+    decode_2d_into as decode_2d_into_u64, decode_2d_into_soa as decode_2d_into_soa_u64,
+    decode_2d_lut as decode_2d_lut_u64, decode_2d_order as decode_2d_order_u64,
+    decode_2d_order_oriented as decode_2d_order_oriented_u64,
+    decode_2d_oriented as decode_2d_oriented_u64,
+    decode_2d_transformed as decode_2d_transformed_u64, decode_4d as decode_4d_u64,
+    decode_nd as decode_nd_u64, decode_nd_compact as decode_nd_compact_u64,
+    decode_nd_oriented as decode_nd_oriented_u64, direction_at as direction_at_u64,
+    encode_2d as encode_2d_u64, encode_2d_fast as encode_2d_fast_u64,
+    encode_2d_into as encode_2d_into_u64, encode_2d_into_soa as encode_2d_into_soa_u64,
+    encode_2d_order as encode_2d_order_u64,
+    encode_2d_order_oriented as encode_2d_order_oriented_u64,
+    encode_2d_oriented as encode_2d_oriented_u64,
+    encode_2d_transformed as encode_2d_transformed_u64, encode_4d as encode_4d_u64,
+    encode_bulk as encode_bulk_u64, encode_nd as encode_nd_u64,
+    encode_nd_compact as encode_nd_compact_u64, encode_nd_oriented as encode_nd_oriented_u64,
+    encode_stream as encode_stream_u64, index_distance as index_distance_u64,
+    iter_2d as iter_2d_u64, iter_2d_order as iter_2d_order_u64,
+    iter_2d_order_oriented as iter_2d_order_oriented_u64, iter_blocks_2d as iter_blocks_2d_u64,
+    iter_clipped_2d as iter_clipped_2d_u64, iter_from_2d as iter_from_2d_u64,
+    iter_masked_2d as iter_masked_2d_u64, iter_range_2d as iter_range_2d_u64,
+    iter_rect as iter_rect_u64, iter_rect_query as iter_rect_query_u64,
+    iter_window_2d as iter_window_2d_u64, join_2d as join_2d_u64, neighbors_2d as neighbors_2d_u64,
+    next_index_in_rect as next_index_in_rect_u64, ranges_for_rect as ranges_for_rect_u64,
+    steps_2d as steps_2d_u64, steps_range_2d as steps_range_2d_u64, try_cell_2d as try_cell_2d_u64,
+    try_decode_2d as try_decode_2d_u64, try_decode_2d_order as try_decode_2d_order_u64,
+    try_decode_2d_order_oriented as try_decode_2d_order_oriented_u64,
+    try_encode_2d as try_encode_2d_u64, try_encode_2d_order as try_encode_2d_order_u64,
+    try_encode_2d_order_oriented as try_encode_2d_order_oriented_u64, Block2D as Block2DU64,
+    Blocks2D as Blocks2DU64, ClippedIter2D as ClippedIter2DU64, IndexedIter2D as IndexedIter2DU64,
+    Iter2D as Iter2DU64, IterRect as IterRectU64, Join2D as Join2DU64,
+    MaskedIter2D as MaskedIter2DU64, OrderIter2D as OrderIter2DU64,
+    RectQueryIter as RectQueryIterU64, RectRanges as RectRangesU64, Steps2D as Steps2DU64,
+    Window2D as Window2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{bits, Coordinate, Coordinates2D, CurveIdx, Error, MaskAction};
+    use bits::test_utils::*;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    // Tests that would check every curve index check a sample of them with
+    // wider curve index types, and tests that go through the whole curve only
+    // run with the default 16-bit curve indices. Wider curve index types are
+    // also tested using random inputs, see wide_tests below.
+
+    #[test]
+    fn decode_2d() {
+        for input in sampled_indices() {
+            let mut input_buf = input.reverse_bits();
+            let mut results = [0; 2];
+            let mut swap = false;
+            let mut invert = false;
+            for _bit_idx in 0..(bits::num_bits::<Coordinate>()) {
+                let high_order_bit = pop_bit(&mut input_buf);
+                let low_order_bit = pop_bit(&mut input_buf);
+                let mut x_bit = high_order_bit ^ low_order_bit;
+                let mut y_bit = high_order_bit;
+                if swap {
+                    core::mem::swap(&mut x_bit, &mut y_bit);
+                }
+                push_bit(&mut results[0], x_bit ^ invert);
+                push_bit(&mut results[1], y_bit ^ invert);
+                swap ^= !(high_order_bit ^ low_order_bit);
+                invert ^= high_order_bit & low_order_bit;
+            }
+            assert_eq!(
+                super::decode_2d(input),
+                results,
+                "Unexpected 2D Hilbert code decoding result for input {input:08b}"
+            );
+        }
+    }
+
+    #[test]
+    fn oriented_2d() {
+        let corners = [[false, false], [true, false], [false, true], [true, true]];
+        let mut orientations = Vec::new();
+        for start in corners {
+            for end in corners {
+                let orientation = super::Orientation::from_corners(start, end);
+                let is_side = (start[0] != end[0]) != (start[1] != end[1]);
+                assert_eq!(orientation.is_some(), is_side);
+                if let Some(orientation) = orientation {
+                    assert_eq!(orientation.start(), start);
+                    assert_eq!(orientation.end(), end);
+                    orientations.push(orientation);
+                }
+            }
+        }
+        assert_eq!(orientations.len(), 8);
+        assert_eq!(
+            super::Orientation::from_corners([false, false], [false, true]),
+            Some(super::Orientation::IDENTITY)
+        );
+
+        let corner = |[x, y]: [bool; 2]| {
+            let coord = |high| if high { Coordinate::MAX } else { 0 };
+            [coord(x), coord(y)]
+        };
+        for orientation in orientations {
+            assert_eq!(
+                super::decode_2d_oriented(CurveIdx::MIN, orientation),
+                corner(orientation.start())
+            );
+            assert_eq!(
+                super::decode_2d_oriented(CurveIdx::MAX, orientation),
+                corner(orientation.end())
+            );
+            for code in sampled_indices() {
+                let [x, y] = super::decode_2d_oriented(code, orientation);
+                assert_eq!(super::encode_2d_oriented([x, y], orientation), code);
+                if code < CurveIdx::MAX {
+                    let [next_x, next_y] = super::decode_2d_oriented(code + 1, orientation);
+                    assert_eq!(
+                        (x as i32 - next_x as i32).abs() + (y as i32 - next_y as i32).abs(),
+                        1
+                    );
+                }
+            }
+        }
+    }
+
+    // Check that iter_rect() visits every cell of the rectangle once, moving to
+    // a direct neighbor at every step
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn check_iter_rect(width: Coordinate, height: Coordinate) {
+        let iter = super::iter_rect(width, height);
+        let len = width as usize * height as usize;
+        assert_eq!(iter.size_hint(), (len, Some(len)));
+        let mut visited = vec![false; len];
+        let mut last: Option<Coordinates2D> = None;
+        for [x, y] in iter {
+            assert!(x < width && y < height);
+            let point_idx = y as usize * width as usize + x as usize;
+            assert!(!visited[point_idx], "Point {:?} was visited twice", [x, y]);
+            visited[point_idx] = true;
+            if let Some([last_x, last_y]) = last {
+                assert_eq!(
+                    (x as i32 - last_x as i32).abs() + (y as i32 - last_y as i32).abs(),
+                    1,
+                    "Iteration jumped from {:?} to {:?} in a {width}x{height} rectangle",
+                    [last_x, last_y],
+                    [x, y]
+                );
+            }
+            last = Some([x, y]);
+        }
+        assert!(visited.into_iter().all(|visited| visited));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_rect() {
+        assert_eq!(super::iter_rect(0, 42).next(), None);
+        assert_eq!(super::iter_rect(42, 0).next(), None);
+        for width in 1..=40 {
+            for height in 1..=40 {
+                check_iter_rect(width, height);
+            }
+        }
+        for (width, height) in [
+            (Coordinate::MAX, Coordinate::MAX),
+            (Coordinate::MAX, Coordinate::MAX - 1),
+            (Coordinate::MAX - 1, Coordinate::MAX),
+            (Coordinate::MAX, 1),
+            (3, Coordinate::MAX),
+        ] {
+            check_iter_rect(width, height);
+        }
+    }
+
+    #[test]
+    fn fast() {
+        for input in sampled_indices() {
+            let coords = super::decode_2d(input);
+            assert_eq!(super::decode_2d_fast(input), coords);
+            assert_eq!(super::encode_2d_fast(coords), input);
+        }
+    }
+
+    #[test]
+    fn build_table() {
+        const TABLE: [Coordinates2D; 1000] = super::build_table();
+        assert!(TABLE.iter().copied().eq(super::iter_2d().take(1000)));
+        let full_table = super::build_table_u16::<65536>();
+        assert!(full_table.iter().copied().eq(super::iter_2d_u16()));
+        assert!(super::build_table::<0>().is_empty());
+    }
+
+    #[test]
+    fn lut() {
+        for input in sampled_indices() {
+            assert_eq!(super::decode_2d_lut(input), super::decode_2d(input));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn simd() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let coords = super::decode_2d_simd(&codes);
+        assert!(codes
+            .into_iter()
+            .zip(coords)
+            .all(|(code, coords)| coords == super::decode_2d(code)));
+    }
+
+    #[test]
+    fn into() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let mut coords = vec![[0; 2]; codes.len()];
+        super::decode_2d_into(&codes, &mut coords);
+        assert!(codes
+            .iter()
+            .zip(&coords)
+            .all(|(&code, &coords)| coords == super::decode_2d(code)));
+        let mut codes2 = vec![0; coords.len()];
+        super::encode_2d_into(&coords, &mut codes2);
+        assert_eq!(codes2, codes);
+    }
+
+    #[test]
+    fn into_soa() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let mut xs = vec![0; codes.len()];
+        let mut ys = vec![0; codes.len()];
+        super::decode_2d_into_soa(&codes, &mut xs, &mut ys);
+        assert!(codes
+            .iter()
+            .zip(xs.iter().zip(&ys))
+            .all(|(&code, (&x, &y))| super::decode_2d(code) == [x, y]));
+        let mut codes2 = vec![0; codes.len()];
+        super::encode_2d_into_soa(&xs, &ys, &mut codes2);
+        assert_eq!(codes2, codes);
+    }
+
+    #[test]
+    fn bulk() {
+        let coords = sampled_indices().map(super::decode_2d).collect::<Vec<_>>();
+        let mut codes = vec![0; coords.len()];
+        super::encode_bulk(&coords, &mut codes);
+        assert!(codes.iter().copied().eq(sampled_indices()));
+        let mut streamed = Vec::new();
+        super::encode_stream(coords.iter().copied(), |chunk| {
+            assert!(!chunk.is_empty());
+            streamed.extend_from_slice(chunk)
+        });
+        assert_eq!(streamed, codes);
+        super::encode_stream(core::iter::empty(), |_| unreachable!());
+    }
+
+    #[test]
+    #[should_panic]
+    fn into_length_mismatch() {
+        super::decode_2d_into(&[0; 3], &mut [[0; 2]; 2]);
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn decode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            CurveIdx::MIN,
+            || CurveIdx::arbitrary(&mut gen),
+            super::decode_2d,
+        );
+    }
+
+    #[test]
+    fn encode_2d() {
+        for input in sampled_indices() {
+            let coords = super::decode_2d(input);
+            assert_eq!(
+                super::encode_2d(coords),
+                input,
+                "Unexpected 2D Hilbert code encoding result for input {coords:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn cmp_2d() {
+        let mut gen = Gen::new(usize::MAX);
+        for _ in 0..100_000 {
+            let a = <[Coordinate; 2]>::arbitrary(&mut gen);
+            let b = <[Coordinate; 2]>::arbitrary(&mut gen);
+            assert_eq!(
+                super::cmp_2d(a, b),
+                super::encode_2d(a).cmp(&super::encode_2d(b)),
+                "Unexpected comparison result for {a:?} and {b:?}"
+            );
+        }
+        for idx in sampled_indices().filter(|&idx| idx != CurveIdx::MAX) {
+            let [a, b] = [super::decode_2d(idx), super::decode_2d(idx + 1)];
+            assert_eq!(super::cmp_2d(a, b), core::cmp::Ordering::Less);
+            assert_eq!(super::cmp_2d(b, a), core::cmp::Ordering::Greater);
+            assert_eq!(super::cmp_2d(a, a), core::cmp::Ordering::Equal);
+        }
+    }
 
-/// Compute the coordinate of the i-th point of a ]-shaped Hilbert curve
-///
-/// Compared to the Morton curve, the Hilbert curve never jumps across space, it
-/// always moves from one point of space to one of its direct neighbors. The
-/// price to pay for this superior spatial locality is that it follows a more
-/// complex geometrical pattern (that, logically, requires more complex
-/// computations), based on recursively flipped C-like shapes.
-///
-/// There are technically 4 C-like shapes that one could start from. Here we use
-/// a vertically flipped C shape (]) instead of the U shape that is more
-/// commonly seen in literature, because an extension to N dimensions and
-/// non-square domains has been performed in the "Compact Hilbert Indices"
-/// paper by Chris Hamilton (ref: CS-2006-07) for this particular variation of
-/// the Hilbert curve, and a ] is just a coordinate transpose away from a U.
-///
-/// The first 256 iterations of this Hilbert curve look like this:
-///
-/// ┬┌─┐┌─┐┌─┐┌─┐┌─┐
-/// └┘┌┘└┐└┘┌┘└┐└┘┌┘
-/// ┌┐└┐┌┘┌┐│┌┐│┌┐└┐
-/// │└─┘└─┘│└┘└┘│└─┘
-/// └┐┌──┐┌┘┌┐┌┐│┌─┐
-/// ┌┘└┐┌┘└┐│└┘│└┘┌┘
-/// │┌┐││┌┐│└┐┌┘┌┐└┐
-/// └┘└┘└┘└┘┌┘└─┘└─┘
-/// ┌┐┌┐┌┐┌┐└┐┌─┐┌─┐
-/// │└┘││└┘│┌┘└┐└┘┌┘
-/// └┐┌┘└┐┌┘│┌┐│┌┐└┐
-/// ┌┘└──┘└┐└┘└┘│└─┘
-/// │┌─┐┌─┐│┌┐┌┐│┌─┐
-/// └┘┌┘└┐└┘│└┘│└┘┌┘
-/// ┌┐└┐┌┘┌┐└┐┌┘┌┐└┐
-/// v└─┘└─┘└─┘└─┘└─┘
-///
-#[inline]
-pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
-    // Here's the mathematical derivation of this algorithm.
-    //
-    // ---
-    //
-    // Remember that we took this shape as our basic pattern:
-    //
-    // ├┐
-    // <┘
-    //
-    // This means that...
-    //
-    // * On iteration 0, (x, y) is (0, 0)
-    // * On iteration 1, (x, y) is (1, 0)
-    // * On iteration 2, (x, y) is (1, 1)
-    // * On iteration 3, (x, y) is (0, 1)
-    //
-    // So if we denote ij the binary digits of the iteration number, we have...
-    //
-    // * x = i XOR j
-    // * y = i
-    //
-    // ...which happens to be the Gray code associated with the 2D Morton code
-    // yx. This is totally not a coincidence, it simplifies extension to higher
-    // dimensions, where both the Gray and Morton code are defined...
-    //
-    // Now, if we were to turn this basic shape into a fractal without extra
-    // precautions, we would still get jumps from some sub-patterns to the next:
-    //
-    // 0┐1┐
-    // <┘┌┘
-    // 3┐2┐
-    // <┘<┘
-    //
-    // To avoid this, we need to transform the sub-patterns, which we can do
-    // without changing the curve's endpoints by swapping the coordinates of
-    // sub-pattern 0...
-    //
-    // 0┌1┐
-    // └┘┌┘
-    // 3┐2┐
-    // <┘<┘
-    //
-    // ...and swapping and inverting the coordinates of sub-pattern 3:
-    //
-    // 0┌1┐
-    // └┘┌┘
-    // ┌┐2┐
-    // v3─┘
-    //
-    // Let's translate those transformations into binary arithmetic:
-    //
-    // - If (i XOR j) is 0, we need to swap the coordinates of our sub-pattern
-    // - If (i AND j) is 1, we need to invert the coordinates of our sub-pattern
-    //   * In binary, this can be done by NOT-ing x and y when (i AND j) is 1...
-    //   * ...which we can do without testing the value of i and j by XORing x
-    //     and y with (i AND j).
-    //
-    // With that, we get the first layer of fractal recursion, but then we must
-    // recursively apply the same recursion rules to our transformed patterns at
-    // the next level of recursion:
-    //
-    // 0┐┌─1┌─┐
-    // ┌┘└┐└┘┌┘
-    // │┌┐│┌┐└┐
-    // └┘└┘│└─┘
-    // ┌┐┌┐2┌─┐
-    // │└┘│└┘┌┘
-    // └┐┌┘┌┐└┐
-    // <┘└3┘└─┘
-    //
-    // It so happens, however, that the transforms applied above are their own
-    // inverse: swapping coordinates twice gives back the original coordinates,
-    // and inverting coordinates twice gives back the original coordinates.
-    //
-    // Therefore, if for every level of recursion, we can compute a bit b that
-    // controls whether a certain transform is applied when going to the next
-    // level of recursion, the truth that we need to apply that transform at a
-    // given recursion depth is given by the XOR of those control bits at all
-    // previous recursion depths.
-    //
-    // ---
-    //
-    // Now, with that in mind, let's make the observation that when written out
-    // in binary, the index of a point on the curve is [ i1 j1 i2 j2 ... iN jN ]
-    // where (ix, jx) controls how the pattern is followed at recursion depth x.
-    //
-    // This looks very much like a 2D Morton code, and we can use a 2D Morton
-    // code decoder to separate that index into two integers with bits
-    // [ j1 j2 ... jN ] and [ i1 i2 ... iN ].
-    //
-    debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
-    let [low_order, high_order] = morton::decode_2d(code);
-
-    // From that, we can compute the binary combinations of i-s and j-s that we
-    // need at every depth in order to move through the curve's basic ]-shaped
-    // pattern and recurse to the next depth.
-    //
-    let and_bits = low_order & high_order; // Controls coordinate inversion
-    let xor_bits = low_order ^ high_order; // Basic pattern's x coordinate
-    let not_xor_bits = !(xor_bits); // Controls coordinate swapping
-
-    // Then we can compute whether coordinates should be swapped or inverted
-    // at every depth by computing the XOR of the recursive swapping/conversion
-    // bits at every previous depth. This is most efficiently done by using a
-    // bitwise version of the parallel scan algorithm.
-    //
-    let coord_swap_bits = bits::bitwise_xor_ltr_exclusive_scan(not_xor_bits);
-    let coord_not_bits = bits::bitwise_xor_ltr_exclusive_scan(and_bits);
-
-    // Finally, we start from the top-level Gray code coordinates, transform
-    // every bit through coordinate swapping and inversion as appropriate, and
-    // we get integer words whose bits are the coordinate on the Hilbert curve
-    // at increasing recursion depths, which is what we want.
-    //
-    let [coord1, coord2] = bits::bitwise_swaps(coord_swap_bits, xor_bits, high_order);
-    [coord1 ^ coord_not_bits, coord2 ^ coord_not_bits]
-}
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn encode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            [Coordinate::MIN; 2],
+            || Coordinates2D::arbitrary(&mut gen),
+            super::encode_2d,
+        );
+    }
 
-// TODO: Study if there's a faster way to iterate over the 2D Hilbert curve than
-//       by repeatedly decoding increasing Hilbert curve indices
+    #[test]
+    fn try_encode_2d() {
+        for domain_bits in 0..=Coordinate::BITS {
+            let last = bits::low_order_mask(2 * domain_bits);
+            for code in sampled_indices() {
+                let coords = super::decode_2d(code);
+                let inside = code <= last;
+                assert_eq!(
+                    super::try_decode_2d(code, domain_bits),
+                    Some(coords)
+                        .filter(|_| inside)
+                        .ok_or(Error::IndexOutOfDomain)
+                );
+                assert_eq!(
+                    super::try_encode_2d(coords, domain_bits),
+                    Some(code)
+                        .filter(|_| inside)
+                        .ok_or(Error::CoordinateOutOfDomain)
+                );
+            }
+        }
+        assert_eq!(
+            super::try_encode_2d([0, 0], Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+        assert_eq!(
+            super::try_decode_2d(0, Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::Coordinate;
-    use bits::test_utils::*;
+    #[test]
+    fn decode_2d_order() {
+        for order in 0..=MAX_TESTED_ORDER {
+            let last = (1 as CurveIdx)
+                .checked_shl(2 * order)
+                .map_or(CurveIdx::MAX, |num_points| num_points - 1);
+            let swap = (Coordinate::BITS - order) % 2 == 1;
+            let mut iter = super::iter_2d_order(order);
+            for code in 0..=last {
+                let [x, y] = super::decode_2d(code);
+                let coords = if swap { [y, x] } else { [x, y] };
+                assert_eq!(super::decode_2d_order(code, order), coords);
+                assert_eq!(super::encode_2d_order(coords, order), code);
+                assert_eq!(super::try_decode_2d_order(code, order), Ok(coords));
+                assert_eq!(super::try_encode_2d_order(coords, order), Ok(code));
+                assert_eq!(iter.next(), Some(coords));
+            }
+            assert_eq!(iter.next(), None);
+            if order < Coordinate::BITS {
+                assert_eq!(
+                    super::try_decode_2d_order(last + 1, order),
+                    Err(Error::IndexOutOfDomain)
+                );
+                assert_eq!(
+                    super::try_encode_2d_order([0, 1 << order], order),
+                    Err(Error::CoordinateOutOfDomain)
+                );
+            }
+
+            // Every order has the same ]-shaped orientation
+            let max_coord = ((1u128 << order) - 1) as Coordinate;
+            assert_eq!(super::decode_2d_order(0, order), [0, 0]);
+            assert_eq!(super::decode_2d_order(last, order), [0, max_coord]);
+            assert!(super::iter_2d_order(order).rev().eq((0..=last)
+                .rev()
+                .map(|code| super::decode_2d_order(code, order))));
+        }
+        assert_eq!(
+            super::try_decode_2d_order(0, Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+        assert_eq!(
+            super::try_encode_2d_order([0, 0], Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+    }
 
     #[test]
-    fn decode_2d() {
-        for input in 0..=CurveIdx::MAX {
-            let mut input_buf = input.reverse_bits();
-            let mut results = [0; 2];
-            let mut swap = false;
-            let mut invert = false;
-            for _bit_idx in 0..(bits::num_bits::<Coordinate>()) {
-                let high_order_bit = pop_bit(&mut input_buf);
-                let low_order_bit = pop_bit(&mut input_buf);
-                let mut x_bit = high_order_bit ^ low_order_bit;
-                let mut y_bit = high_order_bit;
-                if swap {
-                    core::mem::swap(&mut x_bit, &mut y_bit);
+    #[should_panic]
+    fn decode_2d_order_out_of_range() {
+        super::decode_2d_order(4, 1);
+    }
+
+    #[test]
+    fn following() {
+        let corners = [[false, false], [true, false], [false, true], [true, true]];
+        let directions = [
+            (super::Direction::North, [0, 1]),
+            (super::Direction::South, [0, -1]),
+            (super::Direction::East, [1, 0]),
+            (super::Direction::West, [-1, 0]),
+        ];
+        for order in [1, 2, Coordinate::BITS] {
+            let side = 1i128 << order;
+            let last = bits::low_order_mask(2 * order);
+            for start in corners {
+                for end in corners {
+                    let orientation = match super::Orientation::from_corners(start, end) {
+                        Some(orientation) => orientation,
+                        None => continue,
+                    };
+                    for (direction, [dx, dy]) in directions {
+                        let [last_x, last_y] =
+                            super::decode_2d_order_oriented(last, order, orientation);
+                        let faces_direction = match direction {
+                            super::Direction::North => end[1],
+                            super::Direction::South => !end[1],
+                            super::Direction::East => end[0],
+                            super::Direction::West => !end[0],
+                        };
+                        let num_following = corners
+                            .iter()
+                            .filter(|&&next_end| {
+                                orientation.following(direction, next_end).is_some()
+                            })
+                            .count();
+                        assert_eq!(num_following, if faces_direction { 2 } else { 0 });
+                        for next_end in corners {
+                            let next = match orientation.following(direction, next_end) {
+                                Some(next) => next,
+                                None => continue,
+                            };
+                            assert_eq!(next.end(), next_end);
+
+                            // The first point of the next tile, in the coordinates
+                            // of this tile, is next to the last point of this tile
+                            let [first_x, first_y] =
+                                super::decode_2d_order_oriented(0, order, next);
+                            assert_eq!(
+                                [
+                                    i128::from(first_x) + dx * side,
+                                    i128::from(first_y) + dy * side
+                                ],
+                                [i128::from(last_x) + dx, i128::from(last_y) + dy]
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            super::Orientation::IDENTITY.following(super::Direction::North, [true, false]),
+            super::Orientation::from_corners([false, false], [true, false])
+        );
+        assert_eq!(
+            super::Orientation::IDENTITY.following(super::Direction::East, [true, false]),
+            None
+        );
+    }
+
+    #[test]
+    fn decode_2d_transformed() {
+        for order in 0..=MAX_TESTED_ORDER {
+            let last = (1 as CurveIdx)
+                .checked_shl(2 * order)
+                .map_or(CurveIdx::MAX, |num_points| num_points - 1);
+            for transform in crate::Transform2D::ALL {
+                for code in 0..=last {
+                    let coords = transform.apply(super::decode_2d_order(code, order), order);
+                    assert_eq!(super::decode_2d_transformed(code, order, transform), coords);
+                    assert_eq!(super::encode_2d_transformed(coords, order, transform), code);
                 }
-                push_bit(&mut results[0], x_bit ^ invert);
-                push_bit(&mut results[1], y_bit ^ invert);
-                swap ^= !(high_order_bit ^ low_order_bit);
-                invert ^= high_order_bit & low_order_bit;
             }
+        }
+    }
+
+    #[test]
+    fn decode_2d_order_oriented() {
+        for order in 0..=MAX_TESTED_ORDER {
+            let last = (1 as CurveIdx)
+                .checked_shl(2 * order)
+                .map_or(CurveIdx::MAX, |num_points| num_points - 1);
+            let max = ((1u128 << order) - 1) as Coordinate;
+            for &swap in &[false, true] {
+                for &mirror_x in &[false, true] {
+                    for &mirror_y in &[false, true] {
+                        let orientation = super::Orientation {
+                            swap,
+                            mirror_x,
+                            mirror_y,
+                        };
+                        let mut iter = super::iter_2d_order_oriented(order, orientation);
+                        for code in 0..=last {
+                            let [x, y] = super::decode_2d_order(code, order);
+                            let [x, y] = if swap { [y, x] } else { [x, y] };
+                            let x = if mirror_x { max - x } else { x };
+                            let y = if mirror_y { max - y } else { y };
+                            assert_eq!(
+                                super::decode_2d_order_oriented(code, order, orientation),
+                                [x, y]
+                            );
+                            assert_eq!(
+                                super::encode_2d_order_oriented([x, y], order, orientation),
+                                code
+                            );
+                            assert_eq!(iter.next(), Some([x, y]));
+                        }
+                        assert_eq!(iter.next(), None);
+                        if order < Coordinate::BITS {
+                            assert_eq!(
+                                super::try_decode_2d_order_oriented(last + 1, order, orientation),
+                                Err(Error::IndexOutOfDomain)
+                            );
+                            assert_eq!(
+                                super::try_encode_2d_order_oriented(
+                                    [1 << order, 0],
+                                    order,
+                                    orientation
+                                ),
+                                Err(Error::CoordinateOutOfDomain)
+                            );
+                        }
+                    }
+                }
+            }
+
+            // The transposed curve goes along the x axis
+            let transposed = super::Orientation {
+                swap: true,
+                ..super::Orientation::IDENTITY
+            };
             assert_eq!(
-                super::decode_2d(input),
-                results,
-                "Unexpected 2D Hilbert code decoding result for input {input:08b}"
+                super::decode_2d_order_oriented(last, order, transposed),
+                [max, 0]
+            );
+        }
+        assert_eq!(
+            super::try_encode_2d_order_oriented(
+                [0, 0],
+                Coordinate::BITS + 1,
+                super::Orientation::IDENTITY
+            ),
+            Err(Error::UnsupportedOrder)
+        );
+    }
+
+    #[test]
+    fn cell_2d() {
+        for level in 0..=Coordinate::BITS {
+            let side_bits = Coordinate::BITS - level;
+            let mask = bits::low_order_mask(2 * side_bits);
+            for code in sampled_indices() {
+                let ([x, y], side) = super::cell_2d(code, level);
+                assert_eq!(side, 1 << side_bits);
+                assert_eq!((x | y) as CurveIdx % side, 0);
+                let [px, py] = super::decode_2d(code);
+                assert!(((px - x) as CurveIdx) < side && ((py - y) as CurveIdx) < side);
+                assert_eq!(super::cell_2d(code & !mask, level), ([x, y], side));
+                assert_eq!(super::cell_2d(code | mask, level), ([x, y], side));
+                assert_eq!(super::try_cell_2d(code, level), Ok(([x, y], side)));
+            }
+        }
+        assert_eq!(
+            super::try_cell_2d(0, Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_2d_too_deep() {
+        super::cell_2d(0, Coordinate::BITS + 1);
+    }
+
+    #[test]
+    fn iter_blocks_2d() {
+        use super::idx16::iter_blocks_2d;
+        let coord_bits = u8::BITS;
+        for block_bits in 0..=coord_bits {
+            let side = 1u32 << block_bits;
+            let blocks = iter_blocks_2d(block_bits);
+            assert_eq!(blocks.len(), 1 << (2 * (coord_bits - block_bits)));
+            let mut next_idx = 0u32;
+            for block in blocks {
+                assert_eq!(u32::from(*block.indices.start()), next_idx);
+                next_idx += side * side;
+                assert_eq!(u32::from(*block.indices.end()), next_idx - 1);
+                assert_eq!(block.min.map(|c| u32::from(c) % side), [0, 0]);
+                assert_eq!(
+                    block.max.map(u32::from),
+                    block.min.map(|c| u32::from(c) + side - 1)
+                );
+                assert!(block.points().all(|[x, y]| {
+                    (block.min[0]..=block.max[0]).contains(&x)
+                        && (block.min[1]..=block.max[1]).contains(&y)
+                }));
+            }
+            assert_eq!(next_idx, 1 << 16);
+        }
+        assert_eq!(
+            iter_blocks_2d(2).nth_back(1),
+            iter_blocks_2d(2).nth((1 << 12) - 2)
+        );
+    }
+
+    #[test]
+    fn neighbors_2d() {
+        for code in sampled_indices() {
+            let [x, y] = super::decode_2d(code);
+            let exclude = super::neighbors_2d(code, crate::Boundary::Exclude);
+            let clamp = super::neighbors_2d(code, crate::Boundary::Clamp);
+            let wrap = super::neighbors_2d(code, crate::Boundary::Wrap);
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    let [x_wrap, y_wrap] = [
+                        x.wrapping_add(dx as Coordinate).wrapping_sub(1),
+                        y.wrapping_add(dy as Coordinate).wrapping_sub(1),
+                    ];
+                    let wrapped = super::encode_2d([x_wrap, y_wrap]);
+                    assert_eq!(wrap[dy][dx], Some(wrapped));
+                    let in_domain = (x_wrap as i128 - x as i128).abs() <= 1
+                        && (y_wrap as i128 - y as i128).abs() <= 1;
+                    assert_eq!(exclude[dy][dx], Some(wrapped).filter(|_| in_domain));
+                    let [x_clamp, y_clamp] = [
+                        (x as i128 + dx as i128 - 1).clamp(0, Coordinate::MAX.into()),
+                        (y as i128 + dy as i128 - 1).clamp(0, Coordinate::MAX.into()),
+                    ];
+                    assert_eq!(
+                        clamp[dy][dx],
+                        Some(super::encode_2d([
+                            x_clamp as Coordinate,
+                            y_clamp as Coordinate
+                        ]))
+                    );
+                }
+            }
+            assert_eq!(exclude[1][1], Some(code));
+        }
+    }
+
+    #[test]
+    fn direction_at() {
+        use super::Direction;
+        for code in sampled_indices() {
+            let [x, y] = super::decode_2d(code);
+            let expected = (code != CurveIdx::MAX).then(|| {
+                let [next_x, next_y] = super::decode_2d(code + 1);
+                match [next_x as i128 - x as i128, next_y as i128 - y as i128] {
+                    [0, 1] => Direction::North,
+                    [0, -1] => Direction::South,
+                    [1, 0] => Direction::East,
+                    [-1, 0] => Direction::West,
+                    _ => unreachable!("Hilbert curve moves by single-coordinate steps"),
+                }
+            });
+            assert_eq!(super::direction_at(code), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn steps_2d() {
+        assert!(
+            super::steps_2d().eq((0..CurveIdx::MAX).map(|code| super::direction_at(code).unwrap()))
+        );
+        assert_eq!(super::idx16::steps_2d().len(), usize::from(u16::MAX));
+        assert!(super::steps_2d().rev().eq((0..CurveIdx::MAX)
+            .rev()
+            .map(|code| super::direction_at(code).unwrap())));
+        assert!(super::steps_range_2d(10..20)
+            .eq((10..20).map(|code| super::direction_at(code).unwrap())));
+        assert_eq!(super::steps_range_2d(CurveIdx::MAX..).next(), None);
+        assert_eq!(super::steps_range_2d(..0).next(), None);
+        assert_eq!(super::steps_2d().nth(42), super::direction_at(42));
+    }
+
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    mod iter_from_2d {
+        use super::*;
+        use quickcheck::quickcheck;
+
+        // This test really takes a long while to run in debug mode...
+        #[test]
+        #[ignore]
+        fn exhaustive() {
+            for start in CurveIdx::MIN..=CurveIdx::MAX {
+                test(super::super::iter_from_2d(start), start);
+            }
+        }
+
+        // ...instead, random testing should be good enough for most purposes
+        quickcheck! {
+            fn quick(start: CurveIdx) -> bool {
+                test(super::super::iter_from_2d(start), start);
+                true
+            }
+        }
+
+        // Whichever way you probe the parameter space, for each set of
+        // parameters, we perform the following check:
+        pub fn test(iter: impl Iterator<Item = Coordinates2D>, start: CurveIdx) {
+            let mut num_items = 0;
+            for (iter, (coords, idx)) in iter.zip(start..=CurveIdx::MAX).enumerate() {
+                assert_eq!(
+                    coords,
+                    super::super::decode_2d(idx),
+                    "Unexpected 2D Hilbert curve iterator output at iteration {iter}"
+                );
+                num_items += 1;
+            }
+            assert_eq!(num_items, CurveIdx::MAX as usize - start as usize + 1);
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_2d() {
+        iter_from_2d::test(super::iter_2d(), CurveIdx::MIN);
+    }
+
+    #[test]
+    fn iter_2d_len() {
+        let mut iter = super::idx16::iter_from_2d(42);
+        assert_eq!(iter.len(), usize::from(u16::MAX) - 41);
+        iter.next_back();
+        let copy = iter.clone();
+        assert_eq!(iter.by_ref().count(), copy.len());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_clipped_2d() {
+        for (width, height) in [
+            (0, 0),
+            (0, 42),
+            (1, 1),
+            (3, 200),
+            (100, 37),
+            (128, 128),
+            (Coordinate::MAX, 1),
+            (Coordinate::MAX, Coordinate::MAX),
+        ] {
+            assert!(super::iter_clipped_2d(width, height)
+                .eq(super::iter_2d().filter(|&[x, y]| x < width && y < height)));
+        }
+    }
+
+    #[test]
+    fn index_distance() {
+        let points = [[0, 0], [3, 1], [Coordinate::MAX, 0], [Coordinate::MAX; 2]];
+        for &a in &points {
+            for &b in &points {
+                let [a_idx, b_idx] = [super::encode_2d(a), super::encode_2d(b)];
+                let distance = super::index_distance(a, b);
+                assert_eq!(distance, a_idx.max(b_idx) - a_idx.min(b_idx));
+                assert_eq!(super::index_distance(b, a), distance);
+            }
+        }
+        let [first, last] = [super::decode_2d(0), super::decode_2d(CurveIdx::MAX)];
+        assert_eq!(super::index_distance(last, first), CurveIdx::MAX);
+    }
+
+    #[test]
+    fn iter_window_2d() {
+        use super::idx16::{decode_2d, iter_window_2d};
+        for start in [0, 1, 2, 1000, u16::MAX - 1, u16::MAX] {
+            let window = iter_window_2d(start);
+            assert_eq!(window.len(), usize::from(u16::MAX) + 1);
+            let indices = window.clone().map(|(idx, coords)| {
+                assert_eq!(coords, decode_2d(idx));
+                idx
+            });
+            let mut expected = (0..=u16::MAX).collect::<Vec<_>>();
+            expected.sort_by_key(|&idx| {
+                let distance = i32::from(idx) - i32::from(start);
+                (distance.abs(), distance < 0)
+            });
+            assert!(indices.eq(expected));
+        }
+        let mut window = iter_window_2d(1);
+        assert_eq!(window.next(), Some((1, decode_2d(1))));
+        assert_eq!(window.next(), Some((2, decode_2d(2))));
+        assert_eq!(window.next(), Some((0, decode_2d(0))));
+        assert_eq!(window.next(), Some((3, decode_2d(3))));
+        assert_eq!(window.len(), usize::from(u16::MAX) - 3);
+    }
+
+    #[test]
+    fn join_2d() {
+        use super::idx16::{decode_2d, join_2d};
+        let mut state = 42u32;
+        let mut random_keys = |len: usize| {
+            let mut keys = (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (state >> 16) as u16
+                })
+                .collect::<Vec<_>>();
+            keys.sort_unstable();
+            keys
+        };
+        let (a, b) = (random_keys(300), random_keys(2000));
+        for distance in [0, 1, 3, 20, u8::MAX] {
+            let mut expected = Vec::new();
+            for (i, &a_idx) in a.iter().enumerate() {
+                let [ax, ay] = decode_2d(a_idx);
+                for (j, &b_idx) in b.iter().enumerate() {
+                    let [bx, by] = decode_2d(b_idx);
+                    if ax.max(bx) - ax.min(bx) <= distance && ay.max(by) - ay.min(by) <= distance {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            assert_eq!(join_2d(&a, &b, distance).collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(join_2d(&a, &[], 10).count(), 0);
+        assert_eq!(join_2d(&[], &b, 10).count(), 0);
+    }
+
+    #[test]
+    fn iter_masked_2d() {
+        use super::idx16::{iter_2d, iter_clipped_2d, iter_masked_2d};
+        // Trivial filters only see the square that covers the whole domain
+        let mut calls = 0;
+        assert!(iter_masked_2d(|corner, side| {
+            calls += 1;
+            assert_eq!((corner, side), ([0, 0], 1 << u8::BITS));
+            MaskAction::Keep
+        })
+        .eq(iter_2d()));
+        assert_eq!(calls, 1);
+        assert_eq!(iter_masked_2d(|_, _| MaskAction::Skip).count(), 0);
+        assert!(iter_masked_2d(|_, _| MaskAction::Descend).eq(iter_2d()));
+
+        // Rectangles yield the same points as clipped iteration
+        let (width, height) = (100, 37);
+        assert!(iter_masked_2d(|[x, y], side| {
+            let [x, y] = [u16::from(x), u16::from(y)];
+            let [width, height] = [u16::from(width), u16::from(height)];
+            if x + side <= width && y + side <= height {
+                MaskAction::Keep
+            } else if x >= width || y >= height {
+                MaskAction::Skip
+            } else {
+                MaskAction::Descend
+            }
+        })
+        .eq(iter_clipped_2d(width, height)));
+
+        // Disks are handled by descending into the squares on their edge
+        let ([cx, cy], radius) = ([100i32, 60], 50i32);
+        let in_disk = |[x, y]: [i32; 2]| (x - cx).pow(2) + (y - cy).pow(2) <= radius.pow(2);
+        let mut calls = 0;
+        let masked = iter_masked_2d(|[x, y], side| {
+            calls += 1;
+            let [x0, y0] = [i32::from(x), i32::from(y)];
+            let [x1, y1] = [x0 + i32::from(side) - 1, y0 + i32::from(side) - 1];
+            let nearest = [cx.max(x0).min(x1), cy.max(y0).min(y1)];
+            if [[x0, y0], [x0, y1], [x1, y0], [x1, y1]]
+                .iter()
+                .all(|&p| in_disk(p))
+            {
+                MaskAction::Keep
+            } else if !in_disk(nearest) {
+                MaskAction::Skip
+            } else {
+                MaskAction::Descend
+            }
+        })
+        .collect::<Vec<_>>();
+        assert!(masked
+            .iter()
+            .copied()
+            .eq(iter_2d().filter(|&[x, y]| in_disk([i32::from(x), i32::from(y)]))));
+        assert!(calls < masked.len());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn ranges_for_rect() {
+        for (min, max) in [
+            ([0, 0], [0, 0]),
+            ([3, 5], [2, 5]),
+            ([0, 0], [Coordinate::MAX; 2]),
+            ([2, 3], [9, 7]),
+            ([1, 1], [Coordinate::MAX - 1, 200]),
+            ([100, 37], [100, 200]),
+            ([Coordinate::MAX, 0], [Coordinate::MAX; 2]),
+        ] {
+            // Compute the expected ranges by going through the whole curve
+            let mut expected = Vec::<core::ops::RangeInclusive<CurveIdx>>::new();
+            for (idx, [x, y]) in super::iter_2d().indexed() {
+                if x < min[0] || x > max[0] || y < min[1] || y > max[1] {
+                    continue;
+                }
+                match expected.last_mut() {
+                    Some(range) if *range.end() + 1 == idx => *range = *range.start()..=idx,
+                    _ => expected.push(idx..=idx),
+                }
+            }
+            assert_eq!(
+                super::ranges_for_rect(min, max).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn next_index_in_rect() {
+        for (min, max) in [
+            ([0, 0], [0, 0]),
+            ([3, 5], [2, 5]),
+            ([0, 0], [Coordinate::MAX; 2]),
+            ([2, 3], [9, 7]),
+            ([1, 1], [Coordinate::MAX - 1, 200]),
+            ([100, 37], [100, 200]),
+            ([Coordinate::MAX, 0], [Coordinate::MAX; 2]),
+        ] {
+            // Compute the expected results by going through the curve backwards
+            let mut expected = None;
+            for (idx, [x, y]) in super::iter_2d().indexed().rev() {
+                if x >= min[0] && x <= max[0] && y >= min[1] && y <= max[1] {
+                    expected = Some(idx);
+                }
+                assert_eq!(super::next_index_in_rect(idx, min, max), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_rect_query() {
+        for (min, max) in [
+            ([0, 0], [0, 0]),
+            ([3, 5], [2, 5]),
+            ([0, 0], [Coordinate::MAX; 2]),
+            ([2, 3], [9, 7]),
+            ([1, 1], [Coordinate::MAX - 1, 200]),
+            ([100, 37], [100, 200]),
+        ] {
+            assert!(
+                super::iter_rect_query(min, max).eq(super::iter_2d()
+                    .indexed()
+                    .filter(|(_idx, [x, y])| (min[0]..=max[0]).contains(x)
+                        && (min[1]..=max[1]).contains(y)))
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn sort_radix() {
+        let mut points = (CurveIdx::MIN..=CurveIdx::MAX)
+            .map(|idx| super::decode_2d(idx.wrapping_mul(0x9E37)))
+            .collect::<Vec<_>>();
+        super::sort_radix(&mut points);
+        assert!(points.into_iter().eq(super::iter_2d()));
+        let mut points = [[2, 1], [0, 0], [2, 1]];
+        super::sort_radix(&mut points);
+        assert_eq!(points, [[0, 0], [2, 1], [2, 1]]);
+        super::sort_radix(&mut []);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn sort_by_key() {
+        let mut items = (CurveIdx::MIN..=CurveIdx::MAX)
+            .rev()
+            .map(|idx| (super::decode_2d(idx), idx))
+            .collect::<Vec<_>>();
+        let num_calls = core::cell::Cell::new(0);
+        super::sort_by_key(&mut items, |&(coords, _idx)| {
+            num_calls.set(num_calls.get() + 1);
+            coords
+        });
+        assert_eq!(num_calls.get(), items.len());
+        assert!(items
+            .into_iter()
+            .map(|(_coords, idx)| idx)
+            .eq(CurveIdx::MIN..=CurveIdx::MAX));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn permute_in_place() {
+        for order in 0..=Coordinate::BITS.min(8) {
+            let width = 1usize << order;
+            let row_major = (0..width * width).collect::<Vec<_>>();
+            let mut data = row_major.clone();
+            super::permute_in_place(&mut data, width);
+            for (code, &row_major) in data.iter().enumerate() {
+                let [x, y] = super::decode_2d(code as CurveIdx);
+                assert_eq!(row_major, y as usize * width + x as usize);
+            }
+            super::unpermute_in_place(&mut data, width);
+            assert_eq!(data, row_major);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(feature = "alloc")]
+    fn permute_in_place_non_square() {
+        super::permute_in_place(&mut [0; 32], 4);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_2d_rev() {
+        for (iter, (coords, idx)) in super::iter_2d()
+            .rev()
+            .zip((CurveIdx::MIN..=CurveIdx::MAX).rev())
+            .enumerate()
+        {
+            assert_eq!(
+                coords,
+                super::decode_2d(idx),
+                "Unexpected reverse 2D Hilbert curve iterator output at iteration {iter}"
+            );
+        }
+    }
+
+    quickcheck! {
+        fn iter_from_2d_both_ends(start: CurveIdx, from_back: Vec<bool>) -> bool {
+            let mut iter = super::iter_from_2d(start);
+            let mut indices = start..=CurveIdx::MAX;
+            from_back.into_iter().all(|from_back| {
+                if from_back {
+                    iter.next_back() == indices.next_back().map(super::decode_2d)
+                } else {
+                    iter.next() == indices.next().map(super::decode_2d)
+                }
+            })
+        }
+
+        #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+        fn iter_range_2d(start: CurveIdx, end: CurveIdx) -> bool {
+            super::iter_range_2d(start..end).eq((start..end).map(super::decode_2d))
+                && super::iter_range_2d(start..=end)
+                    .rev()
+                    .eq((start..=end).rev().map(super::decode_2d))
+                && super::iter_range_2d(..end).eq((0..end).map(super::decode_2d))
+        }
+
+        #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+        fn iter_from_2d_indexed(start: CurveIdx, n: u16) -> bool {
+            let expected = |idx| (idx, super::decode_2d(idx));
+            let mut iter = super::iter_from_2d(start).indexed();
+            let mut indices = start..=CurveIdx::MAX;
+            iter.clone().eq(indices.clone().map(expected))
+                && iter.next_back() == indices.next_back().map(expected)
+                && iter.nth(n.into()) == indices.nth(n.into()).map(expected)
+                && iter.nth_back(n.into()) == indices.nth_back(n.into()).map(expected)
+        }
+
+        fn iter_from_2d_nth(start: CurveIdx, jumps: Vec<(bool, u16)>) -> bool {
+            let mut iter = super::iter_from_2d(start);
+            let mut indices = start..=CurveIdx::MAX;
+            jumps.into_iter().all(|(from_back, n)| {
+                let n = usize::from(n);
+                if from_back {
+                    iter.nth_back(n) == indices.nth_back(n).map(super::decode_2d)
+                } else {
+                    iter.nth(n) == indices.nth(n).map(super::decode_2d)
+                }
+            })
+        }
+    }
+
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    mod nd {
+        use super::*;
+        use crate::NumBits;
+
+        // Check that the N-dimensional curve is a Hilbert curve, i.e. that it
+        // goes through every point of its domain once, by unit steps, and that
+        // encoding is the inverse of decoding.
+        fn test<const N: usize>() {
+            let order =
+                (bits::num_bits::<CurveIdx>() / N as NumBits).min(bits::num_bits::<Coordinate>());
+            let num_points = 1usize << (N as NumBits * order);
+            let mut visited = vec![false; num_points];
+            let mut prev_coords = None;
+            for code in 0..num_points {
+                let code = code as CurveIdx;
+                let coords = super::super::decode_nd::<N>(code);
+                assert_eq!(super::super::encode_nd(coords), code);
+                let linear_idx = coords
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, &coord| (acc << order) | coord as usize);
+                assert!(!visited[linear_idx], "Point {coords:?} visited twice");
+                visited[linear_idx] = true;
+                if let Some(prev_coords) = prev_coords {
+                    let distance = coords
+                        .iter()
+                        .zip(prev_coords)
+                        .map(|(&coord, prev_coord)| (coord as isize - prev_coord as isize).abs())
+                        .sum::<isize>();
+                    assert_eq!(
+                        distance, 1,
+                        "Curve jumped from {prev_coords:?} to {coords:?}"
+                    );
+                }
+                prev_coords = Some(coords);
+            }
+        }
+
+        // Check that compact Hilbert indices go through every point of their
+        // anisotropic domain once, in the order of the Hilbert curve that spans
+        // the enclosing hypercube, and that encoding is the inverse of decoding.
+        fn test_compact<const N: usize>(precisions: [NumBits; N]) {
+            let order = precisions.iter().copied().max().unwrap();
+            let num_bits = precisions.iter().sum::<NumBits>();
+            let mut visited = vec![false; 1usize << num_bits];
+            let mut prev_full_code = None;
+            for code in 0..(1usize << num_bits) {
+                let code = code as CurveIdx;
+                let coords = super::super::decode_nd_compact(code, precisions);
+                assert_eq!(super::super::encode_nd_compact(coords, precisions), code);
+                let mut linear_idx = 0;
+                for (&coord, &precision) in coords.iter().zip(&precisions).rev() {
+                    assert!((coord as usize) < 1 << precision);
+                    linear_idx = (linear_idx << precision) | coord as usize;
+                }
+                assert!(!visited[linear_idx], "Point {coords:?} visited twice");
+                visited[linear_idx] = true;
+                let full_code = super::super::encode_nd_compact(coords, [order; N]);
+                assert!(
+                    prev_full_code < Some(full_code),
+                    "Point {coords:?} is out of Hilbert curve order"
+                );
+                prev_full_code = Some(full_code);
+            }
+        }
+
+        #[test]
+        fn compact() {
+            for code in 0..=CurveIdx::MAX {
+                assert_eq!(
+                    super::super::decode_nd_compact(code, [bits::num_bits::<Coordinate>(); 2]),
+                    super::super::decode_2d(code)
+                );
+            }
+            let order = bits::num_bits::<CurveIdx>() / 3;
+            for code in 0..(1 << (3 * order)) {
+                assert_eq!(
+                    super::super::decode_nd_compact(code, [order; 3]),
+                    super::super::decode_nd::<3>(code)
+                );
+            }
+            test_compact([6, 2]);
+            test_compact([2, 6]);
+            test_compact([5, 0]);
+            test_compact([3, 5, 1]);
+            test_compact([4, 4, 2, 1]);
+        }
+
+        #[test]
+        fn dims_1() {
+            test::<1>();
+        }
+
+        #[test]
+        fn dims_2() {
+            test::<2>();
+            for code in 0..=CurveIdx::MAX {
+                assert_eq!(
+                    super::super::decode_nd::<2>(code),
+                    super::super::decode_2d(code)
+                );
+            }
+        }
+
+        #[test]
+        fn dims_3() {
+            test::<3>();
+        }
+
+        #[test]
+        fn dims_4() {
+            test::<4>();
+            for code in 0..=CurveIdx::MAX {
+                let coords = super::super::decode_4d(code);
+                assert_eq!(coords, super::super::decode_nd::<4>(code));
+                assert_eq!(super::super::encode_4d(coords), code);
+            }
+        }
+
+        #[test]
+        fn dims_5() {
+            test::<5>();
+        }
+
+        #[test]
+        fn dims_max() {
+            test::<{ bits::num_bits::<CurveIdx>() as usize }>();
+        }
+
+        // Check that every orientation of the N-dimensional curve goes between
+        // the expected corners, and that curves chain seamlessly across the faces
+        // of neighboring domains
+        fn test_oriented<const N: usize>() {
+            let order =
+                (bits::num_bits::<CurveIdx>() / N as NumBits).min(bits::num_bits::<Coordinate>());
+            let last = bits::low_order_mask(N as NumBits * order);
+            let max = bits::low_order_mask(order) as Coordinate;
+            let side = 1i128 << order;
+            let from_bits = |corner_bits: usize| {
+                let mut corner = [false; N];
+                for (dim, high) in corner.iter_mut().enumerate() {
+                    *high = (corner_bits >> dim) & 1 == 1;
+                }
+                corner
+            };
+            let corners = (0..(1usize << N)).map(from_bits).collect::<Vec<_>>();
+            let coords = |corner: [bool; N]| corner.map(|high| if high { max } else { 0 });
+
+            let mut orientations = Vec::new();
+            for &start in &corners {
+                for &end in &corners {
+                    let num_diffs = start.iter().zip(&end).filter(|(a, b)| a != b).count();
+                    let orientation = super::super::OrientationNd::from_corners(start, end);
+                    assert_eq!(orientation.is_some(), num_diffs == 1);
+                    if let Some(orientation) = orientation {
+                        assert_eq!(orientation.start(), start);
+                        assert_eq!(orientation.end(), end);
+                        orientations.push(orientation);
+                    }
+                }
+            }
+            assert_eq!(orientations.len(), N << N);
+            let mut identity_end = [false; N];
+            identity_end[N - 1] = true;
+            assert_eq!(
+                super::super::OrientationNd::from_corners([false; N], identity_end),
+                Some(super::super::OrientationNd::IDENTITY)
             );
+
+            for orientation in orientations {
+                let decode = |code| super::super::decode_nd_oriented::<N>(code, orientation);
+                assert_eq!(decode(0), coords(orientation.start()));
+                assert_eq!(decode(last), coords(orientation.end()));
+                let mut prev_coords = None;
+                for code in 0..=last {
+                    let point = decode(code);
+                    assert_eq!(super::super::encode_nd_oriented(point, orientation), code);
+                    if let Some(prev_coords) = prev_coords {
+                        let distance = point
+                            .iter()
+                            .zip(prev_coords)
+                            .map(|(&coord, prev_coord)| {
+                                (coord as isize - prev_coord as isize).abs()
+                            })
+                            .sum::<isize>();
+                        assert_eq!(distance, 1);
+                    }
+                    prev_coords = Some(point);
+                }
+
+                for axis in 0..N {
+                    for increasing in [false, true] {
+                        let faces = orientation.end()[axis] == increasing;
+                        let mut num_following = 0;
+                        for &end in &corners {
+                            let next = match orientation.following(axis, increasing, end) {
+                                Some(next) => next,
+                                None => continue,
+                            };
+                            num_following += 1;
+                            assert_eq!(next.end(), end);
+
+                            // The first point of the next domain, in the
+                            // coordinates of this domain, is next to the last
+                            // point of this domain
+                            let step = if increasing { 1 } else { -1 };
+                            let mut first =
+                                super::super::decode_nd_oriented::<N>(0, next).map(i128::from);
+                            let mut expected = decode(last).map(i128::from);
+                            first[axis] += step * side;
+                            expected[axis] += step;
+                            assert_eq!(first, expected);
+                        }
+                        assert_eq!(num_following, if faces { N } else { 0 });
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn oriented() {
+            test_oriented::<1>();
+            test_oriented::<2>();
+            test_oriented::<3>();
+        }
+
+        #[test]
+        #[should_panic]
+        fn following_bad_axis() {
+            super::super::OrientationNd::<3>::IDENTITY.following(3, true, [false; 3]);
         }
     }
+
+    // Wider curve index types are tested using random inputs and consistency
+    // checks with respect to narrower curve index types
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty, $coordinate:ty, $narrower:ident) => {
+            mod $width {
+                use crate::hilbert::{$narrower, $width};
+                use crate::MaskAction;
+                use quickcheck::quickcheck;
+
+                #[allow(unused)]
+                const IDX_BITS: u32 = <$curve_idx>::BITS;
+                #[allow(unused)]
+                const COORD_BITS: u32 = <$coordinate>::BITS;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let coords = $width::decode_2d(code);
+                        let next_coords = $width::decode_2d(code.wrapping_add(1));
+                        let distance = coords
+                            .iter()
+                            .zip(next_coords)
+                            .map(|(&coord, next_coord)| (coord as i128 - next_coord as i128).abs())
+                            .sum::<i128>();
+                        $width::encode_2d(coords) == code
+                            && (code == <$curve_idx>::MAX || distance == 1)
+                    }
+
+                    fn cmp_2d(a: [$coordinate; 2], b: [$coordinate; 2]) -> bool {
+                        $width::cmp_2d(a, b) == $width::encode_2d(a).cmp(&$width::encode_2d(b))
+                    }
+
+                    fn decode_2d_oriented(code: $curve_idx, swap: bool, mirror_x: bool, mirror_y: bool) -> bool {
+                        let orientation = crate::hilbert::Orientation { swap, mirror_x, mirror_y };
+                        let coords = $width::decode_2d_oriented(code, orientation);
+                        $width::encode_2d_oriented(coords, orientation) == code
+                    }
+
+                    fn decode_2d_order_oriented(code: $curve_idx, order: u32, swap: bool, mirror_x: bool, mirror_y: bool) -> bool {
+                        let order = order % (COORD_BITS + 1);
+                        let code = code & crate::bits::$width::low_order_mask(2 * order);
+                        let orientation = crate::hilbert::Orientation { swap, mirror_x, mirror_y };
+                        let coords = $width::decode_2d_order_oriented(code, order, orientation);
+                        $width::encode_2d_order_oriented(coords, order, orientation) == code
+                    }
+
+                    fn decode_nd_compact(code: $curve_idx, x_precision: u8) -> bool {
+                        let x_precision = u32::from(x_precision) % (COORD_BITS + 1);
+                        let precisions = [x_precision, COORD_BITS, COORD_BITS - x_precision];
+                        $width::encode_nd_compact($width::decode_nd_compact(code, precisions), precisions) == code
+                    }
+
+                    fn decode_nd(code: $curve_idx) -> bool {
+                        let code = code >> (IDX_BITS % 3);
+                        $width::encode_nd($width::decode_nd::<3>(code)) == code
+                    }
+
+                    fn decode_4d(code: $curve_idx) -> bool {
+                        let coords = $width::decode_4d(code);
+                        coords.iter().all(|&coord| coord >> (IDX_BITS / 4) == 0)
+                            && $width::encode_4d(coords) == code
+                    }
+
+                    fn decode_nd_oriented(code: $curve_idx, start: [bool; 3], end_axis: u8) -> bool {
+                        let code = code >> (IDX_BITS % 3);
+                        let mut end = start;
+                        end[usize::from(end_axis % 3)] ^= true;
+                        let orientation = crate::hilbert::OrientationNd::from_corners(start, end).unwrap();
+                        let coords = $width::decode_nd_oriented::<3>(code, orientation);
+                        $width::encode_nd_oriented(coords, orientation) == code
+                    }
+
+                    fn decode_2d_fast(code: $curve_idx) -> bool {
+                        $width::decode_2d_fast(code) == $width::decode_2d(code)
+                    }
+
+                    fn encode_2d_fast(coords: [$coordinate; 2]) -> bool {
+                        $width::encode_2d_fast(coords) == $width::encode_2d(coords)
+                    }
+
+                    fn decode_2d_lut(code: $curve_idx) -> bool {
+                        $width::decode_2d_lut(code) == $width::decode_2d(code)
+                    }
+
+                    #[cfg(feature = "alloc")]
+                    fn decode_2d_simd(codes: Vec<$curve_idx>) -> bool {
+                        $width::decode_2d_simd(&codes)
+                            .into_iter()
+                            .eq(codes.into_iter().map($width::decode_2d))
+                    }
+
+                    fn iter_rect(width: $coordinate, height: $coordinate) -> bool {
+                        let mut iter = $width::iter_rect(width, height);
+                        let mut last = match iter.next() {
+                            Some(first) => first,
+                            None => return width == 0 || height == 0,
+                        };
+                        iter.take(1000).all(|[x, y]| {
+                            let [last_x, last_y] = core::mem::replace(&mut last, [x, y]);
+                            let distance = x.max(last_x) - x.min(last_x) + y.max(last_y) - y.min(last_y);
+                            x < width && y < height && distance == 1
+                        })
+                    }
+
+                    fn iter_from_2d(start: $curve_idx) -> bool {
+                        $width::iter_from_2d(start)
+                            .zip(start..=<$curve_idx>::MAX)
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+
+                    fn iter_from_2d_step_by(start: $curve_idx, step: u16) -> bool {
+                        let step = usize::from(step) + 1;
+                        $width::iter_from_2d(start)
+                            .step_by(step)
+                            .zip((start..=<$curve_idx>::MAX).step_by(step))
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+
+                    fn iter_masked_2d(min: [$coordinate; 2], size: [u8; 2]) -> bool {
+                        let max = [
+                            min[0].saturating_add(size[0].into()),
+                            min[1].saturating_add(size[1].into()),
+                        ];
+                        let masked = $width::iter_masked_2d(|corner, side| {
+                            let last = |axis: usize| u128::from(corner[axis]) + side as u128 - 1;
+                            if (0..2).all(|axis| corner[axis] >= min[axis] && last(axis) <= u128::from(max[axis])) {
+                                MaskAction::Keep
+                            } else if (0..2).any(|axis| corner[axis] > max[axis] || last(axis) < u128::from(min[axis])) {
+                                MaskAction::Skip
+                            } else {
+                                MaskAction::Descend
+                            }
+                        });
+                        masked.eq($width::ranges_for_rect(min, max).flat_map($width::iter_range_2d))
+                    }
+
+                    fn iter_clipped_2d(width: u8, height: u8) -> bool {
+                        let bounds = [<$coordinate>::from(width), <$coordinate>::from(height)];
+                        let mut last_idx = None;
+                        let mut count = 0;
+                        for coords in $width::iter_clipped_2d(bounds[0], bounds[1]) {
+                            let idx = $width::encode_2d(coords);
+                            if coords[0] >= bounds[0] || coords[1] >= bounds[1] || last_idx >= Some(idx) {
+                                return false;
+                            }
+                            last_idx = Some(idx);
+                            count += 1;
+                        }
+                        count == usize::from(width) * usize::from(height)
+                    }
+
+                    fn ranges_for_rect(min: [$coordinate; 2], size: [u8; 2]) -> bool {
+                        let max = [
+                            min[0].saturating_add(size[0].into()),
+                            min[1].saturating_add(size[1].into()),
+                        ];
+                        let in_rect = |[x, y]: [$coordinate; 2]| {
+                            x >= min[0] && x <= max[0] && y >= min[1] && y <= max[1]
+                        };
+                        let mut num_points = 0;
+                        let mut prev_end = None;
+                        for range in $width::ranges_for_rect(min, max) {
+                            let (start, end) = (*range.start(), *range.end());
+                            if prev_end.map_or(false, |prev_end| prev_end + 1 >= start)
+                                || start.checked_sub(1).map_or(false, |prev| in_rect($width::decode_2d(prev)))
+                                || end.checked_add(1).map_or(false, |next| in_rect($width::decode_2d(next)))
+                                || !range.clone().all(|idx| in_rect($width::decode_2d(idx)))
+                            {
+                                return false;
+                            }
+                            num_points += end - start + 1;
+                            prev_end = Some(end);
+                        }
+                        let width = <$curve_idx>::from(max[0] - min[0]) + 1;
+                        let height = <$curve_idx>::from(max[1] - min[1]) + 1;
+                        num_points == width * height
+                    }
+
+                    fn iter_rect_query(min: [$coordinate; 2], size: [u8; 2]) -> bool {
+                        let max = [
+                            min[0].saturating_add(size[0].into()),
+                            min[1].saturating_add(size[1].into()),
+                        ];
+                        let mut prev_idx = None;
+                        let mut num_points = 0;
+                        for (idx, [x, y]) in $width::iter_rect_query(min, max) {
+                            if prev_idx >= Some(idx)
+                                || $width::decode_2d(idx) != [x, y]
+                                || !(min[0]..=max[0]).contains(&x)
+                                || !(min[1]..=max[1]).contains(&y)
+                            {
+                                return false;
+                            }
+                            prev_idx = Some(idx);
+                            num_points += 1;
+                        }
+                        let width = <$curve_idx>::from(max[0] - min[0]) + 1;
+                        let height = <$curve_idx>::from(max[1] - min[1]) + 1;
+                        num_points == width * height
+                    }
+
+                    fn iter_from_2d_rev(start: $curve_idx) -> bool {
+                        $width::iter_from_2d(start)
+                            .rev()
+                            .zip((start..=<$curve_idx>::MAX).rev())
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+                }
+
+                #[test]
+                fn iter_blocks_2d() {
+                    for block_bits in 0..=COORD_BITS {
+                        let last = $width::iter_blocks_2d(block_bits).next_back().unwrap();
+                        assert_eq!(*last.indices.end(), <$curve_idx>::MAX);
+                        let mask = <$coordinate>::MAX.checked_shl(block_bits).unwrap_or(0);
+                        assert_eq!(last.min.map(|c| c & !mask), [0, 0]);
+                        assert_eq!(last.max.map(|c| c & !mask), [!mask; 2]);
+                        let [x, y] = $width::decode_2d(<$curve_idx>::MAX);
+                        assert_eq!([x & mask, y & mask], last.min);
+                    }
+                }
+
+                #[test]
+                fn narrower() {
+                    for code in 0..=u16::MAX {
+                        assert_eq!(
+                            $width::decode_2d(code.into()),
+                            $narrower::decode_2d(code.into()).map(<$coordinate>::from)
+                        );
+                    }
+                }
+
+                #[test]
+                fn iter_end() {
+                    assert_eq!($width::iter_from_2d(<$curve_idx>::MAX - 41).count(), 42);
+                    assert_eq!($width::iter_range_2d(<$curve_idx>::MAX - 41..).count(), 42);
+                    assert_eq!($width::iter_range_2d(..42).count(), 42);
+                    assert_eq!($width::iter_range_2d(..0).count(), 0);
+                    assert_eq!(
+                        $width::iter_range_2d((
+                            core::ops::Bound::Excluded(<$curve_idx>::MAX),
+                            core::ops::Bound::Unbounded
+                        ))
+                        .count(),
+                        0
+                    );
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32, u16, idx16);
+    wide_tests!(idx64, u64, u32, idx32);
+    wide_tests!(idx128, u128, u64, idx64);
 }
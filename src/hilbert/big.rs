@@ -0,0 +1,149 @@
+//! N-dimensional Hilbert curve with arbitrary-precision curve indices
+//!
+//! The number of bits needed to index an N-dimensional Hilbert curve grows
+//! linearly with N, and quickly outgrows machine integers (e.g. 10 dimensions
+//! with 32 bits per axis need 320-bit curve indices). This module handles such
+//! curves by using `BigUint` curve indices. Hypercube corners still fit in a
+//! machine integer, so the per-depth transforms are shared with the 64-bit
+//! version of the N-dimensional Hilbert curve.
+
+use super::idx64::{direction_nd, entry_nd, rotate_left_nd, rotate_right_nd};
+use crate::NumBits;
+use num_bigint::BigUint;
+
+/// Compute the coordinates of the i-th point of an N-dimensional Hilbert curve
+/// with an arbitrary-precision curve index
+///
+/// This is the same curve as `decode_nd()`, except the number of bits per
+/// coordinate (aka curve order) is not deduced from the curve index type but
+/// specified by the `order` parameter. It can be at most 64, and so can N.
+/// Bits of the curve index above N x `order` must be zero.
+///
+pub fn decode_nd_big<const N: usize>(code: &BigUint, order: NumBits) -> [u64; N] {
+    let num_dims = num_dims_big::<N>(order);
+    debug_assert!(code.bits() <= u64::from(num_dims * order));
+
+    // See `decode_nd()` for an explanation of this algorithm
+    let mut coords = [0; N];
+    let mut entry = 0;
+    let mut direction = num_dims - 1;
+    for depth in (0..order).rev() {
+        let digit = (0..num_dims).fold(0, |digit, dim| {
+            digit | (u64::from(code.bit(u64::from(depth * num_dims + dim))) << dim)
+        });
+        let gray = digit ^ (digit >> 1);
+        let corner = rotate_left_nd(gray, direction + 1, num_dims) ^ entry;
+        for (dim, coord) in coords.iter_mut().enumerate() {
+            *coord |= ((corner >> dim) & 1) << depth;
+        }
+        entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+        direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+    }
+    coords
+}
+
+/// Compute the arbitrary-precision index of a point on an N-dimensional
+/// Hilbert curve
+///
+/// This is the inverse of `decode_nd_big()`, see its documentation for details.
+///
+pub fn encode_nd_big<const N: usize>(coords: [u64; N], order: NumBits) -> BigUint {
+    let num_dims = num_dims_big::<N>(order);
+
+    // See `encode_nd()` for an explanation of this algorithm
+    let mut code = BigUint::default();
+    let mut entry = 0;
+    let mut direction = num_dims - 1;
+    for depth in (0..order).rev() {
+        let corner = coords.iter().enumerate().fold(0, |corner, (dim, &coord)| {
+            debug_assert!(order == 64 || coord >> order == 0);
+            corner | (((coord >> depth) & 1) << dim)
+        });
+        let gray = rotate_right_nd(corner ^ entry, direction + 1, num_dims);
+        let mut digit = gray;
+        let mut stride = 1;
+        while stride < num_dims {
+            digit ^= digit >> stride;
+            stride *= 2;
+        }
+        for dim in 0..num_dims {
+            code.set_bit(u64::from(depth * num_dims + dim), (digit >> dim) & 1 != 0);
+        }
+        entry ^= rotate_left_nd(entry_nd(digit), direction + 1, num_dims);
+        direction = (direction + direction_nd(digit, num_dims) + 1) % num_dims;
+    }
+    code
+}
+
+/// Number of dimensions of an N-dimensional Hilbert curve with an
+/// arbitrary-precision curve index, after checking the curve parameters
+#[inline(always)]
+fn num_dims_big<const N: usize>(order: NumBits) -> NumBits {
+    assert!(
+        N != 0 && N <= 64,
+        "Unsupported number of dimensions {N}, must be between 1 and 64"
+    );
+    assert!(
+        order <= 64,
+        "Unsupported curve order {order}, must be at most 64"
+    );
+    N as _
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::quickcheck;
+
+    // Check that arbitrary-precision indices reproduce the 128-bit curve
+    fn check_vs_u128<const N: usize>(code: u128) -> bool {
+        let order = 128 / N as NumBits;
+        let code = code & crate::bits::idx128::low_order_mask(N as NumBits * order);
+        let coords = decode_nd_big::<N>(&BigUint::from(code), order);
+        coords == super::super::decode_nd_u128::<N>(code)
+            && encode_nd_big(coords, order) == BigUint::from(code)
+    }
+
+    quickcheck! {
+        fn vs_u128_dims_2(code: u128) -> bool {
+            check_vs_u128::<2>(code)
+        }
+
+        fn vs_u128_dims_3(code: u128) -> bool {
+            check_vs_u128::<3>(code)
+        }
+
+        fn vs_u128_dims_5(code: u128) -> bool {
+            check_vs_u128::<5>(code)
+        }
+
+        fn roundtrip_dims_10(coords: Vec<u32>) -> bool {
+            let mut coords_10 = [0; 10];
+            for (dst, src) in coords_10.iter_mut().zip(coords) {
+                *dst = src.into();
+            }
+            let code = encode_nd_big(coords_10, 32);
+            code.bits() <= 320 && decode_nd_big::<10>(&code, 32) == coords_10
+        }
+    }
+
+    #[test]
+    fn unit_steps_dims_10() {
+        let mut code = BigUint::from(0x1234_5678_9abc_def0_u64) << 200u32;
+        let mut prev_coords = decode_nd_big::<10>(&code, 32);
+        for _ in 0..1000 {
+            code += 1u32;
+            let coords = decode_nd_big::<10>(&code, 32);
+            let distance = coords
+                .iter()
+                .zip(prev_coords)
+                .map(|(&coord, prev_coord)| (coord as i64 - prev_coord as i64).abs())
+                .sum::<i64>();
+            assert_eq!(
+                distance, 1,
+                "Curve jumped from {prev_coords:?} to {coords:?}"
+            );
+            prev_coords = coords;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Utilities related to the H-index space-filling curve
+//!
+//! The H-index (or H-order) was introduced by Niedermeier, Reinhardt and Sanders
+//! in the context of parallel mesh partitioning. It follows the same recursive
+//! splitting of right isosceles triangles as the Sierpiński curve, but picks the
+//! grid cells that it goes through so that every point is a direct neighbor of
+//! the next one, instead of a possibly diagonal neighbor. Like the Sierpiński
+//! and Moore curves, it is a closed loop.
+//!
+//! Aligned ranges of curve indices whose length is a power of two cover regions
+//! that are close to right isosceles triangles or squares, which makes this
+//! curve a good fit for triangular and rectangular domain decompositions.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+//! Like those of the Sierpiński curve, decoding and encoding go through
+//! recursion depths one by one and do not run in constant time.
+
+/// Generate H-index curve utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! hindex_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::{bits::$width as bits, sierpinski::$width as sierpinski};
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Number of bits in a curve index
+        const IDX_BITS: u32 = CurveIdx::BITS;
+
+        /// Compute the coordinates of the i-th point of the H-index curve
+        ///
+        /// The curve's domain is recursively split into triangles as in the
+        /// Sierpiński curve, down to triangles made of four halves of grid cells.
+        /// The middle two halves form a full grid cell, whereas the first and last
+        /// ones belong to grid cells that are shared with neighboring triangles.
+        ///
+        /// The curve goes through the full grid cell of each triangle and through
+        /// one of its shared grid cells: the first one if the number of 1 bits in
+        /// the position of the triangle within its half of the domain is even, and
+        /// the last one otherwise. This way, each shared grid cell is visited once,
+        /// and every point of the curve is a direct neighbor of the next one, which
+        /// remains true when wrapping around from the last point to the first one.
+        ///
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // The high-order bit of the curve index selects one of the two halves of
+            // the domain, and the low-order bit selects a grid cell within the
+            // triangle designated by the remaining bits.
+            let upper = code >> (IDX_BITS - 1) != 0;
+            let triangle = (code >> 1) & bits::low_order_mask(IDX_BITS - 2);
+            let low = code & 1;
+            let half_cell = if triangle.count_ones() % 2 == 0 {
+                low
+            } else {
+                1 + 2 * low
+            };
+            sierpinski::half_cell_coords(upper, (triangle << 2) | half_cell)
+        }
+
+        /// Compute the index of a point on the H-index curve
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        ///
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            // Find the position of one half of the cell, and if the curve does not
+            // go through the cell from that half, then it goes through the other
+            // half. See sierpinski::encode_2d() for the choice of probe points.
+            let [x, y] = coords;
+            let [x, y] = [4 * x as CurveIdx, 4 * y as CurveIdx];
+            let (upper, position) = sierpinski::triangle_position([x + 1, y + 2]);
+            if let Some(code) = half_cell_code(upper, position) {
+                return code;
+            }
+            let (upper, position) = sierpinski::triangle_position([x + 3, y + 2]);
+            match half_cell_code(upper, position) {
+                Some(code) => code,
+                None => unreachable!(),
+            }
+        }
+
+        /// Curve index through which the curve enters a grid cell from a certain
+        /// half of it, if any, given the position of that half-cell in the format
+        /// of `sierpinski::triangle_position()`
+        #[inline(always)]
+        const fn half_cell_code(upper: bool, position: CurveIdx) -> Option<CurveIdx> {
+            let triangle = position >> 2;
+            let owns_first = triangle.count_ones() % 2 == 0;
+            let low = match position & 3 {
+                0 if owns_first => 0,
+                1 => owns_first as CurveIdx,
+                3 if !owns_first => 1,
+                _ => return None,
+            };
+            Some(((upper as CurveIdx) << (IDX_BITS - 1)) | (triangle << 1) | low)
+        }
+
+        decode_iter_impl!("H-index");
+    };
+}
+
+/// H-index curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    hindex_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// H-index curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    hindex_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// H-index curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    hindex_impl!(idx64, u64, u32);
+}
+
+/// H-index curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    hindex_impl!(idx128, u128, u64);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    // Check that the curve visits every point once, moving to a direct neighbor
+    // at every step, including when wrapping around from the end to the start
+    #[test]
+    fn decode_2d() {
+        use super::decode_2d_u16 as decode_2d;
+        assert_eq!(decode_2d(0), [0, 0]);
+        assert_eq!(decode_2d(u16::MAX), [0, 1]);
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        for code in 0..=u16::MAX {
+            let [x, y] = decode_2d(code);
+            let [next_x, next_y] = decode_2d(code.wrapping_add(1));
+            assert_eq!(
+                (x as i32 - next_x as i32).abs() + (y as i32 - next_y as i32).abs(),
+                1,
+                "Curve jumped from {code} to the next point"
+            );
+            let point_idx = (usize::from(y) << u8::BITS) | usize::from(x);
+            assert!(!visited[point_idx], "Point {code} was visited twice");
+            visited[point_idx] = true;
+        }
+    }
+
+    #[test]
+    fn encode_2d() {
+        use super::{decode_2d_u16 as decode_2d, encode_2d_u16 as encode_2d};
+        for code in 0..=u16::MAX {
+            assert_eq!(encode_2d(decode_2d(code)), code);
+        }
+    }
+
+    #[test]
+    fn iter_2d() {
+        use super::{decode_2d_u16 as decode_2d, iter_2d_u16 as iter_2d};
+        assert!(iter_2d().eq((0..=u16::MAX).map(decode_2d)));
+        assert!(super::iter_range_2d(42..=1234)
+            .rev()
+            .step_by(7)
+            .eq((42..=1234).rev().step_by(7).map(super::decode_2d)));
+    }
+
+    // Wider curve index types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty) => {
+            mod $width {
+                use crate::hindex::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let [x, y] = $width::decode_2d(code);
+                        let [next_x, next_y] = $width::decode_2d(code.wrapping_add(1));
+                        let distance = x.max(next_x) - x.min(next_x) + y.max(next_y) - y.min(next_y);
+                        distance == 1
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == code
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
@@ -0,0 +1,183 @@
+//! Integration with the `image` crate
+//!
+//! This module, which is only available when the `image` cargo feature is
+//! enabled, lets users read and write the pixels of an `ImageBuffer` in the
+//! order of a space-filling curve, and convert images to and from a byte layout
+//! where pixels are stored in curve order. The latter is a useful preprocessing
+//! step for compression schemes that exploit the similarity of consecutive
+//! pixels, such as delta coding.
+//!
+//! Images need not be square, nor have power-of-two dimensions: when they
+//! don't, the curve order is the order in which the curve goes through the
+//! pixels of the image, skipping points that are outside of it. But their
+//! dimensions must be smaller than `Coordinate::MAX`.
+
+use crate::{Coordinate, Coordinates2D, CurveKind};
+use ::image::{ImageBuffer, Pixel};
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+/// Iterate over the pixels of an image in curve order, along with their
+/// coordinates
+///
+/// # Panics
+///
+/// If the image is too large for the curve's domain.
+///
+pub fn pixels_curve_order<'a, P, Container>(
+    image: &'a ImageBuffer<P, Container>,
+    kind: CurveKind,
+) -> impl Iterator<Item = (Coordinates2D, &'a P)> + 'a
+where
+    P: Pixel + 'a,
+    Container: Deref<Target = [P::Subpixel]>,
+{
+    let [width, height] = image_size(image);
+    kind.iter_clipped_2d(width, height)
+        .map(move |[x, y]| ([x, y], image.get_pixel(to_u32(x), to_u32(y))))
+}
+
+/// Overwrite the pixels of an image with pixels in curve order
+///
+/// # Panics
+///
+/// If the image is too large for the curve's domain, or if `pixels` does not
+/// yield exactly one pixel per point of the image.
+///
+pub fn put_pixels_curve_order<P, Container>(
+    image: &mut ImageBuffer<P, Container>,
+    kind: CurveKind,
+    pixels: impl IntoIterator<Item = P>,
+) where
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]> + DerefMut,
+{
+    let [width, height] = image_size(image);
+    let mut pixels = pixels.into_iter();
+    for [x, y] in kind.iter_clipped_2d(width, height) {
+        let pixel = pixels.next().expect("Not enough pixels for this image");
+        image.put_pixel(to_u32(x), to_u32(y), pixel);
+    }
+    assert!(pixels.next().is_none(), "Too many pixels for this image");
+}
+
+/// Extract the channels of an image's pixels, with pixels in curve order
+///
+/// The result has the same layout as the image's own buffer, except for the
+/// order of pixels.
+///
+/// # Panics
+///
+/// If the image is too large for the curve's domain.
+///
+pub fn to_curve_order_raw<P, Container>(
+    image: &ImageBuffer<P, Container>,
+    kind: CurveKind,
+) -> Vec<P::Subpixel>
+where
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]>,
+{
+    let mut raw = Vec::with_capacity(image.as_raw().len());
+    for (_coords, pixel) in pixels_curve_order(image, kind) {
+        raw.extend_from_slice(pixel.channels());
+    }
+    raw
+}
+
+/// Build an image from the output of `to_curve_order_raw()`
+///
+/// # Panics
+///
+/// If the image is too large for the curve's domain, or if the length of `raw`
+/// does not match its dimensions.
+///
+pub fn from_curve_order_raw<P: Pixel>(
+    kind: CurveKind,
+    width: Coordinate,
+    height: Coordinate,
+    raw: &[P::Subpixel],
+) -> ImageBuffer<P, Vec<P::Subpixel>> {
+    assert!(
+        width as u128 <= u32::MAX.into() && height as u128 <= u32::MAX.into(),
+        "{width}x{height} image is too large for the image crate"
+    );
+    let mut image = ImageBuffer::new(to_u32(width), to_u32(height));
+    let channels = usize::from(P::CHANNEL_COUNT);
+    assert_eq!(
+        raw.len(),
+        image.as_raw().len(),
+        "Expected {width}x{height} pixels of {channels} channels"
+    );
+    let pixels = raw
+        .chunks_exact(channels)
+        .map(|chunk| *P::from_slice(chunk));
+    put_pixels_curve_order(&mut image, kind, pixels);
+    image
+}
+
+/// Width and height of an image, checking that it fits in the curve's domain
+fn image_size<P, Container>(image: &ImageBuffer<P, Container>) -> Coordinates2D
+where
+    P: Pixel,
+    Container: Deref<Target = [P::Subpixel]>,
+{
+    let (width, height) = image.dimensions();
+    let max = Coordinate::MAX as u128;
+    assert!(
+        u128::from(width) <= max && u128::from(height) <= max,
+        "{width}x{height} image is too large for the curve's domain"
+    );
+    [width as Coordinate, height as Coordinate]
+}
+
+/// Convert a coordinate that is known to fit into an image coordinate
+#[inline]
+#[allow(clippy::unnecessary_cast)] // Coordinate is u32 with idx-u64
+fn to_u32(coord: Coordinate) -> u32 {
+    coord as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::CLIPPED_KINDS;
+    use crate::CurveOrder;
+    use ::image::Rgb;
+
+    #[test]
+    fn curve_order() {
+        for &kind in CLIPPED_KINDS {
+            for &[width, height] in &[[0, 0], [1, 1], [3, 5], [16, 16], [200, 7]] {
+                let order = CurveOrder::new(kind, width.into(), height.into());
+                let image = ImageBuffer::from_fn(to_u32(width), to_u32(height), |x, y| {
+                    Rgb([x as u16, y as u16, 42])
+                });
+                let row_major = image.pixels().copied().collect::<Vec<_>>();
+                let expected = order.reorder_to_curve(&row_major);
+
+                assert!(pixels_curve_order(&image, kind)
+                    .map(|([x, y], &pixel)| {
+                        assert_eq!(pixel.0.map(usize::from), [x as usize, y as usize, 42]);
+                        pixel
+                    })
+                    .eq(expected.iter().copied()));
+
+                let raw = to_curve_order_raw(&image, kind);
+                assert!(raw.iter().copied().eq(expected.iter().flat_map(|p| p.0)));
+                let rebuilt = from_curve_order_raw::<Rgb<u16>>(kind, width, height, &raw);
+                assert_eq!(rebuilt, image);
+
+                let mut overwritten = ImageBuffer::new(to_u32(width), to_u32(height));
+                put_pixels_curve_order(&mut overwritten, kind, expected);
+                assert_eq!(overwritten, image);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_curve_order_raw_length_mismatch() {
+        from_curve_order_raw::<Rgb<u8>>(CurveKind::Morton, 4, 4, &[0; 47]);
+    }
+}
@@ -0,0 +1,112 @@
+//! Spatial joins between sets of points sorted by curve index
+//!
+//! Broad-phase matching between two point datasets needs to find the pairs of
+//! points that are close to each other. When both datasets are sorted by curve
+//! index, the neighborhood of each point of the first dataset can be decomposed
+//! into ranges of curve indices, which are then looked up in the second dataset
+//! by binary search, so that the parts of the second dataset that are too far
+//! away are never looked at.
+
+/// Generate spatial join utilities for a curve module
+///
+/// The module must provide `decode_2d()`, `ranges_for_rect()` and
+/// `RectRanges`, along with the usual `CurveIdx` and `Coordinate` names.
+///
+macro_rules! join_impl {
+    ($curve_name:literal) => {
+        #[doc = concat!("Iterate over the pairs of nearby points of two sets of 2D ", $curve_name)]
+        /// curve indices
+        ///
+        /// `b` must be sorted in ascending order, whereas `a` can be in any order.
+        /// The iterator yields the `(i, j)` pairs such that the points of curve
+        /// indices `a[i]` and `b[j]` are at most `distance` cells apart along each
+        /// axis, ordered by `i`, then by `b[j]`. If `b` is not sorted, some pairs
+        /// may be missing.
+        ///
+        /// Each point of `a` is looked up in `b` through the curve index ranges of
+        /// the square around it, as computed by `ranges_for_rect()`, so each
+        /// point costs a few binary searches in `b`, and the parts of `b` that are
+        /// too far away from it are skipped in bulk.
+        ///
+        pub fn join_2d<'a>(
+            a: &'a [CurveIdx],
+            b: &'a [CurveIdx],
+            distance: Coordinate,
+        ) -> Join2D<'a> {
+            Join2D {
+                a,
+                b,
+                distance,
+                next_a: 0,
+                current: None,
+            }
+        }
+
+        #[doc = concat!("Iterator over the pairs of nearby points of two sets of 2D ", $curve_name)]
+        /// curve indices, created by `join_2d()`
+        #[derive(Clone, Debug)]
+        pub struct Join2D<'a> {
+            /// First set of curve indices, in any order
+            a: &'a [CurveIdx],
+
+            /// Second set of curve indices, sorted in ascending order
+            b: &'a [CurveIdx],
+
+            /// Maximal distance between the points of a pair, along each axis
+            distance: Coordinate,
+
+            /// Position of the next element of `a` to be joined
+            next_a: usize,
+
+            /// Position of the element of `a` that is being joined, along with
+            /// the remaining curve index ranges around it and the positions of
+            /// the elements of `b` that are in the current range
+            current: Option<(usize, RectRanges, core::ops::Range<usize>)>,
+        }
+        //
+        impl Iterator for Join2D<'_> {
+            type Item = (usize, usize);
+
+            #[inline]
+            fn next(&mut self) -> Option<(usize, usize)> {
+                loop {
+                    if let Some((i, ranges, matches)) = &mut self.current {
+                        // Yield the elements of b that are in the current range
+                        if let Some(j) = matches.next() {
+                            return Some((*i, j));
+                        }
+
+                        // Look up the next range in the part of b that follows the
+                        // previous one, as ranges are sorted in ascending order
+                        if let Some(range) = ranges.next() {
+                            let rest = &self.b[matches.end..];
+                            let start =
+                                matches.end + rest.partition_point(|&idx| idx < *range.start());
+                            let end =
+                                matches.end + rest.partition_point(|&idx| idx <= *range.end());
+                            *matches = start..end;
+                            continue;
+                        }
+                    }
+
+                    // Move to the next element of a, if any
+                    let i = self.next_a;
+                    let &idx = self.a.get(i)?;
+                    self.next_a += 1;
+                    let [x, y] = decode_2d(idx);
+                    let min = [
+                        x.saturating_sub(self.distance),
+                        y.saturating_sub(self.distance),
+                    ];
+                    let max = [
+                        x.saturating_add(self.distance),
+                        y.saturating_add(self.distance),
+                    ];
+                    self.current = Some((i, ranges_for_rect(min, max), 0..0));
+                }
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Join2D<'_> {}
+    };
+}
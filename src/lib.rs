@@ -1,6 +1,97 @@
-pub(crate) mod bits;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// Re-export items from the curve index width module that matches the
+/// `CurveIdx` type selected via cargo features
+///
+/// When several width features are enabled, the widest one wins, as cargo
+/// features must be additive.
+///
+macro_rules! pub_use_default_width {
+    ($($items:tt)*) => {
+        #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+        pub use idx16::{$($items)*};
+        #[cfg(all(feature = "idx-u32", not(any(feature = "idx-u64", feature = "idx-u128"))))]
+        pub use idx32::{$($items)*};
+        #[cfg(all(feature = "idx-u64", not(feature = "idx-u128")))]
+        pub use idx64::{$($items)*};
+        #[cfg(feature = "idx-u128")]
+        pub use idx128::{$($items)*};
+    };
+}
+
+#[macro_use]
+mod blocks;
+#[macro_use]
+mod clip_iter;
+#[macro_use]
+mod decode_iter;
+#[macro_use]
+mod join;
+#[macro_use]
+mod neighbors;
+
+pub mod analysis;
+mod arch;
+mod batch;
+pub mod bits;
+pub mod cell;
+pub mod curve;
+mod curve_uint;
+pub mod dilated;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod geo;
+pub mod gosper;
+pub mod gray;
+#[cfg(feature = "alloc")]
+pub mod grid;
 pub mod hilbert;
+pub mod hindex;
+#[cfg(all(feature = "image", feature = "alloc"))]
+pub mod image;
+pub mod moore;
 pub mod morton;
+#[cfg(all(feature = "ndarray", feature = "alloc"))]
+pub mod ndarray;
+#[cfg(feature = "alloc")]
+pub mod order;
+#[cfg(feature = "rayon")]
+pub mod par;
+pub mod quantize;
+#[cfg(feature = "alloc")]
+mod radix;
+#[cfg(feature = "alloc")]
+pub mod render;
+#[cfg(feature = "alloc")]
+pub mod rtree;
+pub mod sampling;
+pub mod sierpinski;
+pub mod snake;
+pub mod spiral;
+pub mod swizzle;
+pub mod transform;
+pub mod typed;
+
+pub use cell::CellId;
+pub use clip_iter::MaskAction;
+pub use curve::{
+    BoundedHilbert2D, BoundedMorton2D, ClippedCurveIter2D, CurveKind, HIndex2D, Hilbert2D, Moore2D,
+    Morton2D, Sierpinski2D, Snake2D, SpaceFillingCurve2D, Spiral2D,
+};
+pub use curve_uint::CurveUint;
+pub use dilated::Dilated;
+pub use error::Error;
+#[cfg(feature = "alloc")]
+pub use grid::{HilbertGrid, SparseGrid2D};
+pub use neighbors::Boundary;
+#[cfg(feature = "alloc")]
+pub use order::CurveOrder;
+pub use transform::Transform2D;
+pub use typed::{HilbertIndex, MortonIndex, Point2D};
 
 /// Integer type suitable for counting number of bits
 ///
@@ -13,17 +104,46 @@ pub type NumBits = u32;
 ///
 /// Ideally, this crate would be generic over this type, but `const fn`
 /// currently cannot handle this as const traits (and thus a version of
-/// `num-traits` that can be used in const fn) are not yet available.
+/// `num-traits` that can be used in const fn) are not yet available. In the
+/// meantime, the `CurveUint` trait provides non-const genericity over the
+/// supported curve index types.
 ///
+/// By default, this is a 16-bit integer, but a wider type can be selected using
+/// the `idx-u32`, `idx-u64` and `idx-u128` cargo features.
+///
+#[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
 pub type CurveIdx = u16;
+#[cfg(all(
+    feature = "idx-u32",
+    not(any(feature = "idx-u64", feature = "idx-u128"))
+))]
+pub type CurveIdx = u32;
+#[cfg(all(feature = "idx-u64", not(feature = "idx-u128")))]
+pub type CurveIdx = u64;
+#[cfg(feature = "idx-u128")]
+pub type CurveIdx = u128;
 
 /// Coordinate of a point on a space-filling curve
 ///
 /// Ideally, this crate would be generic over this type, but `const fn`
 /// currently cannot handle this as const traits (and thus a version of
-/// `num-traits` that can be used in const fn) are not yet available.
+/// `num-traits` that can be used in const fn) are not yet available. See
+/// `CurveUint::Coordinate` for the non-const alternative.
+///
+/// This type always has half as many bits as `CurveIdx`, and is selected by the
+/// same cargo features.
 ///
+#[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
 pub type Coordinate = u8;
+#[cfg(all(
+    feature = "idx-u32",
+    not(any(feature = "idx-u64", feature = "idx-u128"))
+))]
+pub type Coordinate = u16;
+#[cfg(all(feature = "idx-u64", not(feature = "idx-u128")))]
+pub type Coordinate = u32;
+#[cfg(feature = "idx-u128")]
+pub type Coordinate = u64;
 
 /// Coordinates of a 2D point on a space-filling curve (in x, y order)
 ///
@@ -31,4 +151,4 @@ pub type Coordinate = u8;
 /// const generics, which aren't stable yet, and algorithmic adaptations which I
 /// have not carried out yet as I haven't needed them so far.
 ///
-pub type Coordinates2D = [u8; 2];
+pub type Coordinates2D = [Coordinate; 2];
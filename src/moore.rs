@@ -0,0 +1,256 @@
+//! Utilities related to the Moore space-filling curve
+//!
+//! The Moore curve is the closed-loop variant of the Hilbert curve: it is made
+//! of four Hilbert curves of the next lower order, arranged so that the last
+//! point of the curve is a direct neighbor of the first one. This makes it
+//! suitable for cyclic traversals where the curve index wraps around from
+//! `CurveIdx::MAX` to 0.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+
+/// Generate Moore curve utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! moore_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::{bits::$width as bits, hilbert::$width as hilbert};
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Number of bits in a curve index
+        const IDX_BITS: u32 = CurveIdx::BITS;
+
+        /// Number of bits in a coordinate
+        const COORD_BITS: u32 = Coordinate::BITS;
+
+        /// Side length of one quadrant of the curve's domain, which is also the
+        /// position of the high-order coordinate bit
+        const HALF: Coordinate = 1 << (COORD_BITS - 1);
+
+        /// Compute the coordinates of the i-th point of the Moore curve
+        ///
+        /// The curve's domain is split into four quadrants, each of which is
+        /// covered by a Hilbert curve of the next lower order. They are visited in
+        /// the order lower-left, upper-left, upper-right, lower-right, so the curve
+        /// starts at `[HALF - 1, 0]` and ends at `[HALF, 0]`, where `HALF` is half
+        /// the side length of the domain.
+        ///
+        /// Every point of the curve is a direct neighbor of the next one, and this
+        /// remains true when wrapping around from the last point to the first one.
+        ///
+        /// This function runs in constant time: it contains no branch or memory
+        /// access whose outcome depends on the value of `code`.
+        ///
+        #[inline]
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // The two high-order bits of the curve index select a quadrant, and the
+            // other bits select a point on the lower-order Hilbert curve covering
+            // it. The full-order Hilbert curve's first quadrant covers it with a
+            // lower-order Hilbert curve whose coordinates are swapped, since the
+            // number of coordinate bits is always even, so we swap them back to get
+            // a curve that goes from [0, 0] to [0, HALF - 1].
+            let quadrant = (code >> (IDX_BITS - 2)) as Coordinate;
+            let [y, x] = hilbert::decode_2d(code & bits::low_order_mask(IDX_BITS - 2));
+
+            // Quadrants 2 and 3 are on the right of the domain and visited from top
+            // to bottom, while quadrants 1 and 2 are in the upper half of the
+            // domain. Left quadrants are mirrored horizontally so that they start
+            // and end next to the right quadrants, whereas right quadrants are
+            // mirrored vertically since they are traversed from top to bottom.
+            let right = (quadrant >> 1).wrapping_neg();
+            let upper = ((quadrant ^ (quadrant >> 1)) & 1).wrapping_neg();
+            [
+                (x ^ ((HALF - 1) & !right)) | (HALF & right),
+                (y ^ ((HALF - 1) & right)) | (HALF & upper),
+            ]
+        }
+
+        /// Compute the index of a point on the Moore curve
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        ///
+        /// This function runs in constant time: it contains no branch or memory
+        /// access whose outcome depends on the value of `coords`.
+        ///
+        #[inline]
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            // Find out which quadrant the point belongs to...
+            let [x, y] = coords;
+            let right = x >> (COORD_BITS - 1);
+            let upper = y >> (COORD_BITS - 1);
+            let quadrant = (right << 1) | (upper ^ right);
+
+            // ...then undo the quadrant's mirroring to get the coordinates on the
+            // lower-order Hilbert curve, and encode them as in decode_2d().
+            let right = right.wrapping_neg();
+            let sub_x = (x ^ ((HALF - 1) & !right)) & (HALF - 1);
+            let sub_y = (y ^ ((HALF - 1) & right)) & (HALF - 1);
+            ((quadrant as CurveIdx) << (IDX_BITS - 2)) | hilbert::encode_2d([sub_y, sub_x])
+        }
+
+        decode_iter_impl!("Moore");
+    };
+}
+
+/// Moore curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    moore_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Moore curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    moore_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Moore curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    moore_impl!(idx64, u64, u32);
+}
+
+/// Moore curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    moore_impl!(idx128, u128, u64);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{bits::test_utils::*, Coordinate, CurveIdx};
+    use quickcheck::{Arbitrary, Gen};
+
+    // Check that consecutive points are neighbors, including when wrapping
+    // around from the end of the curve to its start
+    #[test]
+    fn decode_2d() {
+        use super::decode_2d_u16 as decode_2d;
+        assert_eq!(decode_2d(0), [(1 << 7) - 1, 0]);
+        assert_eq!(decode_2d(u16::MAX), [1 << 7, 0]);
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        for code in 0..=u16::MAX {
+            let [x, y] = decode_2d(code);
+            let [next_x, next_y] = decode_2d(code.wrapping_add(1));
+            assert_eq!(
+                (x as i32 - next_x as i32).abs() + (y as i32 - next_y as i32).abs(),
+                1,
+                "Curve jumped from {code} to the next point"
+            );
+            let point_idx = (usize::from(y) << u8::BITS) | usize::from(x);
+            assert!(!visited[point_idx], "Point {code} was visited twice");
+            visited[point_idx] = true;
+        }
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn decode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            CurveIdx::MIN,
+            || CurveIdx::arbitrary(&mut gen),
+            super::decode_2d,
+        );
+    }
+
+    #[test]
+    fn encode_2d() {
+        use super::{decode_2d_u16 as decode_2d, encode_2d_u16 as encode_2d};
+        for code in 0..=u16::MAX {
+            assert_eq!(encode_2d(decode_2d(code)), code);
+        }
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn encode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            [Coordinate::MIN; 2],
+            || <[Coordinate; 2]>::arbitrary(&mut gen),
+            super::encode_2d,
+        );
+    }
+
+    #[test]
+    fn iter_2d() {
+        use super::{decode_2d_u16 as decode_2d, iter_2d_u16 as iter_2d};
+        assert!(iter_2d().eq((0..=u16::MAX).map(decode_2d)));
+        assert_eq!(iter_2d().len(), 1 << 16);
+        assert!(super::iter_range_2d(42..=1234)
+            .rev()
+            .step_by(7)
+            .eq((42..=1234).rev().step_by(7).map(super::decode_2d)));
+        assert_eq!(
+            super::iter_from_2d(CurveIdx::MAX).collect::<Vec<_>>(),
+            vec![super::decode_2d(CurveIdx::MAX)]
+        );
+    }
+
+    // Wider curve index types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty) => {
+            mod $width {
+                use crate::moore::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let [x, y] = $width::decode_2d(code);
+                        let [next_x, next_y] = $width::decode_2d(code.wrapping_add(1));
+                        let distance = x.max(next_x) - x.min(next_x) + y.max(next_y) - y.min(next_y);
+                        distance == 1
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == code
+                    }
+
+                    fn iter_from_2d(code: $curve_idx) -> bool {
+                        $width::iter_from_2d(code)
+                            .zip(code..=<$curve_idx>::MAX)
+                            .take(3)
+                            .all(|(coords, code)| coords == $width::decode_2d(code))
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
@@ -1,106 +1,1916 @@
 //! Utilities related to the Morton space-filling curve
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types. Functions without a suffix use the `CurveIdx` and `Coordinate`
+//! types, while functions with a `_uN` suffix use N-bit curve indices and
+//! N/2-bit coordinates (e.g. `_u64` functions use 64-bit curve indices and
+//! 32-bit coordinates). Supported curve index widths are 16, 32, 64 and 128
+//! bits.
 
 use crate::{bits, Coordinate, Coordinates2D, CurveIdx};
 
-/// Decode an 2-dimensional Morton code into its two inner indices
+/// Generate Morton curve utilities for a certain curve index type
 ///
-/// A Morton code combines two integers with bit patterns [ x1 x2 ... xN ] and
-/// [ y1 y2 ... yN ] into the interleaved bit pattern [ y1 x1 y2 x2 ... yN xN ].
+/// See the `bits` module for an explanation of why this is a macro.
 ///
-/// Decoding the set of Morton codes produces a fractal space-filling curve with
-/// a recuring Z-shaped pattern that has reasonable spatial locality properties,
-/// though it does brutally jump from one area of 2D space to another at times.
+macro_rules! morton_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::{bits::$width as bits, morton::Orientation, NumBits};
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Decode an 2-dimensional Morton code into its two inner indices
+        ///
+        /// A Morton code combines two integers with bit patterns [ x1 x2 ... xN ] and
+        /// [ y1 y2 ... yN ] into the interleaved bit pattern [ y1 x1 y2 x2 ... yN xN ].
+        ///
+        /// Decoding the set of Morton codes produces a fractal space-filling curve with
+        /// a recuring Z-shaped pattern that has reasonable spatial locality properties,
+        /// though it does brutally jump from one area of 2D space to another at times.
+        ///
+        /// This function runs in constant time: it contains no branch or memory access
+        /// whose outcome depends on the value of `code`, which makes it suitable for
+        /// processing secret data (e.g. private locations).
+        ///
+        #[inline]
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // Align the low-order bits of the two input sub-codes:
+            // [ XX x1 XX x2 XX x3 XX x4 ... xN-1   XX xN ]
+            // [ XX y1 XX y2 XX y3 XX y4 ... yN-1   XX yN ]
+            debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
+            let mut sub_codes = [code, code >> 1];
+            let mut sub_code_idx = 0;
+            while sub_code_idx < 2 {
+                // We start with a coordinate's bits interleaved with irrelevant junk:
+                // [ XX a1 XX a2 XX a3 XX a4 ... XX aN-1 XX aN ]
+                // Let's clean that up by zeroing out the junk:
+                // [  0 a1  0 a2  0 a3  0 a4 ...  0 aN-1  0 aN ]
+                let mut sub_code = sub_codes[sub_code_idx] & bits::striped_mask(1);
+                // We will then pack the coordinate's bits together by recursively
+                // grouping them in pairs, groups of 4, and so on.
+                // Initially, bits are isolated, so we have groups of one.
+                // We're done once we have grouped half of the input bits together,
+                // since the other bits will be zero.
+                let mut group_size = 1;
+                while group_size < bits::num_bits::<CurveIdx>() / 2 {
+                    // Duplicate the current bit pattern into neighboring zeroes on the
+                    // right in order to group pairs of subcode bits together
+                    // Iteration 1: [  0 a1 a1 a2 a2 a3 a3 a4 ... aN-2 aN-1 aN-1 aN ]
+                    // Iteration 2: [  0  0 a1 a2 a1 a2 a3 a4 ... aN-3 aN-2 aN-1 aN ]
+                    sub_code |= sub_code >> group_size;
+                    group_size *= 2;
+                    // Only keep the paired bit groups, zeroing out the rest
+                    // Iteration 1: [  0  0 a1 a2  0  0 a3 a4 ...    0    0 aN-1 aN ]
+                    // Iteration 2: [  0  0  0  0 a1 a2 a3 a4 ... aN-3 aN-2 aN-1 aN ]
+                    sub_code &= bits::striped_mask(group_size);
+                }
+                // Record the decoded coordinate and move to the next one
+                sub_codes[sub_code_idx] = sub_code;
+                sub_code_idx += 1;
+            }
+            [sub_codes[0] as _, sub_codes[1] as _]
+        }
+
+        /// Encode two inner indices into a 2-dimensional Morton code
+        ///
+        /// This is the inverse of `decode_2d()`: it combines two integers with bit
+        /// patterns [ x1 x2 ... xN ] and [ y1 y2 ... yN ] into the interleaved bit
+        /// pattern [ y1 x1 y2 x2 ... yN xN ].
+        ///
+        /// Like `decode_2d()`, this function runs in constant time.
+        ///
+        #[inline]
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
+            let mut code = 0;
+            let mut coord_idx = 0;
+            while coord_idx < 2 {
+                // We start with a coordinate's bits packed in the low-order bits:
+                // [  0  0 ...  0  0 a1 a2 a3 a4 ... aN-1 aN ]
+                let mut sub_code = coords[coord_idx] as CurveIdx;
+                // We will then spread the coordinate's bits apart by recursively
+                // splitting them into groups of half the size, until the bits are
+                // isolated. This is the reverse of the Morton decoding process.
+                let mut group_size = bits::num_bits::<CurveIdx>() / 2;
+                while group_size > 1 {
+                    // Duplicate the current bit pattern into neighboring zeroes on the
+                    // left in order to split bit groups in two, with junk in between
+                    // (examples are given for 8-bit coordinates)
+                    // Iteration 1: [  0  0  0  0 a1 a2 a3 a4 XX XX XX XX a5 a6 a7 a8 ]
+                    // Iteration 2: [  0  0 a1 a2 XX XX a3 a4 XX XX a5 a6 XX XX a7 a8 ]
+                    group_size /= 2;
+                    sub_code |= sub_code << group_size;
+                    // Only keep the split bit groups, zeroing out the junk
+                    // Iteration 1: [  0  0  0  0 a1 a2 a3 a4  0  0  0  0 a5 a6 a7 a8 ]
+                    // Iteration 2: [  0  0 a1 a2  0  0 a3 a4  0  0 a5 a6  0  0 a7 a8 ]
+                    sub_code &= bits::striped_mask(group_size);
+                }
+                // Merge the spread-out coordinate into the Morton code: after this
+                // process, its bits are [  0 a1  0 a2 ...  0 aN ], and the second
+                // coordinate must go to the odd bits of the Morton code.
+                code |= sub_code << coord_idx;
+                coord_idx += 1;
+            }
+            code
+        }
+
+        /// Decode a 4-dimensional Morton code into its four inner indices
+        ///
+        /// This is the 4D counterpart of `decode_2d()`, meant for e.g. indexing
+        /// points of spacetime: it splits the bit pattern
+        /// [ t1 z1 y1 x1 t2 z2 y2 x2 ... tN zN yN xN ] into four integers with bit
+        /// patterns [ x1 x2 ... xN ], [ y1 y2 ... yN ], [ z1 z2 ... zN ] and
+        /// [ t1 t2 ... tN ], each of which has a quarter of the bits of
+        /// `CurveIdx`.
+        ///
+        /// This is done by decoding the 2D Morton code twice, so it also runs in
+        /// constant time.
+        ///
+        #[inline]
+        pub const fn decode_4d(code: CurveIdx) -> [Coordinate; 4] {
+            // Splitting even and odd bits gives the [ z1 x1 ... zN xN ] and
+            // [ t1 y1 ... tN yN ] bit patterns, which are 2D Morton codes again
+            let [xz, yt] = decode_2d(code);
+            let [x, z] = decode_2d(xz as CurveIdx);
+            let [y, t] = decode_2d(yt as CurveIdx);
+            [x, y, z, t]
+        }
+
+        /// Encode four inner indices into a 4-dimensional Morton code
+        ///
+        /// This is the inverse of `decode_4d()`. The high-order bits of the
+        /// coordinates, beyond the quarter of the bits of `CurveIdx`, must be zero.
+        ///
+        /// Like `decode_4d()`, this function runs in constant time.
+        ///
+        #[inline]
+        pub const fn encode_4d(coords: [Coordinate; 4]) -> CurveIdx {
+            let [x, y, z, t] = coords;
+            debug_assert!(
+                ((x | y | z | t) as CurveIdx) >> (bits::num_bits::<CurveIdx>() / 4) == 0
+            );
+            let xz = encode_2d([x, z]);
+            let yt = encode_2d([y, t]);
+            encode_2d([xz as Coordinate, yt as Coordinate])
+        }
+
+        /// Like `encode_2d()`, but restricted to the first 4^`domain_bits` points
+        /// of the curve, which cover the square of 2^`domain_bits` x
+        /// 2^`domain_bits` points at the origin
+        ///
+        /// Returns `Error::CoordinateOutOfDomain` if `coords` is outside of this
+        /// square, or `Error::UnsupportedOrder` if the square does not fit in the
+        /// curve's domain. This is meant for coordinates that come from untrusted
+        /// input, such as network requests.
+        ///
+        #[inline]
+        pub const fn try_encode_2d(
+            coords: Coordinates2D,
+            domain_bits: NumBits,
+        ) -> Result<CurveIdx, $crate::Error> {
+            if domain_bits > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if (coords[0] | coords[1]) as CurveIdx > bits::low_order_mask(domain_bits) {
+                return Err($crate::Error::CoordinateOutOfDomain);
+            }
+            Ok(encode_2d(coords))
+        }
+
+        /// Like `decode_2d()`, but restricted to the first 4^`domain_bits` points
+        /// of the curve
+        ///
+        /// Returns `Error::IndexOutOfDomain` if `code` is not smaller than
+        /// 4^`domain_bits`, or `Error::UnsupportedOrder` if the square of
+        /// 2^`domain_bits` x 2^`domain_bits` points does not fit in the curve's
+        /// domain. This is the inverse of `try_encode_2d()`.
+        ///
+        #[inline]
+        pub const fn try_decode_2d(
+            code: CurveIdx,
+            domain_bits: NumBits,
+        ) -> Result<Coordinates2D, $crate::Error> {
+            if domain_bits > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            if code > bits::low_order_mask(2 * domain_bits) {
+                return Err($crate::Error::IndexOutOfDomain);
+            }
+            Ok(decode_2d(code))
+        }
+
+        /// Decode a Morton code using a certain bit layout
+        ///
+        /// This is `decode_2d()` followed by the coordinate transform described by
+        /// `orientation`, which lets Morton codes produced by other software be
+        /// decoded even if they interleave coordinates the other way around, or
+        /// count them from the opposite edge of the domain. Like `decode_2d()`, it
+        /// runs in constant time.
+        ///
+        #[inline]
+        pub const fn decode_2d_oriented(code: CurveIdx, orientation: Orientation) -> Coordinates2D {
+            mirror(swap(decode_2d(code), orientation), orientation)
+        }
+
+        /// Encode two inner indices into a Morton code using a certain bit layout
+        ///
+        /// This is the inverse of `decode_2d_oriented()`, and it also runs in
+        /// constant time.
+        ///
+        #[inline]
+        pub const fn encode_2d_oriented(coords: Coordinates2D, orientation: Orientation) -> CurveIdx {
+            encode_2d(swap(mirror(coords, orientation), orientation))
+        }
+
+        /// Decode one of the first 4^`order` Morton codes, which cover a
+        /// 2^`order` x 2^`order` domain, and transform the resulting point by a
+        /// symmetry of this domain
+        ///
+        /// This is `decode_2d()` followed by `transform.apply()`, and it also runs
+        /// in constant time.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`, or if
+        /// `code` is not smaller than 4^`order`.
+        ///
+        #[inline]
+        pub const fn decode_2d_transformed(
+            code: CurveIdx,
+            order: NumBits,
+            transform: $crate::Transform2D,
+        ) -> Coordinates2D {
+            match try_decode_2d(code, order) {
+                Ok(coords) => transform_within(coords, order, transform),
+                Err($crate::Error::UnsupportedOrder) => panic!("Order is too high for this curve"),
+                Err(_) => panic!("Curve index is out of range for this order"),
+            }
+        }
+
+        /// Encode a point of a 2^`order` x 2^`order` domain into a Morton code,
+        /// after undoing a symmetry of this domain
+        ///
+        /// This is the inverse of `decode_2d_transformed()`.
+        ///
+        /// # Panics
+        ///
+        /// If `order` is larger than the number of bits of `Coordinate`, or if
+        /// `coords` are not smaller than 2^`order`.
+        ///
+        #[inline]
+        pub const fn encode_2d_transformed(
+            coords: Coordinates2D,
+            order: NumBits,
+            transform: $crate::Transform2D,
+        ) -> CurveIdx {
+            if order > Coordinate::BITS {
+                panic!("Order is too high for this curve");
+            }
+            // Mirroring keeps the bits above the mask, so coordinates that are out
+            // of range stay out of range
+            let coords = transform_within(coords, order, transform.inverse());
+            match try_encode_2d(coords, order) {
+                Ok(code) => code,
+                Err(_) => panic!("Coordinates are out of range for this order"),
+            }
+        }
+
+        /// Apply a `Transform2D` to a point of a 2^`order` x 2^`order` domain
+        #[inline(always)]
+        const fn transform_within(
+            coords: Coordinates2D,
+            order: NumBits,
+            transform: $crate::Transform2D,
+        ) -> Coordinates2D {
+            let [x, y] = swap(
+                coords,
+                Orientation {
+                    swap: transform.swap,
+                    ..Orientation::Z
+                },
+            );
+            let mask = bits::low_order_mask(order) as Coordinate;
+            [
+                x ^ (mask & (transform.mirror_x as Coordinate).wrapping_neg()),
+                y ^ (mask & (transform.mirror_y as Coordinate).wrapping_neg()),
+            ]
+        }
+
+        /// Swap coordinates if an `Orientation` says so, without branching
+        #[inline(always)]
+        const fn swap(coords: Coordinates2D, orientation: Orientation) -> Coordinates2D {
+            let [x, y] = coords;
+            let diff = (x ^ y) & (orientation.swap as Coordinate).wrapping_neg();
+            [x ^ diff, y ^ diff]
+        }
+
+        /// Mirror coordinates if an `Orientation` says so, without branching
+        #[inline(always)]
+        const fn mirror(coords: Coordinates2D, orientation: Orientation) -> Coordinates2D {
+            let [x, y] = coords;
+            [
+                x ^ (orientation.mirror_x as Coordinate).wrapping_neg(),
+                y ^ (orientation.mirror_y as Coordinate).wrapping_neg(),
+            ]
+        }
+
+        /// Compute the aligned square of the domain that the curve goes through at
+        /// the same time as the point of index `code`, at a certain recursion level
+        ///
+        /// At level L, the curve goes through 4^L aligned squares of side
+        /// 2^(N - L), where N is the number of bits of `Coordinate`, and the curve
+        /// indices within each square share the same 2 x L high-order bits. In a
+        /// quadtree that recursively splits the domain into 4 squares, these are
+        /// the cells at depth L.
+        ///
+        /// The square is returned as the coordinates of its lower corner, along
+        /// with its side length. It contains the points of curve indices
+        /// `code & !mask` to `code | mask`, where `mask` is 4^(N - L) - 1.
+        ///
+        /// # Panics
+        ///
+        /// If `level` is larger than the number of bits of `Coordinate`. See
+        /// `try_cell_2d()` for a non-panicking alternative.
+        ///
+        #[inline]
+        pub const fn cell_2d(code: CurveIdx, level: NumBits) -> (Coordinates2D, CurveIdx) {
+            match try_cell_2d(code, level) {
+                Ok(cell) => cell,
+                Err(_) => panic!("Level is too deep for this curve"),
+            }
+        }
+
+        /// Like `cell_2d()`, but returns `Error::UnsupportedOrder` if `level` is
+        /// larger than the number of bits of `Coordinate`
+        #[inline]
+        pub const fn try_cell_2d(
+            code: CurveIdx,
+            level: NumBits,
+        ) -> Result<(Coordinates2D, CurveIdx), $crate::Error> {
+            if level > Coordinate::BITS {
+                return Err($crate::Error::UnsupportedOrder);
+            }
+            let side_bits = Coordinate::BITS - level;
+            let [x, y] = decode_2d(code);
+            let low_mask = bits::low_order_mask(side_bits) as Coordinate;
+            Ok(([x & !low_mask, y & !low_mask], 1 << side_bits))
+        }
+
+        /// Add an offset to the x coordinate of a point, given and returned as a
+        /// Morton code
+        ///
+        /// This is equivalent to `encode_2d([x.wrapping_add(dx), y])` where
+        /// `[x, y] = decode_2d(code)`, but it operates directly on the Morton code
+        /// using dilated integer arithmetic: the bits of y are set so that carries
+        /// propagate through them, then the bits of x are extracted from the sum.
+        /// Like `Coordinate` arithmetic, it wraps around the edges of the domain.
+        ///
+        #[inline]
+        pub const fn add_x(code: CurveIdx, dx: Coordinate) -> CurveIdx {
+            dilated_add(code, encode_2d([dx, 0]), bits::striped_mask(1))
+        }
+
+        /// Subtract an offset from the x coordinate of a point, given and returned
+        /// as a Morton code
+        ///
+        /// This is the inverse of `add_x()`, and it also wraps around the edges of
+        /// the domain.
+        ///
+        #[inline]
+        pub const fn sub_x(code: CurveIdx, dx: Coordinate) -> CurveIdx {
+            dilated_sub(code, encode_2d([dx, 0]), bits::striped_mask(1))
+        }
+
+        /// Add an offset to the y coordinate of a point, given and returned as a
+        /// Morton code
+        ///
+        /// This is the y counterpart of `add_x()`.
+        ///
+        #[inline]
+        pub const fn add_y(code: CurveIdx, dy: Coordinate) -> CurveIdx {
+            dilated_add(code, encode_2d([0, dy]), bits::striped_mask(1) << 1)
+        }
+
+        /// Subtract an offset from the y coordinate of a point, given and returned
+        /// as a Morton code
+        ///
+        /// This is the y counterpart of `sub_x()`.
+        ///
+        #[inline]
+        pub const fn sub_y(code: CurveIdx, dy: Coordinate) -> CurveIdx {
+            dilated_sub(code, encode_2d([0, dy]), bits::striped_mask(1) << 1)
+        }
+
+        /// Add a dilated offset to the bits of a Morton code that are selected by
+        /// `axis_mask`, leaving the other bits alone
+        #[inline(always)]
+        const fn dilated_add(code: CurveIdx, offset: CurveIdx, axis_mask: CurveIdx) -> CurveIdx {
+            // Carries propagate through the bits of the other axis once they are set
+            let sum = (code | !axis_mask).wrapping_add(offset);
+            (sum & axis_mask) | (code & !axis_mask)
+        }
+
+        /// Subtract a dilated offset from the bits of a Morton code that are
+        /// selected by `axis_mask`, leaving the other bits alone
+        #[inline(always)]
+        const fn dilated_sub(code: CurveIdx, offset: CurveIdx, axis_mask: CurveIdx) -> CurveIdx {
+            // Borrows propagate through the bits of the other axis once they are
+            // cleared
+            let difference = (code & axis_mask).wrapping_sub(offset);
+            (difference & axis_mask) | (code & !axis_mask)
+        }
+
+        /// Encode two coordinates into the index of a generalized-radix Lebesgue
+        /// curve, which interleaves them in digits of `digit_bits` bits
+        ///
+        /// The Morton curve interleaves coordinates one bit at a time, which is
+        /// the special case where `digit_bits` is 1. Coarser digits produce
+        /// Z-order curves of higher radix, e.g. base-4 Z-order for 2-bit digits
+        /// or base-8 Z-order for 3-bit digits, down to the row-major order that
+        /// `digit_bits` equal to the number of bits of `Coordinate` yields. Their
+        /// locality is worse, but they only take one loop iteration per digit
+        /// to encode and decode, and some on-disk layouts use them.
+        ///
+        /// As with `encode_2d()`, digits of x come right below the digits of y of
+        /// the same order. If `digit_bits` does not divide the number of bits of
+        /// `Coordinate`, the highest-order digits are truncated to the bits that
+        /// are left, so that every curve index is used.
+        ///
+        /// # Panics
+        ///
+        /// If `digit_bits` is zero or larger than the number of bits of
+        /// `Coordinate`.
+        ///
+        #[inline]
+        pub const fn encode_2d_radix(coords: Coordinates2D, digit_bits: NumBits) -> CurveIdx {
+            check_digit_bits(digit_bits);
+            let mut code = 0;
+            let mut shift = 0;
+            while shift < Coordinate::BITS {
+                let (mask, width) = digit_mask_and_width(shift, digit_bits);
+                let x = (coords[0] >> shift) as CurveIdx & mask;
+                let y = (coords[1] >> shift) as CurveIdx & mask;
+                code |= (x | (y << width)) << (2 * shift);
+                shift += digit_bits;
+            }
+            code
+        }
+
+        /// Decode the index of a generalized-radix Lebesgue curve into two
+        /// coordinates
+        ///
+        /// This is the inverse of `encode_2d_radix()`, see its documentation for
+        /// details.
+        ///
+        /// # Panics
+        ///
+        /// If `digit_bits` is zero or larger than the number of bits of
+        /// `Coordinate`.
+        ///
+        #[inline]
+        pub const fn decode_2d_radix(code: CurveIdx, digit_bits: NumBits) -> Coordinates2D {
+            check_digit_bits(digit_bits);
+            let mut coords: Coordinates2D = [0; 2];
+            let mut shift = 0;
+            while shift < Coordinate::BITS {
+                let (mask, width) = digit_mask_and_width(shift, digit_bits);
+                let digits = code >> (2 * shift);
+                coords[0] |= ((digits & mask) as Coordinate) << shift;
+                coords[1] |= (((digits >> width) & mask) as Coordinate) << shift;
+                shift += digit_bits;
+            }
+            coords
+        }
+
+        /// Check the digit length of `encode_2d_radix()` and `decode_2d_radix()`
+        #[inline(always)]
+        const fn check_digit_bits(digit_bits: NumBits) {
+            assert!(
+                digit_bits != 0 && digit_bits <= Coordinate::BITS,
+                "Digits must be nonzero and fit in a coordinate"
+            );
+        }
+
+        /// Mask and number of bits of the coordinate digit that starts at a
+        /// certain bit, which is truncated if it is the highest-order digit
+        #[inline(always)]
+        const fn digit_mask_and_width(shift: NumBits, digit_bits: NumBits) -> (CurveIdx, NumBits) {
+            let remaining_bits = Coordinate::BITS - shift;
+            let width = if digit_bits < remaining_bits {
+                digit_bits
+            } else {
+                remaining_bits
+            };
+            (bits::low_order_mask(width), width)
+        }
+
+        /// Compare two points according to the order of their Morton codes
+        ///
+        /// This is equivalent to comparing `encode_2d(a)` with `encode_2d(b)`, but
+        /// does not compute the Morton codes. Instead, it finds out which
+        /// coordinate has the highest-order differing bit, using the fact that
+        /// for two integers u and v, the highest-order bit of u is lower than that
+        /// of v if and only if u < v and u < u XOR v.
+        ///
+        /// This is faster than encoding the points, and the same technique can
+        /// compare points whose coordinates are too wide for their Morton codes to
+        /// fit in any integer type.
+        ///
+        #[inline]
+        pub const fn cmp_2d(a: Coordinates2D, b: Coordinates2D) -> core::cmp::Ordering {
+            // In a Morton code, each y bit comes right above the x bit of the same
+            // order, so y decides unless x has a higher-order differing bit
+            let x_diff = a[0] ^ b[0];
+            let y_diff = a[1] ^ b[1];
+            let x_decides = y_diff < x_diff && y_diff < (x_diff ^ y_diff);
+            let dim = if x_decides { 0 } else { 1 };
+            if a[dim] < b[dim] {
+                core::cmp::Ordering::Less
+            } else if a[dim] > b[dim] {
+                core::cmp::Ordering::Greater
+            } else {
+                core::cmp::Ordering::Equal
+            }
+        }
+
+        /// Decode a batch of curve indices using SIMD instructions
+        ///
+        /// This is equivalent to calling `decode_2d()` on each curve index, but the
+        /// decoding loop is compiled so that multiple curve indices are processed
+        /// at once, using the widest SIMD instruction set that the CPU supports.
+        /// Like `decode_2d()`, this runs in constant time.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn decode_2d_simd(codes: &[CurveIdx]) -> alloc::vec::Vec<Coordinates2D> {
+            let mut coords = alloc::vec![[0; 2]; codes.len()];
+            decode_2d_into(codes, &mut coords);
+            coords
+        }
+
+        /// Decode a batch of curve indices into a caller-provided buffer
+        ///
+        /// This is the non-allocating version of `decode_2d_simd()`, for use in
+        /// loops that can reuse the same output buffer.
+        ///
+        /// # Panics
+        ///
+        /// If `codes` and `coords` do not have the same length.
+        ///
+        pub fn decode_2d_into(codes: &[CurveIdx], coords: &mut [Coordinates2D]) {
+            $crate::batch::map(codes, coords, decode_2d);
+        }
+
+        /// Encode a batch of points into a caller-provided buffer
+        ///
+        /// This is equivalent to calling `encode_2d()` on each point, but uses
+        /// SIMD instructions like `decode_2d_simd()`.
+        ///
+        /// # Panics
+        ///
+        /// If `coords` and `codes` do not have the same length.
+        ///
+        pub fn encode_2d_into(coords: &[Coordinates2D], codes: &mut [CurveIdx]) {
+            $crate::batch::map(coords, codes, encode_2d);
+        }
+
+        /// Variant of `decode_2d_into()` that writes the x and y coordinates into
+        /// separate buffers (structure-of-arrays layout)
+        ///
+        /// # Panics
+        ///
+        /// If `codes`, `xs` and `ys` do not have the same length.
+        ///
+        pub fn decode_2d_into_soa(
+            codes: &[CurveIdx],
+            xs: &mut [Coordinate],
+            ys: &mut [Coordinate],
+        ) {
+            $crate::batch::map_to_soa(codes, [xs, ys], decode_2d);
+        }
+
+        /// Variant of `encode_2d_into()` that reads the x and y coordinates from
+        /// separate buffers (structure-of-arrays layout)
+        ///
+        /// # Panics
+        ///
+        /// If `xs`, `ys` and `codes` do not have the same length.
+        ///
+        pub fn encode_2d_into_soa(xs: &[Coordinate], ys: &[Coordinate], codes: &mut [CurveIdx]) {
+            $crate::batch::map_from_soa([xs, ys], codes, encode_2d);
+        }
+
+        /// Encode a large batch of points into a caller-provided buffer
+        ///
+        /// This is equivalent to `encode_2d_into()`, but when the `rayon` feature
+        /// is enabled, the batch is split into chunks that are encoded in
+        /// parallel. This is the fastest way to encode e.g. a point cloud with
+        /// millions of points.
+        ///
+        /// # Panics
+        ///
+        /// If `coords` and `codes` do not have the same length.
+        ///
+        pub fn encode_bulk(coords: &[Coordinates2D], codes: &mut [CurveIdx]) {
+            $crate::batch::map_bulk(coords, codes, encode_2d);
+        }
+
+        /// Encode a stream of points, passing the resulting curve indices to
+        /// `sink` in chunks
+        ///
+        /// Points are gathered into fixed-size chunks, which are encoded using
+        /// SIMD instructions like `encode_2d_into()`, so this is much faster than
+        /// encoding the points of an iterator one by one. Chunks are passed to
+        /// `sink` in order, and all of them but the last one have the same length.
+        ///
+        pub fn encode_stream(
+            coords: impl IntoIterator<Item = Coordinates2D>,
+            sink: impl FnMut(&[CurveIdx]),
+        ) {
+            $crate::batch::map_stream(coords, sink, encode_2d);
+        }
+
+        /// Sort items by the Morton code of their coordinates
+        ///
+        /// `key` extracts the coordinates of an item, and is called only once per
+        /// item since the resulting curve indices are cached. Like
+        /// `slice::sort_by_cached_key()`, which it is built upon, this sort is
+        /// stable and allocates a temporary buffer.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn sort_by_key<T>(items: &mut [T], key: impl Fn(&T) -> Coordinates2D) {
+            items.sort_by_cached_key(|item| encode_2d(key(item)));
+        }
+
+        /// Sort points in Morton curve order, using a radix sort
+        ///
+        /// This computes the Morton code of every point, sorts these with a radix
+        /// sort, which takes linear time, and decodes them back into points.
+        /// Unlike `sort_by_key()`, it only sorts points, not items that have
+        /// coordinates, but its running time scales better with the number of
+        /// points, so it is usually faster for large datasets. Check the
+        /// `morton sort` benchmark on your hardware to find out. It allocates
+        /// temporary buffers for two curve indices per point.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn sort_radix(points: &mut [Coordinates2D]) {
+            let mut codes = alloc::vec![0; points.len()];
+            encode_2d_into(points, &mut codes);
+            $crate::radix::sort(&mut codes);
+            decode_2d_into(&codes, points);
+        }
+
+        /// Build a table of the coordinates of the first `LEN` points of the curve
+        ///
+        /// This is meant to be used in const context, in order to bake a table of
+        /// curve points into the binary at compile time, e.g.
+        /// `const TABLE: [Coordinates2D; 256] = build_table();`. `LEN` must not be
+        /// larger than the number of points of the curve.
+        ///
+        pub const fn build_table<const LEN: usize>() -> [Coordinates2D; LEN] {
+            assert!(LEN == 0 || LEN - 1 <= CurveIdx::MAX as usize);
+            let mut table = [[0; 2]; LEN];
+            let mut idx = 0;
+            while idx < LEN {
+                table[idx] = decode_2d(idx as CurveIdx);
+                idx += 1;
+            }
+            table
+        }
+
+        /// Variant of `decode_2d()` that uses a lookup table
+        ///
+        /// This decodes the curve index one byte at a time using a 256-byte table,
+        /// which can be faster than the bitwise algorithm of `decode_2d()` on
+        /// hardware where memory accesses are cheap and shifts are expensive,
+        /// such as some microcontrollers. Since the table is indexed by the curve
+        /// index, this function does not run in constant time.
+        ///
+        #[inline]
+        pub fn decode_2d_lut(code: CurveIdx) -> Coordinates2D {
+            let mut coords: Coordinates2D = [0; 2];
+            let mut shift = bits::num_bits::<CurveIdx>();
+            while shift > 0 {
+                shift -= 8;
+                let entry = super::DECODE_LUT[((code >> shift) & 0xFF) as usize];
+                coords[0] = (coords[0] << 4) | (entry & 0xF) as Coordinate;
+                coords[1] = (coords[1] << 4) | (entry >> 4) as Coordinate;
+            }
+            coords
+        }
+
+        /// Variant of `decode_2d()` that uses hardware acceleration if available
+        ///
+        /// On x86_64 CPUs that support the BMI2 instruction set extension, Morton
+        /// decoding can be done using one PEXT instruction per coordinate, which is
+        /// much faster than the portable bitwise algorithm of `decode_2d()`. This
+        /// function checks for BMI2 support at runtime and falls back to
+        /// `decode_2d()` if it is not available.
+        ///
+        /// Unlike `decode_2d()`, this function is not `const fn`. Further, it does
+        /// not run in constant time on all CPUs, since some implementations of
+        /// BMI2 (e.g. AMD Zen 1 and 2) have a data-dependent execution time. And on
+        /// such CPUs, it is actually slower than `decode_2d()`.
+        ///
+        #[inline]
+        pub fn decode_2d_fast(code: CurveIdx) -> Coordinates2D {
+            #[cfg(target_arch = "x86_64")]
+            {
+                if $crate::arch::has_bmi2() {
+                    // Safe because we just checked that the CPU supports BMI2
+                    return unsafe { decode_2d_bmi2(code) };
+                }
+            }
+            decode_2d(code)
+        }
+
+        /// Implementation of `decode_2d_fast()` for CPUs with BMI2 support
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "bmi2")]
+        unsafe fn decode_2d_bmi2(code: CurveIdx) -> Coordinates2D {
+            let x_mask = bits::striped_mask(1);
+            [
+                bits::pext(code, x_mask) as Coordinate,
+                bits::pext(code, !x_mask) as Coordinate,
+            ]
+        }
+
+        /// Variant of `encode_2d()` that uses hardware acceleration if available
+        ///
+        /// This uses one PDEP instruction per coordinate on x86_64 CPUs that
+        /// support BMI2, see `decode_2d_fast()` for details and caveats.
+        ///
+        #[inline]
+        pub fn encode_2d_fast(coords: Coordinates2D) -> CurveIdx {
+            #[cfg(target_arch = "x86_64")]
+            {
+                if $crate::arch::has_bmi2() {
+                    // Safe because we just checked that the CPU supports BMI2
+                    return unsafe { encode_2d_bmi2(coords) };
+                }
+            }
+            encode_2d(coords)
+        }
+
+        /// Implementation of `encode_2d_fast()` for CPUs with BMI2 support
+        #[cfg(target_arch = "x86_64")]
+        #[target_feature(enable = "bmi2")]
+        unsafe fn encode_2d_bmi2(coords: Coordinates2D) -> CurveIdx {
+            let x_mask = bits::striped_mask(1);
+            bits::pdep(coords[0] as CurveIdx, x_mask) | bits::pdep(coords[1] as CurveIdx, !x_mask)
+        }
+
+        /// Iterate over the 2D Morton curve
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of all possible
+        /// curve indices (CurveIdx::MIN..=CurveIdx), but a bit more efficient.
+        ///
+        pub fn iter_2d() -> Iter2D {
+            iter_from_2d(CurveIdx::MIN)
+        }
+
+        /// Iterate over the 2D Morton curve, starting from a certain index
+        ///
+        /// This is equivalent to running `decode_2d()` on the sequence of curve
+        /// indices (start..=CurveIdx), but should be a bit more efficient.
+        ///
+        pub fn iter_from_2d(start: CurveIdx) -> Iter2D {
+            iter_range_2d(start..)
+        }
+
+        /// Iterate over a range of indices of the 2D Morton curve
+        ///
+        /// This is equivalent to running `decode_2d()` on the range of curve
+        /// indices, but should be a bit more efficient.
+        ///
+        pub fn iter_range_2d(indices: impl core::ops::RangeBounds<CurveIdx>) -> Iter2D {
+            let indices = bits::inclusive_range(indices);
+            if indices.is_empty() {
+                return Iter2D {
+                    coords: [0; 2],
+                    back_coords: [0; 2],
+                    indices,
+                };
+            }
+            Iter2D {
+                coords: decode_2d(*indices.start()),
+                back_coords: decode_2d(*indices.end()),
+                indices,
+            }
+        }
+
+        /// Iterate over the discontinuities of the 2D Morton curve
+        ///
+        /// Only half of the steps of the Morton curve go to a horizontal
+        /// neighbor: the others jump to a point that is not a direct neighbor of
+        /// the previous one. These jumps occur after the curve indices whose
+        /// `level` low-order bits are set, where `level` is at least 1, and the
+        /// curve then moves between the two halves of an aligned block of
+        /// 2^(`level` + 1) points. At even levels 2k, these are squares of side
+        /// 2^k that are side by side, and the curve moves by +1 along x and by
+        /// -(2^k - 1) along y. At odd levels 2k + 1, they are rectangles of
+        /// 2^(k + 1) x 2^k points on top of each other, and the curve moves by
+        /// -(2^(k + 1) - 1) along x and by +1 along y.
+        ///
+        /// This iterator yields the jumps whose level is at least `min_level`, in
+        /// curve order, without decoding the point that follows each jump.
+        ///
+        pub fn iter_jumps_2d(min_level: NumBits) -> Jumps2D {
+            let min_level = min_level.max(1);
+            let num_blocks = CurveIdx::MAX.checked_shr(min_level).unwrap_or(0);
+            Jumps2D {
+                blocks: 0..num_blocks,
+                min_level,
+            }
+        }
+
+        /// Discontinuity of the 2D Morton curve, yielded by `iter_jumps_2d()`
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub struct Jump2D {
+            /// Curve index of the point before the jump
+            pub index: CurveIdx,
+
+            /// Point before the jump
+            pub from: Coordinates2D,
+
+            /// Point after the jump, whose curve index is `index + 1`
+            pub to: Coordinates2D,
+
+            /// Number of trailing ones of `index`, see `iter_jumps_2d()`
+            pub level: NumBits,
+        }
+        //
+        impl Jump2D {
+            /// Describe the jump that follows a certain curve index
+            #[inline]
+            fn after(index: CurveIdx) -> Self {
+                let level = index.trailing_ones();
+                let from = decode_2d(index);
+                let half_side_bits = level / 2;
+                let to = if level % 2 == 0 {
+                    let dy = bits::low_order_mask(half_side_bits) as Coordinate;
+                    [from[0] + 1, from[1] - dy]
+                } else {
+                    let dx = bits::low_order_mask(half_side_bits + 1) as Coordinate;
+                    [from[0] - dx, from[1] + 1]
+                };
+                Self {
+                    index,
+                    from,
+                    to,
+                    level,
+                }
+            }
+        }
+
+        /// Iterator over the discontinuities of the 2D Morton curve, created by
+        /// `iter_jumps_2d()`
+        #[derive(Clone, Debug)]
+        pub struct Jumps2D {
+            /// Numbers of the remaining aligned blocks of 2^min_level points,
+            /// each of which is followed by a jump, except for the last block of
+            /// the curve
+            blocks: core::ops::Range<CurveIdx>,
+
+            /// Minimal level of the jumps
+            min_level: NumBits,
+        }
+        //
+        impl Jumps2D {
+            /// Describe the jump that follows a certain block
+            #[inline]
+            fn jump_after(&self, block: CurveIdx) -> Jump2D {
+                let mask = bits::low_order_mask(self.min_level);
+                Jump2D::after((block << self.min_level) | mask)
+            }
+        }
+        //
+        impl Iterator for Jumps2D {
+            type Item = Jump2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Jump2D> {
+                let block = self.blocks.next()?;
+                Some(self.jump_after(block))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.blocks.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Jumps2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Jump2D> {
+                let block = self.blocks.next_back()?;
+                Some(self.jump_after(block))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Jumps2D {}
+
+        /// Iterate over the 2D Morton curve in parallel
+        ///
+        /// This is the parallel version of `iter_2d()`, see the `par` module for
+        /// details. It panics if the curve has more points than `usize` can count.
+        ///
+        #[cfg(feature = "rayon")]
+        pub fn par_iter_2d() -> $crate::par::ParIter2D<CurveIdx, Iter2D> {
+            par_iter_range_2d(CurveIdx::MIN..=CurveIdx::MAX)
+        }
+
+        /// Iterate over a range of indices of the 2D Morton curve in parallel
+        ///
+        /// This is equivalent to running `decode_2d()` on the range of curve
+        /// indices in parallel, but more efficient. It panics if the range has more
+        /// points than `usize` can count.
+        ///
+        #[cfg(feature = "rayon")]
+        pub fn par_iter_range_2d(
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        ) -> $crate::par::ParIter2D<CurveIdx, Iter2D> {
+            $crate::par::ParIter2D::new(indices, decode_2d, iter_from_2d)
+        }
+
+        /// Iterator over the 2D Morton curve, created by `iter_2d()`,
+        /// `iter_from_2d()` and `iter_range_2d()`
+        ///
+        /// This iterator can also go through the curve backwards, using the same
+        /// incremental algorithm as when going forward. Its length is exactly known
+        /// for 16-bit curve indices, and for 32-bit curve indices on 64-bit
+        /// targets, where `ExactSizeIterator` is implemented.
+        ///
+        #[derive(Clone, Debug)]
+        pub struct Iter2D {
+            /// Coordinates of the next point from the front
+            coords: Coordinates2D,
+
+            /// Coordinates of the next point from the back
+            back_coords: Coordinates2D,
+
+            /// Indices of the remaining points
+            indices: core::ops::RangeInclusive<CurveIdx>,
+        }
+        //
+        impl Iter2D {
+            /// Also yield the curve index of each point, as `(index, coordinates)`
+            #[inline]
+            pub fn indexed(self) -> IndexedIter2D {
+                IndexedIter2D { iter: self }
+            }
+
+            /// Propagate the bit flips of a curve index change to coordinates
+            #[inline(always)]
+            fn flip(coords: &mut Coordinates2D, flipped_bits: CurveIdx) {
+                // The flipped bits will be spread out ~evenly across "even" bits (which
+                // represent the second coordinate of the Morton code) and "odd" bits
+                // (which represent the first coordinate), with the extra flipped bit
+                // going to the first coordinate when a tie occurs.
+                let num_flipped_bits = flipped_bits.trailing_ones();
+                let num_flipped_even = num_flipped_bits / 2;
+                let num_flipped_odd = num_flipped_bits - num_flipped_even;
+
+                // From this we can propagate the bit-flipping changes of the index
+                // increment to the Morton indices without redoing Morton code decoding.
+                coords[0] ^= (flipped_bits >> num_flipped_even) as Coordinate;
+                coords[1] ^= (flipped_bits >> num_flipped_odd) as Coordinate;
+            }
+        }
+        //
+        impl Iterator for Iter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                let idx = self.indices.next()?;
+
+                // We'll return the current coordinates after preparing the next ones
+                let result = self.coords;
+
+                // In binary, incrementing an integer flips a row of low-order bits.
+                // Check which of the curve index's bits will be flipped next time.
+                Self::flip(&mut self.coords, idx ^ idx.wrapping_add(1));
+                Some(result)
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<Coordinates2D> {
+                // Skipping points is faster done by decoding the curve index that
+                // we're jumping to than by flipping bits once per skipped point.
+                if n > 0 {
+                    let idx = self.indices.nth(n - 1)?;
+                    if idx != CurveIdx::MAX {
+                        self.coords = decode_2d(idx + 1);
+                    }
+                }
+                self.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.indices.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for Iter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Coordinates2D> {
+                let idx = self.indices.next_back()?;
+
+                // Decrementing an integer also flips a row of low-order bits
+                let result = self.back_coords;
+                Self::flip(&mut self.back_coords, idx ^ idx.wrapping_sub(1));
+                Some(result)
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<Coordinates2D> {
+                if n > 0 {
+                    let idx = self.indices.nth_back(n - 1)?;
+                    if idx != CurveIdx::MIN {
+                        self.back_coords = decode_2d(idx - 1);
+                    }
+                }
+                self.next_back()
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Iter2D {}
+
+        /// Iterator over the 2D Morton curve that also yields curve indices,
+        /// created by `Iter2D::indexed()`
+        #[derive(Clone, Debug)]
+        pub struct IndexedIter2D {
+            /// Underlying curve iterator
+            iter: Iter2D,
+        }
+        //
+        impl Iterator for IndexedIter2D {
+            type Item = (CurveIdx, Coordinates2D);
+
+            #[inline]
+            fn next(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.start();
+                self.iter.next().map(|coords| (idx, coords))
+            }
+
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<(CurveIdx, Coordinates2D)> {
+                // If the n-th point exists, n fits in a CurveIdx
+                let idx = *self.iter.indices.start();
+                self.iter.nth(n).map(|coords| (idx + n as CurveIdx, coords))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for IndexedIter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.end();
+                self.iter.next_back().map(|coords| (idx, coords))
+            }
+
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<(CurveIdx, Coordinates2D)> {
+                let idx = *self.iter.indices.end();
+                self.iter.nth_back(n).map(|coords| (idx - n as CurveIdx, coords))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for IndexedIter2D {}
+
+        clip_iter_impl!("Morton");
+
+        neighbors_impl!("Morton");
+
+        blocks_impl!("Morton");
+
+        join_impl!("Morton");
+
+        /// Compute the index of a point in a tiled Morton layout
+        ///
+        /// In this layout, the domain is split into square tiles of side
+        /// 2^`tile_bits`, whose points are in Morton order. Tiles are laid out in
+        /// row-major order, in rows of `row_pitch` points. This is how texture
+        /// pipelines usually combine the spatial locality of the Morton curve with
+        /// support for non-square domains, which the Morton curve alone would
+        /// need to pad to the next power-of-two square.
+        ///
+        /// # Panics
+        ///
+        /// If `tile_bits` is not smaller than the number of bits of `Coordinate`,
+        /// if `row_pitch` is not a multiple of the tile side or is larger than the
+        /// side of the curve's domain, or if `x` is not smaller than `row_pitch`.
+        ///
+        #[inline]
+        pub const fn tiled_index(
+            x: Coordinate,
+            y: Coordinate,
+            tile_bits: NumBits,
+            row_pitch: CurveIdx,
+        ) -> CurveIdx {
+            check_tiling(tile_bits, row_pitch);
+            assert!((x as CurveIdx) < row_pitch, "x is outside of the tiled domain");
+            let tile_mask = bits::low_order_mask(tile_bits) as Coordinate;
+            let tile_x = (x >> tile_bits) as CurveIdx;
+            let tile_y = (y >> tile_bits) as CurveIdx;
+            let tile_idx = tile_y * (row_pitch >> tile_bits) + tile_x;
+            (tile_idx << (2 * tile_bits)) | encode_2d([x & tile_mask, y & tile_mask])
+        }
+
+        /// Compute the coordinates of the point at a certain index in a tiled
+        /// Morton layout
+        ///
+        /// This is the inverse of `tiled_index()`, see its documentation for
+        /// details.
+        ///
+        #[inline]
+        pub const fn tiled_coords(
+            index: CurveIdx,
+            tile_bits: NumBits,
+            row_pitch: CurveIdx,
+        ) -> Coordinates2D {
+            check_tiling(tile_bits, row_pitch);
+            let tile_idx = index >> (2 * tile_bits);
+            let tiles_per_row = row_pitch >> tile_bits;
+            let tile_x = (tile_idx % tiles_per_row) as Coordinate;
+            let tile_y = (tile_idx / tiles_per_row) as Coordinate;
+            let [x, y] = decode_2d(index & bits::low_order_mask(2 * tile_bits));
+            [(tile_x << tile_bits) | x, (tile_y << tile_bits) | y]
+        }
+
+        /// Check the tiling parameters of `tiled_index()` and `tiled_coords()`
+        #[inline(always)]
+        const fn check_tiling(tile_bits: NumBits, row_pitch: CurveIdx) {
+            assert!(
+                tile_bits < Coordinate::BITS,
+                "Tiles must be smaller than the curve's domain"
+            );
+            assert!(
+                row_pitch != 0
+                    && row_pitch & bits::low_order_mask(tile_bits) == 0
+                    && row_pitch <= 1 << Coordinate::BITS,
+                "Row pitch must be a nonzero multiple of the tile side that fits in the curve's domain"
+            );
+        }
+
+        /// Iterate over the points of a tiled Morton layout, in layout order
+        ///
+        /// The iteration covers every point of the domain with rows of `row_pitch`
+        /// points and `height` rows, rounded up to a whole number of rows of tiles.
+        /// See `tiled_index()` for more information about this layout.
+        ///
+        /// # Panics
+        ///
+        /// If the tiling parameters are invalid, as in `tiled_index()`, or if
+        /// `height` is larger than the side of the curve's domain.
+        ///
+        pub fn iter_tiled_2d(tile_bits: NumBits, row_pitch: CurveIdx, height: CurveIdx) -> TiledIter2D {
+            check_tiling(tile_bits, row_pitch);
+            assert!(
+                height <= 1 << Coordinate::BITS,
+                "Height must fit in the curve's domain"
+            );
+            let tile_side = 1 << tile_bits;
+            let tile_rows = height / tile_side + (height % tile_side != 0) as CurveIdx;
+            let tile_row_len = row_pitch << tile_bits;
+            let indices = match tile_rows.checked_sub(1) {
+                Some(last_row) => 0..=last_row * tile_row_len + (tile_row_len - 1),
+                None => bits::inclusive_range(..0),
+            };
+            TiledIter2D {
+                indices,
+                tile_bits,
+                row_pitch,
+            }
+        }
+
+        /// Iterator over the points of a tiled Morton layout, created by
+        /// `iter_tiled_2d()`
+        #[derive(Clone, Debug)]
+        pub struct TiledIter2D {
+            /// Indices of the remaining points in the layout
+            indices: core::ops::RangeInclusive<CurveIdx>,
+
+            /// Base-2 logarithm of the tile side
+            tile_bits: NumBits,
+
+            /// Number of points in a row of the layout
+            row_pitch: CurveIdx,
+        }
+        //
+        impl Iterator for TiledIter2D {
+            type Item = Coordinates2D;
+
+            #[inline]
+            fn next(&mut self) -> Option<Coordinates2D> {
+                let index = self.indices.next()?;
+                Some(tiled_coords(index, self.tile_bits, self.row_pitch))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.indices.size_hint()
+            }
+        }
+        //
+        impl DoubleEndedIterator for TiledIter2D {
+            #[inline]
+            fn next_back(&mut self) -> Option<Coordinates2D> {
+                let index = self.indices.next_back()?;
+                Some(tiled_coords(index, self.tile_bits, self.row_pitch))
+            }
+        }
+        //
+        impl core::iter::FusedIterator for TiledIter2D {}
+
+        /// Convert the Morton code of a map tile into its quadkey
+        ///
+        /// Quadkeys identify the tiles of web maps (e.g. Bing Maps) at a certain
+        /// zoom level by a string of base-4 digits, one per zoom level, where the
+        /// i-th digit is made of the i-th bits of the tile's x and y coordinates.
+        /// Since the y bit comes first, this is exactly the base-4 representation
+        /// of the Morton code of the tile's coordinates, and `index` must
+        /// therefore be the Morton code of tile coordinates with `level` bits.
+        ///
+        /// # Panics
+        ///
+        /// If `level` is larger than the number of bits of `Coordinate`.
+        ///
+        #[cfg(feature = "alloc")]
+        pub fn to_quadkey(index: CurveIdx, level: NumBits) -> alloc::string::String {
+            assert!(level <= Coordinate::BITS, "Zoom level is too high for this curve");
+            (0..level)
+                .rev()
+                .map(|digit_idx| char::from(b'0' + ((index >> (2 * digit_idx)) & 3) as u8))
+                .collect()
+        }
+
+        /// Convert a quadkey into the Morton code of the map tile, along with
+        /// its zoom level
+        ///
+        /// This is the inverse of `to_quadkey()`, see its documentation for
+        /// details.
+        ///
+        pub fn from_quadkey(quadkey: &str) -> Result<(CurveIdx, NumBits), super::ParseQuadkeyError> {
+            if quadkey.len() > Coordinate::BITS as usize {
+                return Err(super::ParseQuadkeyError);
+            }
+            let index = quadkey.bytes().try_fold(0, |index: CurveIdx, digit| match digit {
+                b'0'..=b'3' => Ok((index << 2) | CurveIdx::from(digit - b'0')),
+                _ => Err(super::ParseQuadkeyError),
+            })?;
+            Ok((index, quadkey.len() as NumBits))
+        }
+
+        /// Decompose an axis-aligned rectangle into ranges of Morton codes
+        ///
+        /// The rectangle goes from `min` to `max` coordinates, inclusive. The
+        /// resulting iterator yields the minimal set of contiguous ranges of curve
+        /// indices that cover it, in increasing order, which is what range queries
+        /// on a database indexed by Morton codes need.
+        ///
+        /// This uses the BIGMIN and LITMAX algorithms by Tropf and Herzog to jump
+        /// from the end of a range to the start of the next one when iterating
+        /// forward and backward, respectively. Ranges themselves are delimited by
+        /// going through the largest aligned blocks of curve indices that are
+        /// inside of the rectangle, which correspond to squares of space.
+        ///
+        pub fn ranges_for_rect(min: Coordinates2D, max: Coordinates2D) -> RectRanges {
+            RectRanges {
+                front: encode_2d(min),
+                back: encode_2d(max),
+                min,
+                max,
+                done: min[0] > max[0] || min[1] > max[1],
+            }
+        }
+
+        /// Iterator over the ranges of Morton codes that cover a rectangle, created
+        /// by `ranges_for_rect()`
+        #[derive(Clone, Debug)]
+        pub struct RectRanges {
+            /// Start of the next range from the front
+            front: CurveIdx,
+
+            /// End of the next range from the back
+            back: CurveIdx,
+
+            /// Lower corner of the rectangle
+            min: Coordinates2D,
+
+            /// Upper corner of the rectangle
+            max: Coordinates2D,
+
+            /// Truth that all ranges have been yielded
+            done: bool,
+        }
+        //
+        impl RectRanges {
+            /// Truth that the aligned block of 4^depth curve indices starting at a
+            /// certain curve index is inside of the rectangle
+            #[inline(always)]
+            fn block_inside(&self, start: CurveIdx, depth: NumBits) -> bool {
+                let [x, y] = decode_2d(start);
+                let size = bits::low_order_mask(depth) as Coordinate;
+                x >= self.min[0]
+                    && y >= self.min[1]
+                    && x <= self.max[0]
+                    && size <= self.max[0] - x
+                    && y <= self.max[1]
+                    && size <= self.max[1] - y
+            }
+
+            /// Truth that a curve index is aligned on a block of 4^depth indices
+            #[inline(always)]
+            fn is_aligned(idx: CurveIdx, depth: NumBits) -> bool {
+                idx & bits::low_order_mask(2 * depth) == 0
+            }
+        }
+        //
+        impl Iterator for RectRanges {
+            type Item = core::ops::RangeInclusive<CurveIdx>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.done {
+                    return None;
+                }
+
+                // Go through the largest aligned blocks of curve indices that are
+                // inside of the rectangle, until the next index is outside of it
+                let start = self.front;
+                let mut next = start;
+                loop {
+                    let mut depth = 0;
+                    while depth < bits::num_bits::<Coordinate>()
+                        && Self::is_aligned(next, depth + 1)
+                        && self.block_inside(next, depth + 1)
+                    {
+                        depth += 1;
+                    }
+                    if !self.block_inside(next, depth) {
+                        break;
+                    }
+                    let end = next | bits::low_order_mask(2 * depth);
+                    if end >= self.back {
+                        self.done = true;
+                        return Some(start..=self.back);
+                    }
+                    next = end + 1;
+                }
+
+                // Jump to the next index that is inside of the rectangle
+                self.front = bigmin(next, encode_2d(self.min), encode_2d(self.max));
+                Some(start..=next - 1)
+            }
+        }
+        //
+        impl DoubleEndedIterator for RectRanges {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                if self.done {
+                    return None;
+                }
+
+                // Go through the largest aligned blocks of curve indices that are
+                // inside of the rectangle, backwards, until the previous index is
+                // outside of it
+                let end = self.back;
+                let mut prev = end;
+                loop {
+                    let mut depth = 0;
+                    while depth < bits::num_bits::<Coordinate>()
+                        && Self::is_aligned(prev.wrapping_add(1), depth + 1)
+                        && self.block_inside(prev & !bits::low_order_mask(2 * (depth + 1)), depth + 1)
+                    {
+                        depth += 1;
+                    }
+                    let start = prev & !bits::low_order_mask(2 * depth);
+                    if !self.block_inside(start, depth) {
+                        break;
+                    }
+                    if start <= self.front {
+                        self.done = true;
+                        return Some(self.front..=end);
+                    }
+                    prev = start - 1;
+                }
+
+                // Jump to the previous index that is inside of the rectangle
+                self.back = litmax(prev, encode_2d(self.min), encode_2d(self.max));
+                Some(prev + 1..=end)
+            }
+        }
+        //
+        impl core::iter::FusedIterator for RectRanges {}
+
+        /// Smallest Morton code above `code` that is within the rectangle whose
+        /// corners have Morton codes `min` and `max`, noted BIGMIN by Tropf and
+        /// Herzog, assuming that `code` itself is outside of the rectangle
+        fn bigmin(code: CurveIdx, mut min: CurveIdx, mut max: CurveIdx) -> CurveIdx {
+            let mut bigmin = 0;
+            let mut bit = bits::num_bits::<CurveIdx>();
+            while bit > 0 {
+                bit -= 1;
+                match [(code >> bit) & 1, (min >> bit) & 1, (max >> bit) & 1] {
+                    [0, 0, 1] => {
+                        bigmin = load_high(min, bit);
+                        max = load_low(max, bit);
+                    }
+                    [0, 1, 1] => return min,
+                    [1, 0, 0] => return bigmin,
+                    [1, 0, 1] => min = load_high(min, bit),
+                    _ => {}
+                }
+            }
+            bigmin
+        }
+
+        /// Largest Morton code below `code` that is within the rectangle whose
+        /// corners have Morton codes `min` and `max`, noted LITMAX by Tropf and
+        /// Herzog, assuming that `code` itself is outside of the rectangle
+        fn litmax(code: CurveIdx, mut min: CurveIdx, mut max: CurveIdx) -> CurveIdx {
+            let mut litmax = 0;
+            let mut bit = bits::num_bits::<CurveIdx>();
+            while bit > 0 {
+                bit -= 1;
+                match [(code >> bit) & 1, (min >> bit) & 1, (max >> bit) & 1] {
+                    [0, 0, 1] => max = load_low(max, bit),
+                    [0, 1, 1] => return litmax,
+                    [1, 0, 0] => return max,
+                    [1, 0, 1] => {
+                        litmax = load_low(max, bit);
+                        min = load_high(min, bit);
+                    }
+                    _ => {}
+                }
+            }
+            litmax
+        }
+
+        /// Set a bit of a Morton code, and clear lower-order bits of the same
+        /// coordinate, i.e. move to the lower corner of the upper half of a block
+        #[inline(always)]
+        fn load_high(code: CurveIdx, bit: NumBits) -> CurveIdx {
+            (code & !same_coordinate_bits(bit)) | (1 << bit)
+        }
+
+        /// Clear a bit of a Morton code, and set lower-order bits of the same
+        /// coordinate, i.e. move to the upper corner of the lower half of a block
+        #[inline(always)]
+        fn load_low(code: CurveIdx, bit: NumBits) -> CurveIdx {
+            (code & !same_coordinate_bits(bit)) | (same_coordinate_bits(bit) & !(1 << bit))
+        }
+
+        /// Mask of the bits of a Morton code that encode the same coordinate as a
+        /// certain bit, at the same or lower order
+        #[inline(always)]
+        fn same_coordinate_bits(bit: NumBits) -> CurveIdx {
+            debug_assert!(bit < bits::num_bits::<CurveIdx>());
+            (bits::striped_mask(1) << (bit % 2)) & bits::low_order_mask(bit + 1)
+        }
+    };
+}
+
+/// Lookup table used by `decode_2d_lut()`
+///
+/// Maps each byte of a Morton code to the corresponding 4 bits of the x
+/// coordinate (in the low-order nibble) and y coordinate (in the high-order
+/// nibble).
+///
+static DECODE_LUT: [u8; 256] = build_decode_lut();
+
+/// Generate `DECODE_LUT` at compile time
+const fn build_decode_lut() -> [u8; 256] {
+    let mut lut = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let [x, y] = idx16::decode_2d(byte as u16);
+        lut[byte] = x | (y << 4);
+        byte += 1;
+    }
+    lut
+}
+
+/// Error returned when parsing an invalid quadkey
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseQuadkeyError;
+//
+impl core::fmt::Display for ParseQuadkeyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("invalid quadkey, expected base-4 digits, one per zoom level")
+    }
+}
+//
+#[cfg(feature = "std")]
+impl std::error::Error for ParseQuadkeyError {}
+
+/// Bit layout of a Morton code, for `decode_2d_oriented()` and
+/// `encode_2d_oriented()`
+///
+/// This crate puts the x coordinate in the even bits of Morton codes and the y
+/// coordinate in the odd bits, which traces a Z-shaped pattern when y points
+/// downwards. Other software may instead put y in the even bits, which traces
+/// an N-shaped pattern, or count coordinates from the opposite edge of the
+/// domain. An `Orientation` describes the transform that maps the coordinates
+/// of this crate to such a layout.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Orientation {
+    /// Truth that y is in the even bits of the Morton code and x in the odd
+    /// bits, so that the curve goes along the y axis first
+    pub swap: bool,
+
+    /// Truth that the x coordinate is mirrored, after any swap
+    pub mirror_x: bool,
+
+    /// Truth that the y coordinate is mirrored, after any swap
+    pub mirror_y: bool,
+}
+//
+impl Orientation {
+    /// Z-shaped layout of `decode_2d()` and `encode_2d()`, where x is in the
+    /// even bits of the Morton code
+    pub const Z: Self = Self {
+        swap: false,
+        mirror_x: false,
+        mirror_y: false,
+    };
+
+    /// N-shaped layout, where y is in the even bits of the Morton code
+    pub const N: Self = Self {
+        swap: true,
+        mirror_x: false,
+        mirror_y: false,
+    };
+}
+
+/// Interoperability with geohashes
+mod geohash;
+
+/// Morton curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    morton_impl!(idx16, u16, u8);
+
+    // The number of points of a curve must fit in a usize for ExactSizeIterator,
+    // which is always true of curves with 16-bit indices...
+    impl ExactSizeIterator for Blocks2D {}
+    impl ExactSizeIterator for Iter2D {}
+    impl ExactSizeIterator for IndexedIter2D {}
+    impl ExactSizeIterator for Jumps2D {}
+    impl ExactSizeIterator for Window2D {}
+}
+
+/// Morton curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    morton_impl!(idx32, u32, u16);
+
+    // ...but only true of curves with 32-bit indices on 64-bit targets
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Blocks2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for IndexedIter2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Jumps2D {}
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Window2D {}
+}
+
+/// Morton curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    morton_impl!(idx64, u64, u32);
+}
+
+/// Morton curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    morton_impl!(idx128, u128, u64);
+}
+
+#[cfg(feature = "alloc")]
+pub use geohash::to_geohash;
+pub use geohash::{
+    code_to_lat_lon, coords_to_lat_lon, from_geohash, from_geohash_u64, lat_lon_to_code,
+    lat_lon_to_coords, to_geohash_u64, ParseGeohashError, MAX_GEOHASH_LEN,
+};
+pub use idx128::{
+    add_x as add_x_u128, add_y as add_y_u128, build_table as build_table_u128,
+    cell_2d as cell_2d_u128, cmp_2d as cmp_2d_u128, decode_2d as decode_2d_u128,
+    decode_2d_fast as decode_2d_fast_u128, decode_2d_into as decode_2d_into_u128,
+    decode_2d_into_soa as decode_2d_into_soa_u128, decode_2d_lut as decode_2d_lut_u128,
+    decode_2d_oriented as decode_2d_oriented_u128, decode_2d_radix as decode_2d_radix_u128,
+    decode_2d_transformed as decode_2d_transformed_u128, decode_4d as decode_4d_u128,
+    encode_2d as encode_2d_u128, encode_2d_fast as encode_2d_fast_u128,
+    encode_2d_into as encode_2d_into_u128, encode_2d_into_soa as encode_2d_into_soa_u128,
+    encode_2d_oriented as encode_2d_oriented_u128, encode_2d_radix as encode_2d_radix_u128,
+    encode_2d_transformed as encode_2d_transformed_u128, encode_4d as encode_4d_u128,
+    encode_bulk as encode_bulk_u128, encode_stream as encode_stream_u128,
+    from_quadkey as from_quadkey_u128, index_distance as index_distance_u128,
+    iter_2d as iter_2d_u128, iter_blocks_2d as iter_blocks_2d_u128,
+    iter_clipped_2d as iter_clipped_2d_u128, iter_from_2d as iter_from_2d_u128,
+    iter_jumps_2d as iter_jumps_2d_u128, iter_masked_2d as iter_masked_2d_u128,
+    iter_range_2d as iter_range_2d_u128, iter_tiled_2d as iter_tiled_2d_u128,
+    iter_window_2d as iter_window_2d_u128, join_2d as join_2d_u128,
+    neighbors_2d as neighbors_2d_u128, ranges_for_rect as ranges_for_rect_u128,
+    sub_x as sub_x_u128, sub_y as sub_y_u128, tiled_coords as tiled_coords_u128,
+    tiled_index as tiled_index_u128, try_cell_2d as try_cell_2d_u128,
+    try_decode_2d as try_decode_2d_u128, try_encode_2d as try_encode_2d_u128,
+    Block2D as Block2DU128, Blocks2D as Blocks2DU128, ClippedIter2D as ClippedIter2DU128,
+    IndexedIter2D as IndexedIter2DU128, Iter2D as Iter2DU128, Join2D as Join2DU128,
+    Jump2D as Jump2DU128, Jumps2D as Jumps2DU128, MaskedIter2D as MaskedIter2DU128,
+    RectRanges as RectRangesU128, TiledIter2D as TiledIter2DU128, Window2D as Window2DU128,
+};
+#[cfg(feature = "alloc")]
+pub use idx128::{
+    decode_2d_simd as decode_2d_simd_u128, sort_by_key as sort_by_key_u128,
+    sort_radix as sort_radix_u128, to_quadkey as to_quadkey_u128,
+};
+#[cfg(feature = "alloc")]
+pub_use_default_width!(decode_2d_simd, sort_by_key, sort_radix, to_quadkey);
+#[cfg(feature = "rayon")]
+pub use idx128::{
+    par_blocks_2d as par_blocks_2d_u128, par_iter_2d as par_iter_2d_u128,
+    par_iter_range_2d as par_iter_range_2d_u128,
+};
+#[cfg(feature = "alloc")]
+pub use idx16::{
+    decode_2d_simd as decode_2d_simd_u16, sort_by_key as sort_by_key_u16,
+    sort_radix as sort_radix_u16, to_quadkey as to_quadkey_u16,
+};
+#[cfg(feature = "alloc")]
+pub use idx32::{
+    decode_2d_simd as decode_2d_simd_u32, sort_by_key as sort_by_key_u32,
+    sort_radix as sort_radix_u32, to_quadkey as to_quadkey_u32,
+};
+#[cfg(feature = "alloc")]
+pub use idx64::{
+    decode_2d_simd as decode_2d_simd_u64, sort_by_key as sort_by_key_u64,
+    sort_radix as sort_radix_u64, to_quadkey as to_quadkey_u64,
+};
+#[cfg(feature = "rayon")]
+pub_use_default_width!(par_blocks_2d, par_iter_2d, par_iter_range_2d);
+#[cfg(feature = "rayon")]
+pub use idx16::{
+    par_blocks_2d as par_blocks_2d_u16, par_iter_2d as par_iter_2d_u16,
+    par_iter_range_2d as par_iter_range_2d_u16,
+};
+#[cfg(feature = "rayon")]
+pub use idx32::{
+    par_blocks_2d as par_blocks_2d_u32, par_iter_2d as par_iter_2d_u32,
+    par_iter_range_2d as par_iter_range_2d_u32,
+};
+#[cfg(feature = "rayon")]
+pub use idx64::{
+    par_blocks_2d as par_blocks_2d_u64, par_iter_2d as par_iter_2d_u64,
+    par_iter_range_2d as par_iter_range_2d_u64,
+};
+pub_use_default_width!(
+    Block2D,
+    Blocks2D,
+    iter_blocks_2d,
+    ClippedIter2D,
+    Jump2D,
+    Jumps2D,
+    Window2D,
+    Join2D,
+    MaskedIter2D,
+    IndexedIter2D,
+    Iter2D,
+    build_table,
+    add_x,
+    add_y,
+    cell_2d,
+    sub_x,
+    sub_y,
+    decode_2d_radix,
+    encode_2d_radix,
+    cmp_2d,
+    decode_2d,
+    decode_2d_oriented,
+    decode_2d_transformed,
+    encode_2d_oriented,
+    encode_2d_transformed,
+    decode_2d_fast,
+    decode_2d_into,
+    decode_2d_into_soa,
+    decode_2d_lut,
+    decode_4d,
+    encode_2d,
+    encode_2d_fast,
+    encode_2d_into,
+    encode_2d_into_soa,
+    encode_4d,
+    encode_bulk,
+    encode_stream,
+    from_quadkey,
+    iter_2d,
+    iter_clipped_2d,
+    iter_jumps_2d,
+    iter_window_2d,
+    index_distance,
+    join_2d,
+    iter_masked_2d,
+    iter_from_2d,
+    iter_range_2d,
+    neighbors_2d,
+    iter_tiled_2d,
+    ranges_for_rect,
+    tiled_coords,
+    tiled_index,
+    try_cell_2d,
+    try_decode_2d,
+    try_encode_2d,
+    RectRanges,
+    TiledIter2D
+);
+pub use idx16::{
+    add_x as add_x_u16, add_y as add_y_u16, build_table as build_table_u16, cell_2d as cell_2d_u16,
+    cmp_2d as cmp_2d_u16, decode_2d as decode_2d_u16, decode_2d_fast as decode_2d_fast_u16,
+    decode_2d_into as decode_2d_into_u16, decode_2d_into_soa as decode_2d_into_soa_u16,
+    decode_2d_lut as decode_2d_lut_u16, decode_2d_oriented as decode_2d_oriented_u16,
+    decode_2d_radix as decode_2d_radix_u16, decode_2d_transformed as decode_2d_transformed_u16,
+    decode_4d as decode_4d_u16, encode_2d as encode_2d_u16, encode_2d_fast as encode_2d_fast_u16,
+    encode_2d_into as encode_2d_into_u16, encode_2d_into_soa as encode_2d_into_soa_u16,
+    encode_2d_oriented as encode_2d_oriented_u16, encode_2d_radix as encode_2d_radix_u16,
+    encode_2d_transformed as encode_2d_transformed_u16, encode_4d as encode_4d_u16,
+    encode_bulk as encode_bulk_u16, encode_stream as encode_stream_u16,
+    from_quadkey as from_quadkey_u16, index_distance as index_distance_u16, iter_2d as iter_2d_u16,
+    iter_blocks_2d as iter_blocks_2d_u16, iter_clipped_2d as iter_clipped_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_jumps_2d as iter_jumps_2d_u16,
+    iter_masked_2d as iter_masked_2d_u16, iter_range_2d as iter_range_2d_u16,
+    iter_tiled_2d as iter_tiled_2d_u16, iter_window_2d as iter_window_2d_u16,
+    join_2d as join_2d_u16, neighbors_2d as neighbors_2d_u16,
+    ranges_for_rect as ranges_for_rect_u16, sub_x as sub_x_u16, sub_y as sub_y_u16,
+    tiled_coords as tiled_coords_u16, tiled_index as tiled_index_u16,
+    try_cell_2d as try_cell_2d_u16, try_decode_2d as try_decode_2d_u16,
+    try_encode_2d as try_encode_2d_u16, Block2D as Block2DU16, Blocks2D as Blocks2DU16,
+    ClippedIter2D as ClippedIter2DU16, IndexedIter2D as IndexedIter2DU16, Iter2D as Iter2DU16,
+    Join2D as Join2DU16, Jump2D as Jump2DU16, Jumps2D as Jumps2DU16,
+    MaskedIter2D as MaskedIter2DU16, RectRanges as RectRangesU16, TiledIter2D as TiledIter2DU16,
+    Window2D as Window2DU16,
+};
+pub use idx32::{
+    add_x as add_x_u32, add_y as add_y_u32, build_table as build_table_u32, cell_2d as cell_2d_u32,
+    cmp_2d as cmp_2d_u32, decode_2d as decode_2d_u32, decode_2d_fast as decode_2d_fast_u32,
+    decode_2d_into as decode_2d_into_u32, decode_2d_into_soa as decode_2d_into_soa_u32,
+    decode_2d_lut as decode_2d_lut_u32, decode_2d_oriented as decode_2d_oriented_u32,
+    decode_2d_radix as decode_2d_radix_u32, decode_2d_transformed as decode_2d_transformed_u32,
+    decode_4d as decode_4d_u32, encode_2d as encode_2d_u32, encode_2d_fast as encode_2d_fast_u32,
+    encode_2d_into as encode_2d_into_u32, encode_2d_into_soa as encode_2d_into_soa_u32,
+    encode_2d_oriented as encode_2d_oriented_u32, encode_2d_radix as encode_2d_radix_u32,
+    encode_2d_transformed as encode_2d_transformed_u32, encode_4d as encode_4d_u32,
+    encode_bulk as encode_bulk_u32, encode_stream as encode_stream_u32,
+    from_quadkey as from_quadkey_u32, index_distance as index_distance_u32, iter_2d as iter_2d_u32,
+    iter_blocks_2d as iter_blocks_2d_u32, iter_clipped_2d as iter_clipped_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_jumps_2d as iter_jumps_2d_u32,
+    iter_masked_2d as iter_masked_2d_u32, iter_range_2d as iter_range_2d_u32,
+    iter_tiled_2d as iter_tiled_2d_u32, iter_window_2d as iter_window_2d_u32,
+    join_2d as join_2d_u32, neighbors_2d as neighbors_2d_u32,
+    ranges_for_rect as ranges_for_rect_u32, sub_x as sub_x_u32, sub_y as sub_y_u32,
+    tiled_coords as tiled_coords_u32, tiled_index as tiled_index_u32,
+    try_cell_2d as try_cell_2d_u32, try_decode_2d as try_decode_2d_u32,
+    try_encode_2d as try_encode_2d_u32, Block2D as Block2DU32, Blocks2D as Blocks2DU32,
+    ClippedIter2D as ClippedIter2DU32, IndexedIter2D as IndexedIter2DU32, Iter2D as Iter2DU32,
+    Join2D as Join2DU32, Jump2D as Jump2DU32, Jumps2D as Jumps2DU32,
+    MaskedIter2D as MaskedIter2DU32, RectRanges as RectRangesU32, TiledIter2D as TiledIter2DU32,
+    Window2D as Window2DU32,
+};
+pub use idx64::{
+    add_x as add_x_u64, add_y as add_y_u64, build_table as build_table_u64, cell_2d as cell_2d_u64,
+    cmp_2d as cmp_2d_u64, decode_2d as decode_2d_u64, decode_2d_fast as decode_2d_fast_u64,
+    decode_2d_into as decode_2d_into_u64, decode_2d_into_soa as decode_2d_into_soa_u64,
+    decode_2d_lut as decode_2d_lut_u64, decode_2d_oriented as decode_2d_oriented_u64,
+    decode_2d_radix as decode_2d_radix_u64, decode_2d_transformed as decode_2d_transformed_u64,
+    decode_4d as decode_4d_u64, encode_2d as encode_2d_u64, encode_2d_fast as encode_2d_fast_u64,
+    encode_2d_into as encode_2d_into_u64, encode_2d_into_soa as encode_2d_into_soa_u64,
+    encode_2d_oriented as encode_2d_oriented_u64, encode_2d_radix as encode_2d_radix_u64,
+    encode_2d_transformed as encode_2d_transformed_u64, encode_4d as encode_4d_u64,
+    encode_bulk as encode_bulk_u64, encode_stream as encode_stream_u64,
+    from_quadkey as from_quadkey_u64, index_distance as index_distance_u64, iter_2d as iter_2d_u64,
+    iter_blocks_2d as iter_blocks_2d_u64, iter_clipped_2d as iter_clipped_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_jumps_2d as iter_jumps_2d_u64,
+    iter_masked_2d as iter_masked_2d_u64, iter_range_2d as iter_range_2d_u64,
+    iter_tiled_2d as iter_tiled_2d_u64, iter_window_2d as iter_window_2d_u64,
+    join_2d as join_2d_u64, neighbors_2d as neighbors_2d_u64,
+    ranges_for_rect as ranges_for_rect_u64, sub_x as sub_x_u64, sub_y as sub_y_u64,
+    tiled_coords as tiled_coords_u64, tiled_index as tiled_index_u64,
+    try_cell_2d as try_cell_2d_u64, try_decode_2d as try_decode_2d_u64,
+    try_encode_2d as try_encode_2d_u64, Block2D as Block2DU64, Blocks2D as Blocks2DU64,
+    ClippedIter2D as ClippedIter2DU64, IndexedIter2D as IndexedIter2DU64, Iter2D as Iter2DU64,
+    Join2D as Join2DU64, Jump2D as Jump2DU64, Jumps2D as Jumps2DU64,
+    MaskedIter2D as MaskedIter2DU64, RectRanges as RectRangesU64, TiledIter2D as TiledIter2DU64,
+    Window2D as Window2DU64,
+};
+
+/// 3x3 neighborhood of a point on the 2D Morton curve
+///
+/// Neighbors are stored in `[dy][dx]` order, where dx and dy go from 0 to 2,
+/// so that the central point is at `[1][1]` and its neighbor at coordinates
+/// `[x - 1, y + 1]` is at `[2][0]`. Neighbors which fall outside of the curve's
+/// domain are set to `None`.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Neighborhood2D {
+    /// Morton curve indices of the neighbors
+    pub indices: [[Option<CurveIdx>; 3]; 3],
+
+    /// Coordinates of the neighbors
+    pub coords: [[Option<Coordinates2D>; 3]; 3],
+}
+
+/// Iterate over the 2D Morton curve, yielding the 3x3 neighborhood of each point
+///
+/// This is equivalent to iterating over the curve with `iter_2d()` and then
+/// computing the coordinates and Morton code of each neighbor of the current
+/// point, but much more efficient as neighbor Morton codes are computed by
+/// doing arithmetic directly on the current Morton code.
 ///
-#[inline]
-pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
-    // Align the low-order bits of the two input sub-codes:
-    // [ XX x1 XX x2 XX x3 XX x4 ... xN-1   XX xN ]
-    // [ XX y1 XX y2 XX y3 XX y4 ... yN-1   XX yN ]
-    debug_assert!(bits::num_bits::<Coordinates2D>() >= bits::num_bits::<CurveIdx>() / 2);
-    let mut sub_codes = [code, code >> 1];
-    let mut sub_code_idx = 0;
-    while sub_code_idx < 2 {
-        // We start with a coordinate's bits interleaved with irrelevant junk:
-        // [ XX a1 XX a2 XX a3 XX a4 ... XX aN-1 XX aN ]
-        // Let's clean that up by zeroing out the junk:
-        // [  0 a1  0 a2  0 a3  0 a4 ...  0 aN-1  0 aN ]
-        let mut sub_code = sub_codes[sub_code_idx] & bits::striped_mask(1);
-        // We will then pack the coordinate's bits together by recursively
-        // grouping them in pairs, groups of 4, and so on.
-        // Initially, bits are isolated, so we have groups of one.
-        // We're done once we have grouped half of the input bits together,
-        // since the other bits will be zero.
-        let mut group_size = 1;
-        while group_size < bits::num_bits::<CurveIdx>() / 2 {
-            // Duplicate the current bit pattern into neighboring zeroes on the
-            // right in order to group pairs of subcode bits together
-            // Iteration 1: [  0 a1 a1 a2 a2 a3 a3 a4 ... aN-2 aN-1 aN-1 aN ]
-            // Iteration 2: [  0  0 a1 a2 a1 a2 a3 a4 ... aN-3 aN-2 aN-1 aN ]
-            sub_code |= sub_code >> group_size;
-            group_size *= 2;
-            // Only keep the paired bit groups, zeroing out the rest
-            // Iteration 1: [  0  0 a1 a2  0  0 a3 a4 ...    0    0 aN-1 aN ]
-            // Iteration 2: [  0  0  0  0 a1 a2 a3 a4 ... aN-3 aN-2 aN-1 aN ]
-            sub_code &= bits::striped_mask(group_size);
-        }
-        // Record the decoded coordinate and move to the next one
-        sub_codes[sub_code_idx] = sub_code;
-        sub_code_idx += 1;
-    }
-    [sub_codes[0] as _, sub_codes[1] as _]
+/// This trick relies on Morton codes being interleaved coordinates, so it is
+/// only available for the Morton curve. On other curves, including the Hilbert
+/// curve, use `neighbors_2d()` on every point of the curve instead.
+///
+pub fn iter_neighborhoods_2d() -> impl Iterator<Item = Neighborhood2D> {
+    let x_mask = bits::striped_mask(1);
+    let y_mask = !x_mask;
+    (CurveIdx::MIN..=CurveIdx::MAX)
+        .zip(iter_2d())
+        .map(move |(idx, [x, y])| {
+            // A Morton code is the bitwise OR of the "dilated" coordinates, i.e.
+            // coordinates whose bits have been spread out so that they only use
+            // every other bit. So we can compute the neighbors' Morton codes
+            // from the dilated coordinates of the previous and next rows and
+            // columns, which can be computed using dilated integer arithmetic.
+            let neighbors_1d = |coord: Coordinate, mask: CurveIdx| {
+                let dilated = idx & mask;
+                [
+                    (coord != Coordinate::MIN)
+                        .then(|| (coord - 1, dilated_decrement(dilated, mask))),
+                    Some((coord, dilated)),
+                    (coord != Coordinate::MAX)
+                        .then(|| (coord + 1, dilated_increment(dilated, mask))),
+                ]
+            };
+            let xs = neighbors_1d(x, x_mask);
+            let ys = neighbors_1d(y, y_mask);
+
+            // Combine the 1D neighbors into a 2D neighborhood
+            let mut neighborhood = Neighborhood2D {
+                indices: [[None; 3]; 3],
+                coords: [[None; 3]; 3],
+            };
+            for (dy, y) in ys.iter().enumerate() {
+                for (dx, x) in xs.iter().enumerate() {
+                    if let (Some((x, x_dilated)), Some((y, y_dilated))) = (x, y) {
+                        neighborhood.indices[dy][dx] = Some(x_dilated | y_dilated);
+                        neighborhood.coords[dy][dx] = Some([*x, *y]);
+                    }
+                }
+            }
+            neighborhood
+        })
 }
 
-/// Iterate over the 2D Morton curve
+/// Increment a dilated integer, whose bits are spread out according to `mask`
 ///
-/// This is equivalent to running `decode_2d()` on the sequence of all possible
-/// curve indices (CurveIdx::MIN..=CurveIdx), but a bit more efficient.
+/// Filling the holes between the integer's bits with ones lets the carry
+/// propagate through them, then the extra bits can be masked out.
 ///
-pub fn iter_2d() -> impl Iterator<Item = Coordinates2D> {
-    iter_from_2d(CurveIdx::MIN)
+#[inline(always)]
+const fn dilated_increment(dilated: CurveIdx, mask: CurveIdx) -> CurveIdx {
+    (dilated | !mask).wrapping_add(1) & mask
 }
 
-/// Iterate over the 2D Morton curve, starting from a certain index
+/// Decrement a dilated integer, whose bits are spread out according to `mask`
 ///
-/// This is equivalent to running `decode_2d()` on the sequence of curve
-/// indices (start..=CurveIdx), but should be a bit more efficient.
+/// Since the holes between the integer's bits are zeroed, the borrow
+/// propagates through them, and the extra bits can then be masked out.
 ///
-pub fn iter_from_2d(start: CurveIdx) -> impl Iterator<Item = Coordinates2D> {
-    let mut coords = decode_2d(start);
-    (start..=CurveIdx::MAX).map(move |idx| {
-        // We'll return the current coordinates after preparing the next ones
-        let result = coords;
-
-        // In binary, incrementing an integer flips a row of low-order bits.
-        // Check which of the curve index's bits will be flipped next time.
-        let flipped_bits = idx ^ idx.wrapping_add(1);
-        let num_flipped_bits = flipped_bits.trailing_ones();
-
-        // The flipped bits will be spread out ~evenly across "even" bits (which
-        // represent the second coordinate of the Morton code) and "odd" bits
-        // (which represent the first coordinate), with the extra flipped bit
-        // going to the first coordinate when a tie occurs.
-        let num_flipped_even = num_flipped_bits / 2;
-        let num_flipped_odd = num_flipped_bits - num_flipped_even;
-
-        // From this we can propagate the bit-flipping changes of the index
-        // increment to the Morton indices without redoing Morton code decoding.
-        coords[0] ^= (flipped_bits >> num_flipped_even) as Coordinate;
-        coords[1] ^= (flipped_bits >> num_flipped_odd) as Coordinate;
-
-        // And then we return the current coordinates
-        result
-    })
+#[inline(always)]
+const fn dilated_decrement(dilated: CurveIdx, mask: CurveIdx) -> CurveIdx {
+    dilated.wrapping_sub(1) & mask
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Coordinate;
+    use crate::{Coordinate, Error, MaskAction};
     use bits::test_utils::*;
+    use quickcheck::{quickcheck, Arbitrary, Gen};
+
+    // Tests that would check every curve index check a sample of them with
+    // wider curve index types, and tests that go through the whole curve only
+    // run with the default 16-bit curve indices. Wider curve index types are
+    // also tested using random inputs, see wide_tests below.
 
     #[test]
     fn decode_2d() {
-        for input in CurveIdx::MIN..=CurveIdx::MAX {
+        for input in sampled_indices() {
             let mut input_buf = input.reverse_bits();
             let mut results = [0 as Coordinate; 2];
             for _bit_idx in 0..(bits::num_bits::<Coordinate>()) {
@@ -116,6 +1926,336 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fast() {
+        for input in sampled_indices() {
+            let coords = super::decode_2d(input);
+            assert_eq!(super::decode_2d_fast(input), coords);
+            assert_eq!(super::encode_2d_fast(coords), input);
+        }
+    }
+
+    #[test]
+    fn build_table() {
+        const TABLE: [Coordinates2D; 1000] = super::build_table();
+        assert!(TABLE.iter().copied().eq(super::iter_2d().take(1000)));
+        let full_table = super::build_table_u16::<65536>();
+        assert!(full_table.iter().copied().eq(super::iter_2d_u16()));
+        assert!(super::build_table::<0>().is_empty());
+    }
+
+    #[test]
+    fn lut() {
+        for input in sampled_indices() {
+            assert_eq!(super::decode_2d_lut(input), super::decode_2d(input));
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn simd() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let coords = super::decode_2d_simd(&codes);
+        assert!(codes
+            .into_iter()
+            .zip(coords)
+            .all(|(code, coords)| coords == super::decode_2d(code)));
+    }
+
+    #[test]
+    fn into() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let mut coords = vec![[0; 2]; codes.len()];
+        super::decode_2d_into(&codes, &mut coords);
+        assert!(codes
+            .iter()
+            .zip(&coords)
+            .all(|(&code, &coords)| coords == super::decode_2d(code)));
+        let mut codes2 = vec![0; coords.len()];
+        super::encode_2d_into(&coords, &mut codes2);
+        assert_eq!(codes2, codes);
+    }
+
+    #[test]
+    fn into_soa() {
+        let codes = sampled_indices().collect::<Vec<_>>();
+        let mut xs = vec![0; codes.len()];
+        let mut ys = vec![0; codes.len()];
+        super::decode_2d_into_soa(&codes, &mut xs, &mut ys);
+        assert!(codes
+            .iter()
+            .zip(xs.iter().zip(&ys))
+            .all(|(&code, (&x, &y))| super::decode_2d(code) == [x, y]));
+        let mut codes2 = vec![0; codes.len()];
+        super::encode_2d_into_soa(&xs, &ys, &mut codes2);
+        assert_eq!(codes2, codes);
+    }
+
+    #[test]
+    fn bulk() {
+        let coords = sampled_indices().map(super::decode_2d).collect::<Vec<_>>();
+        let mut codes = vec![0; coords.len()];
+        super::encode_bulk(&coords, &mut codes);
+        assert!(codes.iter().copied().eq(sampled_indices()));
+        let mut streamed = Vec::new();
+        super::encode_stream(coords.iter().copied(), |chunk| {
+            assert!(!chunk.is_empty());
+            streamed.extend_from_slice(chunk)
+        });
+        assert_eq!(streamed, codes);
+        super::encode_stream(core::iter::empty(), |_| unreachable!());
+    }
+
+    #[test]
+    #[should_panic]
+    fn into_length_mismatch() {
+        super::decode_2d_into(&[0; 3], &mut [[0; 2]; 2]);
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn decode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            CurveIdx::MIN,
+            || CurveIdx::arbitrary(&mut gen),
+            super::decode_2d,
+        );
+    }
+
+    #[test]
+    fn encode_2d() {
+        for [x, y] in sampled_indices().map(super::decode_2d) {
+            let mut coords_buf = [x.reverse_bits(), y.reverse_bits()];
+            let mut expected: CurveIdx = 0;
+            for _bit_idx in 0..(bits::num_bits::<Coordinate>()) {
+                for coord in coords_buf.iter_mut().rev() {
+                    push_bit(&mut expected, pop_bit(coord));
+                }
+            }
+            assert_eq!(
+                super::encode_2d([x, y]),
+                expected,
+                "Unexpected 2D Morton code encoding result for input {:?}",
+                [x, y]
+            );
+            assert_eq!(
+                super::decode_2d(expected),
+                [x, y],
+                "2D Morton code decoding isn't the inverse of encoding"
+            );
+        }
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn encode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            [Coordinate::MIN; 2],
+            || Coordinates2D::arbitrary(&mut gen),
+            super::encode_2d,
+        );
+    }
+
+    #[test]
+    fn try_encode_2d() {
+        for domain_bits in 0..=Coordinate::BITS {
+            let last = bits::low_order_mask(2 * domain_bits);
+            for code in sampled_indices() {
+                let coords = super::decode_2d(code);
+                let inside = code <= last;
+                assert_eq!(
+                    super::try_decode_2d(code, domain_bits),
+                    Some(coords)
+                        .filter(|_| inside)
+                        .ok_or(Error::IndexOutOfDomain)
+                );
+                assert_eq!(
+                    super::try_encode_2d(coords, domain_bits),
+                    Some(code)
+                        .filter(|_| inside)
+                        .ok_or(Error::CoordinateOutOfDomain)
+                );
+            }
+        }
+        assert_eq!(
+            super::try_encode_2d([0, 0], Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+        assert_eq!(
+            super::try_decode_2d(0, Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+    }
+
+    #[test]
+    fn decode_4d() {
+        for code in sampled_indices() {
+            let coords = super::decode_4d(code);
+            for (dim, &coord) in coords.iter().enumerate() {
+                for bit in 0..(CurveIdx::BITS / 4) {
+                    assert_eq!(
+                        CurveIdx::from((coord >> bit) & 1),
+                        (code >> (4 * bit + dim as u32)) & 1
+                    );
+                }
+                assert_eq!(coord >> (CurveIdx::BITS / 4), 0);
+            }
+            assert_eq!(super::encode_4d(coords), code);
+        }
+    }
+
+    #[test]
+    fn transformed() {
+        for order in 0..=MAX_TESTED_ORDER {
+            let last = (1 as CurveIdx)
+                .checked_shl(2 * order)
+                .map_or(CurveIdx::MAX, |num_points| num_points - 1);
+            for transform in crate::Transform2D::ALL {
+                for code in 0..=last {
+                    let coords = transform.apply(super::decode_2d(code), order);
+                    assert_eq!(super::decode_2d_transformed(code, order, transform), coords);
+                    assert_eq!(super::encode_2d_transformed(coords, order, transform), code);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_2d_transformed_out_of_range() {
+        super::encode_2d_transformed([4, 0], 2, crate::Transform2D::MIRROR_X);
+    }
+
+    #[test]
+    fn oriented() {
+        // N-shaped order of the first 4 points
+        let n: Vec<_> = (0..4)
+            .map(|code| super::decode_2d_oriented(code, Orientation::N))
+            .collect();
+        assert_eq!(n, [[0, 0], [0, 1], [1, 0], [1, 1]]);
+
+        let max = Coordinate::MAX;
+        for &swap in &[false, true] {
+            for &mirror_x in &[false, true] {
+                for &mirror_y in &[false, true] {
+                    let orientation = Orientation {
+                        swap,
+                        mirror_x,
+                        mirror_y,
+                    };
+                    for code in sampled_indices() {
+                        let [x, y] = super::decode_2d(code);
+                        let [x, y] = if swap { [y, x] } else { [x, y] };
+                        let x = if mirror_x { max - x } else { x };
+                        let y = if mirror_y { max - y } else { y };
+                        assert_eq!(super::decode_2d_oriented(code, orientation), [x, y]);
+                        assert_eq!(super::encode_2d_oriented([x, y], orientation), code);
+                    }
+                }
+            }
+        }
+        assert_eq!(Orientation::default(), Orientation::Z);
+    }
+
+    #[test]
+    fn cell_2d() {
+        for level in 0..=Coordinate::BITS {
+            let side_bits = Coordinate::BITS - level;
+            let mask = bits::low_order_mask(2 * side_bits);
+            for code in sampled_indices() {
+                let ([x, y], side) = super::cell_2d(code, level);
+                assert_eq!(side, 1 << side_bits);
+                assert_eq!((x | y) as CurveIdx % side, 0);
+                let [px, py] = super::decode_2d(code);
+                assert!(((px - x) as CurveIdx) < side && ((py - y) as CurveIdx) < side);
+                assert_eq!(super::cell_2d(code & !mask, level), ([x, y], side));
+                assert_eq!(super::cell_2d(code | mask, level), ([x, y], side));
+                assert_eq!(super::try_cell_2d(code, level), Ok(([x, y], side)));
+            }
+        }
+        assert_eq!(
+            super::try_cell_2d(0, Coordinate::BITS + 1),
+            Err(Error::UnsupportedOrder)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn cell_2d_too_deep() {
+        super::cell_2d(0, Coordinate::BITS + 1);
+    }
+
+    #[test]
+    fn iter_blocks_2d() {
+        use super::idx16::iter_blocks_2d;
+        let coord_bits = u8::BITS;
+        for block_bits in 0..=coord_bits {
+            let side = 1u32 << block_bits;
+            let blocks = iter_blocks_2d(block_bits);
+            assert_eq!(blocks.len(), 1 << (2 * (coord_bits - block_bits)));
+            let mut next_idx = 0u32;
+            for block in blocks {
+                assert_eq!(u32::from(*block.indices.start()), next_idx);
+                next_idx += side * side;
+                assert_eq!(u32::from(*block.indices.end()), next_idx - 1);
+                assert_eq!(block.min.map(|c| u32::from(c) % side), [0, 0]);
+                assert_eq!(
+                    block.max.map(u32::from),
+                    block.min.map(|c| u32::from(c) + side - 1)
+                );
+                assert!(block.points().all(|[x, y]| {
+                    (block.min[0]..=block.max[0]).contains(&x)
+                        && (block.min[1]..=block.max[1]).contains(&y)
+                }));
+            }
+            assert_eq!(next_idx, 1 << 16);
+        }
+        assert_eq!(
+            iter_blocks_2d(2).nth_back(1),
+            iter_blocks_2d(2).nth((1 << 12) - 2)
+        );
+    }
+
+    #[test]
+    fn neighbors_2d() {
+        for code in sampled_indices() {
+            let [x, y] = super::decode_2d(code);
+            let exclude = super::neighbors_2d(code, crate::Boundary::Exclude);
+            let clamp = super::neighbors_2d(code, crate::Boundary::Clamp);
+            let wrap = super::neighbors_2d(code, crate::Boundary::Wrap);
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    let [x_wrap, y_wrap] = [
+                        x.wrapping_add(dx as Coordinate).wrapping_sub(1),
+                        y.wrapping_add(dy as Coordinate).wrapping_sub(1),
+                    ];
+                    let wrapped = super::encode_2d([x_wrap, y_wrap]);
+                    assert_eq!(wrap[dy][dx], Some(wrapped));
+                    let in_domain = (x_wrap as i128 - x as i128).abs() <= 1
+                        && (y_wrap as i128 - y as i128).abs() <= 1;
+                    assert_eq!(exclude[dy][dx], Some(wrapped).filter(|_| in_domain));
+                    let [x_clamp, y_clamp] = [
+                        (x as i128 + dx as i128 - 1).clamp(0, Coordinate::MAX.into()),
+                        (y as i128 + dy as i128 - 1).clamp(0, Coordinate::MAX.into()),
+                    ];
+                    assert_eq!(
+                        clamp[dy][dx],
+                        Some(super::encode_2d([
+                            x_clamp as Coordinate,
+                            y_clamp as Coordinate
+                        ]))
+                    );
+                }
+            }
+            assert_eq!(exclude[1][1], Some(code));
+        }
+    }
+
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
     mod iter_from_2d {
         use super::*;
         use quickcheck::quickcheck;
@@ -151,7 +2291,753 @@ mod tests {
     }
 
     #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
     fn iter_2d() {
         iter_from_2d::test(super::iter_2d(), CurveIdx::MIN);
     }
+
+    #[test]
+    fn iter_2d_len() {
+        let mut iter = super::idx16::iter_from_2d(42);
+        assert_eq!(iter.len(), usize::from(u16::MAX) - 41);
+        iter.next_back();
+        let copy = iter.clone();
+        assert_eq!(iter.by_ref().count(), copy.len());
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn iter_jumps_2d() {
+        use super::idx16::{decode_2d, iter_jumps_2d};
+        for min_level in 0..=u16::BITS + 1 {
+            let mut jumps = iter_jumps_2d(min_level);
+            let mut expected_len = jumps.len();
+            for index in 0..u16::MAX {
+                let [from, to] = [decode_2d(index), decode_2d(index + 1)];
+                let level = index.trailing_ones();
+                if level == 0 {
+                    assert_eq!(to, [from[0] + 1, from[1]]);
+                }
+                if level == 0 || level < min_level {
+                    continue;
+                }
+                let jump = jumps.next().unwrap();
+                assert_eq!(
+                    jump,
+                    super::idx16::Jump2D {
+                        index,
+                        from,
+                        to,
+                        level
+                    }
+                );
+                expected_len -= 1;
+                assert_eq!(jumps.len(), expected_len);
+            }
+            assert_eq!(jumps.next(), None);
+            assert!(iter_jumps_2d(min_level).rev().eq(iter_jumps_2d(min_level)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()));
+        }
+        assert_eq!(iter_jumps_2d(0).len(), usize::from(u16::MAX / 2));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_clipped_2d() {
+        for (width, height) in [
+            (0, 0),
+            (0, 42),
+            (1, 1),
+            (3, 200),
+            (100, 37),
+            (128, 128),
+            (Coordinate::MAX, 1),
+            (Coordinate::MAX, Coordinate::MAX),
+        ] {
+            assert!(super::iter_clipped_2d(width, height)
+                .eq(super::iter_2d().filter(|&[x, y]| x < width && y < height)));
+        }
+    }
+
+    #[test]
+    fn index_distance() {
+        let points = [[0, 0], [3, 1], [Coordinate::MAX, 0], [Coordinate::MAX; 2]];
+        for &a in &points {
+            for &b in &points {
+                let [a_idx, b_idx] = [super::encode_2d(a), super::encode_2d(b)];
+                let distance = super::index_distance(a, b);
+                assert_eq!(distance, a_idx.max(b_idx) - a_idx.min(b_idx));
+                assert_eq!(super::index_distance(b, a), distance);
+            }
+        }
+        let [first, last] = [super::decode_2d(0), super::decode_2d(CurveIdx::MAX)];
+        assert_eq!(super::index_distance(last, first), CurveIdx::MAX);
+    }
+
+    #[test]
+    fn iter_window_2d() {
+        use super::idx16::{decode_2d, iter_window_2d};
+        for start in [0, 1, 2, 1000, u16::MAX - 1, u16::MAX] {
+            let window = iter_window_2d(start);
+            assert_eq!(window.len(), usize::from(u16::MAX) + 1);
+            let indices = window.clone().map(|(idx, coords)| {
+                assert_eq!(coords, decode_2d(idx));
+                idx
+            });
+            let mut expected = (0..=u16::MAX).collect::<Vec<_>>();
+            expected.sort_by_key(|&idx| {
+                let distance = i32::from(idx) - i32::from(start);
+                (distance.abs(), distance < 0)
+            });
+            assert!(indices.eq(expected));
+        }
+        let mut window = iter_window_2d(1);
+        assert_eq!(window.next(), Some((1, decode_2d(1))));
+        assert_eq!(window.next(), Some((2, decode_2d(2))));
+        assert_eq!(window.next(), Some((0, decode_2d(0))));
+        assert_eq!(window.next(), Some((3, decode_2d(3))));
+        assert_eq!(window.len(), usize::from(u16::MAX) - 3);
+    }
+
+    #[test]
+    fn join_2d() {
+        use super::idx16::{decode_2d, join_2d};
+        let mut state = 42u32;
+        let mut random_keys = |len: usize| {
+            let mut keys = (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    (state >> 16) as u16
+                })
+                .collect::<Vec<_>>();
+            keys.sort_unstable();
+            keys
+        };
+        let (a, b) = (random_keys(300), random_keys(2000));
+        for distance in [0, 1, 3, 20, u8::MAX] {
+            let mut expected = Vec::new();
+            for (i, &a_idx) in a.iter().enumerate() {
+                let [ax, ay] = decode_2d(a_idx);
+                for (j, &b_idx) in b.iter().enumerate() {
+                    let [bx, by] = decode_2d(b_idx);
+                    if ax.max(bx) - ax.min(bx) <= distance && ay.max(by) - ay.min(by) <= distance {
+                        expected.push((i, j));
+                    }
+                }
+            }
+            assert_eq!(join_2d(&a, &b, distance).collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(join_2d(&a, &[], 10).count(), 0);
+        assert_eq!(join_2d(&[], &b, 10).count(), 0);
+    }
+
+    #[test]
+    fn iter_masked_2d() {
+        use super::idx16::{iter_2d, iter_clipped_2d, iter_masked_2d};
+        // Trivial filters only see the square that covers the whole domain
+        let mut calls = 0;
+        assert!(iter_masked_2d(|corner, side| {
+            calls += 1;
+            assert_eq!((corner, side), ([0, 0], 1 << u8::BITS));
+            MaskAction::Keep
+        })
+        .eq(iter_2d()));
+        assert_eq!(calls, 1);
+        assert_eq!(iter_masked_2d(|_, _| MaskAction::Skip).count(), 0);
+        assert!(iter_masked_2d(|_, _| MaskAction::Descend).eq(iter_2d()));
+
+        // Rectangles yield the same points as clipped iteration
+        let (width, height) = (100, 37);
+        assert!(iter_masked_2d(|[x, y], side| {
+            let [x, y] = [u16::from(x), u16::from(y)];
+            let [width, height] = [u16::from(width), u16::from(height)];
+            if x + side <= width && y + side <= height {
+                MaskAction::Keep
+            } else if x >= width || y >= height {
+                MaskAction::Skip
+            } else {
+                MaskAction::Descend
+            }
+        })
+        .eq(iter_clipped_2d(width, height)));
+
+        // Disks are handled by descending into the squares on their edge
+        let ([cx, cy], radius) = ([100i32, 60], 50i32);
+        let in_disk = |[x, y]: [i32; 2]| (x - cx).pow(2) + (y - cy).pow(2) <= radius.pow(2);
+        let mut calls = 0;
+        let masked = iter_masked_2d(|[x, y], side| {
+            calls += 1;
+            let [x0, y0] = [i32::from(x), i32::from(y)];
+            let [x1, y1] = [x0 + i32::from(side) - 1, y0 + i32::from(side) - 1];
+            let nearest = [cx.max(x0).min(x1), cy.max(y0).min(y1)];
+            if [[x0, y0], [x0, y1], [x1, y0], [x1, y1]]
+                .iter()
+                .all(|&p| in_disk(p))
+            {
+                MaskAction::Keep
+            } else if !in_disk(nearest) {
+                MaskAction::Skip
+            } else {
+                MaskAction::Descend
+            }
+        })
+        .collect::<Vec<_>>();
+        assert!(masked
+            .iter()
+            .copied()
+            .eq(iter_2d().filter(|&[x, y]| in_disk([i32::from(x), i32::from(y)]))));
+        assert!(calls < masked.len());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn ranges_for_rect() {
+        for (min, max) in [
+            ([0, 0], [0, 0]),
+            ([3, 5], [2, 5]),
+            ([0, 0], [Coordinate::MAX; 2]),
+            ([2, 3], [9, 7]),
+            ([1, 1], [Coordinate::MAX - 1, 200]),
+            ([100, 37], [100, 200]),
+            ([Coordinate::MAX, 0], [Coordinate::MAX; 2]),
+        ] {
+            // Compute the expected ranges by going through the whole curve
+            let mut expected = Vec::<core::ops::RangeInclusive<CurveIdx>>::new();
+            for (idx, [x, y]) in super::iter_2d().indexed() {
+                if x < min[0] || x > max[0] || y < min[1] || y > max[1] {
+                    continue;
+                }
+                match expected.last_mut() {
+                    Some(range) if *range.end() + 1 == idx => *range = *range.start()..=idx,
+                    _ => expected.push(idx..=idx),
+                }
+            }
+            assert_eq!(
+                super::ranges_for_rect(min, max).collect::<Vec<_>>(),
+                expected
+            );
+            let mut reversed = super::ranges_for_rect(min, max).rev().collect::<Vec<_>>();
+            reversed.reverse();
+            assert_eq!(reversed, expected);
+            let mut iter = super::ranges_for_rect(min, max);
+            let mut both_ends = Vec::new();
+            while let Some(range) = iter.next() {
+                both_ends.push(range);
+                both_ends.extend(iter.next_back());
+            }
+            both_ends.sort_by_key(|range| *range.start());
+            assert_eq!(both_ends, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn sort_radix() {
+        let mut points = (CurveIdx::MIN..=CurveIdx::MAX)
+            .map(|idx| super::decode_2d(idx.wrapping_mul(0x9E37)))
+            .collect::<Vec<_>>();
+        super::sort_radix(&mut points);
+        assert!(points.into_iter().eq(super::iter_2d()));
+        let mut points = [[2, 1], [0, 0], [2, 1]];
+        super::sort_radix(&mut points);
+        assert_eq!(points, [[0, 0], [2, 1], [2, 1]]);
+        super::sort_radix(&mut []);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn sort_by_key() {
+        let mut items = (CurveIdx::MIN..=CurveIdx::MAX)
+            .rev()
+            .map(|idx| (super::decode_2d(idx), idx))
+            .collect::<Vec<_>>();
+        let num_calls = core::cell::Cell::new(0);
+        super::sort_by_key(&mut items, |&(coords, _idx)| {
+            num_calls.set(num_calls.get() + 1);
+            coords
+        });
+        assert_eq!(num_calls.get(), items.len());
+        assert!(items
+            .into_iter()
+            .map(|(_coords, idx)| idx)
+            .eq(CurveIdx::MIN..=CurveIdx::MAX));
+    }
+
+    #[test]
+    fn cmp_2d() {
+        let mut gen = Gen::new(usize::MAX);
+        for _ in 0..100_000 {
+            let a = <[Coordinate; 2]>::arbitrary(&mut gen);
+            let b = <[Coordinate; 2]>::arbitrary(&mut gen);
+            assert_eq!(
+                super::cmp_2d(a, b),
+                super::encode_2d(a).cmp(&super::encode_2d(b)),
+                "Unexpected comparison result for {a:?} and {b:?}"
+            );
+        }
+        for idx in sampled_indices().filter(|&idx| idx != CurveIdx::MAX) {
+            let [a, b] = [super::decode_2d(idx), super::decode_2d(idx + 1)];
+            assert_eq!(super::cmp_2d(a, b), core::cmp::Ordering::Less);
+            assert_eq!(super::cmp_2d(b, a), core::cmp::Ordering::Greater);
+            assert_eq!(super::cmp_2d(a, a), core::cmp::Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn dilated_arithmetic() {
+        for offset in [0, 1, 2, 3, 100, Coordinate::MAX] {
+            for code in sampled_indices() {
+                let [x, y] = super::decode_2d(code);
+                let encode = super::encode_2d;
+                assert_eq!(
+                    super::add_x(code, offset),
+                    encode([x.wrapping_add(offset), y])
+                );
+                assert_eq!(
+                    super::sub_x(code, offset),
+                    encode([x.wrapping_sub(offset), y])
+                );
+                assert_eq!(
+                    super::add_y(code, offset),
+                    encode([x, y.wrapping_add(offset)])
+                );
+                assert_eq!(
+                    super::sub_y(code, offset),
+                    encode([x, y.wrapping_sub(offset)])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn encode_2d_radix() {
+        let coord_bits = Coordinate::BITS;
+        for digit_bits in 1..=coord_bits {
+            for code in sampled_indices() {
+                let coords = super::decode_2d_radix(code, digit_bits);
+                assert_eq!(super::encode_2d_radix(coords, digit_bits), code);
+            }
+        }
+        for code in sampled_indices() {
+            let [x, y] = super::decode_2d(code);
+            assert_eq!(super::encode_2d_radix([x, y], 1), code);
+            let row_major = ((y as CurveIdx) << coord_bits) | x as CurveIdx;
+            assert_eq!(super::encode_2d_radix([x, y], coord_bits), row_major);
+        }
+
+        // Base-8 digits of 8-bit coordinates, the highest-order one being truncated
+        let [x, y] = [0b10_110_011, 0b01_101_111];
+        let expected = 3 | 7 << 3 | 6 << 6 | 5 << 9 | 2 << 12 | 1 << 14;
+        assert_eq!(super::idx16::encode_2d_radix([x, y], 3), expected);
+        assert_eq!(super::idx16::decode_2d_radix(expected, 3), [x, y]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn encode_2d_radix_empty_digits() {
+        super::encode_2d_radix([0, 0], 0);
+    }
+
+    #[test]
+    fn tiled_index() {
+        for tile_bits in 0..MAX_TESTED_ORDER {
+            let tile_side = 1 << tile_bits;
+            for &num_tiles in &[1, 3] {
+                let row_pitch = num_tiles * tile_side;
+                if row_pitch > 1 << Coordinate::BITS {
+                    continue;
+                }
+                let height = tile_side + 1;
+                let mut visited = std::collections::HashSet::new();
+                for (index, [x, y]) in
+                    super::iter_tiled_2d(tile_bits, row_pitch, height).enumerate()
+                {
+                    let index = index as CurveIdx;
+                    assert_eq!(super::tiled_index(x, y, tile_bits, row_pitch), index);
+                    assert_eq!(super::tiled_coords(index, tile_bits, row_pitch), [x, y]);
+                    let tile_start = index & !(tile_side * tile_side - 1);
+                    let [tile_x, tile_y] = super::tiled_coords(tile_start, tile_bits, row_pitch);
+                    assert_eq!(
+                        [x - tile_x, y - tile_y],
+                        super::decode_2d(index - tile_start)
+                    );
+                    assert!((x as CurveIdx) < row_pitch);
+                    assert!(visited.insert([x, y]));
+                }
+                assert_eq!(visited.len(), 2 * (row_pitch * tile_side) as usize);
+                assert!(super::iter_tiled_2d(tile_bits, row_pitch, height)
+                    .rev()
+                    .eq(expected_tiled_order(tile_bits, row_pitch, height).rev()));
+            }
+            assert_eq!(super::iter_tiled_2d(tile_bits, tile_side, 0).count(), 0);
+        }
+
+        // A layout can cover the whole domain
+        use super::idx16::{iter_tiled_2d, tiled_index};
+        let side = 1 << u8::BITS;
+        assert!(iter_tiled_2d(1, side, side)
+            .map(|[x, y]| tiled_index(x, y, 1, side))
+            .eq(0..=u16::MAX));
+    }
+
+    #[test]
+    #[should_panic]
+    fn iter_tiled_2d_too_tall() {
+        super::iter_tiled_2d(0, 1 << Coordinate::BITS, (1 << Coordinate::BITS) + 1);
+    }
+
+    fn expected_tiled_order(
+        tile_bits: crate::NumBits,
+        row_pitch: CurveIdx,
+        height: CurveIdx,
+    ) -> impl DoubleEndedIterator<Item = Coordinates2D> {
+        let tile_side = 1 << tile_bits;
+        let len = (row_pitch * ((height + tile_side - 1) / tile_side * tile_side)) as usize;
+        (0..len).map(move |index| super::tiled_coords(index as CurveIdx, tile_bits, row_pitch))
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn quadkey() {
+        // Example from the Bing Maps tile system documentation
+        let tile = super::encode_2d([3, 5]);
+        assert_eq!(super::to_quadkey(tile, 3), "213");
+        assert_eq!(super::from_quadkey("213"), Ok((tile, 3)));
+        assert_eq!(super::to_quadkey(0, 0), "");
+        assert_eq!(super::from_quadkey(""), Ok((0, 0)));
+        for code in sampled_indices() {
+            let key = super::to_quadkey(code, Coordinate::BITS);
+            assert_eq!(super::from_quadkey(&key), Ok((code, Coordinate::BITS)));
+        }
+        assert_eq!(super::from_quadkey("0124"), Err(super::ParseQuadkeyError));
+        let too_long = "0".repeat(Coordinate::BITS as usize + 1);
+        assert_eq!(
+            super::from_quadkey(&too_long),
+            Err(super::ParseQuadkeyError)
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_2d_rev() {
+        for (iter, (coords, idx)) in super::iter_2d()
+            .rev()
+            .zip((CurveIdx::MIN..=CurveIdx::MAX).rev())
+            .enumerate()
+        {
+            assert_eq!(
+                coords,
+                super::decode_2d(idx),
+                "Unexpected reverse 2D Morton code iterator output at iteration {iter}"
+            );
+        }
+    }
+
+    quickcheck! {
+        fn iter_from_2d_both_ends(start: CurveIdx, from_back: Vec<bool>) -> bool {
+            let mut iter = super::iter_from_2d(start);
+            let mut indices = start..=CurveIdx::MAX;
+            from_back.into_iter().all(|from_back| {
+                if from_back {
+                    iter.next_back() == indices.next_back().map(super::decode_2d)
+                } else {
+                    iter.next() == indices.next().map(super::decode_2d)
+                }
+            })
+        }
+
+        #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+        fn iter_range_2d(start: CurveIdx, end: CurveIdx) -> bool {
+            super::iter_range_2d(start..end).eq((start..end).map(super::decode_2d))
+                && super::iter_range_2d(start..=end)
+                    .rev()
+                    .eq((start..=end).rev().map(super::decode_2d))
+                && super::iter_range_2d(..end).eq((0..end).map(super::decode_2d))
+        }
+
+        #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+        fn iter_from_2d_indexed(start: CurveIdx, n: u16) -> bool {
+            let expected = |idx| (idx, super::decode_2d(idx));
+            let mut iter = super::iter_from_2d(start).indexed();
+            let mut indices = start..=CurveIdx::MAX;
+            iter.clone().eq(indices.clone().map(expected))
+                && iter.next_back() == indices.next_back().map(expected)
+                && iter.nth(n.into()) == indices.nth(n.into()).map(expected)
+                && iter.nth_back(n.into()) == indices.nth_back(n.into()).map(expected)
+        }
+
+        fn iter_from_2d_nth(start: CurveIdx, jumps: Vec<(bool, u16)>) -> bool {
+            let mut iter = super::iter_from_2d(start);
+            let mut indices = start..=CurveIdx::MAX;
+            jumps.into_iter().all(|(from_back, n)| {
+                let n = usize::from(n);
+                if from_back {
+                    iter.nth_back(n) == indices.nth_back(n).map(super::decode_2d)
+                } else {
+                    iter.nth(n) == indices.nth(n).map(super::decode_2d)
+                }
+            })
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn iter_neighborhoods_2d() {
+        for (idx, neighborhood) in super::iter_neighborhoods_2d().enumerate() {
+            let [x, y] = super::decode_2d(idx as CurveIdx);
+            for (dy, y) in [y.checked_sub(1), Some(y), y.checked_add(1)]
+                .into_iter()
+                .enumerate()
+            {
+                for (dx, x) in [x.checked_sub(1), Some(x), x.checked_add(1)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let coords = x.zip(y).map(|(x, y)| [x, y]);
+                    assert_eq!(
+                        neighborhood.coords[dy][dx], coords,
+                        "Unexpected neighbor coordinates at index {idx}"
+                    );
+                    assert_eq!(
+                        neighborhood.indices[dy][dx].map(super::decode_2d),
+                        coords,
+                        "Unexpected neighbor index at index {idx}"
+                    );
+                }
+            }
+        }
+    }
+
+    // Wider curve index types are tested using random inputs and consistency
+    // checks with respect to narrower curve index types
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty, $coordinate:ty, $narrower:ident) => {
+            mod $width {
+                use crate::morton::{$narrower, $width, Orientation};
+                use crate::MaskAction;
+                use quickcheck::quickcheck;
+
+                #[allow(unused)]
+                const IDX_BITS: u32 = <$curve_idx>::BITS;
+                #[allow(unused)]
+                const COORD_BITS: u32 = <$coordinate>::BITS;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let [x, y] = $width::decode_2d(code);
+                        (0..COORD_BITS).all(|bit| {
+                            <$curve_idx>::from((x >> bit) & 1) == (code >> (2 * bit)) & 1
+                                && <$curve_idx>::from((y >> bit) & 1) == (code >> (2 * bit + 1)) & 1
+                        })
+                    }
+
+                    fn encode_2d(coords: [$coordinate; 2]) -> bool {
+                        $width::decode_2d($width::encode_2d(coords)) == coords
+                    }
+
+                    fn decode_2d_fast(code: $curve_idx) -> bool {
+                        $width::decode_2d_fast(code) == $width::decode_2d(code)
+                    }
+
+                    fn encode_2d_fast(coords: [$coordinate; 2]) -> bool {
+                        $width::encode_2d_fast(coords) == $width::encode_2d(coords)
+                    }
+
+                    fn decode_2d_lut(code: $curve_idx) -> bool {
+                        $width::decode_2d_lut(code) == $width::decode_2d(code)
+                    }
+
+                    #[cfg(feature = "alloc")]
+                    fn decode_2d_simd(codes: Vec<$curve_idx>) -> bool {
+                        $width::decode_2d_simd(&codes)
+                            .into_iter()
+                            .eq(codes.into_iter().map($width::decode_2d))
+                    }
+
+                    fn iter_from_2d(start: $curve_idx) -> bool {
+                        $width::iter_from_2d(start)
+                            .zip(start..=<$curve_idx>::MAX)
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+
+                    fn iter_from_2d_step_by(start: $curve_idx, step: u16) -> bool {
+                        let step = usize::from(step) + 1;
+                        $width::iter_from_2d(start)
+                            .step_by(step)
+                            .zip((start..=<$curve_idx>::MAX).step_by(step))
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+
+                    fn iter_masked_2d(min: [$coordinate; 2], size: [u8; 2]) -> bool {
+                        let max = [
+                            min[0].saturating_add(size[0].into()),
+                            min[1].saturating_add(size[1].into()),
+                        ];
+                        let masked = $width::iter_masked_2d(|corner, side| {
+                            let last = |axis: usize| u128::from(corner[axis]) + side as u128 - 1;
+                            if (0..2).all(|axis| corner[axis] >= min[axis] && last(axis) <= u128::from(max[axis])) {
+                                MaskAction::Keep
+                            } else if (0..2).any(|axis| corner[axis] > max[axis] || last(axis) < u128::from(min[axis])) {
+                                MaskAction::Skip
+                            } else {
+                                MaskAction::Descend
+                            }
+                        });
+                        masked.eq($width::ranges_for_rect(min, max).flat_map($width::iter_range_2d))
+                    }
+
+                    fn iter_jumps_2d(min_level: u32) -> bool {
+                        let min_level = min_level % (2 * COORD_BITS - 1) + 1;
+                        let jumps = $width::iter_jumps_2d(min_level);
+                        jumps.clone().take(100).chain(jumps.rev().take(100)).all(|jump| {
+                            jump.level >= min_level
+                                && jump.level == jump.index.trailing_ones()
+                                && jump.from == $width::decode_2d(jump.index)
+                                && jump.to == $width::decode_2d(jump.index + 1)
+                        })
+                    }
+
+                    fn iter_clipped_2d(width: u8, height: u8) -> bool {
+                        let bounds = [<$coordinate>::from(width), <$coordinate>::from(height)];
+                        let mut last_idx = None;
+                        let mut count = 0;
+                        for coords in $width::iter_clipped_2d(bounds[0], bounds[1]) {
+                            let idx = $width::encode_2d(coords);
+                            if coords[0] >= bounds[0] || coords[1] >= bounds[1] || last_idx >= Some(idx) {
+                                return false;
+                            }
+                            last_idx = Some(idx);
+                            count += 1;
+                        }
+                        count == usize::from(width) * usize::from(height)
+                    }
+
+                    fn ranges_for_rect(min: [$coordinate; 2], size: [u8; 2]) -> bool {
+                        let max = [
+                            min[0].saturating_add(size[0].into()),
+                            min[1].saturating_add(size[1].into()),
+                        ];
+                        let in_rect = |[x, y]: [$coordinate; 2]| {
+                            x >= min[0] && x <= max[0] && y >= min[1] && y <= max[1]
+                        };
+                        let mut num_points = 0;
+                        let mut prev_end = None;
+                        for range in $width::ranges_for_rect(min, max) {
+                            let (start, end) = (*range.start(), *range.end());
+                            if prev_end.map_or(false, |prev_end| prev_end + 1 >= start)
+                                || start.checked_sub(1).map_or(false, |prev| in_rect($width::decode_2d(prev)))
+                                || end.checked_add(1).map_or(false, |next| in_rect($width::decode_2d(next)))
+                                || !range.clone().all(|idx| in_rect($width::decode_2d(idx)))
+                            {
+                                return false;
+                            }
+                            num_points += end - start + 1;
+                            prev_end = Some(end);
+                        }
+                        let width = <$curve_idx>::from(max[0] - min[0]) + 1;
+                        let height = <$curve_idx>::from(max[1] - min[1]) + 1;
+                        num_points == width * height
+                    }
+
+                    fn dilated_arithmetic(code: $curve_idx, offset: $coordinate) -> bool {
+                        let [x, y] = $width::decode_2d(code);
+                        $width::add_x(code, offset) == $width::encode_2d([x.wrapping_add(offset), y])
+                            && $width::sub_x(code, offset) == $width::encode_2d([x.wrapping_sub(offset), y])
+                            && $width::add_y(code, offset) == $width::encode_2d([x, y.wrapping_add(offset)])
+                            && $width::sub_y(code, offset) == $width::encode_2d([x, y.wrapping_sub(offset)])
+                    }
+
+                    fn decode_4d(code: $curve_idx) -> bool {
+                        let coords = $width::decode_4d(code);
+                        let bits_match = coords.iter().enumerate().all(|(dim, &coord)| {
+                            (0..IDX_BITS / 4).all(|bit| {
+                                <$curve_idx>::from((coord >> bit) & 1) == (code >> (4 * bit + dim as u32)) & 1
+                            })
+                        });
+                        bits_match && $width::encode_4d(coords) == code
+                    }
+
+                    fn encode_2d_oriented(coords: [$coordinate; 2], flags: (bool, bool, bool)) -> bool {
+                        let (swap, mirror_x, mirror_y) = flags;
+                        let orientation = Orientation { swap, mirror_x, mirror_y };
+                        let code = $width::encode_2d_oriented(coords, orientation);
+                        $width::decode_2d_oriented(code, orientation) == coords
+                    }
+
+                    fn encode_2d_radix(coords: [$coordinate; 2], digit_bits: u32) -> bool {
+                        let digit_bits = digit_bits % COORD_BITS + 1;
+                        let code = $width::encode_2d_radix(coords, digit_bits);
+                        $width::decode_2d_radix(code, digit_bits) == coords
+                    }
+
+                    fn cmp_2d(a: [$coordinate; 2], b: [$coordinate; 2]) -> bool {
+                        $width::cmp_2d(a, b) == $width::encode_2d(a).cmp(&$width::encode_2d(b))
+                    }
+
+                    fn tiled_index(x: $coordinate, y: $coordinate, tile_bits: u32) -> bool {
+                        let tile_bits = tile_bits % COORD_BITS;
+                        let row_pitch = (x as $curve_idx + 1).next_power_of_two().max(1 << tile_bits);
+                        let index = $width::tiled_index(x, y, tile_bits, row_pitch);
+                        $width::tiled_coords(index, tile_bits, row_pitch) == [x, y]
+                    }
+
+                    fn iter_from_2d_rev(start: $curve_idx) -> bool {
+                        $width::iter_from_2d(start)
+                            .rev()
+                            .zip((start..=<$curve_idx>::MAX).rev())
+                            .take(1000)
+                            .all(|(coords, idx)| coords == $width::decode_2d(idx))
+                    }
+                }
+
+                #[test]
+                fn iter_blocks_2d() {
+                    for block_bits in 0..=COORD_BITS {
+                        let last = $width::iter_blocks_2d(block_bits).next_back().unwrap();
+                        assert_eq!(*last.indices.end(), <$curve_idx>::MAX);
+                        let mask = <$coordinate>::MAX.checked_shl(block_bits).unwrap_or(0);
+                        assert_eq!(last.min.map(|c| c & !mask), [0, 0]);
+                        assert_eq!(last.max.map(|c| c & !mask), [!mask; 2]);
+                        let [x, y] = $width::decode_2d(<$curve_idx>::MAX);
+                        assert_eq!([x & mask, y & mask], last.min);
+                    }
+                }
+
+                #[test]
+                fn narrower() {
+                    for code in 0..=u16::MAX {
+                        assert_eq!(
+                            $width::decode_2d(code.into()),
+                            $narrower::decode_2d(code.into()).map(<$coordinate>::from)
+                        );
+                    }
+                }
+
+                #[test]
+                fn iter_end() {
+                    assert_eq!($width::iter_from_2d(<$curve_idx>::MAX - 41).count(), 42);
+                    assert_eq!($width::iter_range_2d(<$curve_idx>::MAX - 41..).count(), 42);
+                    assert_eq!($width::iter_range_2d(..42).count(), 42);
+                    assert_eq!($width::iter_range_2d(..0).count(), 0);
+                    assert_eq!(
+                        $width::iter_range_2d((
+                            core::ops::Bound::Excluded(<$curve_idx>::MAX),
+                            core::ops::Bound::Unbounded
+                        ))
+                        .count(),
+                        0
+                    );
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32, u16, idx16);
+    wide_tests!(idx64, u64, u32, idx32);
+    wide_tests!(idx128, u128, u64, idx64);
 }
@@ -0,0 +1,176 @@
+//! Interoperability with geohashes
+//!
+//! A geohash is a base32-encoded Morton code of quantized longitude and
+//! latitude, where longitude comes first. Therefore, a 64-bit Morton code of
+//! coordinates `[latitude, longitude]`, each quantized to 32 bits, is a geohash
+//! with some extra low-order bits, which can be truncated to the desired number
+//! of characters. Each geohash character carries 5 bits, so up to 12 of them
+//! fit in a 64-bit curve index.
+
+use super::idx64::{decode_2d, encode_2d};
+use core::fmt;
+
+/// Base32 alphabet used by geohashes, which omits a, i, l and o
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Maximal number of characters of a geohash that fits in a 64-bit curve index
+pub const MAX_GEOHASH_LEN: usize = 12;
+
+/// Quantize a latitude and a longitude, in degrees, into coordinates for the
+/// 64-bit Morton curve
+///
+/// Latitudes go from -90 to 90 degrees and are mapped to the x coordinate,
+/// while longitudes go from -180 to 180 degrees and are mapped to the y
+/// coordinate. Out-of-range values are clamped.
+///
+pub fn lat_lon_to_coords(lat: f64, lon: f64) -> [u32; 2] {
+    [quantize(lat, 90.0), quantize(lon, 180.0)]
+}
+
+/// Latitude and longitude, in degrees, of the south-west corner of the cell
+/// designated by some coordinates from `lat_lon_to_coords()`
+pub fn coords_to_lat_lon([x, y]: [u32; 2]) -> (f64, f64) {
+    (dequantize(x, 90.0), dequantize(y, 180.0))
+}
+
+/// 64-bit Morton code of a point given by its latitude and longitude, in
+/// degrees, see `lat_lon_to_coords()`
+#[inline]
+pub fn lat_lon_to_code(lat: f64, lon: f64) -> u64 {
+    encode_2d(lat_lon_to_coords(lat, lon))
+}
+
+/// Latitude and longitude, in degrees, of the south-west corner of the cell
+/// designated by a 64-bit Morton code from `lat_lon_to_code()`
+#[inline]
+pub fn code_to_lat_lon(code: u64) -> (f64, f64) {
+    coords_to_lat_lon(decode_2d(code))
+}
+
+/// Map a value from [-range; range] to [0; 2^32[
+fn quantize(value: f64, range: f64) -> u32 {
+    // Float-to-integer casts saturate, which takes care of clamping
+    ((value + range) / (2.0 * range) * 4294967296.0) as u32
+}
+
+/// Inverse of `quantize()`, mapping each integer to the lower bound of the
+/// range of values that it represents
+fn dequantize(value: u32, range: f64) -> f64 {
+    f64::from(value) / 4294967296.0 * (2.0 * range) - range
+}
+
+/// Integer form of the geohash with `len` characters that contains the point
+/// with a certain 64-bit Morton code
+///
+/// # Panics
+///
+/// If `len` is larger than `MAX_GEOHASH_LEN`.
+///
+pub const fn to_geohash_u64(code: u64, len: usize) -> u64 {
+    assert!(len <= MAX_GEOHASH_LEN, "Geohash is too long for a u64");
+    if len == 0 {
+        0
+    } else {
+        code >> (64 - 5 * len)
+    }
+}
+
+/// Smallest 64-bit Morton code within the integer form of a geohash with `len`
+/// characters
+///
+/// # Panics
+///
+/// If `len` is larger than `MAX_GEOHASH_LEN`.
+///
+pub const fn from_geohash_u64(hash: u64, len: usize) -> u64 {
+    assert!(len <= MAX_GEOHASH_LEN, "Geohash is too long for a u64");
+    if len == 0 {
+        0
+    } else {
+        hash << (64 - 5 * len)
+    }
+}
+
+/// Geohash with `len` characters that contains the point with a certain
+/// 64-bit Morton code
+///
+/// # Panics
+///
+/// If `len` is larger than `MAX_GEOHASH_LEN`.
+///
+#[cfg(feature = "alloc")]
+pub fn to_geohash(code: u64, len: usize) -> alloc::string::String {
+    let hash = to_geohash_u64(code, len);
+    (0..len)
+        .rev()
+        .map(|char_idx| char::from(ALPHABET[((hash >> (5 * char_idx)) & 31) as usize]))
+        .collect()
+}
+
+/// Smallest 64-bit Morton code within a geohash, along with the number of
+/// characters of that geohash
+///
+/// Geohashes are case-insensitive.
+///
+pub fn from_geohash(hash: &str) -> Result<(u64, usize), ParseGeohashError> {
+    if hash.len() > MAX_GEOHASH_LEN {
+        return Err(ParseGeohashError);
+    }
+    let hash_u64 = hash.bytes().try_fold(0, |acc, byte| {
+        let digit = ALPHABET
+            .iter()
+            .position(|&letter| letter == byte.to_ascii_lowercase())
+            .ok_or(ParseGeohashError)?;
+        Ok((acc << 5) | digit as u64)
+    })?;
+    Ok((from_geohash_u64(hash_u64, hash.len()), hash.len()))
+}
+
+/// Error returned when parsing an invalid geohash
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseGeohashError;
+//
+impl fmt::Display for ParseGeohashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid geohash, expected at most {MAX_GEOHASH_LEN} characters from the geohash alphabet"
+        )
+    }
+}
+//
+#[cfg(feature = "std")]
+impl std::error::Error for ParseGeohashError {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geohash() {
+        // Reference values from the original geohash.org service
+        for &(lat, lon, hash) in &[
+            (57.64911, 10.40744, "u4pruydqqvj"),
+            (42.6, -5.6, "ezs42"),
+            (-25.382708, -49.265506, "6gkzwgjzn820"),
+        ] {
+            let code = lat_lon_to_code(lat, lon);
+            assert_eq!(to_geohash(code, hash.len()), hash);
+            let (cell, len) = from_geohash(hash).unwrap();
+            assert_eq!(len, hash.len());
+            assert_eq!(to_geohash(cell, len), hash);
+            assert_eq!(from_geohash(&hash.to_uppercase()), Ok((cell, len)));
+            assert_eq!(to_geohash_u64(cell, len), to_geohash_u64(code, len));
+            assert_eq!(from_geohash_u64(to_geohash_u64(code, len), len), cell);
+            let (cell_lat, cell_lon) = code_to_lat_lon(cell);
+            assert!(cell_lat <= lat && lat - cell_lat < 180.0 / 2f64.powi(5 * len as i32 / 2));
+            assert!(cell_lon <= lon && lon - cell_lon < 360.0 / 2f64.powi(5 * len as i32 / 2));
+        }
+        assert_eq!(to_geohash(u64::MAX, 0), "");
+        assert_eq!(from_geohash(""), Ok((0, 0)));
+        assert_eq!(from_geohash("ezs42a"), Err(ParseGeohashError));
+        assert_eq!(from_geohash("0123456789bcd"), Err(ParseGeohashError));
+        assert_eq!(lat_lon_to_coords(-1000.0, 1000.0), [0, u32::MAX]);
+        assert_eq!(coords_to_lat_lon([0, 1 << 31]), (-90.0, 0.0));
+    }
+}
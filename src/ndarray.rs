@@ -0,0 +1,140 @@
+//! Integration with the `ndarray` crate
+//!
+//! This module, which is only available when the `ndarray` cargo feature is
+//! enabled, lets users go through the elements of 2D arrays in the order of a
+//! space-filling curve, and build 2D arrays from curve-ordered data.
+//!
+//! By `ndarray` convention, the first axis of a 2D array is the row axis, so the
+//! element of coordinates `[x, y]` is `array[[y, x]]`. Arrays need not be square,
+//! nor have power-of-two dimensions: when they don't, the curve order is the
+//! order in which the curve goes through the elements of the array, skipping
+//! points that are outside of it. But their dimensions must be smaller than
+//! `Coordinate::MAX`.
+
+use crate::{order, Coordinate, Coordinates2D, CurveKind, CurveOrder};
+use ::ndarray::{Array2, ArrayBase, Data, DataMut, Ix2};
+use alloc::vec::Vec;
+
+/// Iterate over the elements of a 2D array in curve order, along with their
+/// coordinates
+///
+/// # Panics
+///
+/// If the array is too large for the curve's domain.
+///
+pub fn iter_curve_order<'a, T: 'a, S: Data<Elem = T>>(
+    array: &'a ArrayBase<S, Ix2>,
+    kind: CurveKind,
+) -> impl Iterator<Item = (Coordinates2D, &'a T)> + 'a {
+    let [width, height] = array_size(array);
+    kind.iter_clipped_2d(width, height)
+        .map(move |[x, y]| ([x, y], &array[[y as usize, x as usize]]))
+}
+
+/// Iterate over the elements of a 2D array in curve order, along with their
+/// coordinates, with mutable access
+///
+/// Since elements cannot be accessed out of row-major order without keeping the
+/// array borrowed, this collects one reference per element into a temporary
+/// buffer before iteration begins.
+///
+/// # Panics
+///
+/// If the array is too large for the curve's domain.
+///
+pub fn iter_curve_order_mut<'a, T: 'a, S: DataMut<Elem = T>>(
+    array: &'a mut ArrayBase<S, Ix2>,
+    kind: CurveKind,
+) -> impl Iterator<Item = (Coordinates2D, &'a mut T)> + 'a {
+    let [width, height] = array_size(array);
+    let mut elements = array.iter_mut().map(Some).collect::<Vec<_>>();
+    kind.iter_clipped_2d(width, height).map(move |[x, y]| {
+        let row_major = y as usize * width as usize + x as usize;
+        let element = elements[row_major]
+            .take()
+            .expect("Curve went through a point twice");
+        ([x, y], element)
+    })
+}
+
+/// Build a `width` x `height` array from elements in curve order
+///
+/// # Panics
+///
+/// If `elements` does not yield exactly `width * height` elements.
+///
+pub fn from_curve_order<T>(
+    kind: CurveKind,
+    width: Coordinate,
+    height: Coordinate,
+    elements: impl IntoIterator<Item = T>,
+) -> Array2<T> {
+    let order = CurveOrder::new(kind, width.into(), height.into());
+    let mut data = elements.into_iter().collect::<Vec<_>>();
+    assert_eq!(
+        data.len(),
+        order.len(),
+        "Expected {width}x{height} elements"
+    );
+    order::permute_in_place(&mut data, |curve| order.from_curve(curve));
+    Array2::from_shape_vec((height as usize, width as usize), data)
+        .expect("Data length was checked above")
+}
+
+/// Width and height of a 2D array, checking that it fits in the curve's domain
+fn array_size<S: Data>(array: &ArrayBase<S, Ix2>) -> Coordinates2D {
+    let (height, width) = array.dim();
+    let max = Coordinate::MAX as usize;
+    assert!(
+        width <= max && height <= max,
+        "{width}x{height} array is too large for the curve's domain"
+    );
+    [width as Coordinate, height as Coordinate]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::CLIPPED_KINDS;
+
+    #[test]
+    fn curve_order() {
+        for &kind in CLIPPED_KINDS {
+            for &[width, height] in &[[0, 0], [1, 1], [3, 5], [16, 16], [200, 7]] {
+                let order = CurveOrder::new(kind, width.into(), height.into());
+                let shape = (height as usize, width as usize);
+                let mut array = Array2::from_shape_fn(shape, |(y, x)| [x, y]);
+                let expected = order
+                    .reorder_to_curve(array.as_slice().unwrap())
+                    .into_iter()
+                    .map(|[x, y]| [x as Coordinate, y as Coordinate])
+                    .collect::<Vec<_>>();
+
+                assert!(iter_curve_order(&array, kind).eq(expected
+                    .iter()
+                    .map(|&[x, y]| ([x, y], &array[[y as usize, x as usize]]))));
+                assert!(iter_curve_order(&array.t().t(), kind)
+                    .map(|(coords, _elem)| coords)
+                    .eq(expected.iter().copied()));
+
+                for (coords, elem) in iter_curve_order_mut(&mut array, kind) {
+                    assert_eq!([elem[0] as Coordinate, elem[1] as Coordinate], coords);
+                    elem[0] = usize::MAX;
+                }
+                assert!(array.iter().all(|elem| elem[0] == usize::MAX));
+
+                let rebuilt = from_curve_order(kind, width, height, expected.iter().copied());
+                assert_eq!(rebuilt.dim(), shape);
+                for ((y, x), &coords) in rebuilt.indexed_iter() {
+                    assert_eq!(coords, [x as Coordinate, y as Coordinate]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_curve_order_length_mismatch() {
+        from_curve_order(CurveKind::Morton, 4, 4, 0..15);
+    }
+}
@@ -0,0 +1,176 @@
+//! Spatial neighbors of points on a space-filling curve
+//!
+//! Points which are next to each other in space usually have distant curve
+//! indices, so finding the curve indices of a point's neighbors requires going
+//! through coordinates. This module provides utilities that do so, with a
+//! choice of policy for the edges of the curve's domain, along with iteration
+//! over the points whose curve indices are closest to that of a point.
+
+/// Handling of neighbors that fall outside of the curve's domain
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Boundary {
+    /// Out-of-domain neighbors are reported as `None`
+    Exclude,
+
+    /// Out-of-domain neighbors are replaced by the nearest point of the domain,
+    /// which is the central point or one of its other neighbors
+    Clamp,
+
+    /// The domain wraps around, so that the neighbors of points on an edge are
+    /// on the opposite edge
+    Wrap,
+}
+
+/// Generate neighbor lookup utilities for a curve module
+///
+/// The module must provide `decode_2d()` and `encode_2d()`, along with the
+/// usual `CurveIdx`, `Coordinate` and `Coordinates2D` names.
+///
+macro_rules! neighbors_impl {
+    ($curve_name:literal) => {
+        #[doc = concat!("Compute the curve indices of the 3x3 neighborhood of a point on the 2D ", $curve_name)]
+        /// curve
+        ///
+        /// Neighbors are stored in `[dy][dx]` order, where dx and dy go from 0 to 2,
+        /// so that the central point `code` is at `[1][1]`, its 4 direct neighbors
+        /// are at `[1][0]`, `[1][2]`, `[0][1]` and `[2][1]`, and its neighbor at
+        /// coordinates `[x - 1, y + 1]` is at `[2][0]`. Neighbors that fall outside
+        /// of the curve's domain are handled according to `boundary`.
+        ///
+        #[inline]
+        pub const fn neighbors_2d(
+            code: CurveIdx,
+            boundary: $crate::Boundary,
+        ) -> [[Option<CurveIdx>; 3]; 3] {
+            let [x, y] = decode_2d(code);
+            let xs = neighbor_coords(x, boundary);
+            let ys = neighbor_coords(y, boundary);
+            let mut neighbors = [[None; 3]; 3];
+            let mut dy = 0;
+            while dy < 3 {
+                let mut dx = 0;
+                while dx < 3 {
+                    if let (Some(x), Some(y)) = (xs[dx], ys[dy]) {
+                        neighbors[dy][dx] = Some(encode_2d([x, y]));
+                    }
+                    dx += 1;
+                }
+                dy += 1;
+            }
+            neighbors
+        }
+
+        #[doc = concat!("Compute the distance between two points along the 2D ", $curve_name, " curve")]
+        ///
+        /// This is the absolute difference between the curve indices of `a` and
+        /// `b`, computed without overflow whatever the order of the indices.
+        ///
+        #[inline]
+        pub const fn index_distance(a: Coordinates2D, b: Coordinates2D) -> CurveIdx {
+            let [a, b] = [encode_2d(a), encode_2d(b)];
+            if a > b {
+                a - b
+            } else {
+                b - a
+            }
+        }
+
+        /// Previous, current and next coordinate along an axis of the domain
+        #[inline(always)]
+        const fn neighbor_coords(
+            coord: Coordinate,
+            boundary: $crate::Boundary,
+        ) -> [Option<Coordinate>; 3] {
+            match boundary {
+                $crate::Boundary::Exclude => {
+                    [coord.checked_sub(1), Some(coord), coord.checked_add(1)]
+                }
+                $crate::Boundary::Clamp => [
+                    Some(coord.saturating_sub(1)),
+                    Some(coord),
+                    Some(coord.saturating_add(1)),
+                ],
+                $crate::Boundary::Wrap => [
+                    Some(coord.wrapping_sub(1)),
+                    Some(coord),
+                    Some(coord.wrapping_add(1)),
+                ],
+            }
+        }
+
+        #[doc = concat!("Iterate over the points of the 2D ", $curve_name, " curve by increasing")]
+        /// distance from a certain curve index
+        ///
+        /// The iterator yields `(index, coordinates)` pairs for the curve indices
+        /// `start`, `start + 1`, `start - 1`, `start + 2`, `start - 2`, and so on.
+        /// Once one end of the curve is reached, the iterator keeps going in the
+        /// other direction only, until every point of the curve has been yielded.
+        ///
+        /// Points that are close on the curve are close in space, so this is a
+        /// cheap way to find candidates for nearest neighbor searches.
+        ///
+        pub fn iter_window_2d(start: CurveIdx) -> Window2D {
+            Window2D {
+                start,
+                up: Some(start),
+                down: start.checked_sub(1),
+            }
+        }
+
+        #[doc = concat!("Iterator over the points of the 2D ", $curve_name, " curve by increasing")]
+        /// distance from a certain curve index, created by `iter_window_2d()`
+        #[derive(Clone, Debug)]
+        pub struct Window2D {
+            /// Curve index around which the window expands
+            start: CurveIdx,
+
+            /// Next curve index above `start`, if any
+            up: Option<CurveIdx>,
+
+            /// Next curve index below `start`, if any
+            down: Option<CurveIdx>,
+        }
+        //
+        impl Iterator for Window2D {
+            type Item = (CurveIdx, Coordinates2D);
+
+            #[inline]
+            fn next(&mut self) -> Option<(CurveIdx, Coordinates2D)> {
+                // Go up on ties, so that start + d comes before start - d
+                let go_up = match (self.up, self.down) {
+                    (Some(up), Some(down)) => up - self.start <= self.start - down,
+                    (up, _) => up.is_some(),
+                };
+                let idx = if go_up {
+                    let up = self.up?;
+                    self.up = up.checked_add(1);
+                    up
+                } else {
+                    let down = self.down?;
+                    self.down = down.checked_sub(1);
+                    down
+                };
+                Some((idx, decode_2d(idx)))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let num_up = self.up.map_or(Some(0), |up| {
+                    usize::try_from(CurveIdx::MAX - up).ok()?.checked_add(1)
+                });
+                let num_down = self.down.map_or(Some(0), |down| {
+                    usize::try_from(down).ok()?.checked_add(1)
+                });
+                match (num_up, num_down) {
+                    (Some(num_up), Some(num_down)) => match num_up.checked_add(num_down) {
+                        Some(len) => (len, Some(len)),
+                        None => (usize::MAX, None),
+                    },
+                    _ => (usize::MAX, None),
+                }
+            }
+        }
+        //
+        impl core::iter::FusedIterator for Window2D {}
+    };
+}
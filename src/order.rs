@@ -0,0 +1,323 @@
+//! Precomputed permutations between row-major order and curve order
+//!
+//! When many arrays of the same shape need to be reordered along a
+//! space-filling curve, decoding the curve again for each of them is wasteful.
+//! This module provides a `CurveOrder` type that decodes the curve once and
+//! stores the resulting permutation, which can then be applied to any number of
+//! slices.
+
+use crate::{Coordinate, CurveIdx, CurveKind};
+use alloc::vec::Vec;
+
+/// Permutation between the row-major order of a rectangular domain and the
+/// order in which a space-filling curve goes through it
+///
+/// The domain is a `width` x `height` rectangle whose lower corner is `[0, 0]`,
+/// and the row-major index of point `[x, y]` is `y * width + x`. Since the curve
+/// covers a larger square domain, curve order is defined as the order in which
+/// the curve goes through the points of the rectangle, skipping the others, so
+/// that curve positions are contiguous indices from 0 to `width * height - 1`.
+///
+/// Like the side length of `CellId::square_2d()`, `width` and `height` are
+/// `CurveIdx`es rather than `Coordinate`s, so that the permutation can cover
+/// the full domain of the curve.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CurveOrder {
+    /// Curve family that was used to compute the permutation
+    kind: CurveKind,
+
+    /// Width of the domain
+    width: CurveIdx,
+
+    /// Height of the domain
+    height: CurveIdx,
+
+    /// Position along the curve of each point, in row-major order
+    to_curve: Vec<usize>,
+
+    /// Row-major index of each point, in curve order
+    from_curve: Vec<usize>,
+}
+//
+impl CurveOrder {
+    /// Compute the permutation for a `width` x `height` domain
+    ///
+    /// # Panics
+    ///
+    /// If `width` or `height` is larger than the side of the curve's domain,
+    /// which is 2^`Coordinate::BITS`, or if the domain has more points than
+    /// `usize` can count.
+    ///
+    pub fn new(kind: CurveKind, width: CurveIdx, height: CurveIdx) -> Self {
+        let side: CurveIdx = 1 << Coordinate::BITS;
+        assert!(
+            width <= side && height <= side,
+            "{width}x{height} domain is too large for the curve's domain"
+        );
+        #[allow(clippy::unnecessary_cast)] // CurveIdx is u128 with idx-u128
+        let len = (width as u128)
+            .checked_mul(height as u128)
+            .and_then(|len| usize::try_from(len).ok())
+            .expect("Too many points in the domain");
+        let mut to_curve = alloc::vec![0; len];
+        let mut from_curve = Vec::with_capacity(len);
+        let mut push = |[x, y]: [Coordinate; 2]| {
+            let row_major = y as usize * width as usize + x as usize;
+            to_curve[row_major] = from_curve.len();
+            from_curve.push(row_major);
+        };
+        // Clipped iteration cannot express a full side of the curve's domain
+        match (Coordinate::try_from(width), Coordinate::try_from(height)) {
+            (Ok(width), Ok(height)) => kind.iter_clipped_2d(width, height).for_each(&mut push),
+            _ => kind
+                .iter_2d()
+                .filter(|&[x, y]| CurveIdx::from(x) < width && CurveIdx::from(y) < height)
+                .for_each(&mut push),
+        }
+        debug_assert_eq!(from_curve.len(), len);
+        Self {
+            kind,
+            width,
+            height,
+            to_curve,
+            from_curve,
+        }
+    }
+
+    /// Curve family that this permutation follows
+    #[inline]
+    pub fn kind(&self) -> CurveKind {
+        self.kind
+    }
+
+    /// Width of the domain
+    #[inline]
+    pub fn width(&self) -> CurveIdx {
+        self.width
+    }
+
+    /// Height of the domain
+    #[inline]
+    pub fn height(&self) -> CurveIdx {
+        self.height
+    }
+
+    /// Number of points in the domain
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.from_curve.len()
+    }
+
+    /// Truth that the domain has no points
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.from_curve.is_empty()
+    }
+
+    /// Position along the curve of the point with a certain row-major index
+    ///
+    /// # Panics
+    ///
+    /// If `row_major` is out of bounds.
+    ///
+    #[inline]
+    pub fn to_curve(&self, row_major: usize) -> usize {
+        self.to_curve[row_major]
+    }
+
+    /// Row-major index of the point at a certain position along the curve
+    ///
+    /// # Panics
+    ///
+    /// If `curve` is out of bounds.
+    ///
+    #[inline]
+    pub fn from_curve(&self, curve: usize) -> usize {
+        self.from_curve[curve]
+    }
+
+    /// Reorder a row-major slice into curve order
+    ///
+    /// # Panics
+    ///
+    /// If the length of `data` does not match the size of the domain.
+    ///
+    pub fn reorder_to_curve<T: Clone>(&self, data: &[T]) -> Vec<T> {
+        self.check_len(data.len());
+        self.from_curve
+            .iter()
+            .map(|&row_major| data[row_major].clone())
+            .collect()
+    }
+
+    /// Reorder a curve-ordered slice back into row-major order
+    ///
+    /// # Panics
+    ///
+    /// If the length of `data` does not match the size of the domain.
+    ///
+    pub fn reorder_from_curve<T: Clone>(&self, data: &[T]) -> Vec<T> {
+        self.check_len(data.len());
+        self.to_curve
+            .iter()
+            .map(|&curve| data[curve].clone())
+            .collect()
+    }
+
+    /// Reorder a row-major slice into curve order, in a caller-provided buffer
+    ///
+    /// This is the non-allocating version of `reorder_to_curve()`, for use in
+    /// loops that can reuse the same output buffer.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `input` or `output` does not match the size of the
+    /// domain.
+    ///
+    pub fn reorder_to_curve_into<T: Clone>(&self, input: &[T], output: &mut [T]) {
+        self.check_len(input.len());
+        self.check_len(output.len());
+        for (out, &row_major) in output.iter_mut().zip(&self.from_curve) {
+            out.clone_from(&input[row_major]);
+        }
+    }
+
+    /// Reorder a curve-ordered slice back into row-major order, in a
+    /// caller-provided buffer
+    ///
+    /// This is the non-allocating version of `reorder_from_curve()`.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `input` or `output` does not match the size of the
+    /// domain.
+    ///
+    pub fn reorder_from_curve_into<T: Clone>(&self, input: &[T], output: &mut [T]) {
+        self.check_len(input.len());
+        self.check_len(output.len());
+        for (out, &curve) in output.iter_mut().zip(&self.to_curve) {
+            out.clone_from(&input[curve]);
+        }
+    }
+
+    /// Check that a slice has one element per point of the domain
+    #[inline]
+    fn check_len(&self, len: usize) {
+        assert_eq!(
+            len,
+            self.len(),
+            "Expected a slice of {}x{} elements",
+            self.width,
+            self.height
+        );
+    }
+}
+
+/// Move every element of `data` from index `i` to index `dest(i)`, where `dest`
+/// must be a permutation of the indices of `data`
+///
+/// The permutation is decomposed into cycles, which are applied one by one using
+/// swaps. Instead of a second copy of `data`, this only allocates a bitset that
+/// keeps track of which elements have already reached their destination.
+///
+pub(crate) fn permute_in_place<T>(data: &mut [T], dest: impl Fn(usize) -> usize) {
+    const WORD_BITS: usize = u64::BITS as usize;
+    let mut done = alloc::vec![0u64; (data.len() + WORD_BITS - 1) / WORD_BITS];
+    for start in 0..data.len() {
+        if done[start / WORD_BITS] & (1 << (start % WORD_BITS)) != 0 {
+            continue;
+        }
+
+        // The element at index `start` is swapped to its destination, which
+        // brings back the element that was there, and so on until the element
+        // that belongs at index `start` comes back.
+        let mut pos = dest(start);
+        while pos != start {
+            data.swap(start, pos);
+            done[pos / WORD_BITS] |= 1 << (pos % WORD_BITS);
+            pos = dest(pos);
+        }
+        done[start / WORD_BITS] |= 1 << (start % WORD_BITS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::CLIPPED_KINDS;
+
+    #[test]
+    fn curve_order() {
+        for &kind in CLIPPED_KINDS {
+            for &[width, height] in &[[0, 0], [1, 1], [3, 5], [16, 16], [200, 7], [255, 255]] {
+                let order = CurveOrder::new(kind, width, height);
+                assert_eq!(order.kind(), kind);
+                assert_eq!([order.width(), order.height()], [width, height]);
+                assert_eq!(order.len(), width as usize * height as usize);
+                assert_eq!(order.is_empty(), width == 0 || height == 0);
+
+                // Curve order must match the order of curve indices
+                let row_major = (0..order.len()).collect::<Vec<_>>();
+                let curve = order.reorder_to_curve(&row_major);
+                let coords = |idx: usize| {
+                    [
+                        (idx % width as usize) as Coordinate,
+                        (idx / width as usize) as Coordinate,
+                    ]
+                };
+                let mut expected = row_major.clone();
+                expected.sort_by_key(|&idx| kind.encode_2d(coords(idx)));
+                assert_eq!(curve, expected);
+
+                // Both directions of the permutation must be consistent
+                for (pos, &idx) in curve.iter().enumerate() {
+                    assert_eq!(order.from_curve(pos), idx);
+                    assert_eq!(order.to_curve(idx), pos);
+                }
+                assert_eq!(order.reorder_from_curve(&curve), row_major);
+                let mut buffer = alloc::vec![0; order.len()];
+                order.reorder_to_curve_into(&row_major, &mut buffer);
+                assert_eq!(buffer, curve);
+                order.reorder_from_curve_into(&curve, &mut buffer);
+                assert_eq!(buffer, row_major);
+            }
+        }
+    }
+
+    // The full domain is only tractable with 16-bit curve indices
+    #[test]
+    #[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+    fn full_domain() {
+        let side: CurveIdx = 1 << Coordinate::BITS;
+        for kind in CurveKind::ALL {
+            let order = CurveOrder::new(kind, side, side);
+            assert_eq!(order.len(), 1 << CurveIdx::BITS);
+            for (pos, coords) in kind.iter_2d().enumerate() {
+                let row_major = coords[1] as usize * side as usize + coords[0] as usize;
+                assert_eq!(order.from_curve(pos), row_major);
+                assert_eq!(order.to_curve(row_major), pos);
+            }
+            let strip = CurveOrder::new(kind, side, 3);
+            let expected = kind
+                .iter_2d()
+                .filter(|&[_x, y]| y < 3)
+                .map(|[x, y]| y as usize * side as usize + x as usize);
+            assert!((0..strip.len())
+                .map(|pos| strip.from_curve(pos))
+                .eq(expected));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_large() {
+        CurveOrder::new(CurveKind::Hilbert, (1 << Coordinate::BITS) + 1, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn length_mismatch() {
+        CurveOrder::new(CurveKind::Morton, 4, 4).reorder_to_curve(&[0; 15]);
+    }
+}
@@ -0,0 +1,488 @@
+//! Parallel iteration over space-filling curves, using rayon
+//!
+//! Parallel iterators are created by the `par_iter_2d()` and
+//! `par_iter_range_2d()` functions of the `morton` and `hilbert` modules. They
+//! split the range of curve indices into chunks, and iterate over each chunk
+//! sequentially with the optimized iterator of the curve, starting from a
+//! direct decoding of the chunk's first index.
+//!
+//! Parallel iterators over the aligned blocks of these curves are created by
+//! their `par_blocks_2d()` functions, and hand whole blocks to worker threads.
+
+use crate::{CurveUint, NumBits};
+use core::ops::RangeInclusive;
+use rayon::iter::{
+    plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer},
+    IndexedParallelIterator, ParallelIterator,
+};
+
+/// Parallel iterator over a range of points of a 2D space-filling curve
+pub struct ParIter2D<Idx: CurveUint, Iter> {
+    producer: Producer2D<Idx, Iter>,
+}
+//
+impl<Idx, Iter> ParIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128>,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    /// Set up parallel iteration over a range of curve indices, given the
+    /// curve's decoding function and sequential iterator constructor
+    ///
+    /// # Panics
+    ///
+    /// If the number of points in `indices` does not fit in a `usize`.
+    ///
+    pub(crate) fn new(
+        indices: RangeInclusive<Idx>,
+        decode: fn(Idx) -> [Idx::Coordinate; 2],
+        iter_from: fn(Idx) -> Iter,
+    ) -> Self {
+        let (start, end) = (*indices.start(), *indices.end());
+        let len = if start > end {
+            0
+        } else {
+            (end.into() - start.into())
+                .checked_add(1)
+                .and_then(|len| usize::try_from(len).ok())
+                .expect("Too many curve points for indexed parallel iteration")
+        };
+        Self {
+            producer: Producer2D {
+                start: start.into(),
+                len,
+                decode,
+                iter_from,
+            },
+        }
+    }
+}
+//
+impl<Idx, Iter> ParallelIterator for ParIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128> + Send,
+    Idx::Coordinate: Send,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    type Item = [Idx::Coordinate; 2];
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.producer.len)
+    }
+}
+//
+impl<Idx, Iter> IndexedParallelIterator for ParIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128> + Send,
+    Idx::Coordinate: Send,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    fn len(&self) -> usize {
+        self.producer.len
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(self.producer)
+    }
+}
+
+/// Rayon producer for `ParIter2D`, which represents a chunk of curve indices
+pub struct Producer2D<Idx: CurveUint, Iter> {
+    /// First curve index of the chunk, as a u128 so that empty chunks can start
+    /// one past the last curve index without overflowing
+    start: u128,
+
+    /// Number of curve indices in the chunk
+    len: usize,
+
+    /// Curve decoding function
+    decode: fn(Idx) -> [Idx::Coordinate; 2],
+
+    /// Curve iterator constructor
+    iter_from: fn(Idx) -> Iter,
+}
+//
+impl<Idx, Iter> Producer for Producer2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128> + Send,
+    Idx::Coordinate: Send,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    type Item = [Idx::Coordinate; 2];
+    type IntoIter = ChunkIter2D<Idx, Iter>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ChunkIter2D {
+            iter: None,
+            front: self.start,
+            len: self.len,
+            decode: self.decode,
+            iter_from: self.iter_from,
+        }
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        debug_assert!(index <= self.len);
+        (
+            Self { len: index, ..self },
+            Self {
+                start: self.start + index as u128,
+                len: self.len - index,
+                ..self
+            },
+        )
+    }
+}
+
+/// Sequential iterator over a chunk of a `ParIter2D`
+///
+/// Forward iteration uses the curve's optimized iterator, which is only set up
+/// on first use, whereas backward iteration directly decodes curve indices.
+///
+pub struct ChunkIter2D<Idx: CurveUint, Iter> {
+    /// Sequential curve iterator, starting at `front`
+    iter: Option<Iter>,
+
+    /// Next curve index from the front
+    front: u128,
+
+    /// Number of remaining curve indices
+    len: usize,
+
+    /// Curve decoding function
+    decode: fn(Idx) -> [Idx::Coordinate; 2],
+
+    /// Curve iterator constructor
+    iter_from: fn(Idx) -> Iter,
+}
+//
+impl<Idx, Iter> ChunkIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128>,
+{
+    /// Convert a curve index that is known to be valid back to Idx
+    fn idx(pos: u128) -> Idx {
+        Idx::try_from(pos).unwrap_or_else(|_| unreachable!("Curve index out of range"))
+    }
+}
+//
+impl<Idx, Iter> Iterator for ChunkIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128>,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    type Item = [Idx::Coordinate; 2];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let iter_from = self.iter_from;
+        let front = self.front;
+        let iter = self.iter.get_or_insert_with(|| iter_from(Self::idx(front)));
+        self.front += 1;
+        self.len -= 1;
+        iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+//
+impl<Idx, Iter> DoubleEndedIterator for ChunkIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128>,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some((self.decode)(Self::idx(self.front + self.len as u128)))
+    }
+}
+//
+impl<Idx, Iter> ExactSizeIterator for ChunkIter2D<Idx, Iter>
+where
+    Idx: CurveUint + Into<u128> + TryFrom<u128>,
+    Iter: Iterator<Item = [Idx::Coordinate; 2]>,
+{
+}
+
+/// Parallel iterator over the aligned blocks of a 2D space-filling curve
+pub struct ParBlocks2D<Idx, Block> {
+    producer: BlocksProducer<Idx, Block>,
+}
+//
+impl<Idx, Block> ParBlocks2D<Idx, Block>
+where
+    Idx: Copy + Into<u128>,
+{
+    /// Set up parallel iteration over a range of block numbers, given the
+    /// curve's block constructor
+    ///
+    /// # Panics
+    ///
+    /// If the number of blocks in `blocks` does not fit in a `usize`.
+    ///
+    pub(crate) fn new(
+        blocks: RangeInclusive<Idx>,
+        block_bits: NumBits,
+        block: fn(Idx, NumBits) -> Block,
+    ) -> Self {
+        let (start, end) = ((*blocks.start()).into(), (*blocks.end()).into());
+        let len = if start > end {
+            0
+        } else {
+            (end - start)
+                .checked_add(1)
+                .and_then(|len| usize::try_from(len).ok())
+                .expect("Too many curve blocks for indexed parallel iteration")
+        };
+        Self {
+            producer: BlocksProducer {
+                start,
+                len,
+                block_bits,
+                block,
+            },
+        }
+    }
+}
+//
+impl<Idx, Block> ParallelIterator for ParBlocks2D<Idx, Block>
+where
+    Idx: TryFrom<u128>,
+    Block: Send,
+{
+    type Item = Block;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.producer.len)
+    }
+}
+//
+impl<Idx, Block> IndexedParallelIterator for ParBlocks2D<Idx, Block>
+where
+    Idx: TryFrom<u128>,
+    Block: Send,
+{
+    fn len(&self) -> usize {
+        self.producer.len
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(self.producer)
+    }
+}
+
+/// Rayon producer for `ParBlocks2D`, which represents a run of blocks
+pub struct BlocksProducer<Idx, Block> {
+    /// Number of the first block of the run, as a u128 so that empty runs can
+    /// start one past the last block without overflowing
+    start: u128,
+
+    /// Number of blocks in the run
+    len: usize,
+
+    /// Each block has 4^block_bits points
+    block_bits: NumBits,
+
+    /// Curve block constructor
+    block: fn(Idx, NumBits) -> Block,
+}
+//
+impl<Idx, Block> Producer for BlocksProducer<Idx, Block>
+where
+    Idx: TryFrom<u128>,
+    Block: Send,
+{
+    type Item = Block;
+    type IntoIter = BlocksChunkIter<Idx, Block>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        BlocksChunkIter {
+            front: self.start,
+            len: self.len,
+            block_bits: self.block_bits,
+            block: self.block,
+        }
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        debug_assert!(index <= self.len);
+        (
+            Self { len: index, ..self },
+            Self {
+                start: self.start + index as u128,
+                len: self.len - index,
+                ..self
+            },
+        )
+    }
+}
+
+/// Sequential iterator over a run of blocks of a `ParBlocks2D`
+pub struct BlocksChunkIter<Idx, Block> {
+    /// Number of the next block from the front
+    front: u128,
+
+    /// Number of remaining blocks
+    len: usize,
+
+    /// Each block has 4^block_bits points
+    block_bits: NumBits,
+
+    /// Curve block constructor
+    block: fn(Idx, NumBits) -> Block,
+}
+//
+impl<Idx: TryFrom<u128>, Block> BlocksChunkIter<Idx, Block> {
+    /// Build the block with a number that is known to be valid
+    fn block(&self, number: u128) -> Block {
+        let number =
+            Idx::try_from(number).unwrap_or_else(|_| unreachable!("Block number out of range"));
+        (self.block)(number, self.block_bits)
+    }
+}
+//
+impl<Idx: TryFrom<u128>, Block> Iterator for BlocksChunkIter<Idx, Block> {
+    type Item = Block;
+
+    #[inline]
+    fn next(&mut self) -> Option<Block> {
+        if self.len == 0 {
+            return None;
+        }
+        let block = self.block(self.front);
+        self.front += 1;
+        self.len -= 1;
+        Some(block)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+//
+impl<Idx: TryFrom<u128>, Block> DoubleEndedIterator for BlocksChunkIter<Idx, Block> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Block> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.block(self.front + self.len as u128))
+    }
+}
+//
+impl<Idx: TryFrom<u128>, Block> ExactSizeIterator for BlocksChunkIter<Idx, Block> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hilbert, morton};
+    use rayon::prelude::*;
+
+    // Going through the whole curve is only tractable with 16-bit curve indices
+    fn test(
+        par_iter: impl IndexedParallelIterator<Item = [u8; 2]>,
+        decode: fn(u16) -> [u8; 2],
+        start: u16,
+    ) {
+        let points = par_iter.collect::<Vec<_>>();
+        assert!(points
+            .iter()
+            .zip(start..=u16::MAX)
+            .all(|(&coords, idx)| coords == decode(idx)));
+        assert_eq!(points.len(), usize::from(u16::MAX - start) + 1);
+    }
+
+    #[test]
+    fn morton() {
+        test(morton::par_iter_2d_u16(), morton::decode_2d_u16, 0);
+        test(
+            morton::par_iter_range_2d_u16(42..=u16::MAX),
+            morton::decode_2d_u16,
+            42,
+        );
+        let (start, end) = (1, 0);
+        assert_eq!(morton::par_iter_range_2d(start..=end).count(), 0);
+    }
+
+    #[test]
+    fn hilbert() {
+        test(hilbert::par_iter_2d_u16(), hilbert::decode_2d_u16, 0);
+        test(
+            hilbert::par_iter_range_2d_u16(42..=u16::MAX),
+            hilbert::decode_2d_u16,
+            42,
+        );
+        assert_eq!(
+            hilbert::par_iter_range_2d_u16(0..=u16::MAX)
+                .rev()
+                .collect::<Vec<_>>(),
+            (0..=u16::MAX)
+                .rev()
+                .map(hilbert::decode_2d_u16)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn wide() {
+        let start = u64::MAX - 99_999;
+        let points = morton::par_iter_range_2d_u64(start..=u64::MAX).collect::<Vec<_>>();
+        assert!(points
+            .into_iter()
+            .eq((start..=u64::MAX).map(morton::decode_2d_u64)));
+    }
+
+    #[test]
+    fn blocks() {
+        for block_bits in 0..=u8::BITS {
+            assert!(morton::par_blocks_2d_u16(block_bits)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .eq(morton::iter_blocks_2d_u16(block_bits)));
+            let blocks = hilbert::par_blocks_2d_u16(block_bits)
+                .map(|block| block.points().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert!(blocks.into_iter().flatten().eq(hilbert::iter_2d_u16()));
+        }
+        assert_eq!(
+            hilbert::par_blocks_2d_u64(4)
+                .rev()
+                .take(1)
+                .collect::<Vec<_>>(),
+            [hilbert::iter_blocks_2d_u64(4).next_back().unwrap()]
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! Conversions between floating-point points of the unit square and curve
+//! coordinates
+//!
+//! Space-filling curves operate on integer coordinates, so floating-point data
+//! must be quantized before it can be ordered along a curve. This module
+//! provides such conversions, with explicit rounding modes so that quantization
+//! and dequantization agree on where each grid cell lies.
+//!
+//! At a precision of N bits, the unit square [0; 1[² is split into a grid of
+//! 2^N x 2^N cells, and the cell of coordinates `[x, y]` covers
+//! [x / 2^N; (x + 1) / 2^N[ x [y / 2^N; (y + 1) / 2^N[. Inputs outside of the
+//! unit square, including NaN, are clamped to the nearest cell.
+
+use crate::{Coordinate, Coordinates2D, CurveIdx, NumBits, SpaceFillingCurve2D};
+
+/// Rounding mode of a quantization, which also specifies which point of a grid
+/// cell dequantization yields
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Rounding {
+    /// Points are mapped to the cell that contains them, and cells are mapped
+    /// back to their lower corner
+    Floor,
+
+    /// Points are mapped to the cell whose lower corner is closest to them,
+    /// with ties going to the cell with an even coordinate, and cells are
+    /// mapped back to their lower corner
+    ///
+    /// This is the mode to use when the data lies on grid points, e.g. when it
+    /// was produced by a previous dequantization with `Rounding::Floor`.
+    ///
+    RoundHalfEven,
+
+    /// Points are mapped to the cell that contains them, and cells are mapped
+    /// back to their center
+    ///
+    /// Dequantization error is then at most half a cell, instead of a full cell
+    /// with `Rounding::Floor`.
+    ///
+    CellCenter,
+}
+
+/// Quantize a point of the unit square into coordinates with `bits` bits of
+/// precision
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`.
+///
+pub fn quantize_2d(p: [f32; 2], bits: NumBits, rounding: Rounding) -> Coordinates2D {
+    check_bits(bits);
+    [
+        quantize(p[0], bits, rounding),
+        quantize(p[1], bits, rounding),
+    ]
+}
+
+/// Map coordinates with `bits` bits of precision back to a point of the unit
+/// square
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`, or if the
+/// coordinates do not fit in `bits` bits.
+///
+pub fn dequantize_2d(coords: Coordinates2D, bits: NumBits, rounding: Rounding) -> [f32; 2] {
+    check_bits(bits);
+    assert!(
+        (coords[0] | coords[1]) as u128 >> bits == 0,
+        "Coordinates are out of range for this precision"
+    );
+    [
+        dequantize(coords[0], bits, rounding),
+        dequantize(coords[1], bits, rounding),
+    ]
+}
+
+/// Compute the index of a point of the unit square on a curve, after
+/// quantizing it with `bits` bits of precision
+///
+/// For the Morton and Hilbert curves, the resulting curve indices lie between
+/// 0 and 4^`bits` - 1.
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`.
+///
+pub fn encode_f32<Curve: SpaceFillingCurve2D>(
+    p: [f32; 2],
+    bits: NumBits,
+    rounding: Rounding,
+) -> CurveIdx {
+    Curve::encode(quantize_2d(p, bits, rounding))
+}
+
+/// Compute the point of the unit square that corresponds to a curve index,
+/// at a precision of `bits` bits
+///
+/// This is the inverse of `encode_f32()`, within the limits of quantization.
+///
+/// # Panics
+///
+/// If `bits` is larger than the number of bits of `Coordinate`, or if the
+/// point of the curve is outside of the grid, which for the Morton and Hilbert
+/// curves means that `code` does not fit in 2 x `bits` bits.
+///
+pub fn decode_f32<Curve: SpaceFillingCurve2D>(
+    code: CurveIdx,
+    bits: NumBits,
+    rounding: Rounding,
+) -> [f32; 2] {
+    dequantize_2d(Curve::decode(code), bits, rounding)
+}
+
+/// Check that a precision is supported
+fn check_bits(bits: NumBits) {
+    assert!(
+        bits <= Coordinate::BITS,
+        "Precision of {bits} bits is too high for this Coordinate type"
+    );
+}
+
+/// Quantize a coordinate of the unit interval
+fn quantize(value: f32, bits: NumBits, rounding: Rounding) -> Coordinate {
+    // Computations are carried out in double precision, where scaling by a
+    // power of two is exact. Float-to-integer casts saturate and map NaN to
+    // zero, so only the upper bound needs clamping.
+    let num_cells = (1u128 << bits) as f64;
+    let scaled = f64::from(value) * num_cells;
+    let truncated = scaled as u128;
+    let rounded = match rounding {
+        Rounding::Floor | Rounding::CellCenter => truncated,
+        Rounding::RoundHalfEven => {
+            let fraction = scaled - truncated as f64;
+            if fraction > 0.5 || (fraction == 0.5 && truncated % 2 == 1) {
+                truncated.saturating_add(1)
+            } else {
+                truncated
+            }
+        }
+    };
+    rounded.min((1u128 << bits) - 1) as Coordinate
+}
+
+/// Dequantize a coordinate into the unit interval
+fn dequantize(coord: Coordinate, bits: NumBits, rounding: Rounding) -> f32 {
+    let num_cells = (1u128 << bits) as f64;
+    let offset = match rounding {
+        Rounding::Floor | Rounding::RoundHalfEven => 0.0,
+        Rounding::CellCenter => 0.5,
+    };
+    ((coord as f64 + offset) / num_cells) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Hilbert2D, Morton2D};
+
+    #[test]
+    fn quantize_2d() {
+        use Rounding::*;
+        let bits = 2;
+        for &(value, floor, round) in &[
+            (-1.0, 0, 0),
+            (0.0, 0, 0),
+            (0.1, 0, 0),
+            (0.125, 0, 0),
+            (0.2, 0, 1),
+            (0.25, 1, 1),
+            (0.375, 1, 2),
+            (0.625, 2, 2),
+            (0.875, 3, 3),
+            (0.99, 3, 3),
+            (1.0, 3, 3),
+            (f32::NAN, 0, 0),
+            (f32::INFINITY, 3, 3),
+        ] {
+            assert_eq!(super::quantize_2d([value; 2], bits, Floor), [floor; 2]);
+            assert_eq!(super::quantize_2d([value; 2], bits, CellCenter), [floor; 2]);
+            assert_eq!(
+                super::quantize_2d([value; 2], bits, RoundHalfEven),
+                [round; 2],
+                "Unexpected rounding of {value}"
+            );
+        }
+        assert_eq!(dequantize_2d([1, 3], bits, Floor), [0.25, 0.75]);
+        assert_eq!(dequantize_2d([1, 3], bits, RoundHalfEven), [0.25, 0.75]);
+        assert_eq!(dequantize_2d([1, 3], bits, CellCenter), [0.375, 0.875]);
+
+        // Quantization must be the inverse of dequantization in every mode
+        for bits in 0..=Coordinate::BITS.min(8) {
+            for x in 0..1 << bits {
+                for &rounding in &[Floor, RoundHalfEven, CellCenter] {
+                    let coords = [x as Coordinate, 0];
+                    let p = dequantize_2d(coords, bits, rounding);
+                    assert_eq!(super::quantize_2d(p, bits, rounding), coords);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encode_f32() {
+        for &rounding in &[
+            Rounding::Floor,
+            Rounding::RoundHalfEven,
+            Rounding::CellCenter,
+        ] {
+            for code in 0..16 {
+                let p = decode_f32::<Morton2D>(code, 2, rounding);
+                assert_eq!(super::encode_f32::<Morton2D>(p, 2, rounding), code);
+                let p = decode_f32::<Hilbert2D>(code, 2, rounding);
+                assert_eq!(super::encode_f32::<Hilbert2D>(p, 2, rounding), code);
+            }
+        }
+        assert_eq!(
+            super::encode_f32::<Morton2D>([0.9, 0.3], 1, Rounding::Floor),
+            crate::morton::encode_2d([1, 0])
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Radix sorting of curve indices
+//!
+//! Sorting data along a space-filling curve amounts to sorting integer curve
+//! indices, which radix sorts do in linear time. For large datasets, this is
+//! much faster than the comparison sorts of the standard library.
+
+/// Number of bits of the digits that each radix sort pass goes through
+///
+/// Larger digits mean fewer passes, but also more scattered memory writes
+/// within each pass. 11-bit digits are a common compromise, which needs 6
+/// passes for 64-bit keys.
+///
+const DIGIT_BITS: u32 = 11;
+
+/// Number of possible values of a digit
+const NUM_DIGITS: usize = 1 << DIGIT_BITS;
+
+/// Sort integer keys with a least significant digit first radix sort
+///
+/// This sort allocates a temporary buffer of the same size as `keys`. Passes
+/// where all keys have the same digit are skipped, so sorting keys whose
+/// high-order bits are all zero, which is common with curve indices, is
+/// faster.
+///
+pub(crate) fn sort<Key: Copy + Default + Into<u128>>(keys: &mut [Key]) {
+    if keys.len() < 2 {
+        return;
+    }
+    let key_bits = core::mem::size_of::<Key>() as u32 * 8;
+    let digit = |key: Key, shift: u32| ((key.into() >> shift) as usize) & (NUM_DIGITS - 1);
+    let mut buffer = alloc::vec![Key::default(); keys.len()];
+    let mut sorted_in_buffer = false;
+    let mut shift = 0;
+    while shift < key_bits {
+        let (src, dst): (&[Key], &mut [Key]) = if sorted_in_buffer {
+            (&buffer[..], &mut *keys)
+        } else {
+            (&*keys, &mut buffer[..])
+        };
+
+        // Count the keys that have each digit, and skip the pass if all keys
+        // have the same digit
+        let mut counts = [0; NUM_DIGITS];
+        for &key in src {
+            counts[digit(key, shift)] += 1;
+        }
+        if counts.contains(&src.len()) {
+            shift += DIGIT_BITS;
+            continue;
+        }
+
+        // Scatter the keys to their place in the output, in order of digits
+        let mut offsets = [0; NUM_DIGITS];
+        let mut offset = 0;
+        for (count, digit_offset) in counts.iter().zip(&mut offsets) {
+            *digit_offset = offset;
+            offset += count;
+        }
+        for &key in src {
+            let digit_offset = &mut offsets[digit(key, shift)];
+            dst[*digit_offset] = key;
+            *digit_offset += 1;
+        }
+        sorted_in_buffer = !sorted_in_buffer;
+        shift += DIGIT_BITS;
+    }
+    if sorted_in_buffer {
+        keys.copy_from_slice(&buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::quickcheck;
+
+    quickcheck! {
+        fn sort_u16(keys: Vec<u16>) -> bool {
+            let mut expected = keys.clone();
+            expected.sort_unstable();
+            let mut keys = keys;
+            super::sort(&mut keys);
+            keys == expected
+        }
+
+        fn sort_u128(keys: Vec<u128>) -> bool {
+            let mut expected = keys.clone();
+            expected.sort_unstable();
+            let mut keys = keys;
+            super::sort(&mut keys);
+            keys == expected
+        }
+    }
+}
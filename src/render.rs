@@ -0,0 +1,482 @@
+//! Rendering of curves as text and images
+//!
+//! This module draws the path that a sequence of points follows using Unicode
+//! box-drawing characters, which is a quick way to visualize a space-filling
+//! curve in a terminal or a text-based user interface, or as an SVG image,
+//! which is better suited to documentation figures and teaching material.
+//! When the `image` cargo feature is enabled, paths can also be rasterized
+//! into bitmap images, either as a line or as a heatmap.
+
+use crate::Coordinates2D;
+#[cfg(feature = "image")]
+use ::image::{Rgba, RgbaImage};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Draw the path that a sequence of points follows with box-drawing characters
+///
+/// The drawing covers the bounding box of the points, with one character per
+/// point and one line of text per y coordinate, so that y grows downwards.
+/// Each line is terminated by a newline, and points that are not visited are
+/// left blank. The start of the path is marked by a T-shaped junction and its
+/// end by an arrow.
+///
+/// Consecutive points are connected when they are direct neighbors. Curves
+/// like the Morton curve also jump between distant points, and the ends of
+/// such jumps are drawn as half-lines, or as a dot when a point is not
+/// connected to any of its neighbors on either side.
+///
+/// This allocates one character per point of the bounding box, which should be
+/// kept small enough to fit on screen anyway.
+///
+pub fn unicode(points: impl IntoIterator<Item = Coordinates2D>) -> String {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let [min, max] = if let Some(bounds) = bounding_box(&points) {
+        bounds
+    } else {
+        return String::new();
+    };
+
+    // Set up a 2D character-based display
+    let width = (max[0] - min[0]) as usize + 1;
+    let height = (max[1] - min[1]) as usize + 1;
+    let mut display = (0..height * (width + 1))
+        .map(|idx| {
+            if idx % (width + 1) == width {
+                '\n'
+            } else {
+                ' '
+            }
+        })
+        .collect::<Vec<_>>();
+    let to_index =
+        |[x, y]: Coordinates2D| (y - min[1]) as usize * (width + 1) + (x - min[0]) as usize;
+
+    // Draw each point, according to the sides through which the path enters
+    // and leaves it
+    let last_idx = points.len() - 1;
+    for (idx, &point) in points.iter().enumerate() {
+        let incoming = idx
+            .checked_sub(1)
+            .and_then(|prev| step(points[prev], point))
+            .map(opposite);
+        let outgoing = points.get(idx + 1).and_then(|&next| step(point, next));
+        display[to_index(point)] = match (idx, incoming, outgoing) {
+            (0, _, Some(RIGHT)) => '├',
+            (0, _, Some(LEFT)) => '┤',
+            (0, _, Some(DOWN)) => '┬',
+            (0, _, Some(UP)) => '┴',
+            (idx, Some(LEFT), _) if idx == last_idx => '>',
+            (idx, Some(RIGHT), _) if idx == last_idx => '<',
+            (idx, Some(UP), _) if idx == last_idx => 'v',
+            (idx, Some(DOWN), _) if idx == last_idx => '^',
+            (_, incoming, outgoing) => glyph(incoming.unwrap_or(0) | outgoing.unwrap_or(0)),
+        };
+    }
+    display.into_iter().collect()
+}
+
+/// Appearance of the images produced by `svg()`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgOptions {
+    /// Distance between neighboring points, in SVG user units
+    pub scale: f32,
+
+    /// Width of the stroke that draws the path
+    pub stroke_width: f32,
+
+    /// Color of the stroke, as an SVG color like `black` or `#ff8000`
+    ///
+    /// This is ignored when `color_by_index` is set.
+    ///
+    pub stroke: String,
+
+    /// Truth that each segment of the path should be colored according to its
+    /// position along the path, going through the hues of the rainbow from red
+    /// to violet, which makes the direction of the path visible
+    pub color_by_index: bool,
+}
+//
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            scale: 10.0,
+            stroke_width: 2.0,
+            stroke: "black".into(),
+            color_by_index: false,
+        }
+    }
+}
+
+/// Draw the path that a sequence of points follows as an SVG image
+///
+/// The image covers the bounding box of the points, plus a margin of half the
+/// distance between neighboring points on each side, and y grows downwards as
+/// in `unicode()`. Consecutive points are always connected by a straight line,
+/// so the jumps of curves like the Morton curve are visible.
+///
+/// By default, the path is drawn as a single polyline. When
+/// `options.color_by_index` is set, each segment is drawn as a separate line
+/// of its own color instead.
+///
+/// An empty sequence of points produces an empty image of the size of a single
+/// point, so that the output is always a valid SVG document.
+///
+pub fn svg(points: impl IntoIterator<Item = Coordinates2D>, options: &SvgOptions) -> String {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let [min, max] = bounding_box(&points).unwrap_or_default();
+    let margin = options.scale / 2.0;
+    let to_svg = |coord: crate::Coordinate, min: crate::Coordinate| {
+        (coord - min) as f32 * options.scale + margin
+    };
+    let [width, height] = [
+        to_svg(max[0], min[0]) + margin,
+        to_svg(max[1], min[1]) + margin,
+    ];
+
+    // Writing into a String cannot fail
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\">"
+    );
+    let stroke_attributes = format!(
+        "fill=\"none\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"",
+        options.stroke_width
+    );
+    if options.color_by_index {
+        let _ = writeln!(svg, "<g {stroke_attributes}>");
+        let num_segments = points.len().saturating_sub(1);
+        for (idx, pair) in points.windows(2).enumerate() {
+            let hue = RAINBOW_HUES * fraction(idx, num_segments);
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"hsl({hue}, 100%, 40%)\"/>",
+                to_svg(pair[0][0], min[0]),
+                to_svg(pair[0][1], min[1]),
+                to_svg(pair[1][0], min[0]),
+                to_svg(pair[1][1], min[1]),
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    } else if !points.is_empty() {
+        let _ = write!(svg, "<polyline points=\"");
+        for (idx, &[x, y]) in points.iter().enumerate() {
+            let separator = if idx == 0 { "" } else { " " };
+            let _ = write!(
+                svg,
+                "{separator}{},{}",
+                to_svg(x, min[0]),
+                to_svg(y, min[1])
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "\" stroke=\"{}\" {stroke_attributes}/>",
+            options.stroke
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draw the path that a sequence of points follows into an image
+///
+/// Each point is drawn as a square of `px_per_cell` x `px_per_cell` pixels,
+/// and the image covers the bounding box of the points, with y growing
+/// downwards as in `unicode()`. The path goes through the centers of these
+/// squares on a white background, with straight lines between consecutive
+/// points, and its color goes through the hues of the rainbow from red to
+/// violet so that its direction is visible.
+///
+/// An empty sequence of points produces an image of zero width and height.
+///
+/// This function is only available when the `image` cargo feature is enabled.
+///
+/// # Panics
+///
+/// If `px_per_cell` is zero, or if the image would be too large for its
+/// dimensions to fit in a `u32`.
+///
+#[cfg(feature = "image")]
+pub fn raster(points: impl IntoIterator<Item = Coordinates2D>, px_per_cell: u32) -> RgbaImage {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let (mut image, min) = blank_image(&points, px_per_cell);
+    let [width, height] = [image.width(), image.height()];
+    let center = |point: Coordinates2D| {
+        let center = |axis: usize| {
+            i64::from(to_u32(point[axis] - min[axis]) * px_per_cell + px_per_cell / 2)
+        };
+        [center(0), center(1)]
+    };
+    let thickness = i64::from((px_per_cell / 4).max(1));
+    let mut draw_dot = |[x, y]: [i64; 2], color: Rgba<u8>| {
+        let start = [x - thickness / 2, y - thickness / 2];
+        for y in start[1].max(0)..(start[1] + thickness).min(i64::from(height)) {
+            for x in start[0].max(0)..(start[0] + thickness).min(i64::from(width)) {
+                image.put_pixel(x as u32, y as u32, color);
+            }
+        }
+    };
+
+    // Draw each segment pixel by pixel, along its longest axis
+    if let [point] = points[..] {
+        draw_dot(center(point), rainbow(0.0));
+    }
+    let num_segments = points.len().saturating_sub(1);
+    for (idx, pair) in points.windows(2).enumerate() {
+        let color = rainbow(fraction(idx, num_segments));
+        let [start, end] = [center(pair[0]), center(pair[1])];
+        let delta = [end[0] - start[0], end[1] - start[1]];
+        let num_steps = delta[0].abs().max(delta[1].abs()).max(1);
+        for step in 0..=num_steps {
+            draw_dot(
+                [
+                    start[0] + delta[0] * step / num_steps,
+                    start[1] + delta[1] * step / num_steps,
+                ],
+                color,
+            );
+        }
+    }
+    image
+}
+
+/// Draw a heatmap of the position of each point along a path into an image
+///
+/// Like `raster()`, this draws each point as a square of `px_per_cell` x
+/// `px_per_cell` pixels on a white background, but fills that square with a
+/// color that goes through the hues of the rainbow from red to violet along
+/// the path, instead of drawing the path itself.
+///
+/// An empty sequence of points produces an image of zero width and height.
+///
+/// This function is only available when the `image` cargo feature is enabled.
+///
+/// # Panics
+///
+/// If `px_per_cell` is zero, or if the image would be too large for its
+/// dimensions to fit in a `u32`.
+///
+#[cfg(feature = "image")]
+pub fn raster_heatmap(
+    points: impl IntoIterator<Item = Coordinates2D>,
+    px_per_cell: u32,
+) -> RgbaImage {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let (mut image, min) = blank_image(&points, px_per_cell);
+    let num_points = points.len();
+    for (idx, &[x, y]) in points.iter().enumerate() {
+        let color = rainbow(fraction(idx, num_points));
+        let [x, y] = [to_u32(x - min[0]), to_u32(y - min[1])];
+        for py in y * px_per_cell..(y + 1) * px_per_cell {
+            for px in x * px_per_cell..(x + 1) * px_per_cell {
+                image.put_pixel(px, py, color);
+            }
+        }
+    }
+    image
+}
+
+/// Set up a white image that covers the bounding box of some points, and
+/// return it along with the lower corner of that bounding box
+///
+/// If there are no points, the image is empty.
+///
+#[cfg(feature = "image")]
+fn blank_image(points: &[Coordinates2D], px_per_cell: u32) -> (RgbaImage, Coordinates2D) {
+    assert!(px_per_cell > 0, "Cells must be at least one pixel wide");
+    let [min, max] = if let Some(bounds) = bounding_box(points) {
+        bounds
+    } else {
+        return (RgbaImage::new(0, 0), [0, 0]);
+    };
+    let size = |axis: usize| {
+        (to_u32(max[axis] - min[axis]) + 1)
+            .checked_mul(px_per_cell)
+            .expect("Image is too large")
+    };
+    let image = RgbaImage::from_pixel(size(0), size(1), Rgba([255; 4]));
+    (image, min)
+}
+
+/// Convert a coordinate offset into an image dimension
+#[cfg(feature = "image")]
+#[allow(clippy::unnecessary_fallible_conversions, clippy::useless_conversion)] // Coordinate is u8 to u64
+fn to_u32(coord: crate::Coordinate) -> u32 {
+    u32::try_from(coord).expect("Image is too large")
+}
+
+/// Color of the rainbow that `raster()` and `raster_heatmap()` use for a
+/// certain position along a path, going from 0.0 for red to 1.0 for violet
+///
+/// This matches the hues that `svg()` uses, at 100% saturation and 40%
+/// lightness.
+///
+#[cfg(feature = "image")]
+fn rainbow(fraction: f32) -> Rgba<u8> {
+    let hue = RAINBOW_HUES * fraction / 60.0;
+    let sector = (hue as u8).min(5);
+    let rising = hue - f32::from(sector);
+    let falling = 1.0 - rising;
+    let [r, g, b] = match sector {
+        0 => [1.0, rising, 0.0],
+        1 => [falling, 1.0, 0.0],
+        2 => [0.0, 1.0, rising],
+        3 => [0.0, falling, 1.0],
+        4 => [rising, 0.0, 1.0],
+        _ => [1.0, 0.0, falling],
+    };
+    let channel = |value: f32| (value * 204.0 + 0.5) as u8;
+    Rgba([channel(r), channel(g), channel(b), 255])
+}
+
+/// Range of hues, in degrees, that paths go through when colored according to
+/// the position along them
+const RAINBOW_HUES: f32 = 270.0;
+
+/// Position of the `idx`-th of `len` elements along a path, from 0.0 for the
+/// first element to 1.0 for the last one
+fn fraction(idx: usize, len: usize) -> f32 {
+    idx as f32 / len.saturating_sub(1).max(1) as f32
+}
+
+/// Lower and upper corner of the bounding box of some points, if any
+fn bounding_box(points: &[Coordinates2D]) -> Option<[Coordinates2D; 2]> {
+    let first = *points.first()?;
+    Some(points.iter().fold([first, first], |[min, max], &[x, y]| {
+        [
+            [min[0].min(x), min[1].min(y)],
+            [max[0].max(x), max[1].max(y)],
+        ]
+    }))
+}
+
+/// Side of a character cell, as a bit of a mask of connected sides
+type Side = u8;
+const LEFT: Side = 1;
+const RIGHT: Side = 2;
+const UP: Side = 4;
+const DOWN: Side = 8;
+
+/// Side of `src` through which the path leaves towards `dst`, if these points
+/// are direct neighbors
+fn step(src: Coordinates2D, dst: Coordinates2D) -> Option<Side> {
+    match (
+        dst[0] as i128 - src[0] as i128,
+        dst[1] as i128 - src[1] as i128,
+    ) {
+        (-1, 0) => Some(LEFT),
+        (1, 0) => Some(RIGHT),
+        (0, -1) => Some(UP),
+        (0, 1) => Some(DOWN),
+        _ => None,
+    }
+}
+
+/// Side of a neighbor through which the path enters it, given the side of the
+/// current point through which it leaves
+fn opposite(side: Side) -> Side {
+    match side {
+        LEFT => RIGHT,
+        RIGHT => LEFT,
+        UP => DOWN,
+        DOWN => UP,
+        _ => unreachable!("Not a single side"),
+    }
+}
+
+/// Box-drawing character that connects a certain set of sides
+fn glyph(sides: Side) -> char {
+    match sides {
+        0 => '•',
+        LEFT => '╴',
+        RIGHT => '╶',
+        UP => '╵',
+        DOWN => '╷',
+        _ if sides == LEFT | RIGHT => '─',
+        _ if sides == UP | DOWN => '│',
+        _ if sides == UP | RIGHT => '└',
+        _ if sides == UP | LEFT => '┘',
+        _ if sides == DOWN | RIGHT => '┌',
+        _ if sides == DOWN | LEFT => '┐',
+        _ => unreachable!("Paths connect at most two sides of a point"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SvgOptions;
+    use crate::{hilbert, morton};
+
+    #[test]
+    fn unicode() {
+        assert_eq!(super::unicode(None), "");
+        assert_eq!(super::unicode(Some([3, 4])), "•\n");
+        assert_eq!(super::unicode(hilbert::iter_2d_order(1)), "├┐\n<┘\n");
+        assert_eq!(
+            super::unicode(hilbert::iter_2d_order(2)),
+            "┬┌─┐\n└┘┌┘\n┌┐└┐\nv└─┘\n"
+        );
+        assert_eq!(super::unicode(morton::iter_2d().take(4)), "├╴\n╶>\n");
+        assert_eq!(super::unicode(vec![[5, 2], [7, 2], [7, 3]]), "• ╷\n  v\n");
+    }
+
+    #[test]
+    fn svg() {
+        let options = SvgOptions::default();
+        assert_eq!(
+            super::svg(hilbert::iter_2d_order(1), &options),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\" viewBox=\"0 0 20 20\">\n\
+             <polyline points=\"5,5 15,5 15,15 5,15\" stroke=\"black\" fill=\"none\" \
+             stroke-width=\"2\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>\n\
+             </svg>\n"
+        );
+        assert_eq!(
+            super::svg(None, &options),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\" viewBox=\"0 0 10 10\">\n\
+             </svg>\n"
+        );
+
+        let options = SvgOptions {
+            scale: 1.0,
+            color_by_index: true,
+            ..SvgOptions::default()
+        };
+        let colored = super::svg(morton::iter_2d().take(4), &options);
+        assert_eq!(colored.matches("<line ").count(), 3);
+        assert!(colored.contains(
+            "<line x1=\"1.5\" y1=\"0.5\" x2=\"0.5\" y2=\"1.5\" stroke=\"hsl(135, 100%, 40%)\"/>"
+        ));
+        assert!(colored.contains("hsl(0, 100%, 40%)") && colored.contains("hsl(270, 100%, 40%)"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn raster() {
+        use ::image::Rgba;
+        let white = Rgba([255; 4]);
+        let [red, violet] = [Rgba([204, 0, 0, 255]), Rgba([102, 0, 204, 255])];
+
+        let image = super::raster(hilbert::iter_2d_order(1), 4);
+        assert_eq!(image.dimensions(), (8, 8));
+        assert_eq!(*image.get_pixel(0, 0), white);
+        assert_eq!(*image.get_pixel(2, 2), red);
+        assert_eq!(*image.get_pixel(4, 4), white);
+        assert_eq!(*image.get_pixel(2, 6), violet);
+        assert_eq!(
+            image.pixels().filter(|&&pixel| pixel != white).count(),
+            3 * 4 + 1
+        );
+        assert_eq!(super::raster(Some([3, 4]), 1).into_raw(), red.0);
+
+        let heatmap = super::raster_heatmap(hilbert::iter_2d_order(1), 2);
+        assert_eq!(heatmap.dimensions(), (4, 4));
+        assert_eq!(*heatmap.get_pixel(1, 1), red);
+        assert_eq!(*heatmap.get_pixel(0, 3), violet);
+        assert_eq!(heatmap.pixels().filter(|&&pixel| pixel == white).count(), 0);
+        assert_eq!(super::raster(None, 3).dimensions(), (0, 0));
+        assert_eq!(super::raster_heatmap(None, 3).dimensions(), (0, 0));
+    }
+}
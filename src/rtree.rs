@@ -0,0 +1,167 @@
+//! Bulk loading of Hilbert R-trees
+//!
+//! A Hilbert R-tree is an R-tree whose items are sorted by the Hilbert curve
+//! index of the center of their bounding box, which keeps nearby items in the
+//! same nodes. When all items are known in advance, the tree can be packed
+//! bottom-up: consecutive runs of items become leaf nodes, consecutive runs of
+//! leaf nodes become the nodes of the next level, and so on until a single
+//! root node remains. This module computes such a packing, which can then be
+//! used to build the nodes of any R-tree implementation.
+//!
+//! Bounding boxes are specified as `[min, max]` pairs of corners, which are
+//! both included in the box, as in `hilbert::ranges_for_rect()`.
+
+use crate::{hilbert, Coordinates2D};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Node of a packed Hilbert R-tree
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PackedNode {
+    /// Indices of the children of this node, which are items for leaf nodes
+    /// and nodes of the previous level otherwise
+    pub children: Range<usize>,
+
+    /// Bounding box of the children of this node, as a `[min, max]` pair
+    pub bbox: [Coordinates2D; 2],
+}
+
+/// Sort items in Hilbert R-tree order and group them into the nodes of a
+/// packed R-tree with a certain fan-out
+///
+/// `bbox` extracts the bounding box of an item as a `[min, max]` pair of
+/// corners, and is called once per item for sorting, then once per item again
+/// for computing the bounding boxes of the leaf nodes.
+///
+/// The result contains one list of nodes per tree level, starting from the
+/// leaves. Each node has `fan_out` children, except for the last node of each
+/// level, which gets the remaining children. The last level contains a single
+/// root node, unless there are no items, in which case the result is empty.
+///
+/// # Panics
+///
+/// If `fan_out` is smaller than 2, or if the bounding box of an item has a
+/// `min` corner that is not lower than its `max` corner.
+///
+pub fn bulk_load<T>(
+    items: &mut [T],
+    bbox: impl Fn(&T) -> [Coordinates2D; 2],
+    fan_out: usize,
+) -> Vec<Vec<PackedNode>> {
+    assert!(fan_out >= 2, "R-tree nodes must have at least 2 children");
+    hilbert::sort_by_key(items, |item| {
+        let [min, max] = bbox(item);
+        assert!(
+            min[0] <= max[0] && min[1] <= max[1],
+            "Bounding box corners are not in [min, max] order"
+        );
+        [
+            min[0] + (max[0] - min[0]) / 2,
+            min[1] + (max[1] - min[1]) / 2,
+        ]
+    });
+
+    let mut levels = Vec::new();
+    if items.is_empty() {
+        return levels;
+    }
+    let mut level = pack_level(items.len(), fan_out, |idx| bbox(&items[idx]));
+    while level.len() > 1 {
+        let next_level = pack_level(level.len(), fan_out, |idx| level[idx].bbox);
+        levels.push(level);
+        level = next_level;
+    }
+    levels.push(level);
+    levels
+}
+
+/// Group consecutive children into nodes of `fan_out` children
+fn pack_level(
+    num_children: usize,
+    fan_out: usize,
+    child_bbox: impl Fn(usize) -> [Coordinates2D; 2],
+) -> Vec<PackedNode> {
+    (0..num_children)
+        .step_by(fan_out)
+        .map(|start| {
+            let children = start..num_children.min(start + fan_out);
+            let bbox = children
+                .clone()
+                .map(&child_bbox)
+                .reduce(|[min1, max1], [min2, max2]| {
+                    [
+                        [min1[0].min(min2[0]), min1[1].min(min2[1])],
+                        [max1[0].max(max2[0]), max1[1].max(max2[1])],
+                    ]
+                })
+                .expect("Nodes have at least one child");
+            PackedNode { children, bbox }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+    use quickcheck::{Arbitrary, Gen};
+
+    #[test]
+    fn bulk_load() {
+        let mut gen = Gen::new(usize::MAX);
+        for &(num_items, fan_out) in &[(0, 2), (1, 4), (5, 2), (16, 4), (100, 7), (1000, 16)] {
+            let mut items = (0..num_items)
+                .map(|_| {
+                    let [a, b] = <[Coordinates2D; 2]>::arbitrary(&mut gen);
+                    [
+                        [a[0].min(b[0]), a[1].min(b[1])],
+                        [a[0].max(b[0]), a[1].max(b[1])],
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let levels = super::bulk_load(&mut items, |&bbox| bbox, fan_out);
+            if num_items == 0 {
+                assert!(levels.is_empty());
+                continue;
+            }
+
+            // Items are sorted by the Hilbert curve index of their center
+            let center_idx = |[min, max]: [Coordinates2D; 2]| {
+                let center = |axis: usize| {
+                    ((u128::from(min[axis]) + u128::from(max[axis])) / 2) as Coordinate
+                };
+                hilbert::encode_2d([center(0), center(1)])
+            };
+            assert!(items
+                .windows(2)
+                .all(|pair| center_idx(pair[0]) <= center_idx(pair[1])));
+
+            // Each level covers the previous one with contiguous runs of
+            // children, and bounding boxes are exact
+            let mut child_bboxes = items.clone();
+            for level in &levels {
+                assert_eq!(level.len(), (child_bboxes.len() + fan_out - 1) / fan_out);
+                let mut next_child = 0;
+                for node in level {
+                    assert_eq!(node.children.start, next_child);
+                    assert!((1..=fan_out).contains(&node.children.len()));
+                    next_child = node.children.end;
+                    let children = &child_bboxes[node.children.clone()];
+                    for axis in 0..2 {
+                        assert_eq!(
+                            node.bbox[0][axis],
+                            children.iter().map(|bbox| bbox[0][axis]).min().unwrap()
+                        );
+                        assert_eq!(
+                            node.bbox[1][axis],
+                            children.iter().map(|bbox| bbox[1][axis]).max().unwrap()
+                        );
+                    }
+                }
+                assert_eq!(next_child, child_bboxes.len());
+                child_bboxes = level.iter().map(|node| node.bbox).collect();
+            }
+            assert_eq!(levels.last().unwrap().len(), 1);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Stratified sampling of the unit square in Hilbert curve order
+//!
+//! Quasi-Monte Carlo methods often split the unit square into a grid of cells,
+//! also known as strata, and draw one sample per cell. Going through the cells
+//! in Hilbert curve order, rather than row by row, makes consecutive samples
+//! spatially close to each other, which improves memory locality when they are
+//! processed, and spreads the first N samples of a coarser curve over a region
+//! that is as compact as possible.
+//!
+//! Cells follow the conventions of the `quantize` module: at a curve order of
+//! N, the unit square [0; 1[² is split into a grid of 2^N x 2^N cells, which
+//! are visited by the ]-shaped curve of `hilbert::decode_2d_order()`.
+
+use crate::{
+    hilbert,
+    quantize::{self, Rounding},
+    CurveIdx, NumBits,
+};
+
+/// Center of the cell of the unit square that the Hilbert curve of a certain
+/// order visits at position `sample`
+///
+/// # Panics
+///
+/// If `order` is larger than the number of bits of `Coordinate`, or if
+/// `sample` is not smaller than 4^`order`.
+///
+pub fn cell_center(sample: CurveIdx, order: NumBits) -> [f32; 2] {
+    quantize::dequantize_2d(
+        hilbert::decode_2d_order(sample, order),
+        order,
+        Rounding::CellCenter,
+    )
+}
+
+/// Iterate over the cells of the first `num_samples` points of the Hilbert
+/// curve of a certain order, yielding one jittered sample position per cell
+///
+/// For each cell, `jitter` is called once and must return the position of the
+/// sample within the cell, as a point of [0; 1[². Passing random points yields
+/// stratified random samples, whereas always returning `[0.5, 0.5]` yields the
+/// same positions as `cell_center()`.
+///
+/// # Panics
+///
+/// If `order` is larger than the number of bits of `Coordinate`, or if
+/// `num_samples` is larger than 4^`order`.
+///
+pub fn iter_jittered(
+    order: NumBits,
+    num_samples: usize,
+    mut jitter: impl FnMut() -> [f32; 2],
+) -> impl Iterator<Item = [f32; 2]> {
+    let cells = hilbert::iter_2d_order(order);
+    assert!(
+        order >= 64 || num_samples as u128 <= 1 << (2 * order),
+        "Curve of order {order} has fewer than {num_samples} points"
+    );
+    let num_cells = (1u128 << order) as f64;
+    cells.take(num_samples).map(move |coords| {
+        let offset = jitter();
+        let sample =
+            |axis: usize| ((coords[axis] as f64 + f64::from(offset[axis])) / num_cells) as f32;
+        [sample(0), sample(1)]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_center() {
+        assert_eq!(super::cell_center(0, 0), [0.5, 0.5]);
+        assert_eq!(
+            (0..4)
+                .map(|sample| super::cell_center(sample, 1))
+                .collect::<Vec<_>>(),
+            [[0.25, 0.25], [0.75, 0.25], [0.75, 0.75], [0.25, 0.75]]
+        );
+    }
+
+    #[test]
+    fn iter_jittered() {
+        let order = 4;
+        let num_samples = 100;
+        assert!(super::iter_jittered(order, num_samples, || [0.5; 2])
+            .eq((0..num_samples as CurveIdx).map(|sample| super::cell_center(sample, order))));
+
+        // Jittered samples stay within their cell
+        let mut state = 42u32;
+        let mut random = move || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1 << 24) as f32
+        };
+        let samples = super::iter_jittered(order, num_samples, || [random(), random()]);
+        for (sample, p) in samples.enumerate() {
+            assert_eq!(
+                quantize::quantize_2d(p, order, Rounding::Floor),
+                hilbert::decode_2d_order(sample as CurveIdx, order)
+            );
+        }
+        assert_eq!(super::iter_jittered(0, 0, || [0.5; 2]).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_samples() {
+        let _ = super::iter_jittered(1, 5, || [0.5; 2]);
+    }
+}
@@ -0,0 +1,309 @@
+//! Utilities related to the Sierpiński space-filling curve
+//!
+//! The Sierpiński curve (also known as the Sierpiński-Knopp curve) recursively
+//! splits right isosceles triangles in two halves. Its worst-case ratio between
+//! distances along the curve and distances in space is lower than that of the
+//! Hilbert curve, which makes it a popular choice for heuristic solutions to the
+//! travelling salesman problem. Like the Moore curve, it is a closed loop.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+//! Unlike the Morton and Hilbert curves, this curve goes through recursion depths
+//! one by one, so decoding and encoding are slower and do not run in constant
+//! time.
+
+/// Generate Sierpiński curve utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! sierpinski_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::bits::$width as bits;
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Vertex of a triangle, which can lie on the upper edge of the domain and
+        /// thus needs more bits than a Coordinate
+        type Vertex = [CurveIdx; 2];
+
+        /// Number of bits in a curve index
+        const IDX_BITS: u32 = CurveIdx::BITS;
+
+        /// Number of bits in a coordinate
+        const COORD_BITS: u32 = Coordinate::BITS;
+
+        /// Compute the coordinates of the i-th point of the Sierpiński curve
+        ///
+        /// The curve's square domain is split along its diagonal into two right
+        /// isosceles triangles, which are recursively split in two halves along
+        /// their height. Each triangle is traversed from one end of its hypotenuse
+        /// to the other, going through the vertex at its right angle in between.
+        ///
+        /// After one more split than there are bits in a curve index, triangles are
+        /// halves of grid cells. It turns out that the two halves of a grid cell
+        /// are never both at even positions in the sequence of triangles, so the
+        /// curve goes through the grid cells of the triangles at even positions.
+        ///
+        /// Every point of the curve is a neighbor of the next one, which may be
+        /// diagonal, and this remains true when wrapping around from the last point
+        /// to the first one.
+        ///
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // The high-order bit of the curve index selects one of the two halves of
+            // the domain, and the other bits select sub-triangles, down to the
+            // triangle made of the two halves of cells that we need to pick from.
+            // The first half of that triangle is a triangle at an even position.
+            half_cell_coords(code >> (IDX_BITS - 1) != 0, code << 1)
+        }
+
+        /// Compute the index of a point on the Sierpiński curve
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        ///
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            // Find the position of the half of the cell that is on the left of both
+            // of its diagonals, and if it is not even, use the other half instead.
+            // Coordinates are scaled up by 4 so that both probe points (which lie
+            // strictly inside of one half of the cell, whichever way the cell is
+            // split) and all triangle vertices have integer coordinates.
+            let [x, y] = coords;
+            let [x, y] = [4 * x as CurveIdx, 4 * y as CurveIdx];
+            let first_probe = triangle_position([x + 1, y + 2]);
+            let (upper, position) = if first_probe.1 & 1 == 0 {
+                first_probe
+            } else {
+                triangle_position([x + 3, y + 2])
+            };
+            ((upper as CurveIdx) << (IDX_BITS - 1)) | (position >> 1)
+        }
+
+        /// Find the position of the grid half-cell that contains a point, in
+        /// coordinates that are scaled up by 4
+        ///
+        /// The result is split into the high-order bit, which does not fit in a
+        /// CurveIdx, and the other bits.
+        ///
+        pub(crate) const fn triangle_position(point: Vertex) -> (bool, CurveIdx) {
+            let upper = point[1] > point[0];
+            let side: CurveIdx = 4 << COORD_BITS;
+            let [mut start, mut right_angle, mut end] = initial_triangle(side, upper);
+            let mut position: CurveIdx = 0;
+            let mut depth = 0;
+            while depth < IDX_BITS {
+                depth += 1;
+                let middle = midpoint(start, end);
+                let in_first_half = is_left_of(right_angle, middle, point)
+                    == is_left_of(right_angle, middle, start);
+                position = (position << 1) | (!in_first_half as CurveIdx);
+                if in_first_half {
+                    end = right_angle;
+                } else {
+                    start = right_angle;
+                }
+                right_angle = middle;
+            }
+            (upper, position)
+        }
+
+        /// Find the grid cell that a half-cell belongs to, given the position of
+        /// that half-cell in the format of `triangle_position()`
+        pub(crate) const fn half_cell_coords(upper: bool, position: CurveIdx) -> Coordinates2D {
+            let side: CurveIdx = 1 << COORD_BITS;
+            let [mut start, mut right_angle, mut end] = initial_triangle(side, upper);
+            let mut depth = IDX_BITS;
+            while depth > 0 {
+                depth -= 1;
+                let middle = midpoint(start, end);
+                if (position >> depth) & 1 == 0 {
+                    end = right_angle;
+                } else {
+                    start = right_angle;
+                }
+                right_angle = middle;
+            }
+
+            // Half-cells have three of the corners of their grid cell as vertices,
+            // so the lowest vertex coordinates are those of the grid cell.
+            [
+                min3(start[0], end[0], right_angle[0]) as Coordinate,
+                min3(start[1], end[1], right_angle[1]) as Coordinate,
+            ]
+        }
+
+        /// First triangle of the recursion for a square domain of a certain side
+        /// length, as a `[start, right_angle, end]` list of vertices
+        #[inline(always)]
+        const fn initial_triangle(side: CurveIdx, upper: bool) -> [Vertex; 3] {
+            if upper {
+                [[side, side], [0, side], [0, 0]]
+            } else {
+                [[0, 0], [side, 0], [side, side]]
+            }
+        }
+
+        /// Midpoint of two triangle vertices
+        #[inline(always)]
+        const fn midpoint(v1: Vertex, v2: Vertex) -> Vertex {
+            [(v1[0] + v2[0]) / 2, (v1[1] + v2[1]) / 2]
+        }
+
+        /// Smallest of three vertex coordinates
+        #[inline(always)]
+        const fn min3(c1: CurveIdx, c2: CurveIdx, c3: CurveIdx) -> CurveIdx {
+            let min12 = if c1 < c2 { c1 } else { c2 };
+            if min12 < c3 {
+                min12
+            } else {
+                c3
+            }
+        }
+
+        /// Truth that a point is on the left of the line going from `from` to `to`,
+        /// which must be horizontal, vertical or diagonal
+        ///
+        /// The point must not be on the line, and all computations are done
+        /// without going through negative numbers.
+        ///
+        #[inline(always)]
+        const fn is_left_of(from: Vertex, to: Vertex, point: Vertex) -> bool {
+            let [[x1, y1], [x2, y2], [x, y]] = [from, to, point];
+            if x2 == x1 {
+                (x < x1) == (y2 > y1)
+            } else if y2 == y1 {
+                (y > y1) == (x2 > x1)
+            } else if (x2 > x1) == (y2 > y1) {
+                // Along the (1, 1) and (-1, -1) directions, compare y - x
+                (y + x1 > x + y1) == (x2 > x1)
+            } else {
+                // Along the (1, -1) and (-1, 1) directions, compare x + y
+                (x + y > x1 + y1) == (x2 > x1)
+            }
+        }
+
+        decode_iter_impl!("Sierpiński");
+    };
+}
+
+/// Sierpiński curve utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    sierpinski_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Sierpiński curve utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    sierpinski_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Sierpiński curve utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    sierpinski_impl!(idx64, u64, u32);
+}
+
+/// Sierpiński curve utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    sierpinski_impl!(idx128, u128, u64);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    // Check that the curve visits every point once, moving to a neighbor (which
+    // may be diagonal) at every step, including when wrapping around from the end
+    // to the start
+    #[test]
+    fn decode_2d() {
+        use super::decode_2d_u16 as decode_2d;
+        assert_eq!(decode_2d(0), [0, 0]);
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        for code in 0..=u16::MAX {
+            let [x, y] = decode_2d(code);
+            let [next_x, next_y] = decode_2d(code.wrapping_add(1));
+            assert_eq!(
+                (x as i32 - next_x as i32)
+                    .abs()
+                    .max((y as i32 - next_y as i32).abs()),
+                1,
+                "Curve jumped from {code} to the next point"
+            );
+            let point_idx = (usize::from(y) << u8::BITS) | usize::from(x);
+            assert!(!visited[point_idx], "Point {code} was visited twice");
+            visited[point_idx] = true;
+        }
+    }
+
+    #[test]
+    fn encode_2d() {
+        use super::{decode_2d_u16 as decode_2d, encode_2d_u16 as encode_2d};
+        for code in 0..=u16::MAX {
+            assert_eq!(encode_2d(decode_2d(code)), code);
+        }
+    }
+
+    #[test]
+    fn iter_2d() {
+        use super::{decode_2d_u16 as decode_2d, iter_2d_u16 as iter_2d};
+        assert!(iter_2d().eq((0..=u16::MAX).map(decode_2d)));
+        assert!(super::iter_range_2d(42..=1234)
+            .rev()
+            .step_by(7)
+            .eq((42..=1234).rev().step_by(7).map(super::decode_2d)));
+    }
+
+    // Wider curve index types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty) => {
+            mod $width {
+                use crate::sierpinski::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        let [x, y] = $width::decode_2d(code);
+                        let [next_x, next_y] = $width::decode_2d(code.wrapping_add(1));
+                        let distance = (x.max(next_x) - x.min(next_x)).max(y.max(next_y) - y.min(next_y));
+                        distance == 1
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == code
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
@@ -0,0 +1,212 @@
+//! Utilities related to the snake (boustrophedon) order
+//!
+//! This is not really a space-filling curve, but the row-major order where every
+//! other row is traversed backwards, so that every point is a direct neighbor of
+//! the next one. It has poor locality, which makes it a useful baseline when
+//! evaluating other curves, and it trivially generalizes to rectangular
+//! domains.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+
+/// Generate snake order utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! snake_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::bits::$width as bits;
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Number of bits in a coordinate
+        const COORD_BITS: u32 = Coordinate::BITS;
+
+        /// Compute the coordinates of the i-th point of the snake order
+        ///
+        /// The low-order half of the curve index is the horizontal coordinate
+        /// and the high-order half is the vertical coordinate, but the
+        /// horizontal coordinate goes from right to left on odd rows. Therefore,
+        /// the curve starts at `[0, 0]` and ends at `[0, Coordinate::MAX]`.
+        ///
+        /// This function runs in constant time: it contains no branch or memory
+        /// access whose outcome depends on the value of `code`.
+        ///
+        #[inline]
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            let y = (code >> COORD_BITS) as Coordinate;
+            let x = code as Coordinate ^ (y & 1).wrapping_neg();
+            [x, y]
+        }
+
+        /// Compute the index of a point on the snake order
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        ///
+        /// This function runs in constant time: it contains no branch or memory
+        /// access whose outcome depends on the value of `coords`.
+        ///
+        #[inline]
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            let [x, y] = coords;
+            let x = x ^ (y & 1).wrapping_neg();
+            ((y as CurveIdx) << COORD_BITS) | x as CurveIdx
+        }
+
+        decode_iter_impl!("snake");
+    };
+}
+
+/// Snake order utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    snake_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Snake order utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    snake_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Snake order utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    snake_impl!(idx64, u64, u32);
+}
+
+/// Snake order utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    snake_impl!(idx128, u128, u64);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    use crate::{bits::test_utils::*, Coordinate, CurveIdx};
+    use quickcheck::{Arbitrary, Gen};
+
+    // Check that the curve visits every point once, moving to a direct neighbor
+    // at every step
+    #[test]
+    fn decode_2d() {
+        use super::decode_2d_u16 as decode_2d;
+        assert_eq!(decode_2d(0), [0, 0]);
+        assert_eq!(decode_2d(u16::MAX), [0, u8::MAX]);
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        for code in 0..=u16::MAX {
+            let [x, y] = decode_2d(code);
+            if code < u16::MAX {
+                let [next_x, next_y] = decode_2d(code + 1);
+                assert_eq!(
+                    (x as i32 - next_x as i32).abs() + (y as i32 - next_y as i32).abs(),
+                    1,
+                    "Curve jumped from {code} to the next point"
+                );
+            }
+            let point_idx = (usize::from(y) << u8::BITS) | usize::from(x);
+            assert!(!visited[point_idx], "Point {code} was visited twice");
+            visited[point_idx] = true;
+        }
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn decode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            CurveIdx::MIN,
+            || CurveIdx::arbitrary(&mut gen),
+            super::decode_2d,
+        );
+    }
+
+    #[test]
+    fn encode_2d() {
+        use super::{decode_2d_u16 as decode_2d, encode_2d_u16 as encode_2d};
+        for code in 0..=u16::MAX {
+            assert_eq!(encode_2d(decode_2d(code)), code);
+        }
+    }
+
+    // Timing measurements are only meaningful in release mode
+    #[test]
+    #[ignore]
+    fn encode_2d_constant_time() {
+        let mut gen = Gen::new(usize::MAX);
+        assert_constant_time(
+            [Coordinate::MIN; 2],
+            || <[Coordinate; 2]>::arbitrary(&mut gen),
+            super::encode_2d,
+        );
+    }
+
+    #[test]
+    fn iter_2d() {
+        use super::{decode_2d_u16 as decode_2d, iter_2d_u16 as iter_2d};
+        assert!(iter_2d().eq((0..=u16::MAX).map(decode_2d)));
+        assert!(super::iter_range_2d(42..=1234)
+            .rev()
+            .step_by(7)
+            .eq((42..=1234).rev().step_by(7).map(super::decode_2d)));
+    }
+
+    // Wider curve index types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty) => {
+            mod $width {
+                use crate::snake::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        if code == <$curve_idx>::MAX {
+                            return true;
+                        }
+                        let [x, y] = $width::decode_2d(code);
+                        let [next_x, next_y] = $width::decode_2d(code + 1);
+                        let distance = x.max(next_x) - x.min(next_x) + y.max(next_y) - y.min(next_y);
+                        distance == 1
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == code
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
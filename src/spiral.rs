@@ -0,0 +1,253 @@
+//! Utilities related to the spiral (onion) order
+//!
+//! This order starts from the center of the domain and spirals outwards, going
+//! through concentric square rings. Any prefix of the curve is thus as close
+//! as possible to the center of the domain, which makes it a good fit for
+//! center-out progressive loading of images. An inward spiral is obtained by
+//! iterating over the curve in reverse order.
+//!
+//! Curve decoding, encoding and iteration are available for all supported curve
+//! index types, following the same naming conventions as the `hilbert` module.
+//! Unlike the Morton and Hilbert curves, decoding computes an integer square
+//! root, so it does not run in constant time.
+
+/// Generate spiral order utilities for a certain curve index type
+///
+/// See the `bits` module for an explanation of why this is a macro.
+///
+macro_rules! spiral_impl {
+    ($width:ident, $curve_idx:ty, $coordinate:ty) => {
+        use $crate::bits::$width as bits;
+
+        type CurveIdx = $curve_idx;
+        type Coordinate = $coordinate;
+        type Coordinates2D = [Coordinate; 2];
+
+        /// Number of bits in a curve index
+        const IDX_BITS: u32 = CurveIdx::BITS;
+
+        /// Number of bits in a coordinate
+        const COORD_BITS: u32 = Coordinate::BITS;
+
+        /// Half the side length of the curve's domain, which is also the
+        /// coordinate of the upper-right cell of the central 2x2 square
+        const HALF: Coordinate = 1 << (COORD_BITS - 1);
+
+        /// Compute the coordinates of the i-th point of the spiral order
+        ///
+        /// The domain is made of concentric square rings around its center, the
+        /// r-th of which has a side length of 2r + 2 and thus starts at curve index
+        /// 4r². Each ring is traversed counterclockwise, starting just above its
+        /// lower-right corner and ending at that corner, which is a direct
+        /// neighbor of the start of the next ring. Therefore, the curve starts at
+        /// `[HALF, HALF]` and ends at `[Coordinate::MAX, 0]`, where `HALF` is half
+        /// the side length of the domain.
+        ///
+        pub const fn decode_2d(code: CurveIdx) -> Coordinates2D {
+            // Find out which ring the point belongs to, and where it is on the ring
+            let ring = isqrt(code >> 2);
+            let position = code - 4 * ring * ring;
+
+            // Rings are made of four sides, each of which is 2r + 1 points long if
+            // we count their first corner but not their last one.
+            let side_length = 2 * ring + 1;
+            let side = position / side_length;
+            let offset = (position % side_length) as Coordinate;
+            let ring = ring as Coordinate;
+            let [low, high] = [HALF - 1 - ring, HALF + ring];
+            match side {
+                0 => [high, low + 1 + offset],
+                1 => [high - 1 - offset, high],
+                2 => [low, high - 1 - offset],
+                _ => [low + 1 + offset, low],
+            }
+        }
+
+        /// Compute the index of a point on the spiral order
+        ///
+        /// This is the inverse of `decode_2d()`, see its documentation for details.
+        ///
+        pub const fn encode_2d(coords: Coordinates2D) -> CurveIdx {
+            // The ring of a point is given by its distance to the central 2x2 square
+            let [x, y] = coords;
+            let [x_dist, y_dist] = [center_distance(x), center_distance(y)];
+            let ring = if x_dist > y_dist { x_dist } else { y_dist };
+
+            // Find which side of the ring the point is on, and where
+            let [low, high] = [HALF - 1 - ring, HALF + ring];
+            let (side, offset): (CurveIdx, Coordinate) = if x == high && y != low {
+                (0, y - low - 1)
+            } else if y == high {
+                (1, high - 1 - x)
+            } else if x == low {
+                (2, high - 1 - y)
+            } else {
+                (3, x - low - 1)
+            };
+            let ring = ring as CurveIdx;
+            4 * ring * ring + side * (2 * ring + 1) + offset as CurveIdx
+        }
+
+        /// Distance from a coordinate to the central 2x2 square, along one axis
+        #[inline(always)]
+        const fn center_distance(coord: Coordinate) -> Coordinate {
+            if coord >= HALF {
+                coord - HALF
+            } else {
+                HALF - 1 - coord
+            }
+        }
+
+        /// Integer square root, rounded down
+        const fn isqrt(value: CurveIdx) -> CurveIdx {
+            // Compute the square root one bit at a time, from the high-order bits
+            let mut remainder = value;
+            let mut root = 0;
+            let mut bit: CurveIdx = 1 << (IDX_BITS - 2);
+            while bit != 0 {
+                if remainder >= root + bit {
+                    remainder -= root + bit;
+                    root = (root >> 1) + bit;
+                } else {
+                    root >>= 1;
+                }
+                bit >>= 2;
+            }
+            root
+        }
+
+        decode_iter_impl!("spiral");
+    };
+}
+
+/// Spiral order utilities for 16-bit curve indices
+pub(crate) mod idx16 {
+    spiral_impl!(idx16, u16, u8);
+
+    // See morton::idx16 for why Iter2D is only ExactSizeIterator for some widths
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Spiral order utilities for 32-bit curve indices
+pub(crate) mod idx32 {
+    spiral_impl!(idx32, u32, u16);
+
+    #[cfg(target_pointer_width = "64")]
+    impl ExactSizeIterator for Iter2D {}
+}
+
+/// Spiral order utilities for 64-bit curve indices
+pub(crate) mod idx64 {
+    spiral_impl!(idx64, u64, u32);
+}
+
+/// Spiral order utilities for 128-bit curve indices
+pub(crate) mod idx128 {
+    spiral_impl!(idx128, u128, u64);
+}
+
+pub_use_default_width!(
+    decode_2d,
+    encode_2d,
+    iter_2d,
+    iter_from_2d,
+    iter_range_2d,
+    Iter2D
+);
+pub use idx128::{
+    decode_2d as decode_2d_u128, encode_2d as encode_2d_u128, iter_2d as iter_2d_u128,
+    iter_from_2d as iter_from_2d_u128, iter_range_2d as iter_range_2d_u128, Iter2D as Iter2DU128,
+};
+pub use idx16::{
+    decode_2d as decode_2d_u16, encode_2d as encode_2d_u16, iter_2d as iter_2d_u16,
+    iter_from_2d as iter_from_2d_u16, iter_range_2d as iter_range_2d_u16, Iter2D as Iter2DU16,
+};
+pub use idx32::{
+    decode_2d as decode_2d_u32, encode_2d as encode_2d_u32, iter_2d as iter_2d_u32,
+    iter_from_2d as iter_from_2d_u32, iter_range_2d as iter_range_2d_u32, Iter2D as Iter2DU32,
+};
+pub use idx64::{
+    decode_2d as decode_2d_u64, encode_2d as encode_2d_u64, iter_2d as iter_2d_u64,
+    iter_from_2d as iter_from_2d_u64, iter_range_2d as iter_range_2d_u64, Iter2D as Iter2DU64,
+};
+
+#[cfg(test)]
+mod tests {
+    // Check that the curve visits every point once, moving to a direct neighbor
+    // at every step, and never going back towards the center of the domain
+    #[test]
+    fn decode_2d() {
+        use super::decode_2d_u16 as decode_2d;
+        let half = 1i32 << (u8::BITS - 1);
+        assert_eq!(decode_2d(0), [half as u8, half as u8]);
+        assert_eq!(decode_2d(u16::MAX), [u8::MAX, 0]);
+        let mut visited = vec![false; usize::from(u16::MAX) + 1];
+        let mut max_dist = 0;
+        for code in 0..=u16::MAX {
+            let [x, y] = decode_2d(code);
+            if code < u16::MAX {
+                let [next_x, next_y] = decode_2d(code + 1);
+                assert_eq!(
+                    (x as i32 - next_x as i32).abs() + (y as i32 - next_y as i32).abs(),
+                    1,
+                    "Curve jumped from {code} to the next point"
+                );
+            }
+            let dist = (2 * i32::from(x) + 1 - 2 * half)
+                .abs()
+                .max((2 * i32::from(y) + 1 - 2 * half).abs());
+            assert!(dist >= max_dist, "Point {code} went back inwards");
+            max_dist = dist;
+            let point_idx = (usize::from(y) << u8::BITS) | usize::from(x);
+            assert!(!visited[point_idx], "Point {code} was visited twice");
+            visited[point_idx] = true;
+        }
+    }
+
+    #[test]
+    fn encode_2d() {
+        use super::{decode_2d_u16 as decode_2d, encode_2d_u16 as encode_2d};
+        for code in 0..=u16::MAX {
+            assert_eq!(encode_2d(decode_2d(code)), code);
+        }
+    }
+
+    #[test]
+    fn iter_2d() {
+        use super::{decode_2d_u16 as decode_2d, iter_2d_u16 as iter_2d};
+        assert!(iter_2d().eq((0..=u16::MAX).map(decode_2d)));
+        assert!(super::iter_range_2d(42..=1234)
+            .rev()
+            .step_by(7)
+            .eq((42..=1234).rev().step_by(7).map(super::decode_2d)));
+    }
+
+    // Wider curve index types are tested using random inputs
+    macro_rules! wide_tests {
+        ($width:ident, $curve_idx:ty) => {
+            mod $width {
+                use crate::spiral::$width;
+                use quickcheck::quickcheck;
+
+                quickcheck! {
+                    fn decode_2d(code: $curve_idx) -> bool {
+                        if code == <$curve_idx>::MAX {
+                            return true;
+                        }
+                        let [x, y] = $width::decode_2d(code);
+                        let [next_x, next_y] = $width::decode_2d(code + 1);
+                        let distance = x.max(next_x) - x.min(next_x) + y.max(next_y) - y.min(next_y);
+                        distance == 1
+                    }
+
+                    fn encode_2d(code: $curve_idx) -> bool {
+                        $width::encode_2d($width::decode_2d(code)) == code
+                    }
+                }
+            }
+        };
+    }
+    wide_tests!(idx32, u32);
+    wide_tests!(idx64, u64);
+    wide_tests!(idx128, u128);
+}
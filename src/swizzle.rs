@@ -0,0 +1,316 @@
+//! Block-linear texture addressing
+//!
+//! GPUs usually store textures in a "swizzled" or "block-linear" layout, where
+//! the texture is split into rectangular tiles that are laid out in row-major
+//! order, and the texels of each tile are laid out in Morton order. This keeps
+//! texels that are close in 2D space close in memory, which improves cache
+//! efficiency when sampling textures, while keeping the address computation
+//! cheap. This module computes the memory offsets of texels in such a layout,
+//! and converts between it and the usual linear (row-major) layout.
+//!
+//! Tile dimensions must be powers of two. When a tile is not square, the texels
+//! of the largest square tile that fits inside of it are laid out in Morton
+//! order, and the resulting square blocks are laid out along the longer side of
+//! the tile, as most hardware does.
+
+use crate::{morton, NumBits};
+
+/// Block-linear memory layout of a 2D texture
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Swizzle {
+    /// Base-2 logarithm of the tile width, in texels
+    tile_width_log2: NumBits,
+
+    /// Base-2 logarithm of the tile height, in texels
+    tile_height_log2: NumBits,
+
+    /// Size of a texel, in bytes
+    texel_size: usize,
+
+    /// Number of tiles in a row of tiles
+    tiles_per_row: usize,
+}
+//
+impl Swizzle {
+    /// Set up a layout with `tile_width` x `tile_height` tiles of
+    /// `texel_size`-byte texels, for a texture that is `width` texels wide
+    ///
+    /// If `width` is not a multiple of `tile_width`, the last tile of each row
+    /// of tiles is padded.
+    ///
+    /// # Panics
+    ///
+    /// If `tile_width` or `tile_height` is not a power of two, or if the
+    /// smallest of them is larger than 2^32.
+    ///
+    pub const fn new(
+        tile_width: usize,
+        tile_height: usize,
+        texel_size: usize,
+        width: usize,
+    ) -> Self {
+        assert!(
+            tile_width.is_power_of_two() && tile_height.is_power_of_two(),
+            "Tile dimensions must be powers of two"
+        );
+        let tile_width_log2 = tile_width.trailing_zeros();
+        let tile_height_log2 = tile_height.trailing_zeros();
+        assert!(
+            min(tile_width_log2, tile_height_log2) <= u32::BITS,
+            "Tiles are too large for Morton addressing"
+        );
+        Self {
+            tile_width_log2,
+            tile_height_log2,
+            texel_size,
+            tiles_per_row: (width + tile_width - 1) / tile_width,
+        }
+    }
+
+    /// Width of a tile, in texels
+    #[inline]
+    pub const fn tile_width(&self) -> usize {
+        1 << self.tile_width_log2
+    }
+
+    /// Height of a tile, in texels
+    #[inline]
+    pub const fn tile_height(&self) -> usize {
+        1 << self.tile_height_log2
+    }
+
+    /// Size of a texel, in bytes
+    #[inline]
+    pub const fn texel_size(&self) -> usize {
+        self.texel_size
+    }
+
+    /// Size of a tile, in bytes
+    #[inline]
+    pub const fn tile_size(&self) -> usize {
+        (self.texel_size << self.tile_width_log2) << self.tile_height_log2
+    }
+
+    /// Size in bytes of a texture that is `height` texels high, including the
+    /// padding of tiles that go past the edges of the texture
+    #[inline]
+    pub const fn texture_size(&self, height: usize) -> usize {
+        let tile_rows = (height + self.tile_height() - 1) >> self.tile_height_log2;
+        tile_rows * self.tiles_per_row * self.tile_size()
+    }
+
+    /// Memory offset of the texel of coordinates `[x, y]`, in bytes
+    #[inline]
+    pub const fn offset(&self, coords: [usize; 2]) -> usize {
+        let [x, y] = coords;
+        let tile_x = x >> self.tile_width_log2;
+        let tile_y = y >> self.tile_height_log2;
+        let tile_idx = tile_y * self.tiles_per_row + tile_x;
+        let in_tile_x = x & (self.tile_width() - 1);
+        let in_tile_y = y & (self.tile_height() - 1);
+
+        // The low-order bits of the in-tile coordinates are interleaved, and the
+        // remaining high-order bits of the longer side come on top
+        let square_log2 = min(self.tile_width_log2, self.tile_height_log2);
+        let square_mask = (1 << square_log2) - 1;
+        let square_idx = morton::encode_2d_u64([
+            (in_tile_x & square_mask) as u32,
+            (in_tile_y & square_mask) as u32,
+        ]) as usize;
+        let block_idx = (in_tile_x | in_tile_y) >> square_log2;
+        let texel_idx = (tile_idx << (self.tile_width_log2 + self.tile_height_log2))
+            | (block_idx << (2 * square_log2))
+            | square_idx;
+        texel_idx * self.texel_size
+    }
+
+    /// Coordinates of the texel at a certain memory offset, in bytes
+    ///
+    /// This is the inverse of `offset()`. Offsets that are not a multiple of the
+    /// texel size are rounded down to the start of the texel that they point
+    /// into.
+    ///
+    #[inline]
+    pub const fn coords(&self, offset: usize) -> [usize; 2] {
+        let texel_idx = offset / self.texel_size;
+        let tile_idx = texel_idx >> (self.tile_width_log2 + self.tile_height_log2);
+        let tile_x = tile_idx % self.tiles_per_row;
+        let tile_y = tile_idx / self.tiles_per_row;
+
+        let square_log2 = min(self.tile_width_log2, self.tile_height_log2);
+        let square_idx = texel_idx & ((1 << (2 * square_log2)) - 1);
+        let [square_x, square_y] = morton::decode_2d_u64(square_idx as u64);
+        let block_idx =
+            (texel_idx & (self.tile_width() * self.tile_height() - 1)) >> (2 * square_log2);
+        let [block_x, block_y] = if self.tile_width_log2 > self.tile_height_log2 {
+            [block_idx, 0]
+        } else {
+            [0, block_idx]
+        };
+        [
+            (tile_x << self.tile_width_log2) | (block_x << square_log2) | square_x as usize,
+            (tile_y << self.tile_height_log2) | (block_y << square_log2) | square_y as usize,
+        ]
+    }
+
+    /// Convert a memory offset in a linear layout with rows of `pitch` bytes
+    /// into the memory offset of the same texel in this layout
+    #[inline]
+    pub const fn linear_to_swizzled(&self, linear: usize, pitch: usize) -> usize {
+        let [row_offset, y] = [linear % pitch, linear / pitch];
+        let x = row_offset / self.texel_size;
+        self.offset([x, y]) + row_offset % self.texel_size
+    }
+
+    /// Convert a memory offset in this layout into the memory offset of the
+    /// same texel in a linear layout with rows of `pitch` bytes
+    #[inline]
+    pub const fn swizzled_to_linear(&self, swizzled: usize, pitch: usize) -> usize {
+        let [x, y] = self.coords(swizzled);
+        y * pitch + x * self.texel_size + swizzled % self.texel_size
+    }
+
+    /// Copy a `width` x `height` texture from a linear layout with rows of
+    /// `pitch` bytes into this layout
+    ///
+    /// Padding bytes of `swizzled` are left untouched.
+    ///
+    /// # Panics
+    ///
+    /// If `linear` or `swizzled` is too small to hold the texture.
+    ///
+    pub fn swizzle(
+        &self,
+        linear: &[u8],
+        pitch: usize,
+        [width, height]: [usize; 2],
+        swizzled: &mut [u8],
+    ) {
+        self.for_each_texel(pitch, [width, height], |linear_start, swizzled_start| {
+            swizzled[swizzled_start..swizzled_start + self.texel_size]
+                .copy_from_slice(&linear[linear_start..linear_start + self.texel_size]);
+        });
+    }
+
+    /// Copy a `width` x `height` texture from this layout into a linear layout
+    /// with rows of `pitch` bytes
+    ///
+    /// Padding bytes of `linear` are left untouched.
+    ///
+    /// # Panics
+    ///
+    /// If `swizzled` or `linear` is too small to hold the texture.
+    ///
+    pub fn unswizzle(
+        &self,
+        swizzled: &[u8],
+        [width, height]: [usize; 2],
+        linear: &mut [u8],
+        pitch: usize,
+    ) {
+        self.for_each_texel(pitch, [width, height], |linear_start, swizzled_start| {
+            linear[linear_start..linear_start + self.texel_size]
+                .copy_from_slice(&swizzled[swizzled_start..swizzled_start + self.texel_size]);
+        });
+    }
+
+    /// Call `f` with the linear and swizzled offsets of every texel of a
+    /// `width` x `height` texture
+    #[inline]
+    fn for_each_texel(
+        &self,
+        pitch: usize,
+        [width, height]: [usize; 2],
+        mut f: impl FnMut(usize, usize),
+    ) {
+        assert!(
+            width.saturating_mul(self.texel_size) <= pitch,
+            "Rows of {width} texels do not fit in a {pitch}-byte pitch"
+        );
+        assert!(
+            width <= self.tiles_per_row << self.tile_width_log2,
+            "Texture is wider than this layout"
+        );
+        for y in 0..height {
+            for x in 0..width {
+                f(y * pitch + x * self.texel_size, self.offset([x, y]));
+            }
+        }
+    }
+}
+
+/// Minimum of two numbers of bits, usable in const fn
+const fn min(x: NumBits, y: NumBits) -> NumBits {
+    if x < y {
+        x
+    } else {
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset() {
+        // A square 4x4 tile follows the Morton curve
+        let swizzle = Swizzle::new(4, 4, 1, 4);
+        for code in 0..16 {
+            let [x, y] = morton::decode_2d_u64(code);
+            assert_eq!(swizzle.offset([x as usize, y as usize]), code as usize);
+        }
+
+        // Non-square tiles go through square blocks along their longer side,
+        // and tiles are laid out in row-major order
+        let swizzle = Swizzle::new(8, 2, 4, 20);
+        assert_eq!(swizzle.tile_size(), 64);
+        assert_eq!(swizzle.texture_size(3), 2 * 3 * 64);
+        assert_eq!(swizzle.offset([1, 1]), 3 * 4);
+        assert_eq!(swizzle.offset([2, 0]), 4 * 4);
+        assert_eq!(swizzle.offset([7, 1]), 15 * 4);
+        assert_eq!(swizzle.offset([8, 0]), 16 * 4);
+        assert_eq!(swizzle.offset([0, 2]), 3 * 16 * 4);
+        let swizzle = Swizzle::new(2, 8, 1, 20);
+        assert_eq!(swizzle.offset([0, 2]), 4);
+        assert_eq!(swizzle.offset([2, 0]), 16);
+    }
+
+    #[test]
+    fn roundtrip() {
+        for &[tile_width, tile_height] in &[[1, 1], [4, 4], [8, 2], [2, 8], [16, 1], [32, 32]] {
+            for &texel_size in &[1, 3, 4] {
+                let [width, height] = [37, 21];
+                let swizzle = Swizzle::new(tile_width, tile_height, texel_size, width);
+                let pitch = width * texel_size + 5;
+                let mut offsets = Vec::new();
+                for y in 0..height {
+                    for x in 0..width {
+                        let offset = swizzle.offset([x, y]);
+                        assert_eq!(swizzle.coords(offset), [x, y]);
+                        assert_eq!(swizzle.coords(offset + texel_size - 1), [x, y]);
+                        let linear = y * pitch + x * texel_size + texel_size - 1;
+                        let swizzled = offset + texel_size - 1;
+                        assert_eq!(swizzle.linear_to_swizzled(linear, pitch), swizzled);
+                        assert_eq!(swizzle.swizzled_to_linear(swizzled, pitch), linear);
+                        offsets.push(offset);
+                    }
+                }
+                offsets.sort_unstable();
+                offsets.dedup();
+                assert_eq!(offsets.len(), width * height);
+                assert!(offsets.last().unwrap() + texel_size <= swizzle.texture_size(height));
+
+                let linear = (0..pitch * height).map(|i| i as u8).collect::<Vec<_>>();
+                let mut swizzled = vec![0; swizzle.texture_size(height)];
+                swizzle.swizzle(&linear, pitch, [width, height], &mut swizzled);
+                let mut roundtrip = vec![0; linear.len()];
+                swizzle.unswizzle(&swizzled, [width, height], &mut roundtrip, pitch);
+                for y in 0..height {
+                    let row = y * pitch..y * pitch + width * texel_size;
+                    assert_eq!(roundtrip[row.clone()], linear[row]);
+                }
+            }
+        }
+    }
+}
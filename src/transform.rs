@@ -0,0 +1,236 @@
+//! Symmetries of the square
+//!
+//! Space-filling curves are often assembled from tiles of smaller curves, which
+//! must be rotated or reflected so that the exit point of each tile is next to
+//! the entry point of the following one. This module provides the 8 symmetries
+//! of a square domain (the dihedral group of order 8) as the `Transform2D` type,
+//! which can be applied to coordinates, composed and inverted. Curve modules
+//! provide `decode_2d_transformed()` and `encode_2d_transformed()` functions that
+//! take such a transform.
+
+use crate::{bits, hilbert, morton, Coordinate, Coordinates2D, NumBits};
+
+/// Symmetry of a square domain of 2^N x 2^N points
+///
+/// Every symmetry of the square can be decomposed into an optional swap of the
+/// x and y coordinates, followed by optional mirroring of each coordinate,
+/// which is how transforms are represented. The named constants of this type
+/// give the usual names of these symmetries, with the x coordinate increasing
+/// towards the east and the y coordinate increasing towards the north.
+///
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Transform2D {
+    /// Truth that x and y coordinates are swapped before mirroring
+    pub swap: bool,
+
+    /// Truth that the x coordinate is mirrored
+    pub mirror_x: bool,
+
+    /// Truth that the y coordinate is mirrored
+    pub mirror_y: bool,
+}
+//
+impl Transform2D {
+    /// Transform that leaves points where they are
+    pub const IDENTITY: Self = Self::new(false, false, false);
+
+    /// Counterclockwise rotation by a quarter turn
+    pub const ROTATE_90: Self = Self::new(true, true, false);
+
+    /// Rotation by half a turn
+    pub const ROTATE_180: Self = Self::new(false, true, true);
+
+    /// Counterclockwise rotation by three quarter turns
+    pub const ROTATE_270: Self = Self::new(true, false, true);
+
+    /// Reflection that mirrors the x coordinate
+    pub const MIRROR_X: Self = Self::new(false, true, false);
+
+    /// Reflection that mirrors the y coordinate
+    pub const MIRROR_Y: Self = Self::new(false, false, true);
+
+    /// Reflection across the diagonal that goes through the origin
+    pub const TRANSPOSE: Self = Self::new(true, false, false);
+
+    /// Reflection across the other diagonal
+    pub const ANTI_TRANSPOSE: Self = Self::new(true, true, true);
+
+    /// All symmetries of the square
+    pub const ALL: [Self; 8] = [
+        Self::IDENTITY,
+        Self::ROTATE_90,
+        Self::ROTATE_180,
+        Self::ROTATE_270,
+        Self::MIRROR_X,
+        Self::MIRROR_Y,
+        Self::TRANSPOSE,
+        Self::ANTI_TRANSPOSE,
+    ];
+
+    /// Build a transform from its swap and mirroring flags
+    #[inline]
+    pub const fn new(swap: bool, mirror_x: bool, mirror_y: bool) -> Self {
+        Self {
+            swap,
+            mirror_x,
+            mirror_y,
+        }
+    }
+
+    /// Transform a point of a 2^`order` x 2^`order` domain
+    ///
+    /// The bits of `coords` that are above `order` are left alone, so points
+    /// outside of the domain stay outside of it.
+    ///
+    /// # Panics
+    ///
+    /// If `order` is larger than the number of bits of `Coordinate`.
+    ///
+    #[inline]
+    pub const fn apply(self, coords: Coordinates2D, order: NumBits) -> Coordinates2D {
+        assert!(
+            order <= Coordinate::BITS,
+            "Order is too high for this curve"
+        );
+        let [x, y] = coords;
+        let [x, y] = if self.swap { [y, x] } else { [x, y] };
+        let mask = bits::low_order_mask(order) as Coordinate;
+        [
+            x ^ (mask & (self.mirror_x as Coordinate).wrapping_neg()),
+            y ^ (mask & (self.mirror_y as Coordinate).wrapping_neg()),
+        ]
+    }
+
+    /// Transform that applies `self`, then `next`
+    #[inline]
+    pub const fn then(self, next: Self) -> Self {
+        // Swapping coordinates after mirroring them is the same as mirroring
+        // the other coordinates after swapping them
+        let [mirror_x, mirror_y] = if next.swap {
+            [self.mirror_y, self.mirror_x]
+        } else {
+            [self.mirror_x, self.mirror_y]
+        };
+        Self::new(
+            self.swap ^ next.swap,
+            mirror_x ^ next.mirror_x,
+            mirror_y ^ next.mirror_y,
+        )
+    }
+
+    /// Transform that undoes `self`
+    #[inline]
+    pub const fn inverse(self) -> Self {
+        if self.swap {
+            Self::new(true, self.mirror_y, self.mirror_x)
+        } else {
+            self
+        }
+    }
+}
+//
+impl From<hilbert::Orientation> for Transform2D {
+    #[inline]
+    fn from(orientation: hilbert::Orientation) -> Self {
+        Self::new(orientation.swap, orientation.mirror_x, orientation.mirror_y)
+    }
+}
+//
+impl From<Transform2D> for hilbert::Orientation {
+    #[inline]
+    fn from(transform: Transform2D) -> Self {
+        Self {
+            swap: transform.swap,
+            mirror_x: transform.mirror_x,
+            mirror_y: transform.mirror_y,
+        }
+    }
+}
+//
+impl From<morton::Orientation> for Transform2D {
+    #[inline]
+    fn from(orientation: morton::Orientation) -> Self {
+        Self::new(orientation.swap, orientation.mirror_x, orientation.mirror_y)
+    }
+}
+//
+impl From<Transform2D> for morton::Orientation {
+    #[inline]
+    fn from(transform: Transform2D) -> Self {
+        Self {
+            swap: transform.swap,
+            mirror_x: transform.mirror_x,
+            mirror_y: transform.mirror_y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named() {
+        let max = Coordinate::MAX;
+        let order = Coordinate::BITS;
+        let cases = [
+            (Transform2D::IDENTITY, [1, 2]),
+            (Transform2D::ROTATE_90, [max - 2, 1]),
+            (Transform2D::ROTATE_180, [max - 1, max - 2]),
+            (Transform2D::ROTATE_270, [2, max - 1]),
+            (Transform2D::MIRROR_X, [max - 1, 2]),
+            (Transform2D::MIRROR_Y, [1, max - 2]),
+            (Transform2D::TRANSPOSE, [2, 1]),
+            (Transform2D::ANTI_TRANSPOSE, [max - 2, max - 1]),
+        ];
+        for (transform, expected) in cases {
+            assert_eq!(transform.apply([1, 2], order), expected);
+        }
+        assert_eq!(
+            Transform2D::ROTATE_90.then(Transform2D::ROTATE_90),
+            Transform2D::ROTATE_180
+        );
+        assert_eq!(
+            Transform2D::ROTATE_90.then(Transform2D::ROTATE_180),
+            Transform2D::ROTATE_270
+        );
+        assert_eq!(Transform2D::ROTATE_90.inverse(), Transform2D::ROTATE_270);
+        assert_eq!(Transform2D::default(), Transform2D::IDENTITY);
+        assert_eq!(Transform2D::TRANSPOSE.apply([3, 0], 2), [0, 3]);
+        assert_eq!(Transform2D::ROTATE_90.apply([3, 0], 2), [3, 3]);
+        assert_eq!(Transform2D::MIRROR_X.apply([4, 0], 2), [7, 0]);
+    }
+
+    #[test]
+    fn group() {
+        // Larger orders would take too long to test exhaustively
+        for order in 0..=Coordinate::BITS.min(8) {
+            let side = 1u32 << order;
+            for a in Transform2D::ALL {
+                assert_eq!(a.then(a.inverse()), Transform2D::IDENTITY);
+                assert_eq!(a.inverse().then(a), Transform2D::IDENTITY);
+                assert_eq!(
+                    Transform2D::from(hilbert::Orientation::from(a)),
+                    a,
+                    "Orientation round trip"
+                );
+                for b in Transform2D::ALL {
+                    let ab = a.then(b);
+                    for x in (0..side).step_by(3) {
+                        for y in (0..side).step_by(5) {
+                            let p = [x as Coordinate, y as Coordinate];
+                            assert_eq!(ab.apply(p, order), b.apply(a.apply(p, order), order));
+                            assert_eq!(a.inverse().apply(a.apply(p, order), order), p);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_order_too_high() {
+        Transform2D::IDENTITY.apply([0, 0], Coordinate::BITS + 1);
+    }
+}
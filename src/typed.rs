@@ -0,0 +1,514 @@
+//! Strongly typed curve indices and points
+//!
+//! Curve indices are plain integers, so nothing prevents a Morton code from
+//! being mistaken for a Hilbert curve index, or the other way around. The
+//! newtypes of this module record which curve an index belongs to, and can be
+//! serialized with serde when the optional `serde` cargo feature is enabled.
+//! Similarly, `Point2D` gives names to the coordinates of a point, so that they
+//! cannot be mixed up like the elements of a `Coordinates2D` array.
+//!
+//! Curve indices are serialized as integers and points as `[x, y]` sequences.
+//! Some formats cannot represent large integers faithfully, like JSON once it
+//! is read by JavaScript, so human-readable formats also accept curve indices
+//! written as decimal strings, and the `as_string` module can be used with
+//! `#[serde(with = "...")]` to serialize them in this form.
+//!
+//! Conversions from and to primitive integer types other than `CurveIdx` and
+//! `Coordinate` are provided via `TryFrom`, and fail with an `Error` when the
+//! value does not fit in the curve's domain or in the target type.
+//!
+//! When the optional `bytemuck` cargo feature is enabled, these newtypes also
+//! implement `bytemuck::Pod`, so that buffers of them can be cast to and from
+//! byte slices, e.g. for GPU upload or memory-mapped storage.
+
+use crate::{hilbert, morton, Coordinate, Coordinates2D, CurveIdx, Error};
+use core::{fmt, num::ParseIntError, str::FromStr};
+
+/// Point of the curves' domain
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Point2D(pub Coordinates2D);
+//
+impl Point2D {
+    /// Point with certain coordinates
+    #[inline]
+    pub const fn new(x: Coordinate, y: Coordinate) -> Self {
+        Self([x, y])
+    }
+
+    /// Horizontal coordinate of this point
+    #[inline]
+    pub const fn x(self) -> Coordinate {
+        self.0[0]
+    }
+
+    /// Vertical coordinate of this point
+    #[inline]
+    pub const fn y(self) -> Coordinate {
+        self.0[1]
+    }
+}
+//
+impl From<Coordinates2D> for Point2D {
+    #[inline]
+    fn from(coords: Coordinates2D) -> Self {
+        Self(coords)
+    }
+}
+//
+impl From<Point2D> for Coordinates2D {
+    #[inline]
+    fn from(point: Point2D) -> Self {
+        point.0
+    }
+}
+//
+impl From<(Coordinate, Coordinate)> for Point2D {
+    #[inline]
+    fn from((x, y): (Coordinate, Coordinate)) -> Self {
+        Self::new(x, y)
+    }
+}
+//
+impl From<Point2D> for (Coordinate, Coordinate) {
+    #[inline]
+    fn from(point: Point2D) -> Self {
+        (point.x(), point.y())
+    }
+}
+//
+impl fmt::Display for Point2D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x(), self.y())
+    }
+}
+//
+/// Generate the component-wise arithmetic of `Point2D` for an operation
+macro_rules! point_arithmetic_impl {
+    ($op:literal, $trait:ident, $method:ident, $checked:ident, $saturating:ident, $wrapping:ident) => {
+        impl Point2D {
+            #[doc = concat!("Checked ", $op, " of coordinates, returning `None` on overflow")]
+            #[inline]
+            pub const fn $checked(self, rhs: Self) -> Option<Self> {
+                match (self.x().$checked(rhs.x()), self.y().$checked(rhs.y())) {
+                    (Some(x), Some(y)) => Some(Self::new(x, y)),
+                    _ => None,
+                }
+            }
+
+            #[doc = concat!("Saturating ", $op, " of coordinates, which stops at the edges of")]
+            /// the curve's domain
+            #[inline]
+            pub const fn $saturating(self, rhs: Self) -> Self {
+                Self::new(self.x().$saturating(rhs.x()), self.y().$saturating(rhs.y()))
+            }
+
+            #[doc = concat!("Wrapping ", $op, " of coordinates, which wraps around the edges of")]
+            /// the curve's domain
+            #[inline]
+            pub const fn $wrapping(self, rhs: Self) -> Self {
+                Self::new(self.x().$wrapping(rhs.x()), self.y().$wrapping(rhs.y()))
+            }
+        }
+        //
+        impl core::ops::$trait for Point2D {
+            type Output = Self;
+
+            /// # Panics
+            ///
+            /// On overflow, if overflow checks are enabled.
+            ///
+            #[inline]
+            fn $method(self, rhs: Self) -> Self {
+                Self::new(self.x().$method(rhs.x()), self.y().$method(rhs.y()))
+            }
+        }
+    };
+}
+//
+point_arithmetic_impl!(
+    "addition",
+    Add,
+    add,
+    checked_add,
+    saturating_add,
+    wrapping_add
+);
+point_arithmetic_impl!(
+    "subtraction",
+    Sub,
+    sub,
+    checked_sub,
+    saturating_sub,
+    wrapping_sub
+);
+
+/// Generate a newtype for the indices of a curve module, which must provide
+/// `decode_2d()` and `encode_2d()`
+macro_rules! curve_index_impl {
+    ($name:ident, $curve:ident, $curve_name:literal) => {
+        #[doc = concat!("Index of a point on the 2D ", $curve_name, " curve")]
+        #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+        #[repr(transparent)]
+        pub struct $name(pub CurveIdx);
+        //
+        impl $name {
+            #[doc = concat!("Index of a point on the 2D ", $curve_name, " curve")]
+            #[inline]
+            pub const fn from_point(point: Point2D) -> Self {
+                Self($curve::encode_2d(point.0))
+            }
+
+            /// Point that this index designates
+            #[inline]
+            pub const fn to_point(self) -> Point2D {
+                Point2D($curve::decode_2d(self.0))
+            }
+        }
+        //
+        impl From<CurveIdx> for $name {
+            #[inline]
+            fn from(code: CurveIdx) -> Self {
+                Self(code)
+            }
+        }
+        //
+        impl From<$name> for CurveIdx {
+            #[inline]
+            fn from(index: $name) -> Self {
+                index.0
+            }
+        }
+        //
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+        //
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, ParseIntError> {
+                s.parse().map(Self)
+            }
+        }
+        //
+        // SAFETY: This is a repr(transparent) wrapper around an integer
+        #[cfg(feature = "bytemuck")]
+        unsafe impl bytemuck::Pod for $name {}
+        //
+        #[cfg(feature = "bytemuck")]
+        unsafe impl bytemuck::Zeroable for $name {}
+        //
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
+        //
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if deserializer.is_human_readable() {
+                    deserializer
+                        .deserialize_any(serde_impl::IndexVisitor)
+                        .map(Self)
+                } else {
+                    CurveIdx::deserialize(deserializer).map(Self)
+                }
+            }
+        }
+    };
+}
+//
+curve_index_impl!(MortonIndex, morton, "Morton");
+curve_index_impl!(HilbertIndex, hilbert, "Hilbert");
+
+/// Generate `TryFrom` conversions between the curve index and point newtypes
+/// and primitive integer types other than `CurveIdx` and `Coordinate`, which
+/// already have infallible `From` conversions
+macro_rules! try_from_int_impl {
+    ($($index_int:ty),* ; $($coord_int:ty),*) => {
+        $(
+            try_from_int_impl!(@index MortonIndex, $index_int);
+            try_from_int_impl!(@index HilbertIndex, $index_int);
+        )*
+        $(
+            impl TryFrom<($coord_int, $coord_int)> for Point2D {
+                type Error = Error;
+
+                #[inline]
+                fn try_from((x, y): ($coord_int, $coord_int)) -> Result<Self, Error> {
+                    match (Coordinate::try_from(x), Coordinate::try_from(y)) {
+                        (Ok(x), Ok(y)) => Ok(Self::new(x, y)),
+                        _ => Err(Error::CoordinateOutOfDomain),
+                    }
+                }
+            }
+            //
+            impl TryFrom<Point2D> for ($coord_int, $coord_int) {
+                type Error = Error;
+
+                #[inline]
+                fn try_from(point: Point2D) -> Result<Self, Error> {
+                    match (<$coord_int>::try_from(point.x()), <$coord_int>::try_from(point.y())) {
+                        (Ok(x), Ok(y)) => Ok((x, y)),
+                        _ => Err(Error::CoordinateOutOfDomain),
+                    }
+                }
+            }
+        )*
+    };
+    (@index $name:ident, $int:ty) => {
+        impl TryFrom<$int> for $name {
+            type Error = Error;
+
+            #[inline]
+            fn try_from(code: $int) -> Result<Self, Error> {
+                CurveIdx::try_from(code)
+                    .map(Self)
+                    .map_err(|_| Error::IndexOutOfDomain)
+            }
+        }
+        //
+        impl TryFrom<$name> for $int {
+            type Error = Error;
+
+            #[inline]
+            fn try_from(index: $name) -> Result<Self, Error> {
+                <$int>::try_from(index.0).map_err(|_| Error::IndexOutOfDomain)
+            }
+        }
+    };
+}
+//
+#[cfg(not(any(feature = "idx-u32", feature = "idx-u64", feature = "idx-u128")))]
+try_from_int_impl!(u8, u32, u64, u128, usize; u16, u32, u64, u128, usize);
+#[cfg(all(
+    feature = "idx-u32",
+    not(any(feature = "idx-u64", feature = "idx-u128"))
+))]
+try_from_int_impl!(u8, u16, u64, u128, usize; u8, u32, u64, u128, usize);
+#[cfg(all(feature = "idx-u64", not(feature = "idx-u128")))]
+try_from_int_impl!(u8, u16, u32, u128, usize; u8, u16, u64, u128, usize);
+#[cfg(feature = "idx-u128")]
+try_from_int_impl!(u8, u16, u32, u64, usize; u8, u16, u32, u128, usize);
+
+// SAFETY: This is a repr(transparent) wrapper around an array of integers
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Pod for Point2D {}
+//
+#[cfg(feature = "bytemuck")]
+unsafe impl bytemuck::Zeroable for Point2D {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Point2D {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+//
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Point2D {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Coordinates2D::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serialization of curve indices as decimal strings
+///
+/// This module is meant to be used via `#[serde(with = "...")]` on fields of
+/// type `MortonIndex` or `HilbertIndex`.
+///
+#[cfg(feature = "serde")]
+pub mod as_string {
+    use super::serde_impl::IndexVisitor;
+    use crate::CurveIdx;
+    use serde::{Deserializer, Serializer};
+
+    /// Serialize a curve index as a decimal string
+    pub fn serialize<Index, S>(index: &Index, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        Index: Copy + Into<CurveIdx>,
+        S: Serializer,
+    {
+        serializer.collect_str(&(*index).into())
+    }
+
+    /// Deserialize a curve index from a decimal string
+    pub fn deserialize<'de, Index, D>(deserializer: D) -> Result<Index, D::Error>
+    where
+        Index: From<CurveIdx>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(IndexVisitor).map(Index::from)
+    }
+}
+
+/// Deserialization utilities shared by all curve index types
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use crate::CurveIdx;
+    use core::{convert::TryFrom, fmt};
+    use serde::de::{Error, Unexpected, Visitor};
+
+    /// Visitor that accepts curve indices as integers or decimal strings
+    pub(super) struct IndexVisitor;
+    //
+    impl<'de> Visitor<'de> for IndexVisitor {
+        type Value = CurveIdx;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a curve index, as an integer or a decimal string")
+        }
+
+        #[allow(clippy::unnecessary_fallible_conversions)] // CurveIdx is u128 with idx-u128
+        fn visit_u64<E: Error>(self, v: u64) -> Result<CurveIdx, E> {
+            CurveIdx::try_from(v).map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<CurveIdx, E> {
+            CurveIdx::try_from(v).map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+        }
+
+        fn visit_u128<E: Error>(self, v: u128) -> Result<CurveIdx, E> {
+            CurveIdx::try_from(v)
+                .map_err(|_| E::invalid_value(Unexpected::Other("out-of-range integer"), &self))
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<CurveIdx, E> {
+            v.parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::test_utils::*;
+
+    #[test]
+    fn conversions() {
+        for code in sampled_indices() {
+            let morton = MortonIndex(code);
+            assert_eq!(MortonIndex::from_point(morton.to_point()), morton);
+            assert_eq!(morton.to_point(), Point2D(morton::decode_2d(code)));
+            let hilbert = HilbertIndex::from(code);
+            assert_eq!(HilbertIndex::from_point(hilbert.to_point()), hilbert);
+            assert_eq!(CurveIdx::from(hilbert), code);
+            assert_eq!(hilbert.to_string().parse(), Ok(hilbert));
+        }
+        let point = Point2D::new(1, 2);
+        assert_eq!([point.x(), point.y()], Coordinates2D::from(point));
+        assert_eq!(Point2D::from((1, 2)), point);
+        assert_eq!(<(Coordinate, Coordinate)>::from(point), (1, 2));
+        assert_eq!(point.to_string(), "(1, 2)");
+        assert!("-1".parse::<MortonIndex>().is_err());
+    }
+
+    #[test]
+    // u128 is the CurveIdx type with the idx-u128 feature
+    #[allow(clippy::unnecessary_fallible_conversions, clippy::useless_conversion)]
+    fn try_from_int() {
+        let max_code = u128::from(CurveIdx::MAX);
+        assert_eq!(
+            HilbertIndex::try_from(max_code),
+            Ok(HilbertIndex(CurveIdx::MAX))
+        );
+        #[cfg(not(feature = "idx-u128"))]
+        assert_eq!(
+            MortonIndex::try_from(max_code + 1),
+            Err(Error::IndexOutOfDomain)
+        );
+        assert_eq!(u128::try_from(MortonIndex(CurveIdx::MAX)), Ok(max_code));
+        assert_eq!(u8::try_from(MortonIndex(255)), Ok(255));
+        assert_eq!(
+            u8::try_from(HilbertIndex(256)),
+            Err(Error::IndexOutOfDomain)
+        );
+
+        let max_coord = u128::from(Coordinate::MAX);
+        assert_eq!(
+            Point2D::try_from((max_coord, 0)),
+            Ok(Point2D::new(Coordinate::MAX, 0))
+        );
+        assert_eq!(
+            Point2D::try_from((0, max_coord + 1)),
+            Err(Error::CoordinateOutOfDomain)
+        );
+        assert_eq!(<(u128, u128)>::try_from(Point2D::new(1, 2)), Ok((1, 2)));
+        assert_eq!(
+            <(u128, u128)>::try_from(Point2D::new(Coordinate::MAX, 2)),
+            Ok((max_coord, 2))
+        );
+    }
+
+    #[test]
+    fn point_arithmetic() {
+        let [a, b] = [Point2D::new(3, Coordinate::MAX - 1), Point2D::new(2, 1)];
+        assert_eq!(a + b, Point2D::new(5, Coordinate::MAX));
+        assert_eq!(a - b, Point2D::new(1, Coordinate::MAX - 2));
+        assert_eq!(a.checked_add(b + b), None);
+        assert_eq!(b.checked_sub(a), None);
+        assert_eq!(a.checked_sub(b), Some(a - b));
+        assert_eq!(a.saturating_add(b + b), Point2D::new(7, Coordinate::MAX));
+        assert_eq!(b.saturating_sub(a), Point2D::new(0, 0));
+        assert_eq!(a.wrapping_add(b + b), Point2D::new(7, 0));
+        assert_eq!(b.wrapping_sub(a), Point2D::new(Coordinate::MAX, 3));
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn bytemuck() {
+        let points = [Point2D::new(1, 2), Point2D::new(3, 4)];
+        let coords: &[Coordinate] = bytemuck::cast_slice(&points);
+        assert_eq!(coords, [1, 2, 3, 4]);
+        assert_eq!(bytemuck::cast_slice::<_, Point2D>(coords), points);
+
+        let indices = [MortonIndex(5), MortonIndex(6)];
+        let bytes: &[u8] = bytemuck::cast_slice(&indices);
+        assert_eq!(bytes.len(), 2 * core::mem::size_of::<CurveIdx>());
+        assert_eq!(bytemuck::cast_slice::<_, CurveIdx>(bytes), [5, 6]);
+        assert_eq!(bytemuck::cast::<CurveIdx, HilbertIndex>(7), HilbertIndex(7));
+        assert_eq!(
+            <Point2D as bytemuck::Zeroable>::zeroed(),
+            Point2D::default()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde_json::{from_str, json, to_string, to_value, value::Serializer};
+
+        let morton = MortonIndex::from_point(Point2D::new(3, 5));
+        assert_eq!(to_value(morton).unwrap(), json!(morton.0));
+        assert_eq!(from_str::<MortonIndex>("39").unwrap(), MortonIndex(39));
+        assert_eq!(
+            from_str::<HilbertIndex>("\"39\"").unwrap(),
+            HilbertIndex(39)
+        );
+        assert!(from_str::<HilbertIndex>("-1").is_err());
+        assert!(from_str::<HilbertIndex>("\"x\"").is_err());
+        let too_large = CurveIdx::MAX.to_string() + "0";
+        assert!(from_str::<HilbertIndex>(&too_large).is_err());
+
+        let point = Point2D::new(3, 5);
+        assert_eq!(to_string(&point).unwrap(), "[3,5]");
+        assert_eq!(from_str::<Point2D>("[3,5]").unwrap(), point);
+
+        let hilbert = HilbertIndex(42);
+        assert_eq!(
+            as_string::serialize(&hilbert, Serializer).unwrap(),
+            json!("42")
+        );
+        assert_eq!(
+            as_string::deserialize::<HilbertIndex, _>(json!("42")).unwrap(),
+            hilbert
+        );
+        assert!(as_string::deserialize::<HilbertIndex, _>(json!(42)).is_err());
+    }
+}